@@ -0,0 +1,284 @@
+// Mark this test as BPF-only due to current `ProgramTest` limitations when CPIing into the system program
+#![cfg(feature = "test-sbf")]
+
+mod program_test;
+
+use {
+    program_test::{program_test, program_test_2022},
+    solana_program::{
+        account_info::AccountInfo, entrypoint::ProgramResult, instruction::*,
+        program::invoke_signed, pubkey::Pubkey,
+    },
+    solana_program_test::*,
+    solana_sdk::{
+        signature::Signer,
+        signer::keypair::Keypair,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_associated_token_account::{
+        get_associated_token_address_with_program_id,
+        instruction::{close_associated_token_account, create_associated_token_account},
+    },
+};
+
+/// Program whose program-derived address is the wallet of an associated
+/// token account, closing it by invoking `CloseAndRecoverRent`
+fn process_program_wallet(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    _input: &[u8],
+) -> ProgramResult {
+    let (wallet_address, bump_seed) = Pubkey::find_program_address(&[b"wallet"], program_id);
+    invoke_signed(
+        &close_associated_token_account(
+            &wallet_address,
+            accounts[1].key,
+            accounts[2].key,
+            accounts[4].key,
+        ),
+        accounts,
+        &[&[b"wallet", &[bump_seed]]],
+    )
+}
+
+async fn check_close(pt: ProgramTest, token_mint_address: Pubkey, token_program_id: Pubkey) {
+    let wallet = Keypair::new();
+    let destination_address = Pubkey::new_unique();
+    let associated_token_address = get_associated_token_address_with_program_id(
+        &wallet.pubkey(),
+        &token_mint_address,
+        &token_program_id,
+    );
+
+    let (mut banks_client, payer, recent_blockhash) = pt.start().await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[create_associated_token_account(
+            &payer.pubkey(),
+            &wallet.pubkey(),
+            &token_mint_address,
+            &token_program_id,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let lamports = banks_client
+        .get_account(associated_token_address)
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[close_associated_token_account(
+            &wallet.pubkey(),
+            &token_mint_address,
+            &destination_address,
+            &token_program_id,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer, &wallet],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // Associated account is gone, and its rent recovered
+    assert!(banks_client
+        .get_account(associated_token_address)
+        .await
+        .unwrap()
+        .is_none());
+    let destination_account = banks_client
+        .get_account(destination_address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(destination_account.lamports, lamports);
+}
+
+#[tokio::test]
+async fn success_close() {
+    let token_mint_address = Pubkey::new_unique();
+    check_close(
+        program_test(token_mint_address, true),
+        token_mint_address,
+        spl_token::id(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn success_close_2022() {
+    let token_mint_address = Pubkey::new_unique();
+    check_close(
+        program_test_2022(token_mint_address, true),
+        token_mint_address,
+        spl_token_2022::id(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn success_close_with_program_wallet() {
+    let token_mint_address = Pubkey::new_unique();
+    let wallet_program_id = Pubkey::new_unique();
+    let destination_address = Pubkey::new_unique();
+    let (wallet_address, _) = Pubkey::find_program_address(&[b"wallet"], &wallet_program_id);
+    let associated_token_address = get_associated_token_address_with_program_id(
+        &wallet_address,
+        &token_mint_address,
+        &spl_token_2022::id(),
+    );
+
+    let mut pt = program_test_2022(token_mint_address, true);
+    pt.add_program(
+        "program_wallet",
+        wallet_program_id,
+        processor!(process_program_wallet),
+    );
+    let (mut banks_client, payer, recent_blockhash) = pt.start().await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[create_associated_token_account(
+            &payer.pubkey(),
+            &wallet_address,
+            &token_mint_address,
+            &spl_token_2022::id(),
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+    let lamports = banks_client
+        .get_account(associated_token_address)
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[Instruction::new_with_bytes(
+            wallet_program_id,
+            &[],
+            vec![
+                AccountMeta::new(associated_token_address, false),
+                AccountMeta::new_readonly(token_mint_address, false),
+                AccountMeta::new(destination_address, false),
+                AccountMeta::new_readonly(wallet_address, false),
+                AccountMeta::new_readonly(spl_token_2022::id(), false),
+                AccountMeta::new_readonly(spl_associated_token_account::id(), false),
+            ],
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    // The program-derived wallet signed, so the account is gone and its rent
+    // recovered
+    assert!(banks_client
+        .get_account(associated_token_address)
+        .await
+        .unwrap()
+        .is_none());
+    let destination_account = banks_client
+        .get_account(destination_address)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(destination_account.lamports, lamports);
+}
+
+#[tokio::test]
+async fn fail_missing_wallet_signature() {
+    let wallet_address = Pubkey::new_unique();
+    let token_mint_address = Pubkey::new_unique();
+    let (mut banks_client, payer, recent_blockhash) =
+        program_test_2022(token_mint_address, true).start().await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[create_associated_token_account(
+            &payer.pubkey(),
+            &wallet_address,
+            &token_mint_address,
+            &spl_token_2022::id(),
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let mut instruction = close_associated_token_account(
+        &wallet_address,
+        &token_mint_address,
+        &payer.pubkey(),
+        &spl_token_2022::id(),
+    );
+    instruction.accounts[3] = AccountMeta::new_readonly(wallet_address, false); // <-- Wallet doesn't sign
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+}
+
+#[tokio::test]
+async fn fail_wrong_wallet() {
+    let wallet_address = Pubkey::new_unique();
+    let wrong_wallet = Keypair::new();
+    let token_mint_address = Pubkey::new_unique();
+    let associated_token_address = get_associated_token_address_with_program_id(
+        &wallet_address,
+        &token_mint_address,
+        &spl_token_2022::id(),
+    );
+    let (mut banks_client, payer, recent_blockhash) =
+        program_test_2022(token_mint_address, true).start().await;
+    let transaction = Transaction::new_signed_with_payer(
+        &[create_associated_token_account(
+            &payer.pubkey(),
+            &wallet_address,
+            &token_mint_address,
+            &spl_token_2022::id(),
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let mut instruction = close_associated_token_account(
+        &wrong_wallet.pubkey(),
+        &token_mint_address,
+        &payer.pubkey(),
+        &spl_token_2022::id(),
+    );
+    instruction.accounts[0] = AccountMeta::new(associated_token_address, false); // <-- Not the signer's account
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer, &wrong_wallet],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidSeeds)
+    );
+}
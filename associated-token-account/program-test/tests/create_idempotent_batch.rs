@@ -0,0 +1,131 @@
+#![cfg(feature = "test-sbf")]
+
+mod program_test;
+
+use {
+    program_test::{program_test, program_test_2022},
+    solana_program::{instruction::*, pubkey::Pubkey},
+    solana_program_test::*,
+    solana_sdk::{
+        signature::Signer,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_associated_token_account::{
+        get_associated_token_address_with_program_id,
+        instruction::{
+            create_associated_token_account, create_associated_token_accounts_idempotent,
+        },
+    },
+    spl_token_2022::{extension::StateWithExtensionsOwned, state::Account},
+};
+
+async fn run_create_batch(
+    mut pt: ProgramTest,
+    token_mint_address: Pubkey,
+    token_program_id: Pubkey,
+) {
+    let wallet_addresses = (0..4).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+
+    pt.set_compute_max_units(400_000);
+    let (mut banks_client, payer, recent_blockhash) = pt.start().await;
+
+    // The first account already exists, and is skipped
+    let transaction = Transaction::new_signed_with_payer(
+        &[create_associated_token_account(
+            &payer.pubkey(),
+            &wallet_addresses[0],
+            &token_mint_address,
+            &token_program_id,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let wallet_and_token_mint_addresses = wallet_addresses
+        .iter()
+        .map(|wallet_address| (*wallet_address, token_mint_address))
+        .collect::<Vec<_>>();
+    let transaction = Transaction::new_signed_with_payer(
+        &[create_associated_token_accounts_idempotent(
+            &payer.pubkey(),
+            &wallet_and_token_mint_addresses,
+            &token_program_id,
+        )],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    for wallet_address in &wallet_addresses {
+        let associated_token_address = get_associated_token_address_with_program_id(
+            wallet_address,
+            &token_mint_address,
+            &token_program_id,
+        );
+        let associated_account = banks_client
+            .get_account(associated_token_address)
+            .await
+            .expect("get_account")
+            .expect("associated_account not none");
+        assert_eq!(associated_account.owner, token_program_id);
+        let token_account =
+            StateWithExtensionsOwned::<Account>::unpack(associated_account.data).unwrap();
+        assert_eq!(token_account.base.owner, *wallet_address);
+        assert_eq!(token_account.base.mint, token_mint_address);
+    }
+}
+
+#[tokio::test]
+async fn success_create_batch() {
+    let token_mint_address = Pubkey::new_unique();
+    run_create_batch(
+        program_test(token_mint_address, true),
+        token_mint_address,
+        spl_token::id(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn success_create_batch_2022() {
+    let token_mint_address = Pubkey::new_unique();
+    run_create_batch(
+        program_test_2022(token_mint_address, true),
+        token_mint_address,
+        spl_token_2022::id(),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn fail_incomplete_triple() {
+    let token_mint_address = Pubkey::new_unique();
+    let wallet_address = Pubkey::new_unique();
+    let (mut banks_client, payer, recent_blockhash) =
+        program_test_2022(token_mint_address, true).start().await;
+
+    let mut instruction = create_associated_token_accounts_idempotent(
+        &payer.pubkey(),
+        &[(wallet_address, token_mint_address)],
+        &spl_token_2022::id(),
+    );
+    instruction.accounts.pop(); // <-- Missing associated token account address
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+}
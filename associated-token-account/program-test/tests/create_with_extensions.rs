@@ -0,0 +1,158 @@
+// Mark this test as BPF-only due to current `ProgramTest` limitations when CPIing into the system program
+#![cfg(feature = "test-sbf")]
+
+mod program_test;
+
+use {
+    program_test::{program_test, program_test_2022},
+    solana_program::{instruction::*, pubkey::Pubkey},
+    solana_program_test::*,
+    solana_sdk::{
+        signature::Signer,
+        signer::keypair::Keypair,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_associated_token_account::{
+        get_associated_token_address_with_program_id,
+        instruction::create_associated_token_account_with_extensions,
+    },
+    spl_token_2022::{
+        extension::{
+            cpi_guard::CpiGuard, memo_transfer::MemoTransfer, BaseStateWithExtensions,
+            ExtensionType, StateWithExtensionsOwned,
+        },
+        state::Account,
+    },
+};
+
+#[tokio::test]
+async fn success_create_with_memo_transfer_and_cpi_guard() {
+    let wallet = Keypair::new();
+    let token_mint_address = Pubkey::new_unique();
+    let associated_token_address = get_associated_token_address_with_program_id(
+        &wallet.pubkey(),
+        &token_mint_address,
+        &spl_token_2022::id(),
+    );
+
+    let (mut banks_client, payer, recent_blockhash) =
+        program_test_2022(token_mint_address, true).start().await;
+    let rent = banks_client.get_rent().await.unwrap();
+    let extension_types = [ExtensionType::MemoTransfer, ExtensionType::CpiGuard];
+    let expected_token_account_len = ExtensionType::try_calculate_account_len::<Account>(&[
+        ExtensionType::ImmutableOwner,
+        ExtensionType::MemoTransfer,
+        ExtensionType::CpiGuard,
+    ])
+    .unwrap();
+
+    let instructions = create_associated_token_account_with_extensions(
+        &payer.pubkey(),
+        &wallet.pubkey(),
+        &token_mint_address,
+        &spl_token_2022::id(),
+        &extension_types,
+    )
+    .unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer.pubkey()),
+        &[&payer, &wallet],
+        recent_blockhash,
+    );
+    banks_client.process_transaction(transaction).await.unwrap();
+
+    let associated_account = banks_client
+        .get_account(associated_token_address)
+        .await
+        .expect("get_account")
+        .expect("associated_account not none");
+    assert_eq!(associated_account.data.len(), expected_token_account_len);
+    assert_eq!(associated_account.owner, spl_token_2022::id());
+    assert_eq!(
+        associated_account.lamports,
+        rent.minimum_balance(expected_token_account_len)
+    );
+
+    let token_account =
+        StateWithExtensionsOwned::<Account>::unpack(associated_account.data).unwrap();
+    assert_eq!(token_account.base.owner, wallet.pubkey());
+    let memo_transfer = token_account.get_extension::<MemoTransfer>().unwrap();
+    assert!(bool::from(memo_transfer.require_incoming_transfer_memos));
+    let cpi_guard = token_account.get_extension::<CpiGuard>().unwrap();
+    assert!(bool::from(cpi_guard.lock_cpi));
+}
+
+#[tokio::test]
+async fn fail_memo_transfer_without_wallet_signature() {
+    let wallet_address = Pubkey::new_unique();
+    let token_mint_address = Pubkey::new_unique();
+    let (mut banks_client, payer, recent_blockhash) =
+        program_test_2022(token_mint_address, true).start().await;
+
+    let mut instructions = create_associated_token_account_with_extensions(
+        &payer.pubkey(),
+        &wallet_address,
+        &token_mint_address,
+        &spl_token_2022::id(),
+        &[ExtensionType::MemoTransfer],
+    )
+    .unwrap();
+    instructions[0].accounts[2] = AccountMeta::new_readonly(wallet_address, false); // <-- Wallet doesn't sign
+
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer.pubkey()),
+        &[&payer],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+}
+
+#[tokio::test]
+async fn fail_original_token_program() {
+    let wallet = Keypair::new();
+    let token_mint_address = Pubkey::new_unique();
+    let (mut banks_client, payer, recent_blockhash) =
+        program_test(token_mint_address, true).start().await;
+
+    let mut instructions = create_associated_token_account_with_extensions(
+        &payer.pubkey(),
+        &wallet.pubkey(),
+        &token_mint_address,
+        &spl_token_2022::id(),
+        &[ExtensionType::MemoTransfer],
+    )
+    .unwrap();
+    instructions[0].accounts[1] = AccountMeta::new(
+        get_associated_token_address_with_program_id(
+            &wallet.pubkey(),
+            &token_mint_address,
+            &spl_token::id(),
+        ),
+        false,
+    );
+    instructions[0].accounts[5] = AccountMeta::new_readonly(spl_token::id(), false); // <-- Extensions need token-2022
+
+    let transaction = Transaction::new_signed_with_payer(
+        &instructions,
+        Some(&payer.pubkey()),
+        &[&payer, &wallet],
+        recent_blockhash,
+    );
+    assert_eq!(
+        banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::IncorrectProgramId)
+    );
+}
@@ -674,3 +674,153 @@ async fn fail_destination_not_wallet_ata() {
     )
     .await;
 }
+
+async fn check_nested_path(context: &mut ProgramTestContext, program_id: &Pubkey) {
+    let wallet = Keypair::new();
+    let (first_owner_mint, _first_owner_mint_authority) = create_mint(context, program_id).await;
+    let (second_owner_mint, _second_owner_mint_authority) = create_mint(context, program_id).await;
+    let (nested_mint, nested_mint_authority) = create_mint(context, program_id).await;
+
+    let first_owner_associated_token_address =
+        create_associated_token_account(context, &wallet.pubkey(), &first_owner_mint, program_id)
+            .await;
+    let second_owner_associated_token_address = create_associated_token_account(
+        context,
+        &first_owner_associated_token_address,
+        &second_owner_mint,
+        program_id,
+    )
+    .await;
+    let nested_associated_token_address = create_associated_token_account(
+        context,
+        &second_owner_associated_token_address,
+        &nested_mint,
+        program_id,
+    )
+    .await;
+    let destination_token_address =
+        create_associated_token_account(context, &wallet.pubkey(), &nested_mint, program_id).await;
+
+    context.last_blockhash = context
+        .banks_client
+        .get_new_latest_blockhash(&context.last_blockhash)
+        .await
+        .unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction::recover_nested_path(
+            &wallet.pubkey(),
+            &[first_owner_mint, second_owner_mint],
+            &nested_mint,
+            program_id,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &wallet],
+        context.last_blockhash,
+    );
+    try_recover_nested(
+        context,
+        program_id,
+        nested_mint,
+        nested_mint_authority,
+        nested_associated_token_address,
+        destination_token_address,
+        wallet,
+        transaction,
+        None,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn success_nested_path() {
+    let dummy_mint = Pubkey::new_unique();
+    let pt = program_test(dummy_mint, true);
+    let mut context = pt.start_with_context().await;
+    check_nested_path(&mut context, &spl_token::id()).await;
+}
+
+#[tokio::test]
+async fn success_nested_path_2022() {
+    let dummy_mint = Pubkey::new_unique();
+    let pt = program_test_2022(dummy_mint, true);
+    let mut context = pt.start_with_context().await;
+    check_nested_path(&mut context, &spl_token_2022::id()).await;
+}
+
+async fn check_nested_path_wrong_owner_chain(
+    context: &mut ProgramTestContext,
+    program_id: &Pubkey,
+) {
+    let wallet = Keypair::new();
+    let (first_owner_mint, _first_owner_mint_authority) = create_mint(context, program_id).await;
+    let (second_owner_mint, _second_owner_mint_authority) = create_mint(context, program_id).await;
+    let (nested_mint, nested_mint_authority) = create_mint(context, program_id).await;
+
+    let first_owner_associated_token_address =
+        create_associated_token_account(context, &wallet.pubkey(), &first_owner_mint, program_id)
+            .await;
+    let second_owner_associated_token_address = create_associated_token_account(
+        context,
+        &first_owner_associated_token_address,
+        &second_owner_mint,
+        program_id,
+    )
+    .await;
+    let nested_associated_token_address = create_associated_token_account(
+        context,
+        &second_owner_associated_token_address,
+        &nested_mint,
+        program_id,
+    )
+    .await;
+    let destination_token_address =
+        create_associated_token_account(context, &wallet.pubkey(), &nested_mint, program_id).await;
+
+    context.last_blockhash = context
+        .banks_client
+        .get_new_latest_blockhash(&context.last_blockhash)
+        .await
+        .unwrap();
+    let mut recover = instruction::recover_nested_path(
+        &wallet.pubkey(),
+        &[first_owner_mint, second_owner_mint],
+        &nested_mint,
+        program_id,
+    );
+    recover.accounts.truncate(7); // <-- Skip the second owner associated token account
+    recover.accounts[0] = AccountMeta::new(nested_associated_token_address, false);
+    let transaction = Transaction::new_signed_with_payer(
+        &[recover],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &wallet],
+        context.last_blockhash,
+    );
+    try_recover_nested(
+        context,
+        program_id,
+        nested_mint,
+        nested_mint_authority,
+        nested_associated_token_address,
+        destination_token_address,
+        wallet,
+        transaction,
+        Some(InstructionError::InvalidSeeds),
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn fail_nested_path_wrong_owner_chain() {
+    let dummy_mint = Pubkey::new_unique();
+    let pt = program_test(dummy_mint, true);
+    let mut context = pt.start_with_context().await;
+    check_nested_path_wrong_owner_chain(&mut context, &spl_token::id()).await;
+}
+
+#[tokio::test]
+async fn fail_nested_path_wrong_owner_chain_2022() {
+    let dummy_mint = Pubkey::new_unique();
+    let pt = program_test_2022(dummy_mint, true);
+    let mut context = pt.start_with_context().await;
+    check_nested_path_wrong_owner_chain(&mut context, &spl_token_2022::id()).await;
+}
@@ -6,8 +6,13 @@ use {
     borsh::{BorshDeserialize, BorshSchema, BorshSerialize},
     solana_program::{
         instruction::{AccountMeta, Instruction},
+        program_error::ProgramError,
         pubkey::Pubkey,
     },
+    spl_token_2022::{
+        check_program_account,
+        extension::{cpi_guard::instruction::enable_cpi_guard, ExtensionType},
+    },
 };
 
 /// Instructions supported by the AssociatedTokenAccount program
@@ -53,6 +58,76 @@ pub enum AssociatedTokenAccountInstruction {
     ///   5. `[writeable, signer]` Wallet address for the owner associated token account
     ///   6. `[]` SPL Token program
     RecoverNested,
+    /// Creates associated token accounts for many wallet addresses and token
+    /// mints at once, skipping the ones that already exist.  Returns an error
+    /// if any account exists, but with a different owner.
+    ///
+    ///   0. `[writeable,signer]` Funding account (must be a system account)
+    ///   1. `[]` System program
+    ///   2. `[]` SPL Token program
+    ///
+    ///   For each associated token account:
+    ///   3 + 3 * i. `[]` Wallet address for the new associated token account
+    ///   4 + 3 * i. `[]` The token mint for the new associated token account
+    ///   5 + 3 * i. `[writeable]` Associated token account address to be created
+    CreateIdempotentBatch,
+    /// Creates an associated token account for the given wallet address and
+    /// token-2022 mint, with room for the given account extensions on top of
+    /// the ones required by the mint.  Returns an error if the account exists.
+    ///
+    /// Required memo transfers are enabled if `MemoTransfer` is requested,
+    /// which needs the wallet's signature.  Other extensions, such as
+    /// `CpiGuard` or `ConfidentialTransferAccount`, can't be configured
+    /// through a cross-program invocation, and are only allocated: the
+    /// wallet configures them with separate token instructions.
+    ///
+    ///   0. `[writeable,signer]` Funding account (must be a system account)
+    ///   1. `[writeable]` Associated token account address to be created
+    ///   2. `[signer]` Wallet address for the new associated token account,
+    ///      only needs to sign if `MemoTransfer` is requested
+    ///   3. `[]` The token mint for the new associated token account
+    ///   4. `[]` System program
+    ///   5. `[]` SPL Token 2022 program
+    CreateWithExtensions {
+        /// Account extensions to allocate, as `ExtensionType` values
+        extension_types: Vec<u16>,
+    },
+    /// Closes an associated token account, moving its lamports to the
+    /// destination. The account must hold no tokens, unless it is a native
+    /// account, in which case its wrapped SOL is moved to the destination
+    /// along with the rent.
+    ///
+    /// The wallet must sign, so a program owning associated token accounts
+    /// through a program-derived address can reclaim their rent by invoking
+    /// this instruction with its signer seeds.
+    ///
+    ///   0. `[writeable]` Associated token account to close, must be owned by `3`
+    ///   1. `[]` Token mint for the associated token account
+    ///   2. `[writeable]` Destination for the associated token account lamports
+    ///   3. `[signer]` Wallet address for the associated token account
+    ///   4. `[]` SPL Token program
+    CloseAndRecoverRent,
+    /// Transfers from and closes a nested associated token account at any
+    /// depth: an associated token account owned by a chain of associated
+    /// token accounts, the first of which is owned by the wallet.
+    ///
+    /// As with `RecoverNested`, which is the case of a single owner
+    /// associated token account, the tokens are moved to the wallet's
+    /// associated token account, and the nested account lamports are moved
+    /// to the wallet.
+    ///
+    ///   0. `[writeable]` Nested associated token account, must be owned by
+    ///      the last owner associated token account
+    ///   1. `[]` Token mint for the nested associated token account
+    ///   2. `[writeable]` Wallet's associated token account
+    ///   3. `[writeable, signer]` Wallet address for the first owner associated token account
+    ///   4. `[]` SPL Token program
+    ///
+    ///   For each owner associated token account, starting from the wallet's:
+    ///   5 + 2 * i. `[]` Owner associated token account address, must be owned
+    ///              by the previous one, or by `3` for the first one
+    ///   6 + 2 * i. `[]` Token mint for the owner associated token account
+    RecoverNestedPath,
 }
 
 fn build_associated_token_account_instruction(
@@ -119,6 +194,168 @@ pub fn create_associated_token_account_idempotent(
     )
 }
 
+/// Creates a `CreateIdempotentBatch` instruction, for each of the given
+/// `(wallet_address, token_mint_address)` pairs
+pub fn create_associated_token_accounts_idempotent(
+    funding_address: &Pubkey,
+    wallet_and_token_mint_addresses: &[(Pubkey, Pubkey)],
+    token_program_id: &Pubkey,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*funding_address, true),
+        AccountMeta::new_readonly(solana_program::system_program::id(), false),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+    for (wallet_address, token_mint_address) in wallet_and_token_mint_addresses {
+        let associated_account_address = get_associated_token_address_with_program_id(
+            wallet_address,
+            token_mint_address,
+            token_program_id,
+        );
+        accounts.push(AccountMeta::new_readonly(*wallet_address, false));
+        accounts.push(AccountMeta::new_readonly(*token_mint_address, false));
+        accounts.push(AccountMeta::new(associated_account_address, false));
+    }
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data: AssociatedTokenAccountInstruction::CreateIdempotentBatch
+            .try_to_vec()
+            .unwrap(),
+    }
+}
+
+/// Creates a `CreateWithExtensions` instruction, followed by the instructions
+/// enabling the requested extensions which must be configured by the wallet
+/// itself, i.e. `CpiGuard`
+pub fn create_associated_token_account_with_extensions(
+    funding_address: &Pubkey,
+    wallet_address: &Pubkey,
+    token_mint_address: &Pubkey,
+    token_program_id: &Pubkey,
+    extension_types: &[ExtensionType],
+) -> Result<Vec<Instruction>, ProgramError> {
+    check_program_account(token_program_id)?;
+    let associated_account_address = get_associated_token_address_with_program_id(
+        wallet_address,
+        token_mint_address,
+        token_program_id,
+    );
+    let wallet_is_signer = extension_types.iter().any(|extension_type| {
+        matches!(
+            extension_type,
+            ExtensionType::MemoTransfer | ExtensionType::CpiGuard
+        )
+    });
+    let instruction_data = AssociatedTokenAccountInstruction::CreateWithExtensions {
+        extension_types: extension_types
+            .iter()
+            .map(|extension_type| u16::from(*extension_type))
+            .collect(),
+    };
+
+    let mut instructions = vec![Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(*funding_address, true),
+            AccountMeta::new(associated_account_address, false),
+            AccountMeta::new_readonly(*wallet_address, wallet_is_signer),
+            AccountMeta::new_readonly(*token_mint_address, false),
+            AccountMeta::new_readonly(solana_program::system_program::id(), false),
+            AccountMeta::new_readonly(*token_program_id, false),
+        ],
+        data: instruction_data.try_to_vec().unwrap(),
+    }];
+    if extension_types.contains(&ExtensionType::CpiGuard) {
+        instructions.push(enable_cpi_guard(
+            token_program_id,
+            &associated_account_address,
+            wallet_address,
+            &[],
+        )?);
+    }
+    Ok(instructions)
+}
+
+/// Creates a `CloseAndRecoverRent` instruction
+pub fn close_associated_token_account(
+    wallet_address: &Pubkey,
+    token_mint_address: &Pubkey,
+    destination_address: &Pubkey,
+    token_program_id: &Pubkey,
+) -> Instruction {
+    let associated_account_address = get_associated_token_address_with_program_id(
+        wallet_address,
+        token_mint_address,
+        token_program_id,
+    );
+
+    let instruction_data = AssociatedTokenAccountInstruction::CloseAndRecoverRent;
+
+    Instruction {
+        program_id: id(),
+        accounts: vec![
+            AccountMeta::new(associated_account_address, false),
+            AccountMeta::new_readonly(*token_mint_address, false),
+            AccountMeta::new(*destination_address, false),
+            AccountMeta::new_readonly(*wallet_address, true),
+            AccountMeta::new_readonly(*token_program_id, false),
+        ],
+        data: instruction_data.try_to_vec().unwrap(),
+    }
+}
+
+/// Creates a `RecoverNestedPath` instruction, for the account of
+/// `nested_token_mint_address` owned by the chain of associated token
+/// accounts for `owner_token_mint_addresses`, starting from the wallet's
+pub fn recover_nested_path(
+    wallet_address: &Pubkey,
+    owner_token_mint_addresses: &[Pubkey],
+    nested_token_mint_address: &Pubkey,
+    token_program_id: &Pubkey,
+) -> Instruction {
+    let destination_associated_account_address = get_associated_token_address_with_program_id(
+        wallet_address,
+        nested_token_mint_address,
+        token_program_id,
+    );
+
+    let mut owner_accounts = vec![];
+    let mut owner_address = *wallet_address;
+    for owner_token_mint_address in owner_token_mint_addresses {
+        owner_address = get_associated_token_address_with_program_id(
+            &owner_address,
+            owner_token_mint_address,
+            token_program_id,
+        );
+        owner_accounts.push(AccountMeta::new_readonly(owner_address, false));
+        owner_accounts.push(AccountMeta::new_readonly(*owner_token_mint_address, false));
+    }
+    let nested_associated_account_address = get_associated_token_address_with_program_id(
+        &owner_address,
+        nested_token_mint_address,
+        token_program_id,
+    );
+
+    let instruction_data = AssociatedTokenAccountInstruction::RecoverNestedPath;
+
+    let mut accounts = vec![
+        AccountMeta::new(nested_associated_account_address, false),
+        AccountMeta::new_readonly(*nested_token_mint_address, false),
+        AccountMeta::new(destination_associated_account_address, false),
+        AccountMeta::new(*wallet_address, true),
+        AccountMeta::new_readonly(*token_program_id, false),
+    ];
+    accounts.extend(owner_accounts);
+
+    Instruction {
+        program_id: id(),
+        accounts,
+        data: instruction_data.try_to_vec().unwrap(),
+    }
+}
+
 /// Creates a `RecoverNested` instruction
 pub fn recover_nested(
     wallet_address: &Pubkey,
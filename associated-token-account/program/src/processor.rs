@@ -59,6 +59,27 @@ pub fn process_instruction(
         AssociatedTokenAccountInstruction::RecoverNested => {
             process_recover_nested(program_id, accounts)
         }
+        AssociatedTokenAccountInstruction::CreateIdempotentBatch => {
+            process_create_associated_token_accounts_idempotent(program_id, accounts)
+        }
+        AssociatedTokenAccountInstruction::CloseAndRecoverRent => {
+            process_close_and_recover_rent(program_id, accounts)
+        }
+        AssociatedTokenAccountInstruction::RecoverNestedPath => {
+            process_recover_nested_path(program_id, accounts)
+        }
+        AssociatedTokenAccountInstruction::CreateWithExtensions { extension_types } => {
+            let extension_types = extension_types
+                .into_iter()
+                .map(ExtensionType::try_from)
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| ProgramError::InvalidInstructionData)?;
+            process_create_associated_token_account_with_extensions(
+                program_id,
+                accounts,
+                &extension_types,
+            )
+        }
     }
 }
 
@@ -76,6 +97,121 @@ fn process_create_associated_token_account(
     let spl_token_mint_info = next_account_info(account_info_iter)?;
     let system_program_info = next_account_info(account_info_iter)?;
     let spl_token_program_info = next_account_info(account_info_iter)?;
+
+    create_associated_token_account_internal(
+        program_id,
+        funder_info,
+        associated_token_account_info,
+        wallet_account_info,
+        spl_token_mint_info,
+        system_program_info,
+        spl_token_program_info,
+        create_mode,
+        &[],
+    )
+}
+
+/// Processes `CreateIdempotentBatch` instruction
+fn process_create_associated_token_accounts_idempotent(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let funder_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let spl_token_program_info = next_account_info(account_info_iter)?;
+
+    while !account_info_iter.as_slice().is_empty() {
+        let wallet_account_info = next_account_info(account_info_iter)?;
+        let spl_token_mint_info = next_account_info(account_info_iter)?;
+        let associated_token_account_info = next_account_info(account_info_iter)?;
+        create_associated_token_account_internal(
+            program_id,
+            funder_info,
+            associated_token_account_info,
+            wallet_account_info,
+            spl_token_mint_info,
+            system_program_info,
+            spl_token_program_info,
+            CreateMode::Idempotent,
+            &[],
+        )?;
+    }
+    Ok(())
+}
+
+/// Processes `CreateWithExtensions` instruction
+fn process_create_associated_token_account_with_extensions(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    extension_types: &[ExtensionType],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let funder_info = next_account_info(account_info_iter)?;
+    let associated_token_account_info = next_account_info(account_info_iter)?;
+    let wallet_account_info = next_account_info(account_info_iter)?;
+    let spl_token_mint_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let spl_token_program_info = next_account_info(account_info_iter)?;
+
+    // The original token program ignores extensions, so only token-2022 can
+    // honor the request
+    spl_token_2022::check_program_account(spl_token_program_info.key)?;
+
+    let enable_memo_transfers = extension_types.contains(&ExtensionType::MemoTransfer);
+    if enable_memo_transfers && !wallet_account_info.is_signer {
+        msg!("Wallet must sign to enable required memo transfers");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    create_associated_token_account_internal(
+        program_id,
+        funder_info,
+        associated_token_account_info,
+        wallet_account_info,
+        spl_token_mint_info,
+        system_program_info,
+        spl_token_program_info,
+        CreateMode::Always,
+        extension_types,
+    )?;
+
+    if enable_memo_transfers {
+        msg!("Enable required memo transfers");
+        invoke(
+            &spl_token_2022::extension::memo_transfer::instruction::enable_required_transfer_memos(
+                spl_token_program_info.key,
+                associated_token_account_info.key,
+                wallet_account_info.key,
+                &[],
+            )?,
+            &[
+                associated_token_account_info.clone(),
+                wallet_account_info.clone(),
+                spl_token_program_info.clone(),
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// Creates and initializes an associated token account, with room for the
+/// given account extensions, unless it already exists and `create_mode` is
+/// `Idempotent`
+#[allow(clippy::too_many_arguments)]
+fn create_associated_token_account_internal<'a>(
+    program_id: &Pubkey,
+    funder_info: &AccountInfo<'a>,
+    associated_token_account_info: &AccountInfo<'a>,
+    wallet_account_info: &AccountInfo<'a>,
+    spl_token_mint_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    spl_token_program_info: &AccountInfo<'a>,
+    create_mode: CreateMode,
+    extension_types: &[ExtensionType],
+) -> ProgramResult {
     let spl_token_program_id = spl_token_program_info.key;
 
     let (associated_token_address, bump_seed) = get_associated_token_address_and_bump_seed_internal(
@@ -118,10 +254,12 @@ fn process_create_associated_token_account(
         &[bump_seed],
     ];
 
+    let mut account_extension_types = vec![ExtensionType::ImmutableOwner];
+    account_extension_types.extend_from_slice(extension_types);
     let account_len = get_account_len(
         spl_token_mint_info,
         spl_token_program_info,
-        &[ExtensionType::ImmutableOwner],
+        &account_extension_types,
     )?;
 
     create_pda_account(
@@ -307,3 +445,232 @@ pub fn process_recover_nested(program_id: &Pubkey, accounts: &[AccountInfo]) ->
         &[owner_associated_token_account_signer_seeds],
     )
 }
+
+/// Processes `CloseAndRecoverRent` instruction
+pub fn process_close_and_recover_rent(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let associated_token_account_info = next_account_info(account_info_iter)?;
+    let spl_token_mint_info = next_account_info(account_info_iter)?;
+    let destination_info = next_account_info(account_info_iter)?;
+    let wallet_account_info = next_account_info(account_info_iter)?;
+    let spl_token_program_info = next_account_info(account_info_iter)?;
+    let spl_token_program_id = spl_token_program_info.key;
+
+    let (associated_token_address, _) = get_associated_token_address_and_bump_seed_internal(
+        wallet_account_info.key,
+        spl_token_mint_info.key,
+        program_id,
+        spl_token_program_id,
+    );
+    if associated_token_address != *associated_token_account_info.key {
+        msg!("Error: Associated address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    if !wallet_account_info.is_signer {
+        msg!("Wallet of the associated token account must sign");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    if associated_token_account_info.owner != spl_token_program_id {
+        msg!("Associated token account not owned by provided token program");
+        return Err(ProgramError::IllegalOwner);
+    }
+    {
+        let account_data = associated_token_account_info.data.borrow();
+        let account = StateWithExtensions::<Account>::unpack(&account_data)?;
+        if account.base.owner != *wallet_account_info.key {
+            msg!("Associated token account not owned by provided wallet");
+            return Err(AssociatedTokenAccountError::InvalidOwner.into());
+        }
+    }
+
+    // The token program only closes the account if it holds no tokens, or if
+    // it is a native account, whose wrapped SOL goes out with the rent
+    invoke(
+        &spl_token_2022::instruction::close_account(
+            spl_token_program_id,
+            associated_token_account_info.key,
+            destination_info.key,
+            wallet_account_info.key,
+            &[],
+        )?,
+        &[
+            associated_token_account_info.clone(),
+            destination_info.clone(),
+            wallet_account_info.clone(),
+            spl_token_program_info.clone(),
+        ],
+    )
+}
+
+/// Processes `RecoverNestedPath` instruction
+pub fn process_recover_nested_path(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let nested_associated_token_account_info = next_account_info(account_info_iter)?;
+    let nested_token_mint_info = next_account_info(account_info_iter)?;
+    let destination_associated_token_account_info = next_account_info(account_info_iter)?;
+    let wallet_account_info = next_account_info(account_info_iter)?;
+    let spl_token_program_info = next_account_info(account_info_iter)?;
+    let spl_token_program_id = spl_token_program_info.key;
+
+    if !wallet_account_info.is_signer {
+        msg!("Wallet of the owner associated token account must sign");
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+
+    // Walk down the chain of owner associated token accounts, keeping what's
+    // needed to sign for the last one
+    let mut owner_address = *wallet_account_info.key;
+    let mut owner_associated_token_account_signer = None;
+    while !account_info_iter.as_slice().is_empty() {
+        let owner_associated_token_account_info = next_account_info(account_info_iter)?;
+        let owner_token_mint_info = next_account_info(account_info_iter)?;
+
+        let (owner_associated_token_address, bump_seed) =
+            get_associated_token_address_and_bump_seed_internal(
+                &owner_address,
+                owner_token_mint_info.key,
+                program_id,
+                spl_token_program_id,
+            );
+        if owner_associated_token_address != *owner_associated_token_account_info.key {
+            msg!("Error: Owner associated address does not match seed derivation");
+            return Err(ProgramError::InvalidSeeds);
+        }
+
+        if owner_token_mint_info.owner != spl_token_program_id {
+            msg!("Owner mint not owned by provided token program");
+            return Err(ProgramError::IllegalOwner);
+        }
+
+        if owner_associated_token_account_info.owner != spl_token_program_id {
+            msg!("Owner associated token account not owned by provided token program, recreate the owner associated token account first");
+            return Err(ProgramError::IllegalOwner);
+        }
+        let owner_account_data = owner_associated_token_account_info.data.borrow();
+        let owner_account = StateWithExtensions::<Account>::unpack(&owner_account_data)?;
+        if owner_account.base.owner != owner_address {
+            msg!("Owner associated token account not owned by the previous owner");
+            return Err(AssociatedTokenAccountError::InvalidOwner.into());
+        }
+
+        owner_associated_token_account_signer = Some((
+            owner_address,
+            owner_associated_token_account_info,
+            owner_token_mint_info,
+            bump_seed,
+        ));
+        owner_address = owner_associated_token_address;
+    }
+    let (
+        owner_owner_address,
+        owner_associated_token_account_info,
+        owner_token_mint_info,
+        bump_seed,
+    ) = owner_associated_token_account_signer.ok_or(ProgramError::NotEnoughAccountKeys)?;
+
+    // Check nested address derivation
+    let (nested_associated_token_address, _) = get_associated_token_address_and_bump_seed_internal(
+        owner_associated_token_account_info.key,
+        nested_token_mint_info.key,
+        program_id,
+        spl_token_program_id,
+    );
+    if nested_associated_token_address != *nested_associated_token_account_info.key {
+        msg!("Error: Nested associated address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    // Check destination address derivation
+    let (destination_associated_token_address, _) =
+        get_associated_token_address_and_bump_seed_internal(
+            wallet_account_info.key,
+            nested_token_mint_info.key,
+            program_id,
+            spl_token_program_id,
+        );
+    if destination_associated_token_address != *destination_associated_token_account_info.key {
+        msg!("Error: Destination associated address does not match seed derivation");
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    // Account data is dropped at the end of this, so the CPI can succeed
+    // without a double-borrow
+    let (amount, decimals) = {
+        // Check nested associated token account data
+        if nested_associated_token_account_info.owner != spl_token_program_id {
+            msg!("Nested associated token account not owned by provided token program");
+            return Err(ProgramError::IllegalOwner);
+        }
+        let nested_account_data = nested_associated_token_account_info.data.borrow();
+        let nested_account = StateWithExtensions::<Account>::unpack(&nested_account_data)?;
+        if nested_account.base.owner != *owner_associated_token_account_info.key {
+            msg!("Nested associated token account not owned by provided associated token account");
+            return Err(AssociatedTokenAccountError::InvalidOwner.into());
+        }
+        let amount = nested_account.base.amount;
+
+        // Check nested token mint data
+        if nested_token_mint_info.owner != spl_token_program_id {
+            msg!("Nested mint account not owned by provided token program");
+            return Err(ProgramError::IllegalOwner);
+        }
+        let nested_mint_data = nested_token_mint_info.data.borrow();
+        let nested_mint = StateWithExtensions::<Mint>::unpack(&nested_mint_data)?;
+        let decimals = nested_mint.base.decimals;
+        (amount, decimals)
+    };
+
+    // Transfer everything out, signing for the last owner associated token
+    // account
+    let owner_associated_token_account_signer_seeds: &[&[_]] = &[
+        &owner_owner_address.to_bytes(),
+        &spl_token_program_id.to_bytes(),
+        &owner_token_mint_info.key.to_bytes(),
+        &[bump_seed],
+    ];
+    invoke_signed(
+        &spl_token_2022::instruction::transfer_checked(
+            spl_token_program_id,
+            nested_associated_token_account_info.key,
+            nested_token_mint_info.key,
+            destination_associated_token_account_info.key,
+            owner_associated_token_account_info.key,
+            &[],
+            amount,
+            decimals,
+        )?,
+        &[
+            nested_associated_token_account_info.clone(),
+            nested_token_mint_info.clone(),
+            destination_associated_token_account_info.clone(),
+            owner_associated_token_account_info.clone(),
+            spl_token_program_info.clone(),
+        ],
+        &[owner_associated_token_account_signer_seeds],
+    )?;
+
+    // Close the nested account so it's never used again
+    invoke_signed(
+        &spl_token_2022::instruction::close_account(
+            spl_token_program_id,
+            nested_associated_token_account_info.key,
+            wallet_account_info.key,
+            owner_associated_token_account_info.key,
+            &[],
+        )?,
+        &[
+            nested_associated_token_account_info.clone(),
+            wallet_account_info.clone(),
+            owner_associated_token_account_info.clone(),
+            spl_token_program_info.clone(),
+        ],
+        &[owner_associated_token_account_signer_seeds],
+    )
+}
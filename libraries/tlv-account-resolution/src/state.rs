@@ -128,6 +128,26 @@ impl ExtraAccountMetas {
         })
     }
 
+    /// Update the TLV entry for the given discriminator, replacing its data
+    /// with the given required accounts and resizing the entry in place.
+    ///
+    /// The buffer must already be large enough for the new entry. If the entry
+    /// shrinks, the freed bytes at the end of the buffer are zeroed, and may be
+    /// dropped by the caller.
+    pub fn update_with_pod_account_metas<T: SplDiscriminate>(
+        data: &mut [u8],
+        pod_account_metas: &[PodAccountMeta],
+    ) -> Result<(), ProgramError> {
+        let mut state = TlvStateMut::unpack(data)?;
+        let tlv_size = PodSlice::<PodAccountMeta>::size_of(pod_account_metas.len())?;
+        let bytes = state.realloc_first::<T>(tlv_size)?;
+        let mut extra_account_metas = PodSliceMut::init(bytes)?;
+        for pod_account_meta in pod_account_metas {
+            extra_account_metas.push(*pod_account_meta)?;
+        }
+        Ok(())
+    }
+
     /// Get the underlying `PodSlice<PodAccountMeta>` from an unpacked TLV
     ///
     /// Due to lifetime annoyances, this function can't just take in the bytes,
@@ -236,6 +256,54 @@ impl ExtraAccountMetas {
         Ok(())
     }
 
+    /// Check that the trailing account infos provided to an instruction match
+    /// the extra account metas in the validation data, in order, resolving
+    /// any PDAs from the leading account infos and the instruction data
+    pub fn check_account_infos<T: SplDiscriminate>(
+        account_infos: &[AccountInfo],
+        instruction_data: &[u8],
+        program_id: &Pubkey,
+        data: &[u8],
+    ) -> Result<(), ProgramError> {
+        let state = TlvStateBorrowed::unpack(data)?;
+        let extra_account_metas = Self::unpack_with_tlv_state::<T>(&state)?;
+        let initial_accounts_len = account_infos
+            .len()
+            .checked_sub(extra_account_metas.data().len())
+            .ok_or(AccountResolutionError::NotEnoughAccounts)?;
+
+        let (initial_account_infos, extra_account_infos) =
+            account_infos.split_at(initial_accounts_len);
+        let mut instruction = Instruction::new_with_bytes(
+            *program_id,
+            instruction_data,
+            initial_account_infos
+                .iter()
+                .map(|info| AccountMeta {
+                    pubkey: *info.key,
+                    is_signer: info.is_signer,
+                    is_writable: info.is_writable,
+                })
+                .collect(),
+        );
+        Self::add_to_instruction::<T>(&mut instruction, data)?;
+
+        for (meta, account_info) in instruction
+            .accounts
+            .iter()
+            .skip(initial_accounts_len)
+            .zip(extra_account_infos)
+        {
+            if !(meta.pubkey == *account_info.key
+                && meta.is_signer == account_info.is_signer
+                && meta.is_writable == account_info.is_writable)
+            {
+                return Err(AccountResolutionError::IncorrectAccount.into());
+            }
+        }
+        Ok(())
+    }
+
     /// Add the additional account metas and account infos for a CPI
     pub fn add_to_cpi_instruction<'a, T: SplDiscriminate>(
         cpi_instruction: &mut Instruction,
@@ -1082,4 +1150,194 @@ mod tests {
             assert_eq!(a.is_writable, b.is_writable);
         }
     }
+
+    #[test]
+    fn check_account_infos_with_pda() {
+        let program_id = Pubkey::new_unique();
+        let owner = Pubkey::new_unique();
+        let literal = "check";
+
+        let extra_meta = AccountMeta::new_readonly(Pubkey::new_unique(), false);
+        let extra_pda = PodAccountMeta::new_with_seeds(
+            &[
+                Seed::Literal {
+                    bytes: literal.as_bytes().to_vec(),
+                },
+                Seed::InstructionData {
+                    index: 0,
+                    length: 1,
+                },
+                Seed::AccountKey { index: 0 },
+            ],
+            false,
+            true,
+        )
+        .unwrap();
+        let metas = [PodAccountMeta::from(&extra_meta), extra_pda];
+        let account_size = ExtraAccountMetas::size_of(metas.len()).unwrap();
+        let mut buffer = vec![0; account_size];
+        ExtraAccountMetas::init_with_pod_account_metas::<TestInstruction>(&mut buffer, &metas)
+            .unwrap();
+
+        let instruction_data = [7];
+        let ix_pubkey = Pubkey::new_unique();
+        let pda_pubkey = Pubkey::find_program_address(
+            &[literal.as_bytes(), &instruction_data, ix_pubkey.as_ref()],
+            &program_id,
+        )
+        .0;
+
+        let mut ix_lamports = 0;
+        let mut ix_data = [];
+        let mut extra_lamports = 0;
+        let mut extra_data = [];
+        let mut pda_lamports = 0;
+        let mut pda_data = [];
+        let mut account_infos = vec![
+            AccountInfo::new(
+                &ix_pubkey,
+                false,
+                true,
+                &mut ix_lamports,
+                &mut ix_data,
+                &owner,
+                false,
+                Epoch::default(),
+            ),
+            AccountInfo::new(
+                &extra_meta.pubkey,
+                false,
+                false,
+                &mut extra_lamports,
+                &mut extra_data,
+                &owner,
+                false,
+                Epoch::default(),
+            ),
+            AccountInfo::new(
+                &pda_pubkey,
+                false,
+                true,
+                &mut pda_lamports,
+                &mut pda_data,
+                &owner,
+                false,
+                Epoch::default(),
+            ),
+        ];
+
+        ExtraAccountMetas::check_account_infos::<TestInstruction>(
+            &account_infos,
+            &instruction_data,
+            &program_id,
+            &buffer,
+        )
+        .unwrap();
+
+        // wrong instruction data resolves a different PDA
+        assert_eq!(
+            ExtraAccountMetas::check_account_infos::<TestInstruction>(
+                &account_infos,
+                &[8],
+                &program_id,
+                &buffer,
+            )
+            .unwrap_err(),
+            AccountResolutionError::IncorrectAccount.into()
+        );
+
+        // out of order
+        account_infos.swap(1, 2);
+        assert_eq!(
+            ExtraAccountMetas::check_account_infos::<TestInstruction>(
+                &account_infos,
+                &instruction_data,
+                &program_id,
+                &buffer,
+            )
+            .unwrap_err(),
+            AccountResolutionError::IncorrectAccount.into()
+        );
+
+        // not enough accounts
+        assert_eq!(
+            ExtraAccountMetas::check_account_infos::<TestInstruction>(
+                &account_infos[..1],
+                &instruction_data,
+                &program_id,
+                &buffer,
+            )
+            .unwrap_err(),
+            AccountResolutionError::NotEnoughAccounts.into()
+        );
+    }
+
+    #[test]
+    fn update_with_pod_account_metas() {
+        let metas = [
+            PodAccountMeta::from(&AccountMeta::new(Pubkey::new_unique(), false)),
+            PodAccountMeta::from(&AccountMeta::new_readonly(Pubkey::new_unique(), true)),
+        ];
+        let other_metas = [PodAccountMeta::from(&AccountMeta::new(
+            Pubkey::new_unique(),
+            true,
+        ))];
+        let new_metas = [
+            PodAccountMeta::from(&AccountMeta::new_readonly(Pubkey::new_unique(), false)),
+            PodAccountMeta::from(&AccountMeta::new(Pubkey::new_unique(), false)),
+            PodAccountMeta::from(&AccountMeta::new(Pubkey::new_unique(), true)),
+        ];
+
+        let account_size = ExtraAccountMetas::size_of(metas.len()).unwrap()
+            + ExtraAccountMetas::size_of(other_metas.len()).unwrap();
+        let mut buffer = vec![0; account_size];
+        ExtraAccountMetas::init_with_pod_account_metas::<TestInstruction>(&mut buffer, &metas)
+            .unwrap();
+        ExtraAccountMetas::init_with_pod_account_metas::<TestOtherInstruction>(
+            &mut buffer,
+            &other_metas,
+        )
+        .unwrap();
+
+        let check_metas = |buffer: &[u8], expected: &[PodAccountMeta], other: &[PodAccountMeta]| {
+            let state = TlvStateBorrowed::unpack(buffer).unwrap();
+            let unpacked =
+                ExtraAccountMetas::unpack_with_tlv_state::<TestInstruction>(&state).unwrap();
+            assert_eq!(unpacked.data(), expected);
+            let unpacked =
+                ExtraAccountMetas::unpack_with_tlv_state::<TestOtherInstruction>(&state).unwrap();
+            assert_eq!(unpacked.data(), other);
+        };
+
+        // buffer too small to grow
+        assert_eq!(
+            ExtraAccountMetas::update_with_pod_account_metas::<TestInstruction>(
+                &mut buffer,
+                &new_metas
+            )
+            .unwrap_err(),
+            ProgramError::InvalidAccountData,
+        );
+        check_metas(&buffer, &metas, &other_metas);
+
+        // grow, the other entry is moved
+        let new_account_size = ExtraAccountMetas::size_of(new_metas.len()).unwrap()
+            + ExtraAccountMetas::size_of(other_metas.len()).unwrap();
+        buffer.resize(new_account_size, 0);
+        ExtraAccountMetas::update_with_pod_account_metas::<TestInstruction>(
+            &mut buffer,
+            &new_metas,
+        )
+        .unwrap();
+        check_metas(&buffer, &new_metas, &other_metas);
+
+        // shrink, the end of the buffer can be dropped
+        ExtraAccountMetas::update_with_pod_account_metas::<TestInstruction>(&mut buffer, &[])
+            .unwrap();
+        buffer.truncate(
+            ExtraAccountMetas::size_of(0).unwrap()
+                + ExtraAccountMetas::size_of(other_metas.len()).unwrap(),
+        );
+        check_metas(&buffer, &[], &other_metas);
+    }
 }
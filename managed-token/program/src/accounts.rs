@@ -1,4 +1,4 @@
-use crate::assert_with_msg;
+use crate::{assert_with_msg, get_policy_address};
 use solana_program::{
     account_info::{next_account_info, AccountInfo},
     program_error::ProgramError,
@@ -261,6 +261,7 @@ pub struct Transfer<'a, 'info> {
     pub upstream_authority: &'a AccountInfo<'info>,
     pub freeze_authority: &'a AccountInfo<'info>,
     pub token_program: &'a AccountInfo<'info>,
+    pub policy: &'a AccountInfo<'info>,
 }
 
 impl<'a, 'info> Transfer<'a, 'info> {
@@ -274,12 +275,18 @@ impl<'a, 'info> Transfer<'a, 'info> {
             upstream_authority: next_account_info(account_iter)?,
             freeze_authority: next_account_info(account_iter)?,
             token_program: next_account_info(account_iter)?,
+            policy: next_account_info(account_iter)?,
         };
         assert_with_msg(
             ctx.mint.owner == &spl_token::id(),
             ProgramError::IllegalOwner,
             "Mint account must be owned by the Token Program",
         )?;
+        assert_with_msg(
+            ctx.policy.key == &get_policy_address(ctx.mint.key),
+            ProgramError::InvalidInstructionData,
+            "Invalid key supplied for transfer policy",
+        )?;
         assert_with_msg(
             ctx.src_account.owner == &spl_token::id(),
             ProgramError::IllegalOwner,
@@ -494,3 +501,65 @@ impl<'a, 'info> Revoke<'a, 'info> {
         Ok(ctx)
     }
 }
+
+pub struct SetTransferPolicy<'a, 'info> {
+    pub mint: &'a AccountInfo<'info>,
+    pub policy: &'a AccountInfo<'info>,
+    pub payer: &'a AccountInfo<'info>,
+    pub upstream_authority: &'a AccountInfo<'info>,
+    pub system_program: &'a AccountInfo<'info>,
+}
+
+impl<'a, 'info> SetTransferPolicy<'a, 'info> {
+    pub fn load(accounts: &'a [AccountInfo<'info>]) -> Result<Self, ProgramError> {
+        let account_iter = &mut accounts.iter();
+        let ctx = Self {
+            mint: next_account_info(account_iter)?,
+            policy: next_account_info(account_iter)?,
+            payer: next_account_info(account_iter)?,
+            upstream_authority: next_account_info(account_iter)?,
+            system_program: next_account_info(account_iter)?,
+        };
+        assert_with_msg(
+            ctx.mint.owner == &spl_token::id(),
+            ProgramError::IllegalOwner,
+            "Mint account must be owned by the Token Program",
+        )?;
+        assert_with_msg(
+            ctx.policy.key == &get_policy_address(ctx.mint.key),
+            ProgramError::InvalidInstructionData,
+            "Invalid key supplied for transfer policy",
+        )?;
+        assert_with_msg(
+            ctx.policy.data_is_empty() || ctx.policy.owner == &crate::id(),
+            ProgramError::IllegalOwner,
+            "Transfer policy must be owned by the Managed Token Program",
+        )?;
+        assert_with_msg(
+            ctx.system_program.key == &system_program::id(),
+            ProgramError::InvalidInstructionData,
+            "Invalid key supplied for System Program",
+        )?;
+        assert_with_msg(
+            ctx.policy.is_writable,
+            ProgramError::InvalidInstructionData,
+            "Transfer policy must be writable",
+        )?;
+        assert_with_msg(
+            ctx.payer.is_writable,
+            ProgramError::InvalidInstructionData,
+            "Payer account must be writable (lamport balance will change)",
+        )?;
+        assert_with_msg(
+            ctx.payer.is_signer,
+            ProgramError::MissingRequiredSignature,
+            "Payer must sign for modification",
+        )?;
+        assert_with_msg(
+            ctx.upstream_authority.is_signer,
+            ProgramError::MissingRequiredSignature,
+            "Freeze authority must sign for modification",
+        )?;
+        Ok(ctx)
+    }
+}
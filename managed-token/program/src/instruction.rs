@@ -8,7 +8,7 @@ use solana_program::{
 };
 use spl_associated_token_account::get_associated_token_address;
 
-use crate::get_authority;
+use crate::{get_authority, get_policy_address, state::ApprovalExpiry};
 
 #[derive(Debug, Clone, ShankInstruction, BorshSerialize, BorshDeserialize)]
 #[rustfmt::skip]
@@ -45,6 +45,7 @@ pub enum ManagedTokenInstruction {
     #[account(4, signer, name = "upstream_authority")]
     #[account(5, name = "freeze_authority")]
     #[account(6, name = "token_program", desc = "Token program")]
+    #[account(7, name = "policy", desc = "Transfer policy of the mint, may be uninitialized")]
     Transfer { amount: u64 },
 
     #[account(0, writable, name = "mint")]
@@ -87,6 +88,16 @@ pub enum ManagedTokenInstruction {
     #[account(4, name = "freeze_authority")]
     #[account(5, name = "token_program", desc = "Token program")]
     Revoke,
+
+    #[account(0, name = "mint")]
+    #[account(1, writable, name = "policy")]
+    #[account(2, writable, signer, name = "payer")]
+    #[account(3, signer, name = "upstream_authority")]
+    #[account(4, name = "system_program", desc = "System program")]
+    SetTransferPolicy {
+        allowed_destinations: Vec<Pubkey>,
+        approval_expiries: Vec<ApprovalExpiry>,
+    },
 }
 
 pub fn create_initialize_mint_instruction(
@@ -174,6 +185,7 @@ pub fn create_transfer_instruction(
             AccountMeta::new_readonly(*upstream_authority, true),
             AccountMeta::new_readonly(freeze_authority, false),
             AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(get_policy_address(mint), false),
         ],
         data: ManagedTokenInstruction::Transfer { amount }.try_to_vec()?,
     })
@@ -200,6 +212,7 @@ pub fn create_transfer_with_delegate_instruction(
             AccountMeta::new_readonly(*upstream_authority, true),
             AccountMeta::new_readonly(freeze_authority, false),
             AccountMeta::new_readonly(spl_token::id(), false),
+            AccountMeta::new_readonly(get_policy_address(mint), false),
         ],
         data: ManagedTokenInstruction::Transfer { amount }.try_to_vec()?,
     })
@@ -293,3 +306,27 @@ pub fn create_revoke_instruction(
         data: ManagedTokenInstruction::Revoke.try_to_vec()?,
     })
 }
+
+pub fn create_set_transfer_policy_instruction(
+    mint: &Pubkey,
+    payer: &Pubkey,
+    upstream_authority: &Pubkey,
+    allowed_destinations: Vec<Pubkey>,
+    approval_expiries: Vec<ApprovalExpiry>,
+) -> Result<Instruction, ProgramError> {
+    Ok(Instruction {
+        program_id: crate::id(),
+        accounts: vec![
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new(get_policy_address(mint), false),
+            AccountMeta::new(*payer, true),
+            AccountMeta::new_readonly(*upstream_authority, true),
+            AccountMeta::new_readonly(system_program::id(), false),
+        ],
+        data: ManagedTokenInstruction::SetTransferPolicy {
+            allowed_destinations,
+            approval_expiries,
+        }
+        .try_to_vec()?,
+    })
+}
@@ -1,10 +1,19 @@
 solana_program::declare_id!("mTok58Lg4YfcmwqyrDHpf7ogp599WRhzb6PxjaBqAxS");
 
-use borsh::BorshDeserialize;
+use borsh::{BorshDeserialize, BorshSerialize};
 use solana_program::{
-    account_info::AccountInfo, entrypoint::ProgramResult, msg, program::invoke,
-    program_error::ProgramError, program_pack::Pack, pubkey::Pubkey, rent::Rent,
-    system_instruction, sysvar::Sysvar,
+    account_info::AccountInfo,
+    clock::Clock,
+    entrypoint::ProgramResult,
+    msg,
+    program::{invoke, invoke_signed},
+    program_error::ProgramError,
+    program_option::COption,
+    program_pack::Pack,
+    pubkey::Pubkey,
+    rent::Rent,
+    system_instruction,
+    sysvar::Sysvar,
 };
 use spl_associated_token_account::instruction::create_associated_token_account;
 
@@ -22,9 +31,14 @@ pub fn assert_with_msg(v: bool, err: impl Into<ProgramError>, msg: &str) -> Prog
 
 pub mod accounts;
 pub mod instruction;
+pub mod state;
 pub mod token;
-use accounts::{Approve, Burn, Close, InitializeAccount, InitializeMint, Mint, Revoke, Transfer};
+use accounts::{
+    Approve, Burn, Close, InitializeAccount, InitializeMint, Mint, Revoke, SetTransferPolicy,
+    Transfer,
+};
 use instruction::ManagedTokenInstruction;
+use state::{ApprovalExpiry, TransferPolicy};
 use token::{approve, burn, close, freeze, initialize_mint, mint_to, revoke, thaw, transfer};
 
 #[cfg(not(feature = "no-entrypoint"))]
@@ -55,6 +69,22 @@ fn get_authority(upstream_authority: &Pubkey) -> (Pubkey, Vec<Vec<u8>>) {
     (key, seeds)
 }
 
+#[inline]
+fn get_policy(mint: &Pubkey) -> (Pubkey, Vec<Vec<u8>>) {
+    let mut seeds = vec![b"policy".to_vec(), mint.as_ref().to_vec()];
+    let (key, bump) = Pubkey::find_program_address(
+        &seeds.iter().map(|s| s.as_slice()).collect::<Vec<&[u8]>>(),
+        &crate::id(),
+    );
+    seeds.push(vec![bump]);
+    (key, seeds)
+}
+
+/// Address of the transfer policy of a mint, which may be uninitialized
+pub fn get_policy_address(mint: &Pubkey) -> Pubkey {
+    get_policy(mint).0
+}
+
 pub fn process_instruction(
     _program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -94,6 +124,13 @@ pub fn process_instruction(
             msg!("ManagedTokenInstruction::Revoke");
             process_revoke(accounts)
         }
+        ManagedTokenInstruction::SetTransferPolicy {
+            allowed_destinations,
+            approval_expiries,
+        } => {
+            msg!("ManagedTokenInstruction::SetTransferPolicy");
+            process_set_transfer_policy(accounts, allowed_destinations, approval_expiries)
+        }
     }
 }
 
@@ -157,8 +194,10 @@ pub fn process_transfer(accounts: &[AccountInfo], amount: u64) -> ProgramResult
         upstream_authority,
         freeze_authority,
         token_program,
+        policy,
     } = Transfer::load(accounts)?;
     let seeds = get_authority_seeds_checked(upstream_authority.key, freeze_authority.key)?;
+    check_transfer_policy(policy, src_account, dst_account, owner)?;
     thaw(freeze_authority, mint, src_account, token_program, &seeds)?;
     thaw(freeze_authority, mint, dst_account, token_program, &seeds)?;
     transfer(src_account, dst_account, owner, token_program, amount)?;
@@ -259,3 +298,91 @@ pub fn process_revoke(accounts: &[AccountInfo]) -> ProgramResult {
     revoke(token_account, owner, token_program)?;
     freeze(freeze_authority, mint, token_account, token_program, &seeds)
 }
+
+/// Check a transfer against the policy of its mint, if there is one. `owner`
+/// is either the owner of the source account or a delegate.
+fn check_transfer_policy(
+    policy: &AccountInfo,
+    src_account: &AccountInfo,
+    dst_account: &AccountInfo,
+    owner: &AccountInfo,
+) -> ProgramResult {
+    if policy.data_is_empty() {
+        return Ok(());
+    }
+    assert_with_msg(
+        policy.owner == &crate::id(),
+        ProgramError::IllegalOwner,
+        "Transfer policy must be owned by the Managed Token Program",
+    )?;
+    let policy = TransferPolicy::deserialize(&mut &policy.data.borrow()[..])?;
+    let src = spl_token::state::Account::unpack(&src_account.data.borrow())?;
+    let dst = spl_token::state::Account::unpack(&dst_account.data.borrow())?;
+    assert_with_msg(
+        policy.is_destination_allowed(&dst.owner),
+        ProgramError::InvalidArgument,
+        "Destination is not allowed by the transfer policy",
+    )?;
+    if owner.key != &src.owner {
+        assert_with_msg(
+            policy.is_delegate_active(owner.key, Clock::get()?.unix_timestamp),
+            ProgramError::InvalidArgument,
+            "Delegate approval is missing or expired in the transfer policy",
+        )?;
+    }
+    Ok(())
+}
+
+pub fn process_set_transfer_policy(
+    accounts: &[AccountInfo],
+    allowed_destinations: Vec<Pubkey>,
+    approval_expiries: Vec<ApprovalExpiry>,
+) -> ProgramResult {
+    let SetTransferPolicy {
+        mint,
+        policy,
+        payer,
+        upstream_authority,
+        system_program,
+    } = SetTransferPolicy::load(accounts)?;
+    let (authority, _) = get_authority(upstream_authority.key);
+    let mint_state = spl_token::state::Mint::unpack(&mint.data.borrow())?;
+    assert_with_msg(
+        mint_state.freeze_authority == COption::Some(authority),
+        ProgramError::InvalidArgument,
+        "Mint is not managed by the upstream authority",
+    )?;
+
+    let data = TransferPolicy {
+        allowed_destinations,
+        approval_expiries,
+    }
+    .try_to_vec()?;
+    let required_lamports = Rent::get()?
+        .minimum_balance(data.len())
+        .saturating_sub(policy.lamports());
+    if required_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(payer.key, policy.key, required_lamports),
+            &[payer.clone(), policy.clone(), system_program.clone()],
+        )?;
+    }
+    if policy.data_is_empty() {
+        let (_, seeds) = get_policy(mint.key);
+        let signer_seeds = seeds.iter().map(|s| s.as_slice()).collect::<Vec<&[u8]>>();
+        invoke_signed(
+            &system_instruction::allocate(policy.key, data.len() as u64),
+            &[policy.clone(), system_program.clone()],
+            &[&signer_seeds],
+        )?;
+        invoke_signed(
+            &system_instruction::assign(policy.key, &crate::id()),
+            &[policy.clone(), system_program.clone()],
+            &[&signer_seeds],
+        )?;
+    } else {
+        policy.realloc(data.len(), false)?;
+    }
+    policy.data.borrow_mut().copy_from_slice(&data);
+    Ok(())
+}
@@ -0,0 +1,34 @@
+use borsh::{BorshDeserialize, BorshSerialize};
+use shank::ShankAccount;
+use solana_program::{clock::UnixTimestamp, pubkey::Pubkey};
+
+/// Time until which a delegate may transfer tokens under a transfer policy
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct ApprovalExpiry {
+    pub delegate: Pubkey,
+    pub expires_at: UnixTimestamp,
+}
+
+/// Transfer policy of a mint, stored at `get_policy_address(mint)`
+///
+/// Once a mint has a policy, transfers can only go to the owners in
+/// `allowed_destinations`, or anywhere if it is empty, and delegates can only
+/// transfer until their expiry in `approval_expiries`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, BorshSerialize, BorshDeserialize, ShankAccount)]
+pub struct TransferPolicy {
+    pub allowed_destinations: Vec<Pubkey>,
+    pub approval_expiries: Vec<ApprovalExpiry>,
+}
+
+impl TransferPolicy {
+    pub fn is_destination_allowed(&self, destination_owner: &Pubkey) -> bool {
+        self.allowed_destinations.is_empty()
+            || self.allowed_destinations.contains(destination_owner)
+    }
+
+    pub fn is_delegate_active(&self, delegate: &Pubkey, unix_timestamp: UnixTimestamp) -> bool {
+        self.approval_expiries
+            .iter()
+            .any(|approval| approval.delegate == *delegate && unix_timestamp < approval.expires_at)
+    }
+}
@@ -2,19 +2,19 @@ use solana_program::program_option::COption;
 use solana_program_test::*;
 use solana_sdk::{
     commitment_config::CommitmentLevel,
-    instruction::Instruction,
+    instruction::{AccountMeta, Instruction, InstructionError},
     native_token::LAMPORTS_PER_SOL,
     pubkey::Pubkey,
     signature::Signature,
     signature::{Keypair, Signer},
     system_instruction,
-    transaction::Transaction,
+    transaction::{Transaction, TransactionError},
 };
 
 use spl_associated_token_account::{
     get_associated_token_address, instruction::create_associated_token_account,
 };
-use spl_managed_token::instruction::*;
+use spl_managed_token::{instruction::*, state::ApprovalExpiry};
 use spl_token::state::Account as TokenAccount;
 
 pub fn sol(amount: f64) -> u64 {
@@ -311,3 +311,242 @@ async fn test_spl_managed_token_with_delegate_transfer() {
             == 1
     );
 }
+
+#[tokio::test]
+async fn test_spl_managed_token_with_transfer_policy() {
+    let mut context = spl_managed_token_test().start_with_context().await;
+    let lwc = &mut context.banks_client;
+    let authority = Keypair::new();
+    transfer(lwc, &context.payer, &authority.pubkey(), sol(10.0))
+        .await
+        .unwrap();
+    let mint = Keypair::new();
+    let mint_key = mint.pubkey();
+    let create_ix =
+        create_initialize_mint_instruction(&mint_key, &authority.pubkey(), &authority.pubkey(), 0)
+            .unwrap();
+    process_transaction(lwc, vec![create_ix], vec![&authority, &mint])
+        .await
+        .unwrap();
+
+    let alice = Keypair::new();
+    let alice_key = alice.pubkey();
+    let bob = Keypair::new();
+    let bob_key = bob.pubkey();
+    let eve = Keypair::new();
+    let eve_key = eve.pubkey();
+    let market = Keypair::new();
+    let market_key = market.pubkey();
+    let stale_market = Keypair::new();
+    let stale_market_key = stale_market.pubkey();
+
+    for k in [&alice_key, &bob_key, &eve_key] {
+        transfer(lwc, &context.payer, k, sol(1.0)).await.unwrap();
+        let create_ata = create_initialize_account_instruction(
+            &mint_key,
+            k,
+            &authority.pubkey(),
+            &authority.pubkey(),
+        )
+        .unwrap();
+        process_transaction(lwc, vec![create_ata], vec![&authority])
+            .await
+            .unwrap();
+    }
+    let mint_to_ix =
+        create_mint_to_instruction(&mint_key, &alice_key, &authority.pubkey(), 10).unwrap();
+    process_transaction(lwc, vec![mint_to_ix], vec![&authority])
+        .await
+        .unwrap();
+
+    // Mints without a policy pass its uninitialized address
+    let eve_ix =
+        create_transfer_instruction(&alice_key, &eve_key, &mint_key, &authority.pubkey(), 1)
+            .unwrap();
+    process_transaction(lwc, vec![eve_ix], vec![&alice, &authority])
+        .await
+        .unwrap();
+
+    // Only the upstream authority of the mint can set its policy
+    let impostor = Keypair::new();
+    transfer(lwc, &context.payer, &impostor.pubkey(), sol(1.0))
+        .await
+        .unwrap();
+    let impostor_policy_ix = create_set_transfer_policy_instruction(
+        &mint_key,
+        &impostor.pubkey(),
+        &impostor.pubkey(),
+        vec![],
+        vec![],
+    )
+    .unwrap();
+    assert_eq!(
+        process_transaction(lwc, vec![impostor_policy_ix], vec![&impostor])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+    );
+
+    let policy_ix = create_set_transfer_policy_instruction(
+        &mint_key,
+        &authority.pubkey(),
+        &authority.pubkey(),
+        vec![bob_key],
+        vec![
+            ApprovalExpiry {
+                delegate: market_key,
+                expires_at: i64::MAX,
+            },
+            ApprovalExpiry {
+                delegate: stale_market_key,
+                expires_at: 1,
+            },
+        ],
+    )
+    .unwrap();
+    process_transaction(lwc, vec![policy_ix], vec![&authority])
+        .await
+        .unwrap();
+
+    // The policy cannot be skipped by leaving it out or passing another account
+    let mut bob_ix =
+        create_transfer_instruction(&alice_key, &bob_key, &mint_key, &authority.pubkey(), 1)
+            .unwrap();
+    bob_ix.accounts.pop();
+    assert_eq!(
+        process_transaction(lwc, vec![bob_ix.clone()], vec![&alice, &authority])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+    bob_ix
+        .accounts
+        .push(AccountMeta::new_readonly(Pubkey::new_unique(), false));
+    assert_eq!(
+        process_transaction(lwc, vec![bob_ix], vec![&alice, &authority])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidInstructionData)
+    );
+
+    // Destinations must be allowed
+    let eve_ix =
+        create_transfer_instruction(&alice_key, &eve_key, &mint_key, &authority.pubkey(), 1)
+            .unwrap();
+    assert_eq!(
+        process_transaction(lwc, vec![eve_ix], vec![&alice, &authority])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+    );
+    let bob_ix =
+        create_transfer_instruction(&alice_key, &bob_key, &mint_key, &authority.pubkey(), 1)
+            .unwrap();
+    process_transaction(lwc, vec![bob_ix], vec![&alice, &authority])
+        .await
+        .unwrap();
+
+    // Delegates must have an approval that has not expired
+    let approve_market_ix =
+        create_approve_instruction(&mint_key, &alice_key, &market_key, &authority.pubkey(), 5)
+            .unwrap();
+    process_transaction(lwc, vec![approve_market_ix], vec![&alice, &authority])
+        .await
+        .unwrap();
+    let market_eve_ix = create_transfer_with_delegate_instruction(
+        &alice_key,
+        &eve_key,
+        &market_key,
+        &mint_key,
+        &authority.pubkey(),
+        2,
+    )
+    .unwrap();
+    assert_eq!(
+        process_transaction(lwc, vec![market_eve_ix], vec![&authority, &market])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+    );
+    let market_bob_ix = create_transfer_with_delegate_instruction(
+        &alice_key,
+        &bob_key,
+        &market_key,
+        &mint_key,
+        &authority.pubkey(),
+        2,
+    )
+    .unwrap();
+    process_transaction(lwc, vec![market_bob_ix], vec![&authority, &market])
+        .await
+        .unwrap();
+
+    let approve_stale_ix = create_approve_instruction(
+        &mint_key,
+        &alice_key,
+        &stale_market_key,
+        &authority.pubkey(),
+        5,
+    )
+    .unwrap();
+    process_transaction(lwc, vec![approve_stale_ix], vec![&alice, &authority])
+        .await
+        .unwrap();
+    let stale_bob_ix = create_transfer_with_delegate_instruction(
+        &alice_key,
+        &bob_key,
+        &stale_market_key,
+        &mint_key,
+        &authority.pubkey(),
+        3,
+    )
+    .unwrap();
+    assert_eq!(
+        process_transaction(lwc, vec![stale_bob_ix], vec![&authority, &stale_market])
+            .await
+            .unwrap_err()
+            .unwrap(),
+        TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+    );
+
+    assert!(
+        lwc.get_packed_account_data::<TokenAccount>(get_associated_token_address(
+            &bob_key, &mint_key
+        ))
+        .await
+        .unwrap()
+        .amount
+            == 3
+    );
+
+    // Updating the policy can open up new destinations
+    let policy_ix = create_set_transfer_policy_instruction(
+        &mint_key,
+        &authority.pubkey(),
+        &authority.pubkey(),
+        vec![bob_key, eve_key],
+        vec![],
+    )
+    .unwrap();
+    let eve_ix =
+        create_transfer_instruction(&alice_key, &eve_key, &mint_key, &authority.pubkey(), 4)
+            .unwrap();
+    process_transaction(lwc, vec![policy_ix, eve_ix], vec![&alice, &authority])
+        .await
+        .unwrap();
+
+    assert!(
+        lwc.get_packed_account_data::<TokenAccount>(get_associated_token_address(
+            &eve_key, &mint_key
+        ))
+        .await
+        .unwrap()
+        .amount
+            == 5
+    );
+}
@@ -1,5 +1,7 @@
 import { PublicKey } from '@solana/web3.js'
+export * from './accounts'
 export * from './instructions'
+export * from './types'
 
 /**
  * Program address
@@ -42,6 +42,7 @@ export const TransferStruct = new beet.BeetArgsStruct<
  * @property [**signer**] owner
  * @property [**signer**] upstreamAuthority
  * @property [] freezeAuthority
+ * @property [] policy Transfer policy of the mint, may be uninitialized
  * @category Instructions
  * @category Transfer
  * @category generated
@@ -54,6 +55,7 @@ export type TransferInstructionAccounts = {
   upstreamAuthority: web3.PublicKey
   freezeAuthority: web3.PublicKey
   tokenProgram?: web3.PublicKey
+  policy: web3.PublicKey
 }
 
 export const transferInstructionDiscriminator = 2
@@ -113,6 +115,11 @@ export function createTransferInstruction(
       isWritable: false,
       isSigner: false,
     },
+    {
+      pubkey: accounts.policy,
+      isWritable: false,
+      isSigner: false,
+    },
   ]
 
   const ix = new web3.TransactionInstruction({
@@ -5,4 +5,5 @@ export * from './InitializeAccount'
 export * from './InitializeMint'
 export * from './MintTo'
 export * from './Revoke'
+export * from './SetTransferPolicy'
 export * from './Transfer'
@@ -6,7 +6,7 @@ use {
         borsh::get_instance_packed_len,
         entrypoint::ProgramResult,
         msg,
-        program::set_return_data,
+        program::{set_return_data, MAX_RETURN_DATA},
         program_error::ProgramError,
         program_option::COption,
         pubkey::Pubkey,
@@ -15,12 +15,14 @@ use {
     spl_token_metadata_interface::{
         error::TokenMetadataError,
         instruction::{
-            Emit, Initialize, RemoveKey, TokenMetadataInstruction, UpdateAuthority, UpdateField,
+            BatchUpdateFields, Emit, EmitField, Initialize, LockField, RemoveKey,
+            TokenMetadataInstruction, UpdateAuthority, UpdateField,
         },
-        state::{OptionalNonZeroPubkey, TokenMetadata},
+        state::{Field, FieldLocks, OptionalNonZeroPubkey, TokenMetadata},
     },
-    spl_type_length_value::state::{
-        realloc_and_pack_first_variable_len, TlvState, TlvStateBorrowed, TlvStateMut,
+    spl_type_length_value::{
+        state::{realloc_and_pack_first_variable_len, TlvState, TlvStateBorrowed, TlvStateMut},
+        variable_len_pack::VariableLenPack,
     },
 };
 
@@ -39,6 +41,14 @@ fn check_update_authority(
     Ok(())
 }
 
+/// Gets the locked fields stored next to the metadata, if any
+fn get_field_locks(state: &TlvStateBorrowed) -> Result<Option<FieldLocks>, ProgramError> {
+    match state.get_first_bytes::<FieldLocks>() {
+        Ok(bytes) => FieldLocks::unpack_from_slice(bytes).map(Some),
+        Err(_) => Ok(None),
+    }
+}
+
 /// Processes a [Initialize](enum.TokenMetadataInstruction.html) instruction.
 pub fn process_initialize(
     _program_id: &Pubkey,
@@ -101,13 +111,17 @@ pub fn process_update_field(
 
     // deserialize the metadata, but scope the data borrow since we'll probably
     // realloc the account
-    let mut token_metadata = {
+    let (mut token_metadata, field_locks) = {
         let buffer = metadata_info.try_borrow_data()?;
         let state = TlvStateBorrowed::unpack(&buffer)?;
-        state.get_first_variable_len_value::<TokenMetadata>()?
+        (
+            state.get_first_variable_len_value::<TokenMetadata>()?,
+            get_field_locks(&state)?.unwrap_or_default(),
+        )
     };
 
     check_update_authority(update_authority_info, &token_metadata.update_authority)?;
+    field_locks.check_field(&data.field)?;
 
     // Update the field
     token_metadata.update(data.field, data.value);
@@ -130,13 +144,17 @@ pub fn process_remove_key(
 
     // deserialize the metadata, but scope the data borrow since we'll probably
     // realloc the account
-    let mut token_metadata = {
+    let (mut token_metadata, field_locks) = {
         let buffer = metadata_info.try_borrow_data()?;
         let state = TlvStateBorrowed::unpack(&buffer)?;
-        state.get_first_variable_len_value::<TokenMetadata>()?
+        (
+            state.get_first_variable_len_value::<TokenMetadata>()?,
+            get_field_locks(&state)?.unwrap_or_default(),
+        )
     };
 
     check_update_authority(update_authority_info, &token_metadata.update_authority)?;
+    field_locks.check_field(&Field::Key(data.key.clone()))?;
     if !token_metadata.remove_key(&data.key) && !data.idempotent {
         return Err(TokenMetadataError::KeyNotFound.into());
     }
@@ -171,6 +189,96 @@ pub fn process_update_authority(
     Ok(())
 }
 
+/// Processes a [BatchUpdateFields](enum.TokenMetadataInstruction.html) instruction.
+pub fn process_batch_update_fields(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: BatchUpdateFields,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let metadata_info = next_account_info(account_info_iter)?;
+    let update_authority_info = next_account_info(account_info_iter)?;
+
+    // deserialize the metadata, but scope the data borrow since we'll probably
+    // realloc the account
+    let (mut token_metadata, field_locks) = {
+        let buffer = metadata_info.try_borrow_data()?;
+        let state = TlvStateBorrowed::unpack(&buffer)?;
+        (
+            state.get_first_variable_len_value::<TokenMetadata>()?,
+            get_field_locks(&state)?.unwrap_or_default(),
+        )
+    };
+
+    check_update_authority(update_authority_info, &token_metadata.update_authority)?;
+    field_locks.check_updates(&data.updates)?;
+
+    // Apply all updates before touching the account, so that it's only
+    // resized once
+    token_metadata.apply_updates(data.updates)?;
+
+    realloc_and_pack_first_variable_len(metadata_info, &token_metadata)?;
+
+    Ok(())
+}
+
+/// Processes a [LockField](enum.TokenMetadataInstruction.html) instruction.
+pub fn process_lock_field(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: LockField,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let metadata_info = next_account_info(account_info_iter)?;
+    let update_authority_info = next_account_info(account_info_iter)?;
+
+    // deserialize the metadata and locks, but scope the data borrow since we'll
+    // probably realloc the account
+    let (token_metadata, field_locks) = {
+        let buffer = metadata_info.try_borrow_data()?;
+        let state = TlvStateBorrowed::unpack(&buffer)?;
+        (
+            state.get_first_variable_len_value::<TokenMetadata>()?,
+            get_field_locks(&state)?,
+        )
+    };
+
+    check_update_authority(update_authority_info, &token_metadata.update_authority)?;
+    if let Field::Key(ref key) = data.field {
+        if !token_metadata
+            .additional_metadata
+            .iter()
+            .any(|(k, _)| k == key)
+        {
+            return Err(TokenMetadataError::KeyNotFound.into());
+        }
+    }
+
+    match field_locks {
+        Some(mut field_locks) => {
+            if field_locks.lock(data.field) {
+                realloc_and_pack_first_variable_len(metadata_info, &field_locks)?;
+            }
+        }
+        None => {
+            // first lock, so allocate a new TLV entry at the end of the account,
+            // assumes that there's enough SOL for the new rent-exemption
+            let mut field_locks = FieldLocks::default();
+            field_locks.lock(data.field);
+            let new_account_len = metadata_info
+                .try_data_len()?
+                .checked_add(field_locks.tlv_size_of()?)
+                .ok_or(ProgramError::InvalidAccountData)?;
+            metadata_info.realloc(new_account_len, true)?;
+            let mut buffer = metadata_info.try_borrow_mut_data()?;
+            let mut state = TlvStateMut::unpack(&mut buffer)?;
+            state.alloc_and_pack_variable_len_entry(&field_locks, false)?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Processes an [Emit](enum.TokenMetadataInstruction.html) instruction.
 pub fn process_emit(program_id: &Pubkey, accounts: &[AccountInfo], data: Emit) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
@@ -191,6 +299,33 @@ pub fn process_emit(program_id: &Pubkey, accounts: &[AccountInfo], data: Emit) -
     Ok(())
 }
 
+/// Processes an [EmitField](enum.TokenMetadataInstruction.html) instruction.
+pub fn process_emit_field(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: EmitField,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let metadata_info = next_account_info(account_info_iter)?;
+
+    if metadata_info.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let buffer = metadata_info.try_borrow_data()?;
+    let state = TlvStateBorrowed::unpack(&buffer)?;
+    let metadata_bytes = state.get_first_bytes::<TokenMetadata>()?;
+
+    let value = TokenMetadata::get_field_value_from_bytes(metadata_bytes, &data.field)?
+        .ok_or::<ProgramError>(TokenMetadataError::KeyNotFound.into())?;
+    if value.len() > MAX_RETURN_DATA {
+        return Err(ProgramError::InvalidArgument);
+    }
+    set_return_data(value.as_bytes());
+
+    Ok(())
+}
+
 /// Processes an [Instruction](enum.Instruction.html).
 pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
     let instruction = TokenMetadataInstruction::unpack(input)?;
@@ -216,5 +351,17 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> P
             msg!("Instruction: Emit");
             process_emit(program_id, accounts, data)
         }
+        TokenMetadataInstruction::BatchUpdateFields(data) => {
+            msg!("Instruction: BatchUpdateFields");
+            process_batch_update_fields(program_id, accounts, data)
+        }
+        TokenMetadataInstruction::LockField(data) => {
+            msg!("Instruction: LockField");
+            process_lock_field(program_id, accounts, data)
+        }
+        TokenMetadataInstruction::EmitField(data) => {
+            msg!("Instruction: EmitField");
+            process_emit_field(program_id, accounts, data)
+        }
     }
 }
@@ -0,0 +1,303 @@
+#![cfg(feature = "test-sbf")]
+
+mod program_test;
+use {
+    program_test::{setup, setup_metadata, setup_mint},
+    solana_program_test::{tokio, ProgramTestBanksClientExt},
+    solana_sdk::{
+        instruction::InstructionError,
+        pubkey::Pubkey,
+        signature::Signer,
+        signer::keypair::Keypair,
+        system_instruction,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token_metadata_interface::{
+        error::TokenMetadataError,
+        instruction::batch_update_fields,
+        state::{Field, FieldUpdate, TokenMetadata},
+    },
+    spl_type_length_value::state::{TlvState, TlvStateBorrowed},
+};
+
+#[tokio::test]
+async fn success_batch_update() {
+    let program_id = Pubkey::new_unique();
+    let (context, client, payer) = setup(&program_id).await;
+
+    let mint_authority = Keypair::new();
+    let mint_authority_pubkey = mint_authority.pubkey();
+
+    let token_program_id = spl_token_2022::id();
+    let decimals = 2;
+    let token = setup_mint(
+        &token_program_id,
+        &mint_authority_pubkey,
+        decimals,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+    let mut context = context.lock().await;
+
+    let update_authority = Keypair::new();
+    let name = "MySuperCoolToken".to_string();
+    let symbol = "MINE".to_string();
+    let uri = "my.super.cool.token".to_string();
+    let mut token_metadata = TokenMetadata {
+        name,
+        symbol,
+        uri,
+        update_authority: Some(update_authority.pubkey()).try_into().unwrap(),
+        mint: *token.get_address(),
+        ..Default::default()
+    };
+
+    let metadata_keypair = Keypair::new();
+    let metadata_pubkey = metadata_keypair.pubkey();
+
+    setup_metadata(
+        &mut context,
+        &program_id,
+        token.get_address(),
+        &token_metadata,
+        &metadata_keypair,
+        &mint_authority,
+    )
+    .await;
+
+    let mut updates = vec![
+        FieldUpdate::Set {
+            field: Field::Name,
+            value: "My even cooler token".to_string(),
+        },
+        FieldUpdate::Set {
+            field: Field::Symbol,
+            value: "COOL".to_string(),
+        },
+        FieldUpdate::Set {
+            field: Field::Uri,
+            value: "my.even.cooler.token".to_string(),
+        },
+    ];
+    for (key, value) in [
+        ("collection", "Cool Collection"),
+        ("edition", "1"),
+        ("max_editions", "100"),
+        ("artist", "Me"),
+        ("temporary", "Gone soon"),
+    ] {
+        updates.push(FieldUpdate::Set {
+            field: Field::Key(key.to_string()),
+            value: value.to_string(),
+        });
+    }
+    updates.push(FieldUpdate::Remove {
+        key: "temporary".to_string(),
+        idempotent: false,
+    });
+
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let old_space = token_metadata.tlv_size_of().unwrap();
+    let old_rent_lamports = rent.minimum_balance(old_space);
+    token_metadata.apply_updates(updates.clone()).unwrap();
+    let new_space = token_metadata.tlv_size_of().unwrap();
+    let new_rent_lamports = rent.minimum_balance(new_space);
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(
+                &payer.pubkey(),
+                &metadata_pubkey,
+                new_rent_lamports.saturating_sub(old_rent_lamports),
+            ),
+            batch_update_fields(
+                &program_id,
+                &metadata_pubkey,
+                &update_authority.pubkey(),
+                updates,
+            ),
+        ],
+        Some(&payer.pubkey()),
+        &[&payer, &update_authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // check that the data is correct
+    let fetched_metadata_account = context
+        .banks_client
+        .get_account(metadata_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(fetched_metadata_account.data.len(), new_space);
+    let fetched_metadata_state = TlvStateBorrowed::unpack(&fetched_metadata_account.data).unwrap();
+    let fetched_metadata = fetched_metadata_state
+        .get_first_variable_len_value::<TokenMetadata>()
+        .unwrap();
+    assert_eq!(fetched_metadata, token_metadata);
+    assert_eq!(fetched_metadata.additional_metadata.len(), 4);
+
+    // refresh blockhash before trying again
+    let last_blockhash = context.last_blockhash;
+    let last_blockhash = context
+        .banks_client
+        .get_new_latest_blockhash(&last_blockhash)
+        .await
+        .unwrap();
+
+    // a failing update aborts the whole batch
+    let transaction = Transaction::new_signed_with_payer(
+        &[batch_update_fields(
+            &program_id,
+            &metadata_pubkey,
+            &update_authority.pubkey(),
+            vec![
+                FieldUpdate::Set {
+                    field: Field::Name,
+                    value: "Short".to_string(),
+                },
+                FieldUpdate::Remove {
+                    key: "temporary".to_string(),
+                    idempotent: false,
+                },
+            ],
+        )],
+        Some(&payer.pubkey()),
+        &[&payer, &update_authority],
+        last_blockhash,
+    );
+    let error = context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TokenMetadataError::KeyNotFound as u32)
+        )
+    );
+
+    let fetched_metadata_account = context
+        .banks_client
+        .get_account(metadata_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    let fetched_metadata_state = TlvStateBorrowed::unpack(&fetched_metadata_account.data).unwrap();
+    let fetched_metadata = fetched_metadata_state
+        .get_first_variable_len_value::<TokenMetadata>()
+        .unwrap();
+    assert_eq!(fetched_metadata, token_metadata);
+}
+
+#[tokio::test]
+async fn fail_authority_checks() {
+    let program_id = Pubkey::new_unique();
+    let (context, client, payer) = setup(&program_id).await;
+
+    let mint_authority = Keypair::new();
+    let mint_authority_pubkey = mint_authority.pubkey();
+
+    let token_program_id = spl_token_2022::id();
+    let decimals = 2;
+    let token = setup_mint(
+        &token_program_id,
+        &mint_authority_pubkey,
+        decimals,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+    let mut context = context.lock().await;
+
+    let update_authority = Keypair::new();
+    let name = "MySuperCoolToken".to_string();
+    let symbol = "MINE".to_string();
+    let uri = "my.super.cool.token".to_string();
+    let token_metadata = TokenMetadata {
+        name,
+        symbol,
+        uri,
+        update_authority: Some(update_authority.pubkey()).try_into().unwrap(),
+        mint: *token.get_address(),
+        ..Default::default()
+    };
+
+    let metadata_keypair = Keypair::new();
+    let metadata_pubkey = metadata_keypair.pubkey();
+
+    setup_metadata(
+        &mut context,
+        &program_id,
+        token.get_address(),
+        &token_metadata,
+        &metadata_keypair,
+        &mint_authority,
+    )
+    .await;
+
+    let updates = vec![FieldUpdate::Set {
+        field: Field::Name,
+        value: "new_name".to_string(),
+    }];
+
+    // no signature
+    let mut instruction = batch_update_fields(
+        &program_id,
+        &metadata_pubkey,
+        &update_authority.pubkey(),
+        updates.clone(),
+    );
+    instruction.accounts[1].is_signer = false;
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&payer.pubkey()),
+        &[payer.as_ref()],
+        context.last_blockhash,
+    );
+    let error = context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature,)
+    );
+
+    // wrong authority
+    let transaction = Transaction::new_signed_with_payer(
+        &[batch_update_fields(
+            &program_id,
+            &metadata_pubkey,
+            &payer.pubkey(),
+            updates,
+        )],
+        Some(&payer.pubkey()),
+        &[payer.as_ref()],
+        context.last_blockhash,
+    );
+    let error = context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap_err()
+        .unwrap();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TokenMetadataError::IncorrectUpdateAuthority as u32),
+        )
+    );
+}
@@ -2,14 +2,17 @@
 
 mod program_test;
 use {
-    program_test::{setup, setup_metadata, setup_mint},
+    program_test::{setup, setup_metadata, setup_mint, setup_update_field},
     solana_program_test::tokio,
     solana_sdk::{
-        borsh::try_from_slice_unchecked, program::MAX_RETURN_DATA, pubkey::Pubkey,
-        signature::Signer, signer::keypair::Keypair, transaction::Transaction,
+        borsh::try_from_slice_unchecked, instruction::Instruction, program::MAX_RETURN_DATA,
+        pubkey::Pubkey, signature::Signer, signer::keypair::Keypair, transaction::Transaction,
     },
     spl_token_metadata_interface::{
-        borsh::BorshSerialize, instruction::emit, state::TokenMetadata,
+        borsh::BorshSerialize,
+        instruction::emit,
+        offchain::{read_token_metadata, read_token_metadata_field, ReturnDataResult},
+        state::{Field, TokenMetadata},
     },
     test_case::test_case,
 };
@@ -20,6 +23,7 @@ use {
 #[test_case(Some(50), Some(49) ; "wrong way")]
 #[test_case(Some(50), None ; "truncate start")]
 #[test_case(None, Some(50) ; "truncate end")]
+#[test_case(Some(50), Some(1_000_000) ; "clamped end")]
 #[test_case(None, None ; "full data")]
 #[tokio::test]
 async fn success(start: Option<u64>, end: Option<u64>) {
@@ -104,3 +108,117 @@ async fn success(start: Option<u64>, end: Option<u64>) {
         assert!(simulation.simulation_details.unwrap().return_data.is_none());
     }
 }
+
+#[tokio::test]
+async fn read_paginated() {
+    let program_id = Pubkey::new_unique();
+    let (context, client, payer) = setup(&program_id).await;
+
+    let mint_authority = Keypair::new();
+    let mint_authority_pubkey = mint_authority.pubkey();
+
+    let token_program_id = spl_token_2022::id();
+    let decimals = 2;
+    let token = setup_mint(
+        &token_program_id,
+        &mint_authority_pubkey,
+        decimals,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+    let mut context = context.lock().await;
+
+    let update_authority = Keypair::new();
+    let name = "MySuperCoolToken".to_string();
+    let symbol = "MINE".to_string();
+    let uri = "my.super.cool.token".to_string();
+    let mut token_metadata = TokenMetadata {
+        name,
+        symbol,
+        uri,
+        update_authority: Some(update_authority.pubkey()).try_into().unwrap(),
+        mint: *token.get_address(),
+        ..Default::default()
+    };
+
+    let metadata_keypair = Keypair::new();
+    let metadata_pubkey = metadata_keypair.pubkey();
+
+    setup_metadata(
+        &mut context,
+        &program_id,
+        token.get_address(),
+        &token_metadata,
+        &metadata_keypair,
+        &mint_authority,
+    )
+    .await;
+
+    // grow the metadata past the return data limit, with zeroes crossing the
+    // end of the first page, which the runtime trims
+    for (key, value) in [
+        ("key1", "a".repeat(450)),
+        ("key2", "\0".repeat(450)),
+        ("key3", "c".repeat(450)),
+    ] {
+        setup_update_field(
+            &mut context,
+            &program_id,
+            &mut token_metadata,
+            &metadata_pubkey,
+            &update_authority,
+            Field::Key(key.to_string()),
+            value,
+        )
+        .await;
+    }
+    assert!(token_metadata.try_to_vec().unwrap().len() > MAX_RETURN_DATA);
+
+    let banks_client = context.banks_client.clone();
+    let last_blockhash = context.last_blockhash;
+    let simulate = |instruction: Instruction| {
+        let mut banks_client = banks_client.clone();
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&payer.pubkey()),
+            &[payer.as_ref()],
+            last_blockhash,
+        );
+        async move {
+            let simulation = banks_client.simulate_transaction(transaction).await?;
+            if let Some(Err(error)) = simulation.result {
+                return Err(error.into());
+            }
+            ReturnDataResult::Ok(
+                simulation
+                    .simulation_details
+                    .and_then(|details| details.return_data)
+                    .map(|return_data| return_data.data),
+            )
+        }
+    };
+
+    let emitted_token_metadata = read_token_metadata(&simulate, &program_id, &metadata_pubkey)
+        .await
+        .unwrap();
+    assert_eq!(emitted_token_metadata, token_metadata);
+
+    let value = read_token_metadata_field(
+        &simulate,
+        &program_id,
+        &metadata_pubkey,
+        Field::Key("key3".to_string()),
+    )
+    .await
+    .unwrap();
+    assert_eq!(value, "c".repeat(450));
+    read_token_metadata_field(
+        &simulate,
+        &program_id,
+        &metadata_pubkey,
+        Field::Key("missing".to_string()),
+    )
+    .await
+    .unwrap_err();
+}
@@ -0,0 +1,168 @@
+#![cfg(feature = "test-sbf")]
+
+mod program_test;
+use {
+    program_test::{setup, setup_metadata, setup_mint, setup_update_field},
+    solana_program_test::tokio,
+    solana_sdk::{
+        account::Account,
+        instruction::InstructionError,
+        program::MAX_RETURN_DATA,
+        pubkey::Pubkey,
+        signature::Signer,
+        signer::keypair::Keypair,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token_metadata_interface::{
+        error::TokenMetadataError,
+        instruction::emit_field,
+        state::{Field, TokenMetadata},
+    },
+    spl_type_length_value::state::TlvStateMut,
+};
+
+#[tokio::test]
+async fn success_and_failures() {
+    let program_id = Pubkey::new_unique();
+    let (context, client, payer) = setup(&program_id).await;
+
+    let mint_authority = Keypair::new();
+    let mint_authority_pubkey = mint_authority.pubkey();
+
+    let token_program_id = spl_token_2022::id();
+    let decimals = 2;
+    let token = setup_mint(
+        &token_program_id,
+        &mint_authority_pubkey,
+        decimals,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+    let mut context = context.lock().await;
+
+    let update_authority = Keypair::new();
+    let name = "MySuperCoolToken".to_string();
+    let symbol = "MINE".to_string();
+    let uri = "my.super.cool.token".to_string();
+    let mut token_metadata = TokenMetadata {
+        name,
+        symbol,
+        uri,
+        update_authority: Some(update_authority.pubkey()).try_into().unwrap(),
+        mint: *token.get_address(),
+        ..Default::default()
+    };
+
+    let metadata_keypair = Keypair::new();
+    let metadata_pubkey = metadata_keypair.pubkey();
+
+    setup_metadata(
+        &mut context,
+        &program_id,
+        token.get_address(),
+        &token_metadata,
+        &metadata_keypair,
+        &mint_authority,
+    )
+    .await;
+
+    setup_update_field(
+        &mut context,
+        &program_id,
+        &mut token_metadata,
+        &metadata_pubkey,
+        &update_authority,
+        Field::Key("collection".to_string()),
+        "my collection".to_string(),
+    )
+    .await;
+
+    // each field emits its raw value
+    for (field, value) in [
+        (Field::Name, "MySuperCoolToken"),
+        (Field::Symbol, "MINE"),
+        (Field::Uri, "my.super.cool.token"),
+        (Field::Key("collection".to_string()), "my collection"),
+    ] {
+        let transaction = Transaction::new_signed_with_payer(
+            &[emit_field(&program_id, &metadata_pubkey, field)],
+            Some(&payer.pubkey()),
+            &[payer.as_ref()],
+            context.last_blockhash,
+        );
+        let simulation = context
+            .banks_client
+            .simulate_transaction(transaction)
+            .await
+            .unwrap();
+        let return_data = simulation.simulation_details.unwrap().return_data.unwrap();
+        assert_eq!(return_data.program_id, program_id);
+        assert_eq!(return_data.data, value.as_bytes());
+    }
+
+    // missing key fails
+    let transaction = Transaction::new_signed_with_payer(
+        &[emit_field(
+            &program_id,
+            &metadata_pubkey,
+            Field::Key("missing".to_string()),
+        )],
+        Some(&payer.pubkey()),
+        &[payer.as_ref()],
+        context.last_blockhash,
+    );
+    let simulation = context
+        .banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    assert_eq!(
+        simulation.result.unwrap().unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TokenMetadataError::KeyNotFound as u32)
+        )
+    );
+
+    // value too large for return data fails, the account is written directly
+    // since the value can't fit in a transaction
+    let large_value = "x".repeat(MAX_RETURN_DATA + 1);
+    token_metadata.update(Field::Key("large".to_string()), large_value);
+    let mut data = vec![0; token_metadata.tlv_size_of().unwrap()];
+    let mut state = TlvStateMut::unpack(&mut data).unwrap();
+    state
+        .alloc_and_pack_variable_len_entry(&token_metadata, false)
+        .unwrap();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    context.set_account(
+        &metadata_pubkey,
+        &Account {
+            lamports: rent.minimum_balance(data.len()),
+            data,
+            owner: program_id,
+            ..Account::default()
+        }
+        .into(),
+    );
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[emit_field(
+            &program_id,
+            &metadata_pubkey,
+            Field::Key("large".to_string()),
+        )],
+        Some(&payer.pubkey()),
+        &[payer.as_ref()],
+        context.last_blockhash,
+    );
+    let simulation = context
+        .banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+    assert_eq!(
+        simulation.result.unwrap().unwrap_err(),
+        TransactionError::InstructionError(0, InstructionError::InvalidArgument)
+    );
+}
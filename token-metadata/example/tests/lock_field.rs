@@ -0,0 +1,403 @@
+#![cfg(feature = "test-sbf")]
+
+mod program_test;
+use {
+    program_test::{setup, setup_metadata, setup_mint, setup_update_field},
+    solana_program_test::{tokio, ProgramTestBanksClientExt, ProgramTestContext},
+    solana_sdk::{
+        instruction::{Instruction, InstructionError},
+        pubkey::Pubkey,
+        signature::Signer,
+        signer::keypair::Keypair,
+        system_instruction,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token_metadata_interface::{
+        error::TokenMetadataError,
+        instruction::{batch_update_fields, lock_field, remove_key, update_field},
+        state::{Field, FieldLocks, FieldUpdate, TokenMetadata},
+    },
+    spl_type_length_value::state::{TlvState, TlvStateBorrowed},
+};
+
+async fn process_with_rent(
+    context: &mut ProgramTestContext,
+    metadata_pubkey: &Pubkey,
+    new_space: usize,
+    instruction: Instruction,
+    update_authority: &Keypair,
+) -> Result<(), TransactionError> {
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let old_lamports = context
+        .banks_client
+        .get_account(*metadata_pubkey)
+        .await
+        .unwrap()
+        .unwrap()
+        .lamports;
+    let new_rent_lamports = rent.minimum_balance(new_space);
+    let last_blockhash = context.last_blockhash;
+    context.last_blockhash = context
+        .banks_client
+        .get_new_latest_blockhash(&last_blockhash)
+        .await
+        .unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(
+                &context.payer.pubkey(),
+                metadata_pubkey,
+                new_rent_lamports.saturating_sub(old_lamports),
+            ),
+            instruction,
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, update_authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+async fn check_state(
+    context: &mut ProgramTestContext,
+    metadata_pubkey: &Pubkey,
+    token_metadata: &TokenMetadata,
+    field_locks: &FieldLocks,
+) {
+    let fetched_metadata_account = context
+        .banks_client
+        .get_account(*metadata_pubkey)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        fetched_metadata_account.data.len(),
+        token_metadata.tlv_size_of().unwrap() + field_locks.tlv_size_of().unwrap()
+    );
+    let fetched_metadata_state = TlvStateBorrowed::unpack(&fetched_metadata_account.data).unwrap();
+    let fetched_metadata = fetched_metadata_state
+        .get_first_variable_len_value::<TokenMetadata>()
+        .unwrap();
+    assert_eq!(fetched_metadata, *token_metadata);
+    let fetched_field_locks = fetched_metadata_state
+        .get_first_variable_len_value::<FieldLocks>()
+        .unwrap();
+    assert_eq!(fetched_field_locks, *field_locks);
+}
+
+#[tokio::test]
+async fn success_lock_field() {
+    let program_id = Pubkey::new_unique();
+    let (context, client, payer) = setup(&program_id).await;
+
+    let mint_authority = Keypair::new();
+    let mint_authority_pubkey = mint_authority.pubkey();
+
+    let token_program_id = spl_token_2022::id();
+    let decimals = 2;
+    let token = setup_mint(
+        &token_program_id,
+        &mint_authority_pubkey,
+        decimals,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+    let mut context = context.lock().await;
+
+    let update_authority = Keypair::new();
+    let name = "MySuperCoolToken".to_string();
+    let symbol = "MINE".to_string();
+    let uri = "my.super.cool.token".to_string();
+    let mut token_metadata = TokenMetadata {
+        name,
+        symbol,
+        uri,
+        update_authority: Some(update_authority.pubkey()).try_into().unwrap(),
+        mint: *token.get_address(),
+        ..Default::default()
+    };
+
+    let metadata_keypair = Keypair::new();
+    let metadata_pubkey = metadata_keypair.pubkey();
+
+    setup_metadata(
+        &mut context,
+        &program_id,
+        token.get_address(),
+        &token_metadata,
+        &metadata_keypair,
+        &mint_authority,
+    )
+    .await;
+
+    let key = "collection".to_string();
+    setup_update_field(
+        &mut context,
+        &program_id,
+        &mut token_metadata,
+        &metadata_pubkey,
+        &update_authority,
+        Field::Key(key.clone()),
+        "Cool Collection".to_string(),
+    )
+    .await;
+
+    // lock the name, which allocates the locks entry
+    let mut field_locks = FieldLocks::default();
+    field_locks.lock(Field::Name);
+    let new_space = token_metadata.tlv_size_of().unwrap() + field_locks.tlv_size_of().unwrap();
+    process_with_rent(
+        &mut context,
+        &metadata_pubkey,
+        new_space,
+        lock_field(
+            &program_id,
+            &metadata_pubkey,
+            &update_authority.pubkey(),
+            Field::Name,
+        ),
+        &update_authority,
+    )
+    .await
+    .unwrap();
+    check_state(
+        &mut context,
+        &metadata_pubkey,
+        &token_metadata,
+        &field_locks,
+    )
+    .await;
+
+    // lock the additional field, which reallocs the locks entry
+    field_locks.lock(Field::Key(key.clone()));
+    let new_space = token_metadata.tlv_size_of().unwrap() + field_locks.tlv_size_of().unwrap();
+    process_with_rent(
+        &mut context,
+        &metadata_pubkey,
+        new_space,
+        lock_field(
+            &program_id,
+            &metadata_pubkey,
+            &update_authority.pubkey(),
+            Field::Key(key.clone()),
+        ),
+        &update_authority,
+    )
+    .await
+    .unwrap();
+    check_state(
+        &mut context,
+        &metadata_pubkey,
+        &token_metadata,
+        &field_locks,
+    )
+    .await;
+
+    // unlocked fields can still be updated, moving the locks entry
+    let new_uri = "my.even.cooler.token".to_string();
+    token_metadata.update(Field::Uri, new_uri.clone());
+    let new_space = token_metadata.tlv_size_of().unwrap() + field_locks.tlv_size_of().unwrap();
+    process_with_rent(
+        &mut context,
+        &metadata_pubkey,
+        new_space,
+        update_field(
+            &program_id,
+            &metadata_pubkey,
+            &update_authority.pubkey(),
+            Field::Uri,
+            new_uri,
+        ),
+        &update_authority,
+    )
+    .await
+    .unwrap();
+    check_state(
+        &mut context,
+        &metadata_pubkey,
+        &token_metadata,
+        &field_locks,
+    )
+    .await;
+
+    // locked fields cannot be updated or removed in any way
+    let locked_error = TransactionError::InstructionError(
+        1,
+        InstructionError::Custom(TokenMetadataError::FieldLocked as u32),
+    );
+    for instruction in [
+        update_field(
+            &program_id,
+            &metadata_pubkey,
+            &update_authority.pubkey(),
+            Field::Name,
+            "Renamed".to_string(),
+        ),
+        remove_key(
+            &program_id,
+            &metadata_pubkey,
+            &update_authority.pubkey(),
+            key.clone(),
+            false,
+        ),
+        batch_update_fields(
+            &program_id,
+            &metadata_pubkey,
+            &update_authority.pubkey(),
+            vec![
+                FieldUpdate::Set {
+                    field: Field::Symbol,
+                    value: "NEW".to_string(),
+                },
+                FieldUpdate::Set {
+                    field: Field::Key(key.clone()),
+                    value: "Other Collection".to_string(),
+                },
+            ],
+        ),
+    ] {
+        let error = process_with_rent(
+            &mut context,
+            &metadata_pubkey,
+            new_space,
+            instruction,
+            &update_authority,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, locked_error);
+    }
+    check_state(
+        &mut context,
+        &metadata_pubkey,
+        &token_metadata,
+        &field_locks,
+    )
+    .await;
+
+    // locking twice does nothing
+    process_with_rent(
+        &mut context,
+        &metadata_pubkey,
+        new_space,
+        lock_field(
+            &program_id,
+            &metadata_pubkey,
+            &update_authority.pubkey(),
+            Field::Name,
+        ),
+        &update_authority,
+    )
+    .await
+    .unwrap();
+    check_state(
+        &mut context,
+        &metadata_pubkey,
+        &token_metadata,
+        &field_locks,
+    )
+    .await;
+}
+
+#[tokio::test]
+async fn fail_lock_field() {
+    let program_id = Pubkey::new_unique();
+    let (context, client, payer) = setup(&program_id).await;
+
+    let mint_authority = Keypair::new();
+    let mint_authority_pubkey = mint_authority.pubkey();
+
+    let token_program_id = spl_token_2022::id();
+    let decimals = 2;
+    let token = setup_mint(
+        &token_program_id,
+        &mint_authority_pubkey,
+        decimals,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+    let mut context = context.lock().await;
+
+    let update_authority = Keypair::new();
+    let name = "MySuperCoolToken".to_string();
+    let symbol = "MINE".to_string();
+    let uri = "my.super.cool.token".to_string();
+    let token_metadata = TokenMetadata {
+        name,
+        symbol,
+        uri,
+        update_authority: Some(update_authority.pubkey()).try_into().unwrap(),
+        mint: *token.get_address(),
+        ..Default::default()
+    };
+
+    let metadata_keypair = Keypair::new();
+    let metadata_pubkey = metadata_keypair.pubkey();
+
+    setup_metadata(
+        &mut context,
+        &program_id,
+        token.get_address(),
+        &token_metadata,
+        &metadata_keypair,
+        &mint_authority,
+    )
+    .await;
+
+    let mut field_locks = FieldLocks::default();
+    field_locks.lock(Field::Name);
+    let new_space = token_metadata.tlv_size_of().unwrap() + field_locks.tlv_size_of().unwrap();
+
+    // wrong authority
+    let wrong_authority = Keypair::new();
+    let error = process_with_rent(
+        &mut context,
+        &metadata_pubkey,
+        new_space,
+        lock_field(
+            &program_id,
+            &metadata_pubkey,
+            &wrong_authority.pubkey(),
+            Field::Name,
+        ),
+        &wrong_authority,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(TokenMetadataError::IncorrectUpdateAuthority as u32),
+        )
+    );
+
+    // additional field must exist
+    let error = process_with_rent(
+        &mut context,
+        &metadata_pubkey,
+        new_space,
+        lock_field(
+            &program_id,
+            &metadata_pubkey,
+            &update_authority.pubkey(),
+            Field::Key("missing".to_string()),
+        ),
+        &update_authority,
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            1,
+            InstructionError::Custom(TokenMetadataError::KeyNotFound as u32),
+        )
+    );
+}
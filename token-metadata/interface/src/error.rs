@@ -23,4 +23,7 @@ pub enum TokenMetadataError {
     /// Key not found in metadata account
     #[error("Key not found in metadata account")]
     KeyNotFound,
+    /// Field is locked and cannot be updated
+    #[error("Field is locked and cannot be updated")]
+    FieldLocked,
 }
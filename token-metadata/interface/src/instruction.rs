@@ -1,7 +1,7 @@
 //! Instruction types
 
 use {
-    crate::state::{Field, OptionalNonZeroPubkey},
+    crate::state::{Field, FieldUpdate, OptionalNonZeroPubkey},
     borsh::{BorshDeserialize, BorshSerialize},
     solana_program::{
         instruction::{AccountMeta, Instruction},
@@ -62,6 +62,30 @@ pub struct Emit {
     pub end: Option<u64>,
 }
 
+/// Batch update fields instruction data
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, SplDiscriminate)]
+#[discriminator_hash_input("spl_token_metadata_interface:batch_update_fields")]
+pub struct BatchUpdateFields {
+    /// Updates to apply to the metadata, in order
+    pub updates: Vec<FieldUpdate>,
+}
+
+/// Lock field instruction data
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, SplDiscriminate)]
+#[discriminator_hash_input("spl_token_metadata_interface:lock_field")]
+pub struct LockField {
+    /// Field to lock in the metadata
+    pub field: Field,
+}
+
+/// Emit field instruction data
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize, SplDiscriminate)]
+#[discriminator_hash_input("spl_token_metadata_interface:emit_field")]
+pub struct EmitField {
+    /// Field to emit from the metadata
+    pub field: Field,
+}
+
 /// All instructions that must be implemented in the token-metadata interface
 #[derive(Clone, Debug, PartialEq)]
 pub enum TokenMetadataInstruction {
@@ -140,10 +164,70 @@ pub enum TokenMetadataInstruction {
     /// interface can return `TokenMetadata` without adhering to the specific
     /// byte layout of the `TokenMetadata` struct in any accounts.
     ///
+    /// Return data is limited to `MAX_RETURN_DATA` bytes, so larger metadata
+    /// must be read in pages by providing a range. The end of the range is
+    /// clamped to the length of the data, and an empty range returns nothing.
+    ///
     /// Accounts expected by this instruction:
     ///
     ///   0. `[]` Metadata account
     Emit(Emit),
+
+    /// Applies several field updates and key removals in a token-metadata
+    /// account at once.
+    ///
+    /// The updates are applied in order, with the same behavior as
+    /// `UpdateField` and `RemoveKey`, and either all of them succeed or the
+    /// instruction fails.
+    ///
+    /// By the end of the instruction, the metadata account must be properly
+    /// resized based on the new size of the TLV entry, with the same rules as
+    /// `UpdateField`. The program should reallocate at most once, based on the
+    /// final size after all updates.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[w]` Metadata account
+    ///   1. `[s]` Update authority
+    ///
+    /// Data: `BatchUpdateFields` data, the list of updates to apply
+    BatchUpdateFields(BatchUpdateFields),
+
+    /// Permanently locks a field in a token-metadata account, so that it can
+    /// never be updated or removed again, even by the update authority.
+    ///
+    /// The field can be one of the required fields (name, symbol, URI), or an
+    /// existing additional field denoted by its "key" string. Locking an
+    /// already locked field does nothing.
+    ///
+    /// Locked fields are stored in a `FieldLocks` TLV entry next to the
+    /// metadata. If the entry does not exist yet, the program must allocate it,
+    /// so the metadata account must have enough lamports to cover the new
+    /// rent-exempt reserve.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[w]` Metadata account
+    ///   1. `[s]` Update authority
+    ///
+    /// Data: `LockField` data, the field to lock
+    LockField(LockField),
+
+    /// Emits the value of a single field of the token-metadata as return data
+    ///
+    /// The field can be one of the required fields (name, symbol, URI), or an
+    /// additional field denoted by its "key" string. The value is emitted as
+    /// raw UTF-8 bytes, without any length prefix.
+    ///
+    /// Fails if the key does not exist, or if the value is too large to fit in
+    /// return data.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Metadata account
+    ///
+    /// Data: `EmitField` data, the field to emit
+    EmitField(EmitField),
 }
 impl TokenMetadataInstruction {
     /// Unpacks a byte buffer into a [TokenMetadataInstruction](enum.TokenMetadataInstruction.html).
@@ -173,6 +257,18 @@ impl TokenMetadataInstruction {
                 let data = Emit::try_from_slice(rest)?;
                 Self::Emit(data)
             }
+            BatchUpdateFields::SPL_DISCRIMINATOR_SLICE => {
+                let data = BatchUpdateFields::try_from_slice(rest)?;
+                Self::BatchUpdateFields(data)
+            }
+            LockField::SPL_DISCRIMINATOR_SLICE => {
+                let data = LockField::try_from_slice(rest)?;
+                Self::LockField(data)
+            }
+            EmitField::SPL_DISCRIMINATOR_SLICE => {
+                let data = EmitField::try_from_slice(rest)?;
+                Self::EmitField(data)
+            }
             _ => return Err(ProgramError::InvalidInstructionData),
         })
     }
@@ -201,6 +297,18 @@ impl TokenMetadataInstruction {
                 buf.extend_from_slice(Emit::SPL_DISCRIMINATOR_SLICE);
                 buf.append(&mut data.try_to_vec().unwrap());
             }
+            Self::BatchUpdateFields(data) => {
+                buf.extend_from_slice(BatchUpdateFields::SPL_DISCRIMINATOR_SLICE);
+                buf.append(&mut data.try_to_vec().unwrap());
+            }
+            Self::LockField(data) => {
+                buf.extend_from_slice(LockField::SPL_DISCRIMINATOR_SLICE);
+                buf.append(&mut data.try_to_vec().unwrap());
+            }
+            Self::EmitField(data) => {
+                buf.extend_from_slice(EmitField::SPL_DISCRIMINATOR_SLICE);
+                buf.append(&mut data.try_to_vec().unwrap());
+            }
         };
         buf
     }
@@ -302,6 +410,52 @@ pub fn emit(
     }
 }
 
+/// Creates a `BatchUpdateFields` instruction
+pub fn batch_update_fields(
+    program_id: &Pubkey,
+    metadata: &Pubkey,
+    update_authority: &Pubkey,
+    updates: Vec<FieldUpdate>,
+) -> Instruction {
+    let data = TokenMetadataInstruction::BatchUpdateFields(BatchUpdateFields { updates });
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*metadata, false),
+            AccountMeta::new_readonly(*update_authority, true),
+        ],
+        data: data.pack(),
+    }
+}
+
+/// Creates a `LockField` instruction
+pub fn lock_field(
+    program_id: &Pubkey,
+    metadata: &Pubkey,
+    update_authority: &Pubkey,
+    field: Field,
+) -> Instruction {
+    let data = TokenMetadataInstruction::LockField(LockField { field });
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![
+            AccountMeta::new(*metadata, false),
+            AccountMeta::new_readonly(*update_authority, true),
+        ],
+        data: data.pack(),
+    }
+}
+
+/// Creates an `EmitField` instruction
+pub fn emit_field(program_id: &Pubkey, metadata: &Pubkey, field: Field) -> Instruction {
+    let data = TokenMetadataInstruction::EmitField(EmitField { field });
+    Instruction {
+        program_id: *program_id,
+        accounts: vec![AccountMeta::new_readonly(*metadata, false)],
+        data: data.pack(),
+    }
+}
+
 #[cfg(test)]
 mod test {
     use {super::*, crate::NAMESPACE, solana_program::hash};
@@ -384,4 +538,44 @@ mod test {
         let discriminator = &preimage.as_ref()[..ArrayDiscriminator::LENGTH];
         check_pack_unpack(check, discriminator, data);
     }
+
+    #[test]
+    fn batch_update_fields_pack() {
+        let data = BatchUpdateFields {
+            updates: vec![
+                FieldUpdate::Set {
+                    field: Field::Uri,
+                    value: "http://test.uri".to_string(),
+                },
+                FieldUpdate::Remove {
+                    key: "MyTestField".to_string(),
+                    idempotent: false,
+                },
+            ],
+        };
+        let check = TokenMetadataInstruction::BatchUpdateFields(data.clone());
+        let preimage = hash::hashv(&[format!("{NAMESPACE}:batch_update_fields").as_bytes()]);
+        let discriminator = &preimage.as_ref()[..ArrayDiscriminator::LENGTH];
+        check_pack_unpack(check, discriminator, data);
+    }
+
+    #[test]
+    fn lock_field_pack() {
+        let data = LockField { field: Field::Name };
+        let check = TokenMetadataInstruction::LockField(data.clone());
+        let preimage = hash::hashv(&[format!("{NAMESPACE}:lock_field").as_bytes()]);
+        let discriminator = &preimage.as_ref()[..ArrayDiscriminator::LENGTH];
+        check_pack_unpack(check, discriminator, data);
+    }
+
+    #[test]
+    fn emit_field_pack() {
+        let data = EmitField {
+            field: Field::Key("MyTestField".to_string()),
+        };
+        let check = TokenMetadataInstruction::EmitField(data.clone());
+        let preimage = hash::hashv(&[format!("{NAMESPACE}:emit_field").as_bytes()]);
+        let discriminator = &preimage.as_ref()[..ArrayDiscriminator::LENGTH];
+        check_pack_unpack(check, discriminator, data);
+    }
 }
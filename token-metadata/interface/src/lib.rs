@@ -6,6 +6,7 @@
 
 pub mod error;
 pub mod instruction;
+pub mod offchain;
 pub mod state;
 
 // Export current sdk types for downstream users building with a different sdk version
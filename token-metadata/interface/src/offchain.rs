@@ -0,0 +1,104 @@
+//! Offchain helpers for reading token-metadata through simulated instructions
+
+use {
+    crate::{
+        instruction::{emit, emit_field},
+        state::{Field, TokenMetadata},
+    },
+    solana_program::{
+        borsh::try_from_slice_unchecked, instruction::Instruction, program::MAX_RETURN_DATA,
+        program_error::ProgramError, pubkey::Pubkey, system_instruction::MAX_PERMITTED_DATA_LENGTH,
+    },
+    std::future::Future,
+};
+
+/// Type representing the output of a simulation function: the return data
+/// set by the program, or `None` if no data was returned
+pub type ReturnDataResult = Result<Option<Vec<u8>>, SimulationError>;
+/// Generic error type that can come out of any client while simulating
+pub type SimulationError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Offchain helper to read the full token-metadata from any program
+/// implementing the interface, by calling `Emit` over consecutive pages
+///
+/// Return data is limited to `MAX_RETURN_DATA` bytes and the runtime trims
+/// trailing zeroes, so each page is padded back to its full size, and pages
+/// are requested until the concatenated data deserializes.
+///
+/// To be client-agnostic and to avoid pulling in the full solana-sdk, this
+/// simply takes a function that will simulate the given instruction and return
+/// its return data, after checking that it was set by the token-metadata
+/// program. Can be called in the following way:
+///
+/// ```rust,ignore
+/// let token_metadata = read_token_metadata(
+///     |instruction| async {
+///         let transaction = Transaction::new_with_payer(&[instruction], Some(&payer));
+///         let simulation = banks_client.simulate_transaction(transaction).await?;
+///         Ok(simulation
+///             .simulation_details
+///             .and_then(|details| details.return_data)
+///             .map(|return_data| return_data.data))
+///     },
+///     &program_id,
+///     &metadata,
+/// )
+/// .await?;
+/// ```
+pub async fn read_token_metadata<F, Fut>(
+    simulate_fn: F,
+    program_id: &Pubkey,
+    metadata: &Pubkey,
+) -> Result<TokenMetadata, SimulationError>
+where
+    F: Fn(Instruction) -> Fut,
+    Fut: Future<Output = ReturnDataResult>,
+{
+    let mut data = vec![];
+    loop {
+        let start = data.len() as u64;
+        let end = start.saturating_add(MAX_RETURN_DATA as u64);
+        // An empty page is either past the end of the metadata, which can't
+        // happen if the previous pages did not deserialize, or a page of zeroes
+        let mut page = simulate_fn(emit(program_id, metadata, Some(start), Some(end)))
+            .await?
+            .unwrap_or_default();
+        if page.len() > MAX_RETURN_DATA {
+            return Err(ProgramError::InvalidAccountData.into());
+        }
+        page.resize(MAX_RETURN_DATA, 0);
+        data.append(&mut page);
+
+        if let Ok(token_metadata) = try_from_slice_unchecked::<TokenMetadata>(&data) {
+            return Ok(token_metadata);
+        }
+        if data.len() as u64 >= MAX_PERMITTED_DATA_LENGTH {
+            return Err(ProgramError::InvalidAccountData.into());
+        }
+    }
+}
+
+/// Offchain helper to read the value of a single field of the token-metadata,
+/// by calling `EmitField`
+///
+/// Fails if the key does not exist. Since the runtime trims trailing zeroes
+/// from return data, values ending in null characters lose them, and values
+/// larger than `MAX_RETURN_DATA` cannot be emitted, so use
+/// `read_token_metadata` for those.
+///
+/// Takes the same simulation function as `read_token_metadata`.
+pub async fn read_token_metadata_field<F, Fut>(
+    simulate_fn: F,
+    program_id: &Pubkey,
+    metadata: &Pubkey,
+    field: Field,
+) -> Result<String, SimulationError>
+where
+    F: Fn(Instruction) -> Fut,
+    Fut: Future<Output = ReturnDataResult>,
+{
+    let value = simulate_fn(emit_field(program_id, metadata, field))
+        .await?
+        .unwrap_or_default();
+    String::from_utf8(value).map_err(|_| ProgramError::InvalidAccountData.into())
+}
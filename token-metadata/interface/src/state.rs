@@ -1,6 +1,7 @@
 //! Token-metadata interface state types
 
 use {
+    crate::error::TokenMetadataError,
     borsh::{BorshDeserialize, BorshSchema, BorshSerialize},
     solana_program::{
         borsh::{get_instance_packed_len, try_from_slice_unchecked},
@@ -12,9 +13,17 @@ use {
         state::{TlvState, TlvStateBorrowed},
         variable_len_pack::VariableLenPack,
     },
-    std::convert::TryFrom,
+    std::{convert::TryFrom, str::FromStr},
 };
 
+/// Key in the additional metadata for the collection that the token belongs
+/// to, stored as the base58 address of the collection, usually its mint
+pub const COLLECTION_KEY: &str = "collection";
+
+/// Key in the additional metadata for the edition of the token within its
+/// collection, stored as a decimal number
+pub const EDITION_KEY: &str = "edition";
+
 /// A Pubkey that encodes `None` as all `0`, meant to be usable as a Pod type,
 /// similar to all NonZero* number types from the bytemuck library.
 #[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, BorshSchema)]
@@ -103,6 +112,26 @@ impl TokenMetadata {
         self.additional_metadata.push((new_key, new_value));
     }
 
+    /// Gets the value for the given key in the additional metadata
+    pub fn get_key_value(&self, key: &str) -> Option<&str> {
+        self.additional_metadata
+            .iter()
+            .find(|(entry_key, _)| entry_key == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Gets the collection stored under `COLLECTION_KEY`, if it is present and
+    /// a valid address
+    pub fn collection(&self) -> Option<Pubkey> {
+        Pubkey::from_str(self.get_key_value(COLLECTION_KEY)?).ok()
+    }
+
+    /// Gets the edition stored under `EDITION_KEY`, if it is present and a
+    /// valid number
+    pub fn edition(&self) -> Option<u64> {
+        self.get_key_value(EDITION_KEY)?.parse().ok()
+    }
+
     /// Removes the key-value pair given by the provided key. Returns true if
     /// the key was found.
     pub fn remove_key(&mut self, key: &str) -> bool {
@@ -117,12 +146,66 @@ impl TokenMetadata {
         found_key
     }
 
+    /// Applies all of the given updates in order, as done by the
+    /// `BatchUpdateFields` instruction
+    ///
+    /// Fails if a non-idempotent removal targets a key that is not present at
+    /// that point in the batch.
+    pub fn apply_updates(&mut self, updates: Vec<FieldUpdate>) -> Result<(), ProgramError> {
+        for update in updates {
+            match update {
+                FieldUpdate::Set { field, value } => self.update(field, value),
+                FieldUpdate::Remove { key, idempotent } => {
+                    if !self.remove_key(&key) && !idempotent {
+                        return Err(TokenMetadataError::KeyNotFound.into());
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
     /// Get the slice corresponding to the given start and end range
+    ///
+    /// The end is clamped to the length of the data, so that clients can page
+    /// through the data in fixed-size chunks without knowing its total length.
     pub fn get_slice(data: &[u8], start: Option<u64>, end: Option<u64>) -> Option<&[u8]> {
         let start = start.unwrap_or(0) as usize;
-        let end = end.map(|x| x as usize).unwrap_or(data.len());
+        let end = end
+            .map(|x| usize::try_from(x).unwrap_or(usize::MAX).min(data.len()))
+            .unwrap_or(data.len());
         data.get(start..end)
     }
+
+    /// Reads the value of a single field directly from Borsh-serialized
+    /// `TokenMetadata` bytes, without deserializing the whole struct
+    ///
+    /// Returns `None` if the field is a key that is not present.
+    pub fn get_field_value_from_bytes<'a>(
+        data: &'a [u8],
+        field: &Field,
+    ) -> Result<Option<&'a str>, ProgramError> {
+        // skip update authority and mint
+        let mut offset = 2 * std::mem::size_of::<Pubkey>();
+        let name = read_str(data, &mut offset)?;
+        let symbol = read_str(data, &mut offset)?;
+        let uri = read_str(data, &mut offset)?;
+        let key = match field {
+            Field::Name => return Ok(Some(name)),
+            Field::Symbol => return Ok(Some(symbol)),
+            Field::Uri => return Ok(Some(uri)),
+            Field::Key(key) => key,
+        };
+        let num_entries = read_u32(data, &mut offset)?;
+        for _ in 0..num_entries {
+            let entry_key = read_str(data, &mut offset)?;
+            let entry_value = read_str(data, &mut offset)?;
+            if entry_key == key {
+                return Ok(Some(entry_value));
+            }
+        }
+        Ok(None)
+    }
 }
 impl VariableLenPack for TokenMetadata {
     fn pack_into_slice(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
@@ -149,6 +232,113 @@ pub enum Field {
     Key(String),
 }
 
+/// Fields of the token-metadata that can never be updated again, stored in a
+/// TLV entry next to the `TokenMetadata` entry
+///
+/// If the entry does not exist, no fields are locked.
+#[derive(Clone, Debug, Default, PartialEq, BorshDeserialize, BorshSerialize, SplDiscriminate)]
+#[discriminator_hash_input("spl_token_metadata_interface:field_locks")]
+pub struct FieldLocks {
+    /// The locked fields, without duplicates
+    pub locked_fields: Vec<Field>,
+}
+impl FieldLocks {
+    /// Gives the total size of this struct as a TLV entry in an account
+    pub fn tlv_size_of(&self) -> Result<usize, ProgramError> {
+        TlvStateBorrowed::get_base_len()
+            .checked_add(get_instance_packed_len(self)?)
+            .ok_or(ProgramError::InvalidAccountData)
+    }
+
+    /// Returns true if the given field is locked
+    pub fn is_locked(&self, field: &Field) -> bool {
+        self.locked_fields.contains(field)
+    }
+
+    /// Locks the given field. Returns false if the field was already locked.
+    pub fn lock(&mut self, field: Field) -> bool {
+        if self.is_locked(&field) {
+            false
+        } else {
+            self.locked_fields.push(field);
+            true
+        }
+    }
+
+    /// Checks that the given field can be updated
+    pub fn check_field(&self, field: &Field) -> Result<(), ProgramError> {
+        if self.is_locked(field) {
+            Err(TokenMetadataError::FieldLocked.into())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Checks that none of the given updates touch a locked field
+    pub fn check_updates(&self, updates: &[FieldUpdate]) -> Result<(), ProgramError> {
+        for update in updates {
+            match update {
+                FieldUpdate::Set { field, .. } => self.check_field(field)?,
+                FieldUpdate::Remove { key, .. } => self.check_field(&Field::Key(key.clone()))?,
+            }
+        }
+        Ok(())
+    }
+}
+impl VariableLenPack for FieldLocks {
+    fn pack_into_slice(&self, dst: &mut [u8]) -> Result<(), ProgramError> {
+        borsh::to_writer(&mut dst[..], self).map_err(Into::into)
+    }
+    fn unpack_from_slice(src: &[u8]) -> Result<Self, ProgramError> {
+        try_from_slice_unchecked(src).map_err(Into::into)
+    }
+    fn get_packed_len(&self) -> Result<usize, ProgramError> {
+        get_instance_packed_len(self).map_err(Into::into)
+    }
+}
+
+/// A single change to the metadata, used for updating several fields at once
+#[derive(Clone, Debug, PartialEq, BorshSerialize, BorshDeserialize)]
+pub enum FieldUpdate {
+    /// Writes the value for the field, creating it if it's a new key
+    Set {
+        /// Field to update in the metadata
+        field: Field,
+        /// Value to write for the field
+        value: String,
+    },
+    /// Removes the key-value pair for the given key in the additional metadata
+    Remove {
+        /// Key to remove in the additional metadata portion
+        key: String,
+        /// If true, the update does not fail if the key does not exist
+        idempotent: bool,
+    },
+}
+
+fn read_u32(data: &[u8], offset: &mut usize) -> Result<u32, ProgramError> {
+    let end = offset
+        .checked_add(std::mem::size_of::<u32>())
+        .ok_or(ProgramError::InvalidAccountData)?;
+    let bytes = data
+        .get(*offset..end)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    *offset = end;
+    Ok(u32::from_le_bytes(bytes.try_into().unwrap()))
+}
+
+fn read_str<'a>(data: &'a [u8], offset: &mut usize) -> Result<&'a str, ProgramError> {
+    let len = read_u32(data, offset)? as usize;
+    let end = offset
+        .checked_add(len)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    let bytes = data
+        .get(*offset..end)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    *offset = end;
+    std::str::from_utf8(bytes).map_err(|_| ProgramError::InvalidAccountData)
+}
+
 #[cfg(test)]
 mod tests {
     use {super::*, crate::NAMESPACE, solana_program::hash};
@@ -244,4 +434,196 @@ mod tests {
         assert!(!token_metadata.remove_key(&key));
         assert_eq!(token_metadata.additional_metadata.len(), 0);
     }
+
+    #[test]
+    fn collection_and_edition() {
+        let collection = Pubkey::new_unique();
+        let mut token_metadata = TokenMetadata::default();
+        assert_eq!(token_metadata.collection(), None);
+        assert_eq!(token_metadata.edition(), None);
+
+        token_metadata
+            .apply_updates(vec![
+                FieldUpdate::Set {
+                    field: Field::Key(COLLECTION_KEY.to_string()),
+                    value: collection.to_string(),
+                },
+                FieldUpdate::Set {
+                    field: Field::Key(EDITION_KEY.to_string()),
+                    value: "42".to_string(),
+                },
+            ])
+            .unwrap();
+        assert_eq!(
+            token_metadata.get_key_value(COLLECTION_KEY),
+            Some(collection.to_string().as_str())
+        );
+        assert_eq!(token_metadata.collection(), Some(collection));
+        assert_eq!(token_metadata.edition(), Some(42));
+
+        // values that don't parse are ignored
+        token_metadata.update(
+            Field::Key(COLLECTION_KEY.to_string()),
+            "not an address".to_string(),
+        );
+        token_metadata.update(Field::Key(EDITION_KEY.to_string()), "-1".to_string());
+        assert_eq!(token_metadata.collection(), None);
+        assert_eq!(token_metadata.edition(), None);
+    }
+
+    #[test]
+    fn apply_updates() {
+        let mut token_metadata = TokenMetadata {
+            name: "name".to_string(),
+            symbol: "symbol".to_string(),
+            uri: "uri".to_string(),
+            additional_metadata: vec![("old".to_string(), "value".to_string())],
+            ..Default::default()
+        };
+
+        token_metadata
+            .apply_updates(vec![
+                FieldUpdate::Set {
+                    field: Field::Name,
+                    value: "new_name".to_string(),
+                },
+                FieldUpdate::Set {
+                    field: Field::Key("collection".to_string()),
+                    value: "my collection".to_string(),
+                },
+                FieldUpdate::Set {
+                    field: Field::Key("edition".to_string()),
+                    value: "1".to_string(),
+                },
+                FieldUpdate::Remove {
+                    key: "old".to_string(),
+                    idempotent: false,
+                },
+                FieldUpdate::Remove {
+                    key: "missing".to_string(),
+                    idempotent: true,
+                },
+            ])
+            .unwrap();
+        assert_eq!(token_metadata.name, "new_name");
+        assert_eq!(
+            token_metadata.additional_metadata,
+            vec![
+                ("collection".to_string(), "my collection".to_string()),
+                ("edition".to_string(), "1".to_string()),
+            ]
+        );
+
+        // removing a missing key without the idempotent flag fails
+        assert_eq!(
+            token_metadata
+                .apply_updates(vec![FieldUpdate::Remove {
+                    key: "missing".to_string(),
+                    idempotent: false,
+                }])
+                .unwrap_err(),
+            TokenMetadataError::KeyNotFound.into()
+        );
+    }
+
+    #[test]
+    fn get_slice() {
+        let data = [1, 2, 3, 4, 5];
+        assert_eq!(TokenMetadata::get_slice(&data, None, None), Some(&data[..]));
+        assert_eq!(
+            TokenMetadata::get_slice(&data, Some(1), Some(3)),
+            Some(&data[1..3])
+        );
+        // end is clamped to the data length
+        assert_eq!(
+            TokenMetadata::get_slice(&data, Some(2), Some(1024)),
+            Some(&data[2..])
+        );
+        assert_eq!(
+            TokenMetadata::get_slice(&data, Some(5), Some(u64::MAX)),
+            Some(&data[5..])
+        );
+        assert_eq!(TokenMetadata::get_slice(&data, Some(6), Some(1024)), None);
+        assert_eq!(TokenMetadata::get_slice(&data, Some(3), Some(2)), None);
+    }
+
+    #[test]
+    fn get_field_value_from_bytes() {
+        let token_metadata = TokenMetadata {
+            update_authority: OptionalNonZeroPubkey(Pubkey::new_unique()),
+            mint: Pubkey::new_unique(),
+            name: "name".to_string(),
+            symbol: "symbol".to_string(),
+            uri: "uri".to_string(),
+            additional_metadata: vec![
+                ("key1".to_string(), "value1".to_string()),
+                ("key2".to_string(), "".to_string()),
+            ],
+        };
+        let data = token_metadata.try_to_vec().unwrap();
+        for (field, value) in [
+            (Field::Name, Some("name")),
+            (Field::Symbol, Some("symbol")),
+            (Field::Uri, Some("uri")),
+            (Field::Key("key1".to_string()), Some("value1")),
+            (Field::Key("key2".to_string()), Some("")),
+            (Field::Key("missing".to_string()), None),
+        ] {
+            assert_eq!(
+                TokenMetadata::get_field_value_from_bytes(&data, &field).unwrap(),
+                value
+            );
+        }
+
+        // truncated data fails
+        assert_eq!(
+            TokenMetadata::get_field_value_from_bytes(
+                &data[..data.len() - 1],
+                &Field::Key("missing".to_string())
+            )
+            .unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+        assert_eq!(
+            TokenMetadata::get_field_value_from_bytes(&data[..40], &Field::Name).unwrap_err(),
+            ProgramError::InvalidAccountData
+        );
+    }
+
+    #[test]
+    fn field_locks() {
+        let mut field_locks = FieldLocks::default();
+        assert!(field_locks.lock(Field::Name));
+        assert!(field_locks.lock(Field::Key("collection".to_string())));
+        assert!(!field_locks.lock(Field::Name));
+        assert_eq!(field_locks.locked_fields.len(), 2);
+
+        assert_eq!(
+            field_locks.check_field(&Field::Name).unwrap_err(),
+            TokenMetadataError::FieldLocked.into()
+        );
+        field_locks.check_field(&Field::Uri).unwrap();
+
+        field_locks
+            .check_updates(&[
+                FieldUpdate::Set {
+                    field: Field::Uri,
+                    value: "uri".to_string(),
+                },
+                FieldUpdate::Remove {
+                    key: "other".to_string(),
+                    idempotent: true,
+                },
+            ])
+            .unwrap();
+        assert_eq!(
+            field_locks
+                .check_updates(&[FieldUpdate::Remove {
+                    key: "collection".to_string(),
+                    idempotent: false,
+                }])
+                .unwrap_err(),
+            TokenMetadataError::FieldLocked.into()
+        );
+    }
 }
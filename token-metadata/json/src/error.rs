@@ -0,0 +1,36 @@
+//! Error types
+
+use {
+    crate::{fetcher::FetchError, resolver::MAX_METADATA_JSON_LEN, schema::SchemaViolation},
+    solana_program::program_error::ProgramError,
+    thiserror::Error,
+};
+
+/// Errors that may be returned while resolving off-chain metadata
+#[derive(Debug, Error)]
+pub enum MetadataJsonError {
+    /// The token-metadata has an empty URI
+    #[error("Token metadata has no URI")]
+    MissingUri,
+    /// The token-metadata URI is malformed or uses an unsupported scheme
+    #[error("Unsupported or malformed URI: {0}")]
+    InvalidUri(String),
+    /// The fetcher failed to get the JSON
+    #[error("Failed to fetch metadata JSON: {0}")]
+    Fetch(FetchError),
+    /// The fetched JSON is too large
+    #[error(
+        "Metadata JSON is {0} bytes, more than the maximum of {}",
+        MAX_METADATA_JSON_LEN
+    )]
+    TooLarge(usize),
+    /// The fetched bytes are not valid JSON for the schema types
+    #[error("Invalid metadata JSON: {0}")]
+    InvalidJson(#[from] serde_json::Error),
+    /// The fetched JSON does not follow the schema
+    #[error("Metadata JSON does not follow the schema: {0:?}")]
+    Schema(Vec<SchemaViolation>),
+    /// The on-chain token-metadata could not be deserialized
+    #[error("Invalid token-metadata data: {0}")]
+    InvalidTokenMetadata(#[from] ProgramError),
+}
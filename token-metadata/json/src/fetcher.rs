@@ -0,0 +1,171 @@
+//! Pluggable fetchers for off-chain metadata
+
+#[cfg(feature = "http")]
+use crate::resolver::MAX_METADATA_JSON_LEN;
+use {
+    async_trait::async_trait,
+    base64::{engine::general_purpose::STANDARD as BASE64_STANDARD, Engine},
+};
+
+/// Generic error type that can come out of any fetcher
+pub type FetchError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Fetches the raw bytes behind a metadata URI
+///
+/// Implement this over your own HTTP client, cache, or gateway setup. Tests can
+/// use a simple in-memory map of URIs to bytes.
+#[async_trait]
+pub trait MetadataFetcher {
+    /// Fetch the contents at the given URI
+    async fn fetch(&self, uri: &str) -> Result<Vec<u8>, FetchError>;
+}
+
+/// Decodes the contents of a `data:` URI, given everything after `data:`
+///
+/// The payload after the first `,` is base64 if the media type ends with
+/// `;base64`, and percent-encoded otherwise.
+pub fn decode_data_uri(data: &str) -> Result<Vec<u8>, FetchError> {
+    let (media_type, payload) = data
+        .split_once(',')
+        .ok_or("Malformed data URI, missing ','")?;
+    if media_type.ends_with(";base64") {
+        return Ok(BASE64_STANDARD.decode(payload)?);
+    }
+    let bytes = payload.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            let hex = payload
+                .get(i + 1..i + 3)
+                .ok_or("Truncated percent-encoding in data URI")?;
+            decoded.push(u8::from_str_radix(hex, 16)?);
+            i += 3;
+        } else {
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+    }
+    Ok(decoded)
+}
+
+/// Fetcher over HTTP(S), with optional support for `ipfs://` and `ar://` URIs
+/// through gateways. `data:` URIs are decoded in place.
+#[cfg(feature = "http")]
+#[derive(Clone, Debug, Default)]
+pub struct HttpFetcher {
+    client: reqwest::Client,
+    ipfs_gateway: Option<String>,
+    arweave_gateway: Option<String>,
+}
+#[cfg(feature = "http")]
+impl HttpFetcher {
+    /// Create a new fetcher with the given client, which only supports HTTP(S)
+    pub fn new(client: reqwest::Client) -> Self {
+        Self {
+            client,
+            ipfs_gateway: None,
+            arweave_gateway: None,
+        }
+    }
+
+    /// Fetch `ipfs://<path>` URIs from `<gateway>/<path>`,
+    /// e.g. "https://ipfs.io/ipfs"
+    pub fn with_ipfs_gateway(mut self, gateway: &str) -> Self {
+        self.ipfs_gateway = Some(gateway.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Fetch `ar://<path>` URIs from `<gateway>/<path>`,
+    /// e.g. "https://arweave.net"
+    pub fn with_arweave_gateway(mut self, gateway: &str) -> Self {
+        self.arweave_gateway = Some(gateway.trim_end_matches('/').to_string());
+        self
+    }
+
+    /// Schemes are matched case-insensitively, as in `is_valid_uri`
+    fn get_url(&self, uri: &str) -> Result<String, FetchError> {
+        let unsupported = || format!("Unsupported URI for HTTP fetcher: {uri}");
+        let (scheme, path) = uri.split_once("://").ok_or_else(unsupported)?;
+        let gateway = match scheme.to_ascii_lowercase().as_str() {
+            "https" | "http" => return Ok(uri.to_string()),
+            "ipfs" => &self.ipfs_gateway,
+            "ar" => &self.arweave_gateway,
+            _ => return Err(unsupported().into()),
+        };
+        match gateway {
+            Some(gateway) => Ok(format!("{gateway}/{path}")),
+            None => Err(format!("No gateway configured for {uri}").into()),
+        }
+    }
+}
+#[cfg(feature = "http")]
+#[async_trait]
+impl MetadataFetcher for HttpFetcher {
+    async fn fetch(&self, uri: &str) -> Result<Vec<u8>, FetchError> {
+        if let Some(data) = uri.strip_prefix("data:") {
+            return decode_data_uri(data);
+        }
+        let url = self.get_url(uri)?;
+        let mut response = self.client.get(url).send().await?.error_for_status()?;
+        let too_large = || format!("Metadata JSON is more than {MAX_METADATA_JSON_LEN} bytes");
+        // The advertised length can be missing or wrong, so the body is also
+        // capped while streaming it
+        if response
+            .content_length()
+            .map_or(false, |len| len > MAX_METADATA_JSON_LEN as u64)
+        {
+            return Err(too_large().into());
+        }
+        let mut data = vec![];
+        while let Some(chunk) = response.chunk().await? {
+            if data.len().saturating_add(chunk.len()) > MAX_METADATA_JSON_LEN {
+                return Err(too_large().into());
+            }
+            data.extend_from_slice(&chunk);
+        }
+        Ok(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_data_uris() {
+        assert_eq!(
+            decode_data_uri("application/json,%7B%22name%22%3A%22a%22%7D").unwrap(),
+            br#"{"name":"a"}"#
+        );
+        assert_eq!(
+            decode_data_uri("application/json;base64,eyJuYW1lIjoiYSJ9").unwrap(),
+            br#"{"name":"a"}"#
+        );
+        assert_eq!(decode_data_uri("application/json,{}").unwrap(), b"{}");
+        assert!(decode_data_uri("application/json").is_err());
+        assert!(decode_data_uri("application/json,%7").is_err());
+        assert!(decode_data_uri("application/json;base64,!!").is_err());
+    }
+
+    #[cfg(feature = "http")]
+    #[test]
+    fn http_fetcher_accepts_valid_uris() {
+        let fetcher = HttpFetcher::default()
+            .with_ipfs_gateway("https://ipfs.io/ipfs/")
+            .with_arweave_gateway("https://arweave.net");
+        assert_eq!(
+            fetcher.get_url("HTTPS://EXAMPLE.COM").unwrap(),
+            "HTTPS://EXAMPLE.COM"
+        );
+        assert_eq!(
+            fetcher.get_url("IPFS://cid").unwrap(),
+            "https://ipfs.io/ipfs/cid"
+        );
+        assert_eq!(
+            fetcher.get_url("ar://abc").unwrap(),
+            "https://arweave.net/abc"
+        );
+        assert!(fetcher.get_url("ftp://example.com").is_err());
+    }
+}
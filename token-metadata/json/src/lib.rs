@@ -0,0 +1,14 @@
+//! Crate for resolving and validating the off-chain JSON metadata that a
+//! token-metadata `uri` points to
+
+#![deny(missing_docs)]
+#![forbid(unsafe_code)]
+
+pub mod error;
+pub mod fetcher;
+pub mod resolver;
+pub mod schema;
+
+// Export current interface types for downstream users building with a
+// different version
+pub use spl_token_metadata_interface;
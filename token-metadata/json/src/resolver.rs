@@ -0,0 +1,265 @@
+//! Resolve the off-chain JSON metadata for on-chain token-metadata
+
+use {
+    crate::{
+        error::MetadataJsonError,
+        fetcher::MetadataFetcher,
+        schema::{is_valid_uri, JsonMetadata},
+    },
+    borsh::BorshDeserialize,
+    spl_token_metadata_interface::state::{Field, TokenMetadata},
+    spl_type_length_value::state::{TlvState, TlvStateBorrowed},
+};
+
+/// Maximum size of JSON metadata accepted from a fetcher, in bytes
+pub const MAX_METADATA_JSON_LEN: usize = 1024 * 1024;
+
+/// Deserializes the token-metadata returned by the `Emit` instruction, when
+/// called without a range
+///
+/// Return data is limited in size, so for larger metadata, call `Emit` over
+/// consecutive ranges and concatenate the results first.
+pub fn token_metadata_from_emit_data(data: &[u8]) -> Result<TokenMetadata, MetadataJsonError> {
+    TokenMetadata::try_from_slice(data)
+        .map_err(|e| MetadataJsonError::InvalidTokenMetadata(e.into()))
+}
+
+/// Deserializes the token-metadata from an account that stores it as a
+/// type-length-value entry, such as the example token-metadata program
+///
+/// For token-2022 mints, use `StateWithExtensions::get_variable_len_extension`
+/// from `spl-token-2022` instead.
+pub fn token_metadata_from_tlv_account_data(
+    data: &[u8],
+) -> Result<TokenMetadata, MetadataJsonError> {
+    let state = TlvStateBorrowed::unpack(data)?;
+    Ok(state.get_first_variable_len_value::<TokenMetadata>()?)
+}
+
+/// A difference between the on-chain token-metadata and its off-chain JSON
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldMismatch {
+    /// The field that differs
+    pub field: Field,
+    /// The value stored on-chain
+    pub on_chain: String,
+    /// The value declared in the JSON
+    pub off_chain: String,
+}
+
+/// Off-chain metadata resolved for on-chain token-metadata
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResolvedMetadata {
+    /// The validated JSON metadata
+    pub json: JsonMetadata,
+    /// Fields where the JSON does not declare the same value as on-chain,
+    /// which clients should warn about or treat as untrusted
+    pub mismatches: Vec<FieldMismatch>,
+}
+impl ResolvedMetadata {
+    /// Returns true if the JSON declares the same name and symbol as on-chain
+    pub fn is_consistent(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+}
+
+/// Parses JSON metadata, failing if it does not follow the schema
+pub fn parse_and_validate(data: &[u8]) -> Result<JsonMetadata, MetadataJsonError> {
+    if data.len() > MAX_METADATA_JSON_LEN {
+        return Err(MetadataJsonError::TooLarge(data.len()));
+    }
+    let json = JsonMetadata::from_slice(data)?;
+    let violations = json.validate();
+    if violations.is_empty() {
+        Ok(json)
+    } else {
+        Err(MetadataJsonError::Schema(violations))
+    }
+}
+
+/// Compares the name and symbol declared in the JSON with the on-chain values,
+/// returning any differences. Values must match exactly.
+pub fn compare_with_token_metadata(
+    json: &JsonMetadata,
+    token_metadata: &TokenMetadata,
+) -> Vec<FieldMismatch> {
+    [
+        (Field::Name, &token_metadata.name, &json.name),
+        (Field::Symbol, &token_metadata.symbol, &json.symbol),
+    ]
+    .into_iter()
+    .filter_map(|(field, on_chain, off_chain)| {
+        let off_chain = off_chain.clone().unwrap_or_default();
+        (*on_chain != off_chain).then(|| FieldMismatch {
+            field,
+            on_chain: on_chain.clone(),
+            off_chain,
+        })
+    })
+    .collect()
+}
+
+/// Fetches, validates, and compares the JSON metadata that the token-metadata
+/// URI points to
+pub async fn resolve_metadata_json<F: MetadataFetcher + ?Sized>(
+    fetcher: &F,
+    token_metadata: &TokenMetadata,
+) -> Result<ResolvedMetadata, MetadataJsonError> {
+    let uri = &token_metadata.uri;
+    if uri.is_empty() {
+        return Err(MetadataJsonError::MissingUri);
+    }
+    if !is_valid_uri(uri) {
+        return Err(MetadataJsonError::InvalidUri(uri.clone()));
+    }
+    let data = fetcher.fetch(uri).await.map_err(MetadataJsonError::Fetch)?;
+    let json = parse_and_validate(&data)?;
+    let mismatches = compare_with_token_metadata(&json, token_metadata);
+    Ok(ResolvedMetadata { json, mismatches })
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::{fetcher::FetchError, schema::SchemaViolation},
+        async_trait::async_trait,
+        borsh::BorshSerialize,
+        spl_type_length_value::state::TlvStateMut,
+        std::collections::HashMap,
+    };
+
+    /// Local stand-in for a real fetcher
+    struct MapFetcher(HashMap<String, Vec<u8>>);
+    #[async_trait]
+    impl MetadataFetcher for MapFetcher {
+        async fn fetch(&self, uri: &str) -> Result<Vec<u8>, FetchError> {
+            self.0
+                .get(uri)
+                .cloned()
+                .ok_or_else(|| format!("Not found: {uri}").into())
+        }
+    }
+
+    const URI: &str = "https://example.com/metadata.json";
+
+    fn token_metadata() -> TokenMetadata {
+        TokenMetadata {
+            name: "My Token".to_string(),
+            symbol: "MINE".to_string(),
+            uri: URI.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn map_fetcher(json: &str) -> MapFetcher {
+        MapFetcher(HashMap::from([(URI.to_string(), json.as_bytes().to_vec())]))
+    }
+
+    #[tokio::test]
+    async fn resolve_consistent() {
+        let fetcher = map_fetcher(
+            r#"{ "name": "My Token", "symbol": "MINE", "image": "https://example.com/image.png" }"#,
+        );
+        let resolved = resolve_metadata_json(&fetcher, &token_metadata())
+            .await
+            .unwrap();
+        assert!(resolved.is_consistent());
+        assert_eq!(
+            resolved.json.image.as_deref(),
+            Some("https://example.com/image.png")
+        );
+    }
+
+    #[tokio::test]
+    async fn resolve_mismatch() {
+        let fetcher = map_fetcher(r#"{ "name": "Another Token", "symbol": "MINE" }"#);
+        let resolved = resolve_metadata_json(&fetcher, &token_metadata())
+            .await
+            .unwrap();
+        assert!(!resolved.is_consistent());
+        assert_eq!(
+            resolved.mismatches,
+            vec![FieldMismatch {
+                field: Field::Name,
+                on_chain: "My Token".to_string(),
+                off_chain: "Another Token".to_string(),
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn resolve_failures() {
+        // schema violation
+        let fetcher = map_fetcher(r#"{ "name": "My Token" }"#);
+        let error = resolve_metadata_json(&fetcher, &token_metadata())
+            .await
+            .unwrap_err();
+        match error {
+            MetadataJsonError::Schema(violations) => assert_eq!(
+                violations,
+                vec![SchemaViolation::MissingField("symbol".to_string())]
+            ),
+            _ => panic!("unexpected error {error:?}"),
+        }
+
+        // not json
+        let fetcher = map_fetcher("<html></html>");
+        let error = resolve_metadata_json(&fetcher, &token_metadata())
+            .await
+            .unwrap_err();
+        assert!(matches!(error, MetadataJsonError::InvalidJson(_)));
+
+        // fetch failure
+        let mut token_metadata = token_metadata();
+        token_metadata.uri = "https://example.com/missing.json".to_string();
+        let error = resolve_metadata_json(&fetcher, &token_metadata)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, MetadataJsonError::Fetch(_)));
+
+        // bad uri, never fetched
+        token_metadata.uri = "file:///etc/passwd".to_string();
+        let error = resolve_metadata_json(&fetcher, &token_metadata)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, MetadataJsonError::InvalidUri(_)));
+
+        // no uri
+        token_metadata.uri = String::new();
+        let error = resolve_metadata_json(&fetcher, &token_metadata)
+            .await
+            .unwrap_err();
+        assert!(matches!(error, MetadataJsonError::MissingUri));
+    }
+
+    #[test]
+    fn too_large() {
+        let data = vec![b' '; MAX_METADATA_JSON_LEN + 1];
+        assert!(matches!(
+            parse_and_validate(&data).unwrap_err(),
+            MetadataJsonError::TooLarge(_)
+        ));
+    }
+
+    #[test]
+    fn from_emit_and_account_data() {
+        let token_metadata = token_metadata();
+        let emitted = token_metadata.try_to_vec().unwrap();
+        assert_eq!(
+            token_metadata_from_emit_data(&emitted).unwrap(),
+            token_metadata
+        );
+        assert!(token_metadata_from_emit_data(&emitted[..emitted.len() - 1]).is_err());
+
+        let mut buffer = vec![0; token_metadata.tlv_size_of().unwrap()];
+        let mut state = TlvStateMut::unpack(&mut buffer).unwrap();
+        state
+            .alloc_and_pack_variable_len_entry(&token_metadata, false)
+            .unwrap();
+        assert_eq!(
+            token_metadata_from_tlv_account_data(&buffer).unwrap(),
+            token_metadata
+        );
+    }
+}
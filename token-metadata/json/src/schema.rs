@@ -0,0 +1,319 @@
+//! Schema for the off-chain JSON metadata
+//!
+//! The JSON behind a token-metadata URI is expected to look like:
+//!
+//! ```json
+//! {
+//!   "name": "My Token",
+//!   "symbol": "MINE",
+//!   "description": "Optional description",
+//!   "image": "https://example.com/image.png",
+//!   "animation_url": "ipfs://<cid>",
+//!   "external_url": "https://example.com",
+//!   "attributes": [
+//!     { "trait_type": "color", "value": "blue" },
+//!     { "trait_type": "level", "value": 3 }
+//!   ],
+//!   "properties": {
+//!     "category": "image",
+//!     "files": [{ "uri": "https://example.com/image.png", "type": "image/png" }]
+//!   }
+//! }
+//! ```
+//!
+//! Only `name` and `symbol` are required, and `name` must not be empty. All
+//! URIs must use one of the `SUPPORTED_URI_SCHEMES` or be a `data:` URI. Every
+//! attribute needs a non-empty `trait_type` and a string or number `value`,
+//! and every file needs a valid `uri` and a non-empty `type`. Unknown fields
+//! are allowed and ignored.
+
+use {
+    serde_derive::{Deserialize, Serialize},
+    serde_json::Value,
+    thiserror::Error,
+};
+
+/// URI schemes accepted in the metadata and its JSON, in addition to `data:`
+pub const SUPPORTED_URI_SCHEMES: &[&str] = &["https", "http", "ipfs", "ar"];
+
+/// Checks that the URI uses a supported scheme and is well-formed enough to
+/// be fetched
+pub fn is_valid_uri(uri: &str) -> bool {
+    if uri.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        return false;
+    }
+    if let Some(data) = uri.strip_prefix("data:") {
+        return data.contains(',');
+    }
+    match uri.split_once("://") {
+        Some((scheme, rest)) => {
+            !rest.is_empty()
+                && SUPPORTED_URI_SCHEMES
+                    .iter()
+                    .any(|supported| supported.eq_ignore_ascii_case(scheme))
+        }
+        None => false,
+    }
+}
+
+/// Off-chain JSON metadata for a token
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct JsonMetadata {
+    /// The longer name of the token, must match the on-chain name
+    pub name: Option<String>,
+    /// The shortened symbol for the token, must match the on-chain symbol
+    pub symbol: Option<String>,
+    /// Description of the token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// URI of the image for the token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+    /// URI of a multimedia attachment for the token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub animation_url: Option<String>,
+    /// URI of an external site about the token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub external_url: Option<String>,
+    /// Traits of the token
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attributes: Vec<Attribute>,
+    /// Additional properties, such as the files for the token
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub properties: Option<Properties>,
+}
+
+/// A single trait of a token
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Attribute {
+    /// Name of the trait
+    pub trait_type: Option<String>,
+    /// Value of the trait, either a string or a number
+    #[serde(default)]
+    pub value: Value,
+}
+
+/// Additional properties of a token
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Properties {
+    /// Category of the token's content, e.g. "image" or "video"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+    /// Files for the token
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub files: Vec<File>,
+}
+
+/// A file attached to the token
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct File {
+    /// URI of the file
+    pub uri: Option<String>,
+    /// MIME type of the file
+    #[serde(rename = "type")]
+    pub file_type: Option<String>,
+}
+
+/// A way in which JSON metadata does not follow the schema
+#[derive(Clone, Debug, Error, PartialEq)]
+pub enum SchemaViolation {
+    /// A required field is missing
+    #[error("Missing required field `{0}`")]
+    MissingField(String),
+    /// A field that must have content is empty
+    #[error("Field `{0}` must not be empty")]
+    EmptyField(String),
+    /// A field contains an unsupported or malformed URI
+    #[error("Field `{field}` has an unsupported or malformed URI: {uri}")]
+    InvalidUri {
+        /// Path to the field
+        field: String,
+        /// The offending URI
+        uri: String,
+    },
+    /// An attribute value is neither a string nor a number
+    #[error("Field `{0}` must be a string or a number")]
+    InvalidAttributeValue(String),
+}
+
+impl JsonMetadata {
+    /// Deserializes JSON metadata, without validating it
+    pub fn from_slice(data: &[u8]) -> Result<Self, serde_json::Error> {
+        serde_json::from_slice(data)
+    }
+
+    /// Returns all the ways in which this metadata does not follow the schema,
+    /// which is empty if it's valid
+    pub fn validate(&self) -> Vec<SchemaViolation> {
+        let mut violations = vec![];
+        match &self.name {
+            None => violations.push(SchemaViolation::MissingField("name".to_string())),
+            Some(name) if name.trim().is_empty() => {
+                violations.push(SchemaViolation::EmptyField("name".to_string()))
+            }
+            Some(_) => {}
+        }
+        if self.symbol.is_none() {
+            violations.push(SchemaViolation::MissingField("symbol".to_string()));
+        }
+
+        let mut check_uri = |field: String, uri: &Option<String>| {
+            if let Some(uri) = uri {
+                if !is_valid_uri(uri) {
+                    violations.push(SchemaViolation::InvalidUri {
+                        field,
+                        uri: uri.clone(),
+                    });
+                }
+            }
+        };
+        check_uri("image".to_string(), &self.image);
+        check_uri("animation_url".to_string(), &self.animation_url);
+        check_uri("external_url".to_string(), &self.external_url);
+
+        for (i, attribute) in self.attributes.iter().enumerate() {
+            let field = format!("attributes[{i}]");
+            match &attribute.trait_type {
+                None => {
+                    violations.push(SchemaViolation::MissingField(format!("{field}.trait_type")))
+                }
+                Some(trait_type) if trait_type.is_empty() => {
+                    violations.push(SchemaViolation::EmptyField(format!("{field}.trait_type")))
+                }
+                Some(_) => {}
+            }
+            if !attribute.value.is_string() && !attribute.value.is_number() {
+                violations.push(SchemaViolation::InvalidAttributeValue(format!(
+                    "{field}.value"
+                )));
+            }
+        }
+
+        if let Some(properties) = &self.properties {
+            for (i, file) in properties.files.iter().enumerate() {
+                let field = format!("properties.files[{i}]");
+                match &file.uri {
+                    None => violations.push(SchemaViolation::MissingField(format!("{field}.uri"))),
+                    Some(uri) if !is_valid_uri(uri) => {
+                        violations.push(SchemaViolation::InvalidUri {
+                            field: format!("{field}.uri"),
+                            uri: uri.clone(),
+                        })
+                    }
+                    Some(_) => {}
+                }
+                match &file.file_type {
+                    None => violations.push(SchemaViolation::MissingField(format!("{field}.type"))),
+                    Some(file_type) if file_type.is_empty() => {
+                        violations.push(SchemaViolation::EmptyField(format!("{field}.type")))
+                    }
+                    Some(_) => {}
+                }
+            }
+        }
+
+        violations
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_uris() {
+        for uri in [
+            "https://example.com/metadata.json",
+            "http://example.com",
+            "ipfs://bafybeigdyrzt5sfp7udm7hu76uh7y26nf3efuylqabf3oclgtqy55fbzdi",
+            "ar://abc",
+            "HTTPS://EXAMPLE.COM",
+            "data:application/json,{}",
+        ] {
+            assert!(is_valid_uri(uri), "{uri}");
+        }
+        for uri in [
+            "",
+            "example.com",
+            "ftp://example.com",
+            "https://",
+            "https://example.com/with space",
+            "data:",
+            "data:application/json",
+            "javascript://alert(1)",
+        ] {
+            assert!(!is_valid_uri(uri), "{uri}");
+        }
+    }
+
+    #[test]
+    fn validate_full() {
+        let json = br#"{
+            "name": "My Token",
+            "symbol": "MINE",
+            "description": "A token",
+            "image": "https://example.com/image.png",
+            "animation_url": "ipfs://cid",
+            "external_url": "https://example.com",
+            "attributes": [
+                { "trait_type": "color", "value": "blue" },
+                { "trait_type": "level", "value": 3 }
+            ],
+            "properties": {
+                "category": "image",
+                "files": [{ "uri": "https://example.com/image.png", "type": "image/png" }]
+            },
+            "unknown": { "fields": "are ignored" }
+        }"#;
+        let metadata = JsonMetadata::from_slice(json).unwrap();
+        assert_eq!(metadata.name.as_deref(), Some("My Token"));
+        assert_eq!(metadata.attributes.len(), 2);
+        assert_eq!(
+            metadata.properties.as_ref().unwrap().files[0]
+                .file_type
+                .as_deref(),
+            Some("image/png")
+        );
+        assert_eq!(metadata.validate(), vec![]);
+    }
+
+    #[test]
+    fn validate_violations() {
+        let json = br#"{
+            "name": " ",
+            "image": "ftp://example.com/image.png",
+            "attributes": [
+                { "value": "blue" },
+                { "trait_type": "level", "value": [1, 2] }
+            ],
+            "properties": {
+                "files": [{ "uri": "not a uri", "type": "" }]
+            }
+        }"#;
+        let metadata = JsonMetadata::from_slice(json).unwrap();
+        assert_eq!(
+            metadata.validate(),
+            vec![
+                SchemaViolation::EmptyField("name".to_string()),
+                SchemaViolation::MissingField("symbol".to_string()),
+                SchemaViolation::InvalidUri {
+                    field: "image".to_string(),
+                    uri: "ftp://example.com/image.png".to_string(),
+                },
+                SchemaViolation::MissingField("attributes[0].trait_type".to_string()),
+                SchemaViolation::InvalidAttributeValue("attributes[1].value".to_string()),
+                SchemaViolation::InvalidUri {
+                    field: "properties.files[0].uri".to_string(),
+                    uri: "not a uri".to_string(),
+                },
+                SchemaViolation::EmptyField("properties.files[0].type".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_json() {
+        assert!(JsonMetadata::from_slice(b"not json").is_err());
+        assert!(JsonMetadata::from_slice(br#"{ "name": 5 }"#).is_err());
+    }
+}
@@ -1,9 +1,10 @@
 use {
     clap::{crate_description, crate_name, crate_version, Arg, Command},
     solana_clap_v3_utils::{
-        input_parsers::pubkey_of,
+        input_parsers::{pubkey_of, value_of},
         input_validators::{
-            is_url_or_moniker, is_valid_pubkey, is_valid_signer, normalize_to_url_if_moniker,
+            is_parsable, is_url_or_moniker, is_valid_pubkey, is_valid_signer,
+            normalize_to_url_if_moniker,
         },
         keypair::{
             signer_from_path, signer_from_path_with_config, DefaultSigner, SignerFromPathConfig,
@@ -28,7 +29,11 @@ use {
         client::{ProgramRpcClient, ProgramRpcClientSendTransaction, RpcClientResponse},
         token::Token,
     },
-    spl_token_upgrade::{get_token_upgrade_authority_address, instruction::exchange},
+    spl_token_upgrade::{
+        get_exchange_config_address, get_token_upgrade_authority_address,
+        instruction::{create_exchange_config, exchange},
+        state::{ExchangeConfig, ExchangeRate, ExchangeTerms},
+    },
     std::{error::Error, process::exit, sync::Arc},
 };
 
@@ -200,6 +205,109 @@ async fn process_exchange(
     Ok(signature)
 }
 
+async fn process_create_config(
+    rpc_client: &Arc<RpcClient>,
+    payer: &Arc<dyn Signer>,
+    original_mint: &Pubkey,
+    new_mint: &Pubkey,
+    admin: &Arc<dyn Signer>,
+    terms: &ExchangeTerms,
+    bulk_signers: Vec<Arc<dyn Signer>>,
+) -> Result<Signature, Box<dyn Error>> {
+    let _ = get_mint_owner_checked(rpc_client, original_mint).await?;
+    let _ = get_mint_owner_checked(rpc_client, new_mint).await?;
+    terms
+        .validate()
+        .map_err(|err| format!("error: invalid exchange terms: {}", err))?;
+    let exchange_config = get_exchange_config_address(
+        original_mint,
+        new_mint,
+        &admin.pubkey(),
+        &spl_token_upgrade::id(),
+    );
+
+    println!(
+        "Creating exchange config {} with admin {}",
+        exchange_config,
+        admin.pubkey()
+    );
+    let mut transaction = Transaction::new_unsigned(Message::new(
+        &[create_exchange_config(
+            &spl_token_upgrade::id(),
+            original_mint,
+            new_mint,
+            &admin.pubkey(),
+            &payer.pubkey(),
+            terms,
+        )],
+        Some(&payer.pubkey()),
+    ));
+
+    let blockhash = rpc_client
+        .get_latest_blockhash()
+        .await
+        .map_err(|err| format!("error: unable to get latest blockhash: {}", err))?;
+
+    transaction
+        .try_sign(&bulk_signers, blockhash)
+        .map_err(|err| format!("error: failed to sign transaction: {}", err))?;
+
+    let signature = rpc_client
+        .send_and_confirm_transaction_with_spinner(&transaction)
+        .await
+        .map_err(|err| format!("error: send transaction: {}", err))?;
+
+    Ok(signature)
+}
+
+async fn process_display_config(
+    rpc_client: &RpcClient,
+    exchange_config: &Pubkey,
+) -> Result<ExchangeConfig, Box<dyn Error>> {
+    let account = rpc_client.get_account(exchange_config).await?;
+    if account.owner != spl_token_upgrade::id() {
+        return Err(format!(
+            "Account {} is not owned by the token-upgrade program",
+            exchange_config
+        )
+        .into());
+    }
+    let config = *ExchangeConfig::unpack(&account.data)
+        .map_err(|_| format!("Account {} is not a valid exchange config", exchange_config))?;
+    let terms = &config.terms;
+    let display_timestamp = |timestamp: i64| {
+        if timestamp == 0 {
+            "unset".to_string()
+        } else {
+            timestamp.to_string()
+        }
+    };
+
+    println!("Exchange config: {}", exchange_config);
+    println!("  Admin: {}", config.admin);
+    println!("  Original mint: {}", config.original_mint);
+    println!("  New mint: {}", config.new_mint);
+    println!(
+        "  Rate: {} new for {} original",
+        u64::from(terms.rate.numerator),
+        u64::from(terms.rate.denominator)
+    );
+    println!("  Adjust decimals: {}", bool::from(terms.adjust_decimals));
+    println!(
+        "  Exchange start: {}",
+        display_timestamp(terms.exchange_start.into())
+    );
+    println!(
+        "  Exchange end: {}",
+        display_timestamp(terms.exchange_end.into())
+    );
+    println!(
+        "  Reverse exchange end: {}",
+        display_timestamp(terms.reverse_exchange_end.into())
+    );
+    Ok(config)
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error>> {
     let app_matches = Command::new(crate_name!())
@@ -341,6 +449,95 @@ async fn main() -> Result<(), Box<dyn Error>> {
                     .help("Member signer of a multisig account")
             )
         )
+        .subcommand(
+            Command::new("create-config").about("Create an exchange config with a rate and time window, admin-controlled")
+            .arg(
+                Arg::new("original_mint")
+                    .validator(|s| is_valid_pubkey(s))
+                    .value_name("ADDRESS")
+                    .required(true)
+                    .takes_value(true)
+                    .index(1)
+                    .help("Original mint address, whose tokens will be burned")
+            )
+            .arg(
+                Arg::new("new_mint")
+                    .validator(|s| is_valid_pubkey(s))
+                    .value_name("ADDRESS")
+                    .required(true)
+                    .takes_value(true)
+                    .index(2)
+                    .help("New mint address, whose tokens will be transferred to users")
+            )
+            .arg(
+                Arg::new("admin")
+                    .long("admin")
+                    .value_name("ADMIN_KEYPAIR")
+                    .validator(|s| is_valid_signer(s))
+                    .takes_value(true)
+                    .help("Specify the admin who can update the config. This may be a keypair file or the ASK keyword. [default: fee payer]"),
+            )
+            .arg(
+                Arg::new("rate_numerator")
+                    .long("rate-numerator")
+                    .value_name("AMOUNT")
+                    .validator(is_parsable::<u64>)
+                    .takes_value(true)
+                    .default_value("1")
+                    .help("Number of new tokens received for every `rate-denominator` original tokens")
+            )
+            .arg(
+                Arg::new("rate_denominator")
+                    .long("rate-denominator")
+                    .value_name("AMOUNT")
+                    .validator(is_parsable::<u64>)
+                    .takes_value(true)
+                    .default_value("1")
+                    .help("Number of original tokens burned for every `rate-numerator` new tokens")
+            )
+            .arg(
+                Arg::new("adjust_decimals")
+                    .long("adjust-decimals")
+                    .takes_value(false)
+                    .help("Apply the rate to UI amounts, scaling by the difference in decimals between the mints")
+            )
+            .arg(
+                Arg::new("exchange_start")
+                    .long("exchange-start")
+                    .value_name("UNIX_TIMESTAMP")
+                    .validator(is_parsable::<i64>)
+                    .takes_value(true)
+                    .help("Time from which exchanges are allowed [default: unbounded]")
+            )
+            .arg(
+                Arg::new("exchange_end")
+                    .long("exchange-end")
+                    .value_name("UNIX_TIMESTAMP")
+                    .validator(is_parsable::<i64>)
+                    .takes_value(true)
+                    .help("Time from which exchanges are no longer allowed [default: unbounded]")
+            )
+            .arg(
+                Arg::new("reverse_exchange_end")
+                    .long("reverse-exchange-end")
+                    .value_name("UNIX_TIMESTAMP")
+                    .validator(is_parsable::<i64>)
+                    .takes_value(true)
+                    .help("Time until which reverse exchanges are allowed [default: reverse exchanges disabled]")
+            )
+        )
+        .subcommand(
+            Command::new("display-config").about("Display the terms of an exchange config")
+            .arg(
+                Arg::new("exchange_config")
+                    .validator(|s| is_valid_pubkey(s))
+                    .value_name("ADDRESS")
+                    .required(true)
+                    .takes_value(true)
+                    .index(1)
+                    .help("Exchange config address")
+            )
+        )
         .get_matches();
 
     let (command, matches) = app_matches.subcommand().unwrap();
@@ -485,6 +682,68 @@ async fn main() -> Result<(), Box<dyn Error>> {
             });
             println!("Signature: {}", signature);
         }
+        ("create-config", arg_matches) => {
+            let mut bulk_signers = vec![config.payer.clone()];
+            let original_mint = pubkey_of(arg_matches, "original_mint").unwrap();
+            let new_mint = pubkey_of(arg_matches, "new_mint").unwrap();
+            let admin = arg_matches
+                .value_of("admin")
+                .map_or(Ok(config.payer.clone()), |path| {
+                    signer_from_path(arg_matches, path, "admin", &mut wallet_manager).map(Arc::from)
+                })
+                .unwrap_or_else(|err| {
+                    eprintln!("error: admin signer: {}", err);
+                    exit(1);
+                });
+            if !bulk_signers.contains(&admin) {
+                bulk_signers.push(admin.clone());
+            }
+            let terms = ExchangeTerms {
+                rate: ExchangeRate {
+                    numerator: value_of::<u64>(arg_matches, "rate_numerator")
+                        .unwrap()
+                        .into(),
+                    denominator: value_of::<u64>(arg_matches, "rate_denominator")
+                        .unwrap()
+                        .into(),
+                },
+                adjust_decimals: arg_matches.is_present("adjust_decimals").into(),
+                exchange_start: value_of::<i64>(arg_matches, "exchange_start")
+                    .unwrap_or_default()
+                    .into(),
+                exchange_end: value_of::<i64>(arg_matches, "exchange_end")
+                    .unwrap_or_default()
+                    .into(),
+                reverse_exchange_end: value_of::<i64>(arg_matches, "reverse_exchange_end")
+                    .unwrap_or_default()
+                    .into(),
+            };
+
+            let signature = process_create_config(
+                &rpc_client,
+                &config.payer,
+                &original_mint,
+                &new_mint,
+                &admin,
+                &terms,
+                bulk_signers,
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("error: create config: {}", err);
+                exit(1);
+            });
+            println!("Signature: {}", signature);
+        }
+        ("display-config", arg_matches) => {
+            let exchange_config = pubkey_of(arg_matches, "exchange_config").unwrap();
+            process_display_config(&rpc_client, &exchange_config)
+                .await
+                .unwrap_or_else(|err| {
+                    eprintln!("error: display config: {}", err);
+                    exit(1);
+                });
+        }
         _ => unreachable!(),
     };
 
@@ -839,4 +1098,77 @@ mod test {
         let destination_account = new_token.get_account_info(&destination).await.unwrap();
         assert_eq!(destination_account.base.amount, amount);
     }
+
+    #[tokio::test]
+    async fn success_create_and_display_config() {
+        let (test_validator, payer) = new_validator_for_test().await;
+        let payer: Arc<dyn Signer> = Arc::new(payer);
+        let rpc_client = Arc::new(test_validator.get_async_rpc_client());
+        let client = Arc::new(ProgramRpcClient::new(
+            rpc_client.clone(),
+            ProgramRpcClientSendTransaction,
+        ));
+
+        let mint_authority = Keypair::new();
+        let original_token = setup_mint(
+            &spl_token::id(),
+            &mint_authority.pubkey(),
+            9,
+            payer.clone(),
+            client.clone(),
+        )
+        .await;
+        let new_token = setup_mint(
+            &spl_token_2022::id(),
+            &mint_authority.pubkey(),
+            6,
+            payer.clone(),
+            client.clone(),
+        )
+        .await;
+
+        let admin: Arc<dyn Signer> = Arc::new(Keypair::new());
+        let terms = ExchangeTerms {
+            rate: ExchangeRate {
+                numerator: 2.into(),
+                denominator: 3.into(),
+            },
+            adjust_decimals: true.into(),
+            exchange_end: i64::MAX.into(),
+            reverse_exchange_end: i64::MAX.into(),
+            ..Default::default()
+        };
+        process_create_config(
+            &rpc_client,
+            &payer,
+            original_token.get_address(),
+            new_token.get_address(),
+            &admin,
+            &terms,
+            vec![payer.clone(), admin.clone()],
+        )
+        .await
+        .unwrap();
+
+        let exchange_config = get_exchange_config_address(
+            original_token.get_address(),
+            new_token.get_address(),
+            &admin.pubkey(),
+            &spl_token_upgrade::id(),
+        );
+        let config = process_display_config(&rpc_client, &exchange_config)
+            .await
+            .unwrap();
+        assert_eq!(config.admin, admin.pubkey());
+        assert_eq!(&config.original_mint, original_token.get_address());
+        assert_eq!(&config.new_mint, new_token.get_address());
+        assert_eq!(config.terms, terms);
+
+        // not an exchange config
+        assert!(
+            process_display_config(&rpc_client, original_token.get_address())
+                .await
+                .is_err()
+        );
+    }
 }
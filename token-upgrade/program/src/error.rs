@@ -16,6 +16,26 @@ pub enum TokenUpgradeError {
     /// Decimals of original and new token mint do not match
     #[error("Decimals of original and new token mint do not match")]
     DecimalsMismatch,
+    /// Exchange rate numerator or denominator is zero
+    #[error("Exchange rate numerator or denominator is zero")]
+    InvalidExchangeRate,
+    /// Exchange window must start before it ends
+    #[error("Exchange window must start before it ends")]
+    InvalidExchangeWindow,
+    /// Exchange is not allowed at the current time
+    #[error("Exchange is not allowed at the current time")]
+    ExchangeNotActive,
+
+    // 5
+    /// Reverse exchange is not allowed at the current time
+    #[error("Reverse exchange is not allowed at the current time")]
+    ReverseExchangeNotActive,
+    /// Converted amount is zero or does not fit in a token amount
+    #[error("Converted amount is zero or does not fit in a token amount")]
+    InvalidConvertedAmount,
+    /// Provided admin does not match the exchange config admin
+    #[error("Provided admin does not match the exchange config admin")]
+    AdminMismatch,
 }
 impl From<TokenUpgradeError> for ProgramError {
     fn from(e: TokenUpgradeError) -> Self {
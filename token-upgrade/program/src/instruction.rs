@@ -1,12 +1,16 @@
 //! Program instructions
 
 use {
-    crate::get_token_upgrade_authority_address,
+    crate::{
+        get_exchange_config_address, get_token_upgrade_authority_address, state::ExchangeTerms,
+    },
     num_enum::{IntoPrimitive, TryFromPrimitive},
     solana_program::{
         instruction::{AccountMeta, Instruction},
         pubkey::Pubkey,
+        system_program,
     },
+    spl_token_2022::pod::{pod_bytes_of, PodU64},
 };
 
 /// Instructions supported by the TokenUpgrade program
@@ -35,6 +39,90 @@ pub enum TokenUpgradeInstruction {
     ///   None
     ///
     Exchange,
+    /// Creates an exchange config for the original and new mints, at the
+    /// address given by
+    /// `get_exchange_config_address(original_mint, new_mint, admin, program_id)`.
+    ///
+    /// Escrows used with the config must be held by or delegated to the config
+    /// address.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writeable]` Exchange config
+    ///   1. `[]` Original token mint
+    ///   2. `[]` New token mint
+    ///   3. `[signer]` Admin of the exchange config
+    ///   4. `[writeable, signer]` Funding account
+    ///   5. `[]` System program
+    ///
+    /// Data expected by this instruction:
+    ///   `ExchangeTerms`
+    ///
+    CreateExchangeConfig,
+    /// Replaces the terms of an exchange config, which can be used to close
+    /// or extend the exchange window or the reverse exchange grace period.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writeable]` Exchange config
+    ///   1. `[signer]` Admin of the exchange config
+    ///
+    /// Data expected by this instruction:
+    ///   `ExchangeTerms`
+    ///
+    UpdateExchangeConfig,
+    /// Burns all of the original tokens in the user's account, and transfers
+    /// new tokens at the rate of the exchange config from an escrow held by or
+    /// delegated to the config into another account.
+    ///
+    /// Fails outside of the exchange window of the config.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writeable]` Original token account to burn from
+    ///   1. `[writeable]` Original token mint
+    ///   2. `[writeable]` Escrow of new tokens held by or delegated to the
+    ///       exchange config
+    ///   3. `[writeable]` New token account to transfer into
+    ///   4. `[]` New token mint
+    ///   5. `[]` Exchange config
+    ///   6. `[]` SPL Token program for original mint
+    ///   7. `[]` SPL Token program for new mint
+    ///   8. `[]` Original token account transfer authority (owner or delegate)
+    ///   9. ..9+M `[signer]` M multisig signer accounts
+    ///
+    /// Data expected by this instruction:
+    ///   None
+    ///
+    ExchangeWithConfig,
+    /// Transfers new tokens from the user's account back into the escrow owned
+    /// by the exchange config, and transfers the corresponding original tokens
+    /// at the rate of the config from an escrow of original tokens held by or
+    /// delegated to the config into another account.
+    ///
+    /// Only allowed during the reverse exchange grace period of the config,
+    /// and only as long as the admin keeps the escrow of original tokens
+    /// funded.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writeable]` New token account to transfer from
+    ///   1. `[]` New token mint
+    ///   2. `[writeable]` Escrow of new tokens owned by the exchange config
+    ///   3. `[writeable]` Escrow of original tokens held by or delegated to the
+    ///       exchange config
+    ///   4. `[]` Original token mint
+    ///   5. `[writeable]` Original token account to transfer into
+    ///   6. `[]` Exchange config
+    ///   7. `[]` SPL Token program for original mint
+    ///   8. `[]` SPL Token program for new mint
+    ///   9. `[]` New token account transfer authority (owner or delegate)
+    ///   10. ..10+M `[signer]` M multisig signer accounts
+    ///
+    /// Data expected by this instruction:
+    ///   `u64` amount of new tokens to return
+    ///
+    ReverseExchange,
 }
 
 /// Create an `Exchange` instruction
@@ -75,3 +163,135 @@ pub fn exchange(
         data: vec![TokenUpgradeInstruction::Exchange.into()],
     }
 }
+
+/// Create a `CreateExchangeConfig` instruction
+pub fn create_exchange_config(
+    program_id: &Pubkey,
+    original_mint: &Pubkey,
+    new_mint: &Pubkey,
+    admin: &Pubkey,
+    funding_account: &Pubkey,
+    terms: &ExchangeTerms,
+) -> Instruction {
+    let exchange_config = get_exchange_config_address(original_mint, new_mint, admin, program_id);
+    let accounts = vec![
+        AccountMeta::new(exchange_config, false),
+        AccountMeta::new_readonly(*original_mint, false),
+        AccountMeta::new_readonly(*new_mint, false),
+        AccountMeta::new_readonly(*admin, true),
+        AccountMeta::new(*funding_account, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    let mut data = vec![TokenUpgradeInstruction::CreateExchangeConfig.into()];
+    data.extend_from_slice(pod_bytes_of(terms));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Create an `UpdateExchangeConfig` instruction
+pub fn update_exchange_config(
+    program_id: &Pubkey,
+    exchange_config: &Pubkey,
+    admin: &Pubkey,
+    terms: &ExchangeTerms,
+) -> Instruction {
+    let accounts = vec![
+        AccountMeta::new(*exchange_config, false),
+        AccountMeta::new_readonly(*admin, true),
+    ];
+    let mut data = vec![TokenUpgradeInstruction::UpdateExchangeConfig.into()];
+    data.extend_from_slice(pod_bytes_of(terms));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Create an `ExchangeWithConfig` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn exchange_with_config(
+    program_id: &Pubkey,
+    original_account: &Pubkey,
+    original_mint: &Pubkey,
+    new_escrow: &Pubkey,
+    new_account: &Pubkey,
+    new_mint: &Pubkey,
+    exchange_config: &Pubkey,
+    original_token_program_id: &Pubkey,
+    new_token_program_id: &Pubkey,
+    original_transfer_authority: &Pubkey,
+    original_multisig_signers: &[&Pubkey],
+) -> Instruction {
+    let mut accounts = Vec::with_capacity(9usize.saturating_add(original_multisig_signers.len()));
+    accounts.push(AccountMeta::new(*original_account, false));
+    accounts.push(AccountMeta::new(*original_mint, false));
+    accounts.push(AccountMeta::new(*new_escrow, false));
+    accounts.push(AccountMeta::new(*new_account, false));
+    accounts.push(AccountMeta::new_readonly(*new_mint, false));
+    accounts.push(AccountMeta::new_readonly(*exchange_config, false));
+    accounts.push(AccountMeta::new_readonly(*original_token_program_id, false));
+    accounts.push(AccountMeta::new_readonly(*new_token_program_id, false));
+    accounts.push(AccountMeta::new_readonly(
+        *original_transfer_authority,
+        original_multisig_signers.is_empty(),
+    ));
+    for signer_pubkey in original_multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: vec![TokenUpgradeInstruction::ExchangeWithConfig.into()],
+    }
+}
+
+/// Create a `ReverseExchange` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn reverse_exchange(
+    program_id: &Pubkey,
+    new_account: &Pubkey,
+    new_mint: &Pubkey,
+    new_escrow: &Pubkey,
+    original_escrow: &Pubkey,
+    original_mint: &Pubkey,
+    original_account: &Pubkey,
+    exchange_config: &Pubkey,
+    original_token_program_id: &Pubkey,
+    new_token_program_id: &Pubkey,
+    new_transfer_authority: &Pubkey,
+    new_multisig_signers: &[&Pubkey],
+    amount: u64,
+) -> Instruction {
+    let mut accounts = Vec::with_capacity(10usize.saturating_add(new_multisig_signers.len()));
+    accounts.push(AccountMeta::new(*new_account, false));
+    accounts.push(AccountMeta::new_readonly(*new_mint, false));
+    accounts.push(AccountMeta::new(*new_escrow, false));
+    accounts.push(AccountMeta::new(*original_escrow, false));
+    accounts.push(AccountMeta::new_readonly(*original_mint, false));
+    accounts.push(AccountMeta::new(*original_account, false));
+    accounts.push(AccountMeta::new_readonly(*exchange_config, false));
+    accounts.push(AccountMeta::new_readonly(*original_token_program_id, false));
+    accounts.push(AccountMeta::new_readonly(*new_token_program_id, false));
+    accounts.push(AccountMeta::new_readonly(
+        *new_transfer_authority,
+        new_multisig_signers.is_empty(),
+    ));
+    for signer_pubkey in new_multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+    let mut data = vec![TokenUpgradeInstruction::ReverseExchange.into()];
+    data.extend_from_slice(pod_bytes_of(&PodU64::from(amount)));
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
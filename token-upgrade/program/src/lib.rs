@@ -6,6 +6,7 @@ mod entrypoint;
 pub mod error;
 pub mod instruction;
 pub mod processor;
+pub mod state;
 
 // Export current SDK types for downstream users building with a different SDK version
 pub use solana_program;
@@ -14,6 +15,7 @@ use solana_program::pubkey::Pubkey;
 solana_program::declare_id!("TkupDoNseygccBCjSsrSpMccjwHfTYwcrjpnDSrFDhC");
 
 const TOKEN_ESCROW_AUTHORITY_SEED: &[u8] = b"token-escrow-authority";
+const EXCHANGE_CONFIG_SEED: &[u8] = b"exchange-config";
 
 /// Get the upgrade token account authority
 pub fn get_token_upgrade_authority_address(
@@ -58,3 +60,54 @@ pub(crate) fn collect_token_upgrade_authority_signer_seeds<'a>(
         bump_seed,
     ]
 }
+
+/// Get the exchange config address for the given mints and admin, which is
+/// also the authority of the escrows used with the config
+pub fn get_exchange_config_address(
+    original_mint: &Pubkey,
+    new_mint: &Pubkey,
+    admin: &Pubkey,
+    program_id: &Pubkey,
+) -> Pubkey {
+    get_exchange_config_address_and_bump_seed(original_mint, new_mint, admin, program_id).0
+}
+
+pub(crate) fn get_exchange_config_address_and_bump_seed(
+    original_mint: &Pubkey,
+    new_mint: &Pubkey,
+    admin: &Pubkey,
+    program_id: &Pubkey,
+) -> (Pubkey, u8) {
+    Pubkey::find_program_address(
+        &collect_exchange_config_seeds(original_mint, new_mint, admin),
+        program_id,
+    )
+}
+
+pub(crate) fn collect_exchange_config_seeds<'a>(
+    original_mint: &'a Pubkey,
+    new_mint: &'a Pubkey,
+    admin: &'a Pubkey,
+) -> [&'a [u8]; 4] {
+    [
+        EXCHANGE_CONFIG_SEED,
+        original_mint.as_ref(),
+        new_mint.as_ref(),
+        admin.as_ref(),
+    ]
+}
+
+pub(crate) fn collect_exchange_config_signer_seeds<'a>(
+    original_mint: &'a Pubkey,
+    new_mint: &'a Pubkey,
+    admin: &'a Pubkey,
+    bump_seed: &'a [u8],
+) -> [&'a [u8]; 5] {
+    [
+        EXCHANGE_CONFIG_SEED,
+        original_mint.as_ref(),
+        new_mint.as_ref(),
+        admin.as_ref(),
+        bump_seed,
+    ]
+}
@@ -2,20 +2,29 @@
 
 use {
     crate::{
-        collect_token_upgrade_authority_signer_seeds, error::TokenUpgradeError,
-        get_token_upgrade_authority_address_and_bump_seed, instruction::TokenUpgradeInstruction,
+        collect_exchange_config_signer_seeds, collect_token_upgrade_authority_signer_seeds,
+        error::TokenUpgradeError,
+        get_exchange_config_address_and_bump_seed,
+        get_token_upgrade_authority_address_and_bump_seed,
+        instruction::TokenUpgradeInstruction,
+        state::{ExchangeConfig, ExchangeTerms},
     },
     solana_program::{
         account_info::{next_account_info, AccountInfo},
+        clock::Clock,
         entrypoint::ProgramResult,
         msg,
         program::{invoke, invoke_signed},
         program_error::ProgramError,
         pubkey::Pubkey,
+        rent::Rent,
+        system_instruction,
+        sysvar::Sysvar,
     },
     spl_token_2022::{
         extension::StateWithExtensions,
-        instruction::decode_instruction_type,
+        instruction::{decode_instruction_data, decode_instruction_type},
+        pod::PodU64,
         state::{Account, Mint},
     },
 };
@@ -53,7 +62,7 @@ fn burn_original_tokens<'a>(
 }
 
 #[allow(clippy::too_many_arguments)]
-fn transfer_new_tokens<'a>(
+fn transfer_from_escrow<'a>(
     new_token_program: AccountInfo<'a>,
     source: AccountInfo<'a>,
     mint: AccountInfo<'a>,
@@ -80,6 +89,73 @@ fn transfer_new_tokens<'a>(
     )
 }
 
+#[allow(clippy::too_many_arguments)]
+fn transfer_tokens<'a>(
+    token_program: AccountInfo<'a>,
+    source: AccountInfo<'a>,
+    mint: AccountInfo<'a>,
+    destination: AccountInfo<'a>,
+    authority: AccountInfo<'a>,
+    multisig_signers: &[AccountInfo<'a>],
+    amount: u64,
+    decimals: u8,
+) -> ProgramResult {
+    let multisig_pubkeys = multisig_signers.iter().map(|s| s.key).collect::<Vec<_>>();
+    let ix = spl_token_2022::instruction::transfer_checked(
+        token_program.key,
+        source.key,
+        mint.key,
+        destination.key,
+        authority.key,
+        &multisig_pubkeys,
+        amount,
+        decimals,
+    )?;
+    let mut account_infos = vec![source, mint, destination, authority];
+    account_infos.extend_from_slice(multisig_signers);
+    invoke(&ix, &account_infos)
+}
+
+fn check_admin(exchange_config: &ExchangeConfig, admin_info: &AccountInfo) -> ProgramResult {
+    if exchange_config.admin != *admin_info.key {
+        return Err(TokenUpgradeError::AdminMismatch.into());
+    }
+    if !admin_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    Ok(())
+}
+
+fn check_decimals(terms: &ExchangeTerms, original_decimals: u8, new_decimals: u8) -> ProgramResult {
+    if !bool::from(terms.adjust_decimals) && original_decimals != new_decimals {
+        msg!(
+            "Original and new token mint decimals mismatch: original has {} decimals, and new has {}",
+            original_decimals,
+            new_decimals,
+        );
+        return Err(TokenUpgradeError::DecimalsMismatch.into());
+    }
+    Ok(())
+}
+
+/// Unpack the exchange config, checking that it's owned by the program and
+/// that it's for the given mints
+fn get_exchange_config(
+    program_id: &Pubkey,
+    exchange_config_info: &AccountInfo,
+    original_mint: &Pubkey,
+    new_mint: &Pubkey,
+) -> Result<ExchangeConfig, ProgramError> {
+    check_owner(exchange_config_info, program_id)?;
+    let data = exchange_config_info.try_borrow_data()?;
+    let exchange_config = *ExchangeConfig::unpack(&data)?;
+    if exchange_config.original_mint != *original_mint || exchange_config.new_mint != *new_mint {
+        msg!("Exchange config is not for the provided mints");
+        return Err(ProgramError::InvalidAccountData);
+    }
+    Ok(exchange_config)
+}
+
 fn process_exchange(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
@@ -168,7 +244,7 @@ fn process_exchange(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramRes
         decimals,
     )?;
 
-    transfer_new_tokens(
+    transfer_from_escrow(
         new_token_program.clone(),
         new_escrow_info.clone(),
         new_mint_info.clone(),
@@ -182,9 +258,355 @@ fn process_exchange(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramRes
     Ok(())
 }
 
+fn process_create_exchange_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    terms: &ExchangeTerms,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let exchange_config_info = next_account_info(account_info_iter)?;
+    let original_mint_info = next_account_info(account_info_iter)?;
+    let new_mint_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+    let funding_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    if !admin_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    terms.validate()?;
+    {
+        let original_mint_data = original_mint_info.try_borrow_data()?;
+        let original_mint = StateWithExtensions::<Mint>::unpack(&original_mint_data)?;
+        let new_mint_data = new_mint_info.try_borrow_data()?;
+        let new_mint = StateWithExtensions::<Mint>::unpack(&new_mint_data)?;
+        check_decimals(terms, original_mint.base.decimals, new_mint.base.decimals)?;
+    }
+
+    let (expected_exchange_config, bump_seed) = get_exchange_config_address_and_bump_seed(
+        original_mint_info.key,
+        new_mint_info.key,
+        admin_info.key,
+        program_id,
+    );
+    if expected_exchange_config != *exchange_config_info.key {
+        msg!(
+            "Expected exchange config {}, received {}",
+            &expected_exchange_config,
+            exchange_config_info.key
+        );
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    // Create the account, which may already hold some lamports
+    let required_lamports = Rent::get()?
+        .minimum_balance(ExchangeConfig::LEN)
+        .saturating_sub(exchange_config_info.lamports());
+    if required_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(
+                funding_info.key,
+                exchange_config_info.key,
+                required_lamports,
+            ),
+            &[
+                funding_info.clone(),
+                exchange_config_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+    }
+    let bump_seed = [bump_seed];
+    let signer_seeds = collect_exchange_config_signer_seeds(
+        original_mint_info.key,
+        new_mint_info.key,
+        admin_info.key,
+        &bump_seed,
+    );
+    invoke_signed(
+        &system_instruction::allocate(exchange_config_info.key, ExchangeConfig::LEN as u64),
+        &[exchange_config_info.clone(), system_program_info.clone()],
+        &[&signer_seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(exchange_config_info.key, program_id),
+        &[exchange_config_info.clone(), system_program_info.clone()],
+        &[&signer_seeds],
+    )?;
+
+    let mut data = exchange_config_info.try_borrow_mut_data()?;
+    let exchange_config = ExchangeConfig::unpack_mut(&mut data)?;
+    exchange_config.admin = *admin_info.key;
+    exchange_config.original_mint = *original_mint_info.key;
+    exchange_config.new_mint = *new_mint_info.key;
+    exchange_config.bump_seed = bump_seed[0];
+    exchange_config.terms = *terms;
+
+    Ok(())
+}
+
+fn process_update_exchange_config(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    terms: &ExchangeTerms,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let exchange_config_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    check_owner(exchange_config_info, program_id)?;
+    let mut data = exchange_config_info.try_borrow_mut_data()?;
+    let exchange_config = ExchangeConfig::unpack_mut(&mut data)?;
+    check_admin(exchange_config, admin_info)?;
+    terms.validate()?;
+
+    exchange_config.terms = *terms;
+
+    Ok(())
+}
+
+fn process_exchange_with_config(program_id: &Pubkey, accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let original_account_info = next_account_info(account_info_iter)?;
+    let original_mint_info = next_account_info(account_info_iter)?;
+    let new_escrow_info = next_account_info(account_info_iter)?;
+    let new_account_info = next_account_info(account_info_iter)?;
+    let new_mint_info = next_account_info(account_info_iter)?;
+    let exchange_config_info = next_account_info(account_info_iter)?;
+    let original_token_program = next_account_info(account_info_iter)?;
+    let new_token_program = next_account_info(account_info_iter)?;
+    let original_transfer_authority_info = next_account_info(account_info_iter)?;
+
+    // owner checks
+    check_owner(original_account_info, original_token_program.key)?;
+    check_owner(original_mint_info, original_token_program.key)?;
+    check_owner(new_escrow_info, new_token_program.key)?;
+    check_owner(new_account_info, new_token_program.key)?;
+    check_owner(new_mint_info, new_token_program.key)?;
+
+    let exchange_config = get_exchange_config(
+        program_id,
+        exchange_config_info,
+        original_mint_info.key,
+        new_mint_info.key,
+    )?;
+    if !exchange_config
+        .terms
+        .is_exchange_active(Clock::get()?.unix_timestamp)
+    {
+        return Err(TokenUpgradeError::ExchangeNotActive.into());
+    }
+    let bump_seed = [exchange_config.bump_seed];
+    let authority_seeds = collect_exchange_config_signer_seeds(
+        original_mint_info.key,
+        new_mint_info.key,
+        &exchange_config.admin,
+        &bump_seed,
+    );
+
+    // pull out these values in a block to drop all data before performing CPIs
+    let (original_amount, new_amount, original_decimals, new_decimals) = {
+        // check mints are actually mints
+        let original_mint_data = original_mint_info.try_borrow_data()?;
+        let original_mint = StateWithExtensions::<Mint>::unpack(&original_mint_data)?;
+        let new_mint_data = new_mint_info.try_borrow_data()?;
+        let new_mint = StateWithExtensions::<Mint>::unpack(&new_mint_data)?;
+
+        // check accounts are actually accounts
+        let original_account_data = original_account_info.try_borrow_data()?;
+        let original_account = StateWithExtensions::<Account>::unpack(&original_account_data)?;
+        let new_escrow_data = new_escrow_info.try_borrow_data()?;
+        let new_escrow = StateWithExtensions::<Account>::unpack(&new_escrow_data)?;
+        let new_account_data = new_account_info.try_borrow_data()?;
+        let _ = StateWithExtensions::<Account>::unpack(&new_account_data)?;
+
+        let original_decimals = original_mint.base.decimals;
+        let new_decimals = new_mint.base.decimals;
+        check_decimals(&exchange_config.terms, original_decimals, new_decimals)?;
+
+        let original_amount = original_account.base.amount;
+        let new_amount = exchange_config
+            .terms
+            .calculate_new_amount(original_amount, original_decimals, new_decimals)
+            .filter(|amount| *amount > 0)
+            .ok_or(TokenUpgradeError::InvalidConvertedAmount)?;
+        if new_escrow.base.amount < new_amount {
+            msg!(
+                "Escrow only has {} tokens, needs at least {}",
+                new_escrow.base.amount,
+                new_amount
+            );
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        (original_amount, new_amount, original_decimals, new_decimals)
+    };
+
+    burn_original_tokens(
+        original_token_program.clone(),
+        original_account_info.clone(),
+        original_mint_info.clone(),
+        original_transfer_authority_info.clone(),
+        account_info_iter.as_slice(),
+        original_amount,
+        original_decimals,
+    )?;
+
+    transfer_from_escrow(
+        new_token_program.clone(),
+        new_escrow_info.clone(),
+        new_mint_info.clone(),
+        new_account_info.clone(),
+        exchange_config_info.clone(),
+        &authority_seeds,
+        new_amount,
+        new_decimals,
+    )?;
+
+    Ok(())
+}
+
+fn process_reverse_exchange(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let new_account_info = next_account_info(account_info_iter)?;
+    let new_mint_info = next_account_info(account_info_iter)?;
+    let new_escrow_info = next_account_info(account_info_iter)?;
+    let original_escrow_info = next_account_info(account_info_iter)?;
+    let original_mint_info = next_account_info(account_info_iter)?;
+    let original_account_info = next_account_info(account_info_iter)?;
+    let exchange_config_info = next_account_info(account_info_iter)?;
+    let original_token_program = next_account_info(account_info_iter)?;
+    let new_token_program = next_account_info(account_info_iter)?;
+    let new_transfer_authority_info = next_account_info(account_info_iter)?;
+
+    // owner checks
+    check_owner(new_account_info, new_token_program.key)?;
+    check_owner(new_mint_info, new_token_program.key)?;
+    check_owner(new_escrow_info, new_token_program.key)?;
+    check_owner(original_escrow_info, original_token_program.key)?;
+    check_owner(original_mint_info, original_token_program.key)?;
+    check_owner(original_account_info, original_token_program.key)?;
+
+    let exchange_config = get_exchange_config(
+        program_id,
+        exchange_config_info,
+        original_mint_info.key,
+        new_mint_info.key,
+    )?;
+    if !exchange_config
+        .terms
+        .is_reverse_exchange_active(Clock::get()?.unix_timestamp)
+    {
+        return Err(TokenUpgradeError::ReverseExchangeNotActive.into());
+    }
+    let bump_seed = [exchange_config.bump_seed];
+    let authority_seeds = collect_exchange_config_signer_seeds(
+        original_mint_info.key,
+        new_mint_info.key,
+        &exchange_config.admin,
+        &bump_seed,
+    );
+
+    // pull out these values in a block to drop all data before performing CPIs
+    let (original_amount, original_decimals, new_decimals) = {
+        // check mints are actually mints
+        let original_mint_data = original_mint_info.try_borrow_data()?;
+        let original_mint = StateWithExtensions::<Mint>::unpack(&original_mint_data)?;
+        let new_mint_data = new_mint_info.try_borrow_data()?;
+        let new_mint = StateWithExtensions::<Mint>::unpack(&new_mint_data)?;
+
+        // check accounts are actually accounts
+        let new_account_data = new_account_info.try_borrow_data()?;
+        let _ = StateWithExtensions::<Account>::unpack(&new_account_data)?;
+        let original_escrow_data = original_escrow_info.try_borrow_data()?;
+        let original_escrow = StateWithExtensions::<Account>::unpack(&original_escrow_data)?;
+        let original_account_data = original_account_info.try_borrow_data()?;
+        let _ = StateWithExtensions::<Account>::unpack(&original_account_data)?;
+
+        // the returned tokens must go back to the config's escrow, and not to
+        // any other account
+        let new_escrow_data = new_escrow_info.try_borrow_data()?;
+        let new_escrow = StateWithExtensions::<Account>::unpack(&new_escrow_data)?;
+        if new_escrow.base.owner != *exchange_config_info.key {
+            msg!(
+                "Escrow of new tokens must be owned by exchange config {}",
+                exchange_config_info.key
+            );
+            return Err(TokenUpgradeError::InvalidOwner.into());
+        }
+
+        let original_decimals = original_mint.base.decimals;
+        let new_decimals = new_mint.base.decimals;
+        check_decimals(&exchange_config.terms, original_decimals, new_decimals)?;
+
+        let original_amount = exchange_config
+            .terms
+            .calculate_original_amount(new_amount, original_decimals, new_decimals)
+            .filter(|amount| *amount > 0)
+            .ok_or(TokenUpgradeError::InvalidConvertedAmount)?;
+        if original_escrow.base.amount < original_amount {
+            msg!(
+                "Escrow only has {} tokens, needs at least {}",
+                original_escrow.base.amount,
+                original_amount
+            );
+            return Err(ProgramError::InsufficientFunds);
+        }
+
+        (original_amount, original_decimals, new_decimals)
+    };
+
+    transfer_tokens(
+        new_token_program.clone(),
+        new_account_info.clone(),
+        new_mint_info.clone(),
+        new_escrow_info.clone(),
+        new_transfer_authority_info.clone(),
+        account_info_iter.as_slice(),
+        new_amount,
+        new_decimals,
+    )?;
+
+    transfer_from_escrow(
+        original_token_program.clone(),
+        original_escrow_info.clone(),
+        original_mint_info.clone(),
+        original_account_info.clone(),
+        exchange_config_info.clone(),
+        &authority_seeds,
+        original_amount,
+        original_decimals,
+    )?;
+
+    Ok(())
+}
+
 /// Instruction processor
 pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
     match decode_instruction_type(input)? {
         TokenUpgradeInstruction::Exchange => process_exchange(program_id, accounts),
+        TokenUpgradeInstruction::CreateExchangeConfig => {
+            let terms = decode_instruction_data::<ExchangeTerms>(input)?;
+            process_create_exchange_config(program_id, accounts, terms)
+        }
+        TokenUpgradeInstruction::UpdateExchangeConfig => {
+            let terms = decode_instruction_data::<ExchangeTerms>(input)?;
+            process_update_exchange_config(program_id, accounts, terms)
+        }
+        TokenUpgradeInstruction::ExchangeWithConfig => {
+            process_exchange_with_config(program_id, accounts)
+        }
+        TokenUpgradeInstruction::ReverseExchange => {
+            let amount = decode_instruction_data::<PodU64>(input)?;
+            process_reverse_exchange(program_id, accounts, u64::from(*amount))
+        }
     }
 }
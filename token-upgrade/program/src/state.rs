@@ -0,0 +1,236 @@
+//! State of an exchange config
+
+use {
+    crate::error::TokenUpgradeError,
+    bytemuck::{Pod, Zeroable},
+    solana_program::{clock::UnixTimestamp, program_error::ProgramError, pubkey::Pubkey},
+    spl_token_2022::pod::{PodBool, PodI64, PodU64},
+    std::mem::size_of,
+};
+
+/// Rate at which original tokens are converted into new tokens, so that
+/// `new_amount = original_amount * numerator / denominator`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct ExchangeRate {
+    /// Numerator of the rate
+    pub numerator: PodU64,
+    /// Denominator of the rate
+    pub denominator: PodU64,
+}
+
+/// Terms of an exchange, set by the admin of the config
+///
+/// Timestamps of `0` are treated as unset.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct ExchangeTerms {
+    /// Rate at which original tokens are converted into new tokens
+    pub rate: ExchangeRate,
+    /// If true, amounts are also scaled by the difference in decimals between
+    /// the mints, so that the rate applies to UI amounts. If false, both mints
+    /// must have the same decimals.
+    pub adjust_decimals: PodBool,
+    /// Time from which exchanges are allowed, or unbounded if unset
+    pub exchange_start: PodI64,
+    /// Time from which exchanges are no longer allowed, or unbounded if unset
+    pub exchange_end: PodI64,
+    /// Time until which reverse exchanges are allowed, or never if unset
+    pub reverse_exchange_end: PodI64,
+}
+impl ExchangeTerms {
+    /// Check that the rate and the exchange window are valid
+    pub fn validate(&self) -> Result<(), ProgramError> {
+        if u64::from(self.rate.numerator) == 0 || u64::from(self.rate.denominator) == 0 {
+            return Err(TokenUpgradeError::InvalidExchangeRate.into());
+        }
+        let start = i64::from(self.exchange_start);
+        let end = i64::from(self.exchange_end);
+        if start != 0 && end != 0 && start >= end {
+            return Err(TokenUpgradeError::InvalidExchangeWindow.into());
+        }
+        Ok(())
+    }
+
+    /// Returns true if exchanges are allowed at the given time
+    pub fn is_exchange_active(&self, unix_timestamp: UnixTimestamp) -> bool {
+        let start = i64::from(self.exchange_start);
+        let end = i64::from(self.exchange_end);
+        (start == 0 || unix_timestamp >= start) && (end == 0 || unix_timestamp < end)
+    }
+
+    /// Returns true if reverse exchanges are allowed at the given time
+    pub fn is_reverse_exchange_active(&self, unix_timestamp: UnixTimestamp) -> bool {
+        let end = i64::from(self.reverse_exchange_end);
+        end != 0 && unix_timestamp < end
+    }
+
+    /// Get the rate as a fraction, scaled by the difference in decimals if
+    /// needed
+    fn scaled_rate(&self, original_decimals: u8, new_decimals: u8) -> Option<(u128, u128)> {
+        let mut numerator = u128::from(u64::from(self.rate.numerator));
+        let mut denominator = u128::from(u64::from(self.rate.denominator));
+        if bool::from(self.adjust_decimals) {
+            let scale = 10u128.checked_pow(u32::from(new_decimals.abs_diff(original_decimals)))?;
+            if new_decimals > original_decimals {
+                numerator = numerator.checked_mul(scale)?;
+            } else {
+                denominator = denominator.checked_mul(scale)?;
+            }
+        }
+        Some((numerator, denominator))
+    }
+
+    /// Convert an amount of original tokens into new tokens, rounding down
+    pub fn calculate_new_amount(
+        &self,
+        original_amount: u64,
+        original_decimals: u8,
+        new_decimals: u8,
+    ) -> Option<u64> {
+        let (numerator, denominator) = self.scaled_rate(original_decimals, new_decimals)?;
+        mul_div(original_amount, numerator, denominator)
+    }
+
+    /// Convert an amount of new tokens back into original tokens, rounding down
+    pub fn calculate_original_amount(
+        &self,
+        new_amount: u64,
+        original_decimals: u8,
+        new_decimals: u8,
+    ) -> Option<u64> {
+        let (numerator, denominator) = self.scaled_rate(original_decimals, new_decimals)?;
+        mul_div(new_amount, denominator, numerator)
+    }
+}
+
+fn mul_div(amount: u64, numerator: u128, denominator: u128) -> Option<u64> {
+    u128::from(amount)
+        .checked_mul(numerator)?
+        .checked_div(denominator)?
+        .try_into()
+        .ok()
+}
+
+/// Exchange config account, at the address given by
+/// `get_exchange_config_address(original_mint, new_mint, admin, program_id)`
+///
+/// The config address is also the authority of the escrows used for exchanges
+/// through it, so that its escrows can never be used for a 1:1 `Exchange`.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct ExchangeConfig {
+    /// Authority that can update the terms
+    pub admin: Pubkey,
+    /// Mint of the tokens that are burned
+    pub original_mint: Pubkey,
+    /// Mint of the tokens that are transferred from the escrow
+    pub new_mint: Pubkey,
+    /// Bump seed of the config address
+    pub bump_seed: u8,
+    /// Terms of the exchange
+    pub terms: ExchangeTerms,
+}
+impl ExchangeConfig {
+    /// Size of an exchange config account
+    pub const LEN: usize = size_of::<Self>();
+
+    /// Unpack an exchange config account
+    pub fn unpack(data: &[u8]) -> Result<&Self, ProgramError> {
+        bytemuck::try_from_bytes(data).map_err(|_| ProgramError::InvalidAccountData)
+    }
+
+    /// Unpack an exchange config account, mutably
+    pub fn unpack_mut(data: &mut [u8]) -> Result<&mut Self, ProgramError> {
+        bytemuck::try_from_bytes_mut(data).map_err(|_| ProgramError::InvalidAccountData)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_terms(numerator: u64, denominator: u64, adjust_decimals: bool) -> ExchangeTerms {
+        ExchangeTerms {
+            rate: ExchangeRate {
+                numerator: numerator.into(),
+                denominator: denominator.into(),
+            },
+            adjust_decimals: adjust_decimals.into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn convert() {
+        // 1 new token for every 1,000 original tokens
+        let terms = new_terms(1, 1_000, false);
+        assert_eq!(terms.calculate_new_amount(1_000_000, 6, 6), Some(1_000));
+        assert_eq!(terms.calculate_new_amount(1_999, 6, 6), Some(1));
+        assert_eq!(terms.calculate_new_amount(999, 6, 6), Some(0));
+        assert_eq!(
+            terms.calculate_original_amount(1_000, 6, 6),
+            Some(1_000_000)
+        );
+
+        // decimals are ignored unless adjusting
+        assert_eq!(terms.calculate_new_amount(1_000_000, 9, 6), Some(1_000));
+
+        // 2 new tokens for every 3 original tokens, going from 9 to 6 decimals
+        let terms = new_terms(2, 3, true);
+        assert_eq!(
+            terms.calculate_new_amount(3_000_000_000, 9, 6),
+            Some(2_000_000)
+        );
+        assert_eq!(
+            terms.calculate_original_amount(2_000_000, 9, 6),
+            Some(3_000_000_000)
+        );
+
+        // going from 0 to 9 decimals
+        let terms = new_terms(1, 1, true);
+        assert_eq!(terms.calculate_new_amount(5, 0, 9), Some(5_000_000_000));
+        assert_eq!(
+            terms.calculate_original_amount(5_999_999_999, 0, 9),
+            Some(5)
+        );
+
+        // overflow
+        assert_eq!(terms.calculate_new_amount(u64::MAX, 0, 9), None);
+        assert_eq!(terms.calculate_new_amount(1, 0, u8::MAX), None);
+        let terms = new_terms(u64::MAX, 1, false);
+        assert_eq!(terms.calculate_new_amount(2, 0, 0), None);
+    }
+
+    #[test]
+    fn windows() {
+        let mut terms = new_terms(1, 1, false);
+        terms.validate().unwrap();
+        assert!(terms.is_exchange_active(0));
+        assert!(terms.is_exchange_active(i64::MAX));
+        assert!(!terms.is_reverse_exchange_active(0));
+
+        terms.exchange_start = 100.into();
+        terms.exchange_end = 200.into();
+        terms.reverse_exchange_end = 300.into();
+        terms.validate().unwrap();
+        assert!(!terms.is_exchange_active(99));
+        assert!(terms.is_exchange_active(100));
+        assert!(terms.is_exchange_active(199));
+        assert!(!terms.is_exchange_active(200));
+        assert!(terms.is_reverse_exchange_active(299));
+        assert!(!terms.is_reverse_exchange_active(300));
+
+        terms.exchange_end = 100.into();
+        assert_eq!(
+            terms.validate().unwrap_err(),
+            TokenUpgradeError::InvalidExchangeWindow.into()
+        );
+
+        let terms = new_terms(0, 1, false);
+        assert_eq!(
+            terms.validate().unwrap_err(),
+            TokenUpgradeError::InvalidExchangeRate.into()
+        );
+    }
+}
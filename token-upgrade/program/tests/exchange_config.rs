@@ -0,0 +1,583 @@
+// Mark this test as SBF-only due to current `ProgramTest` limitations when CPIing into the system program
+#![cfg(feature = "test-sbf")]
+
+use {
+    solana_program_test::{
+        processor,
+        tokio::{self, sync::Mutex},
+        ProgramTest, ProgramTestContext,
+    },
+    solana_sdk::{
+        instruction::{Instruction, InstructionError},
+        pubkey::Pubkey,
+        signature::Signer,
+        signer::keypair::Keypair,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token_client::{
+        client::{
+            ProgramBanksClient, ProgramBanksClientProcessTransaction, ProgramClient,
+            SendTransaction, SimulateTransaction,
+        },
+        token::Token,
+    },
+    spl_token_upgrade::{
+        error::TokenUpgradeError,
+        get_exchange_config_address,
+        instruction::{
+            create_exchange_config, exchange_with_config, reverse_exchange, update_exchange_config,
+        },
+        state::{ExchangeConfig, ExchangeRate, ExchangeTerms},
+    },
+    std::sync::Arc,
+};
+
+fn keypair_clone(kp: &Keypair) -> Keypair {
+    Keypair::from_bytes(&kp.to_bytes()).expect("failed to copy keypair")
+}
+
+async fn setup() -> (
+    Arc<Mutex<ProgramTestContext>>,
+    Arc<dyn ProgramClient<ProgramBanksClientProcessTransaction>>,
+    Arc<Keypair>,
+) {
+    let mut program_test = ProgramTest::new(
+        "spl_token_upgrade",
+        spl_token_upgrade::id(),
+        processor!(spl_token_upgrade::processor::process),
+    );
+
+    program_test.prefer_bpf(false); // simplicity in the build
+
+    program_test.add_program(
+        "spl_token_2022",
+        spl_token_2022::id(),
+        processor!(spl_token_2022::processor::Processor::process),
+    );
+    program_test.add_program(
+        "spl_token",
+        spl_token::id(),
+        processor!(spl_token::processor::Processor::process),
+    );
+
+    let context = program_test.start_with_context().await;
+    let payer = Arc::new(keypair_clone(&context.payer));
+    let context = Arc::new(Mutex::new(context));
+
+    let client: Arc<dyn ProgramClient<ProgramBanksClientProcessTransaction>> =
+        Arc::new(ProgramBanksClient::new_from_context(
+            Arc::clone(&context),
+            ProgramBanksClientProcessTransaction,
+        ));
+    (context, client, payer)
+}
+
+async fn setup_mint<T: SendTransaction + SimulateTransaction>(
+    program_id: &Pubkey,
+    mint_authority: &Pubkey,
+    decimals: u8,
+    payer: Arc<Keypair>,
+    client: Arc<dyn ProgramClient<T>>,
+) -> Token<T> {
+    let mint_account = Keypair::new();
+    let token = Token::new(
+        client,
+        program_id,
+        &mint_account.pubkey(),
+        Some(decimals),
+        payer,
+    );
+    token
+        .create_mint(mint_authority, None, vec![], &[&mint_account])
+        .await
+        .unwrap();
+    token
+}
+
+async fn process_instruction(
+    context: &Mutex<ProgramTestContext>,
+    instruction: Instruction,
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let mut context = context.lock().await;
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|e| e.unwrap())
+}
+
+// 2 new tokens with 6 decimals for every 3 original tokens with 9 decimals
+fn redenomination_terms() -> ExchangeTerms {
+    ExchangeTerms {
+        rate: ExchangeRate {
+            numerator: 2.into(),
+            denominator: 3.into(),
+        },
+        adjust_decimals: true.into(),
+        reverse_exchange_end: i64::MAX.into(),
+        ..Default::default()
+    }
+}
+
+const ORIGINAL_DECIMALS: u8 = 9;
+const NEW_DECIMALS: u8 = 6;
+
+#[tokio::test]
+async fn success_exchange_and_reverse() {
+    let (context, client, payer) = setup().await;
+    let original_program_id = spl_token::id();
+    let new_program_id = spl_token_2022::id();
+
+    let wallet = Keypair::new();
+    let admin = Keypair::new();
+    let mint_authority = Keypair::new();
+    let mint_authority_pubkey = mint_authority.pubkey();
+
+    let original_token = setup_mint(
+        &original_program_id,
+        &mint_authority_pubkey,
+        ORIGINAL_DECIMALS,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+    let new_token = setup_mint(
+        &new_program_id,
+        &mint_authority_pubkey,
+        NEW_DECIMALS,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+
+    let terms = redenomination_terms();
+    process_instruction(
+        &context,
+        create_exchange_config(
+            &spl_token_upgrade::id(),
+            original_token.get_address(),
+            new_token.get_address(),
+            &admin.pubkey(),
+            &payer.pubkey(),
+            &terms,
+        ),
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    let exchange_config_address = get_exchange_config_address(
+        original_token.get_address(),
+        new_token.get_address(),
+        &admin.pubkey(),
+        &spl_token_upgrade::id(),
+    );
+    {
+        let mut context = context.lock().await;
+        let account = context
+            .banks_client
+            .get_account(exchange_config_address)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(account.owner, spl_token_upgrade::id());
+        let exchange_config = ExchangeConfig::unpack(&account.data).unwrap();
+        assert_eq!(exchange_config.admin, admin.pubkey());
+        assert_eq!(exchange_config.original_mint, *original_token.get_address());
+        assert_eq!(exchange_config.new_mint, *new_token.get_address());
+        assert_eq!(exchange_config.terms, terms);
+    }
+
+    // wallet holds 3 original tokens
+    let original_amount = 3_000_000_000;
+    original_token
+        .create_associated_token_account(&wallet.pubkey())
+        .await
+        .unwrap();
+    let original_account = original_token.get_associated_token_address(&wallet.pubkey());
+    original_token
+        .mint_to(
+            &original_account,
+            &mint_authority_pubkey,
+            original_amount,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    new_token
+        .create_associated_token_account(&wallet.pubkey())
+        .await
+        .unwrap();
+    let new_account = new_token.get_associated_token_address(&wallet.pubkey());
+
+    // escrows are held by the config
+    let escrow_amount = 10_000_000;
+    new_token
+        .create_associated_token_account(&exchange_config_address)
+        .await
+        .unwrap();
+    let new_escrow = new_token.get_associated_token_address(&exchange_config_address);
+    new_token
+        .mint_to(
+            &new_escrow,
+            &mint_authority_pubkey,
+            escrow_amount,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    original_token
+        .create_associated_token_account(&exchange_config_address)
+        .await
+        .unwrap();
+    let original_escrow = original_token.get_associated_token_address(&exchange_config_address);
+    original_token
+        .mint_to(
+            &original_escrow,
+            &mint_authority_pubkey,
+            original_amount,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+
+    process_instruction(
+        &context,
+        exchange_with_config(
+            &spl_token_upgrade::id(),
+            &original_account,
+            original_token.get_address(),
+            &new_escrow,
+            &new_account,
+            new_token.get_address(),
+            &exchange_config_address,
+            &original_program_id,
+            &new_program_id,
+            &wallet.pubkey(),
+            &[],
+        ),
+        &[&wallet],
+    )
+    .await
+    .unwrap();
+
+    let original_account_info = original_token
+        .get_account_info(&original_account)
+        .await
+        .unwrap();
+    assert_eq!(original_account_info.base.amount, 0);
+    let new_account_info = new_token.get_account_info(&new_account).await.unwrap();
+    assert_eq!(new_account_info.base.amount, 2_000_000);
+    let new_escrow_info = new_token.get_account_info(&new_escrow).await.unwrap();
+    assert_eq!(new_escrow_info.base.amount, escrow_amount - 2_000_000);
+
+    // give back 1 new token for 1.5 original tokens
+    let reverse_instruction = reverse_exchange(
+        &spl_token_upgrade::id(),
+        &new_account,
+        new_token.get_address(),
+        &new_escrow,
+        &original_escrow,
+        original_token.get_address(),
+        &original_account,
+        &exchange_config_address,
+        &original_program_id,
+        &new_program_id,
+        &wallet.pubkey(),
+        &[],
+        1_000_000,
+    );
+    process_instruction(&context, reverse_instruction.clone(), &[&wallet])
+        .await
+        .unwrap();
+
+    let original_account_info = original_token
+        .get_account_info(&original_account)
+        .await
+        .unwrap();
+    assert_eq!(original_account_info.base.amount, 1_500_000_000);
+    let new_account_info = new_token.get_account_info(&new_account).await.unwrap();
+    assert_eq!(new_account_info.base.amount, 1_000_000);
+    let new_escrow_info = new_token.get_account_info(&new_escrow).await.unwrap();
+    assert_eq!(new_escrow_info.base.amount, escrow_amount - 1_000_000);
+
+    // admin closes the grace period, reverse exchange no longer works
+    let closed_terms = ExchangeTerms {
+        reverse_exchange_end: 1.into(),
+        ..terms
+    };
+    process_instruction(
+        &context,
+        update_exchange_config(
+            &spl_token_upgrade::id(),
+            &exchange_config_address,
+            &admin.pubkey(),
+            &closed_terms,
+        ),
+        &[&admin],
+    )
+    .await
+    .unwrap();
+
+    let mut reverse_instruction = reverse_instruction;
+    reverse_instruction.data[1..].copy_from_slice(&500_000u64.to_le_bytes());
+    let error = process_instruction(&context, reverse_instruction, &[&wallet])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TokenUpgradeError::ReverseExchangeNotActive as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_exchange_outside_window() {
+    let (context, client, payer) = setup().await;
+    let original_program_id = spl_token_2022::id();
+    let new_program_id = spl_token_2022::id();
+
+    let wallet = Keypair::new();
+    let admin = Keypair::new();
+    let mint_authority = Keypair::new();
+    let mint_authority_pubkey = mint_authority.pubkey();
+
+    let original_token = setup_mint(
+        &original_program_id,
+        &mint_authority_pubkey,
+        ORIGINAL_DECIMALS,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+    let new_token = setup_mint(
+        &new_program_id,
+        &mint_authority_pubkey,
+        NEW_DECIMALS,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+
+    // window has not opened yet
+    let terms = ExchangeTerms {
+        exchange_start: (i64::MAX - 1).into(),
+        exchange_end: i64::MAX.into(),
+        ..redenomination_terms()
+    };
+    process_instruction(
+        &context,
+        create_exchange_config(
+            &spl_token_upgrade::id(),
+            original_token.get_address(),
+            new_token.get_address(),
+            &admin.pubkey(),
+            &payer.pubkey(),
+            &terms,
+        ),
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    let exchange_config_address = get_exchange_config_address(
+        original_token.get_address(),
+        new_token.get_address(),
+        &admin.pubkey(),
+        &spl_token_upgrade::id(),
+    );
+
+    original_token
+        .create_associated_token_account(&wallet.pubkey())
+        .await
+        .unwrap();
+    let original_account = original_token.get_associated_token_address(&wallet.pubkey());
+    original_token
+        .mint_to(
+            &original_account,
+            &mint_authority_pubkey,
+            3_000_000_000,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    new_token
+        .create_associated_token_account(&wallet.pubkey())
+        .await
+        .unwrap();
+    let new_account = new_token.get_associated_token_address(&wallet.pubkey());
+    new_token
+        .create_associated_token_account(&exchange_config_address)
+        .await
+        .unwrap();
+    let new_escrow = new_token.get_associated_token_address(&exchange_config_address);
+    new_token
+        .mint_to(
+            &new_escrow,
+            &mint_authority_pubkey,
+            2_000_000,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+
+    let error = process_instruction(
+        &context,
+        exchange_with_config(
+            &spl_token_upgrade::id(),
+            &original_account,
+            original_token.get_address(),
+            &new_escrow,
+            &new_account,
+            new_token.get_address(),
+            &exchange_config_address,
+            &original_program_id,
+            &new_program_id,
+            &wallet.pubkey(),
+            &[],
+        ),
+        &[&wallet],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TokenUpgradeError::ExchangeNotActive as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_create_and_update() {
+    let (context, client, payer) = setup().await;
+    let program_id = spl_token_2022::id();
+
+    let admin = Keypair::new();
+    let mint_authority = Keypair::new();
+    let mint_authority_pubkey = mint_authority.pubkey();
+
+    let original_token = setup_mint(
+        &program_id,
+        &mint_authority_pubkey,
+        ORIGINAL_DECIMALS,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+    let new_token = setup_mint(
+        &program_id,
+        &mint_authority_pubkey,
+        NEW_DECIMALS,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+
+    // decimals differ without adjusting
+    let terms = ExchangeTerms {
+        adjust_decimals: false.into(),
+        ..redenomination_terms()
+    };
+    let error = process_instruction(
+        &context,
+        create_exchange_config(
+            &spl_token_upgrade::id(),
+            original_token.get_address(),
+            new_token.get_address(),
+            &admin.pubkey(),
+            &payer.pubkey(),
+            &terms,
+        ),
+        &[&admin],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TokenUpgradeError::DecimalsMismatch as u32)
+        )
+    );
+
+    // zero rate
+    let terms = ExchangeTerms {
+        rate: ExchangeRate::default(),
+        ..redenomination_terms()
+    };
+    let error = process_instruction(
+        &context,
+        create_exchange_config(
+            &spl_token_upgrade::id(),
+            original_token.get_address(),
+            new_token.get_address(),
+            &admin.pubkey(),
+            &payer.pubkey(),
+            &terms,
+        ),
+        &[&admin],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TokenUpgradeError::InvalidExchangeRate as u32)
+        )
+    );
+
+    process_instruction(
+        &context,
+        create_exchange_config(
+            &spl_token_upgrade::id(),
+            original_token.get_address(),
+            new_token.get_address(),
+            &admin.pubkey(),
+            &payer.pubkey(),
+            &redenomination_terms(),
+        ),
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    let exchange_config_address = get_exchange_config_address(
+        original_token.get_address(),
+        new_token.get_address(),
+        &admin.pubkey(),
+        &spl_token_upgrade::id(),
+    );
+
+    // wrong admin
+    let not_admin = Keypair::new();
+    let error = process_instruction(
+        &context,
+        update_exchange_config(
+            &spl_token_upgrade::id(),
+            &exchange_config_address,
+            &not_admin.pubkey(),
+            &redenomination_terms(),
+        ),
+        &[&not_admin],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TokenUpgradeError::AdminMismatch as u32)
+        )
+    );
+}
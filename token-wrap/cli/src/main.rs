@@ -0,0 +1,1266 @@
+use {
+    clap::{crate_description, crate_name, crate_version, Arg, ArgMatches, Command},
+    solana_account_decoder::{parse_token::TokenAccountType, UiAccountData},
+    solana_clap_v3_utils::{
+        input_parsers::{pubkey_of, value_of},
+        input_validators::{
+            is_parsable, is_url_or_moniker, is_valid_pubkey, is_valid_signer,
+            normalize_to_url_if_moniker,
+        },
+        keypair::{
+            signer_from_path, signer_from_path_with_config, DefaultSigner, SignerFromPathConfig,
+        },
+    },
+    solana_client::{nonblocking::rpc_client::RpcClient, rpc_request::TokenAccountsFilter},
+    solana_remote_wallet::remote_wallet::RemoteWalletManager,
+    solana_sdk::{
+        account::Account as SolanaAccount, commitment_config::CommitmentConfig, pubkey::Pubkey,
+        signature::Signer,
+    },
+    spl_associated_token_account::{
+        get_associated_token_address_with_program_id,
+        instruction::create_associated_token_account_idempotent,
+    },
+    spl_token_2022::{
+        extension::{
+            metadata_pointer::MetadataPointer, BaseStateWithExtensions, StateWithExtensions,
+        },
+        pod::{pod_from_bytes, OptionalNonZeroPubkey},
+        state::{Account, Mint},
+    },
+    spl_token_client::{
+        client::{
+            ProgramClient, ProgramRpcClient, ProgramRpcClientSendTransaction, RpcClientResponse,
+        },
+        token::Token,
+    },
+    spl_token_wrap::{
+        get_wrapped_mint_address, get_wrapped_mint_authority, get_wrapped_mint_backpointer_address,
+        instruction::{create_mint, unwrap, wrap},
+        state::{Backpointer, WrappedMintExtensions},
+    },
+    std::{error::Error, process::exit, sync::Arc},
+};
+
+type RpcProgramClient = Arc<dyn ProgramClient<ProgramRpcClientSendTransaction>>;
+
+struct Config {
+    commitment_config: CommitmentConfig,
+    payer: Arc<dyn Signer>,
+    json_rpc_url: String,
+    verbose: bool,
+}
+
+/// State of a wrapped mint and of the escrows backing it
+#[derive(Debug, PartialEq)]
+struct WrappedMintInfo {
+    wrapped_mint: Pubkey,
+    wrapped_token_program_id: Pubkey,
+    unwrapped_mint: Pubkey,
+    unwrapped_token_program_id: Pubkey,
+    /// Every token account of the unwrapped mint owned by the wrapped mint
+    /// authority, with its balance, sorted by address
+    escrows: Vec<(Pubkey, u64)>,
+    escrow_amount: u64,
+    wrapped_supply: u64,
+    decimals: u8,
+}
+impl WrappedMintInfo {
+    /// Every wrapped token must be backed by an unwrapped token in escrow
+    fn is_fully_backed(&self) -> bool {
+        self.escrow_amount >= self.wrapped_supply
+    }
+}
+
+async fn get_mint_account_checked(
+    program_client: &RpcProgramClient,
+    mint: &Pubkey,
+) -> Result<SolanaAccount, Box<dyn Error>> {
+    let mint_account = program_client
+        .get_account(*mint)
+        .await?
+        .ok_or_else(|| format!("Mint {} not found", mint))?;
+    let _ = StateWithExtensions::<Mint>::unpack(&mint_account.data)
+        .map_err(|_| format!("Account {} is not a valid mint", mint))?;
+    Ok(mint_account)
+}
+
+/// Get the unwrapped mint from the backpointer of a wrapped mint, checking
+/// that the wrapped mint is at the expected address
+async fn get_unwrapped_mint_checked(
+    program_client: &RpcProgramClient,
+    wrapped_mint: &Pubkey,
+    wrapped_token_program_id: &Pubkey,
+) -> Result<Pubkey, Box<dyn Error>> {
+    let backpointer_address = get_wrapped_mint_backpointer_address(wrapped_mint);
+    let backpointer_account = program_client
+        .get_account(backpointer_address)
+        .await?
+        .filter(|account| account.owner == spl_token_wrap::id())
+        .ok_or_else(|| format!("Mint {} is not a wrapped mint", wrapped_mint))?;
+    let unwrapped_mint = pod_from_bytes::<Backpointer>(&backpointer_account.data)
+        .map_err(|_| format!("Account {} is not a valid backpointer", backpointer_address))?
+        .unwrapped_mint;
+    if get_wrapped_mint_address(&unwrapped_mint, wrapped_token_program_id) != *wrapped_mint {
+        return Err(format!(
+            "Backpointer {} does not match wrapped mint {}",
+            backpointer_address, wrapped_mint
+        )
+        .into());
+    }
+    Ok(unwrapped_mint)
+}
+
+/// Get the metadata account to pass when mirroring metadata, which is only
+/// needed if the metadata pointer of the unwrapped mint targets another account
+fn get_unwrapped_metadata_address(
+    unwrapped_mint: &Pubkey,
+    unwrapped_mint_account: &SolanaAccount,
+) -> Result<Option<Pubkey>, Box<dyn Error>> {
+    let mint_state = StateWithExtensions::<Mint>::unpack(&unwrapped_mint_account.data)?;
+    let metadata_address = mint_state
+        .get_extension::<MetadataPointer>()
+        .ok()
+        .and_then(|pointer| Option::<Pubkey>::from(pointer.metadata_address))
+        .ok_or_else(|| format!("Mint {} does not point to any metadata", unwrapped_mint))?;
+    Ok(Some(metadata_address).filter(|address| address != unwrapped_mint))
+}
+
+async fn process_create_mint(
+    program_client: &RpcProgramClient,
+    payer: &Arc<dyn Signer>,
+    unwrapped_mint_authority: &Arc<dyn Signer>,
+    unwrapped_mint: &Pubkey,
+    wrapped_token_program_id: &Pubkey,
+    idempotent: bool,
+    extensions: &WrappedMintExtensions,
+) -> Result<RpcClientResponse, Box<dyn Error>> {
+    let unwrapped_mint_account = get_mint_account_checked(program_client, unwrapped_mint).await?;
+    let unwrapped_metadata = if bool::from(extensions.mirror_metadata) {
+        get_unwrapped_metadata_address(unwrapped_mint, &unwrapped_mint_account)?
+    } else {
+        None
+    };
+    let wrapped_mint = get_wrapped_mint_address(unwrapped_mint, wrapped_token_program_id);
+    // Only the unwrapped mint authority may choose extensions
+    let signers = if extensions.is_empty() {
+        vec![]
+    } else {
+        vec![unwrapped_mint_authority.clone()]
+    };
+
+    let token = Token::new(
+        program_client.clone(),
+        wrapped_token_program_id,
+        &wrapped_mint,
+        None,
+        payer.clone(),
+    );
+
+    println!(
+        "Creating wrapped mint {} for unwrapped mint {}",
+        wrapped_mint, unwrapped_mint
+    );
+    token
+        .process_ixs(
+            &[create_mint(
+                &spl_token_wrap::id(),
+                &wrapped_mint,
+                unwrapped_mint,
+                wrapped_token_program_id,
+                &payer.pubkey(),
+                idempotent,
+                extensions,
+                Some(&unwrapped_mint_authority.pubkey()),
+                unwrapped_metadata.as_ref(),
+            )],
+            &signers,
+        )
+        .await
+        .map_err(|e| e.into())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn process_wrap(
+    program_client: &RpcProgramClient,
+    payer: &Arc<dyn Signer>,
+    unwrapped_mint: &Pubkey,
+    wrapped_token_program_id: &Pubkey,
+    owner: &Arc<dyn Signer>,
+    amount: u64,
+    source: Option<Pubkey>,
+    escrow: Option<Pubkey>,
+    recipient: Option<Pubkey>,
+    multisig_pubkeys: &[Pubkey],
+    bulk_signers: Vec<Arc<dyn Signer>>,
+) -> Result<RpcClientResponse, Box<dyn Error>> {
+    let unwrapped_token_program_id = get_mint_account_checked(program_client, unwrapped_mint)
+        .await?
+        .owner;
+    let wrapped_mint = get_wrapped_mint_address(unwrapped_mint, wrapped_token_program_id);
+    let _ = get_mint_account_checked(program_client, &wrapped_mint)
+        .await
+        .map_err(|_| {
+            format!(
+                "Wrapped mint {} does not exist, create it with `create-mint`",
+                wrapped_mint
+            )
+        })?;
+    let wrapped_mint_authority = get_wrapped_mint_authority(&wrapped_mint);
+
+    // Default accounts are created if needed, so that wrapping works out of
+    // the box
+    let mut instructions = vec![];
+    let escrow = escrow.unwrap_or_else(|| {
+        instructions.push(create_associated_token_account_idempotent(
+            &payer.pubkey(),
+            &wrapped_mint_authority,
+            unwrapped_mint,
+            &unwrapped_token_program_id,
+        ));
+        get_associated_token_address_with_program_id(
+            &wrapped_mint_authority,
+            unwrapped_mint,
+            &unwrapped_token_program_id,
+        )
+    });
+    let recipient = recipient.unwrap_or_else(|| {
+        instructions.push(create_associated_token_account_idempotent(
+            &payer.pubkey(),
+            &owner.pubkey(),
+            &wrapped_mint,
+            wrapped_token_program_id,
+        ));
+        get_associated_token_address_with_program_id(
+            &owner.pubkey(),
+            &wrapped_mint,
+            wrapped_token_program_id,
+        )
+    });
+    let source = source.unwrap_or_else(|| {
+        get_associated_token_address_with_program_id(
+            &owner.pubkey(),
+            unwrapped_mint,
+            &unwrapped_token_program_id,
+        )
+    });
+
+    println!(
+        "Wrapping {} tokens from account {} into account {}",
+        amount, source, recipient
+    );
+    instructions.push(wrap(
+        &spl_token_wrap::id(),
+        &source,
+        &escrow,
+        unwrapped_mint,
+        &wrapped_mint,
+        &recipient,
+        &unwrapped_token_program_id,
+        wrapped_token_program_id,
+        &owner.pubkey(),
+        &multisig_pubkeys.iter().collect::<Vec<_>>(),
+        amount,
+    ));
+
+    let token = Token::new(
+        program_client.clone(),
+        wrapped_token_program_id,
+        &wrapped_mint,
+        None,
+        payer.clone(),
+    );
+    token
+        .process_ixs(&instructions, &bulk_signers)
+        .await
+        .map_err(|e| e.into())
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn process_unwrap(
+    program_client: &RpcProgramClient,
+    payer: &Arc<dyn Signer>,
+    wrapped_mint: &Pubkey,
+    owner: &Arc<dyn Signer>,
+    amount: u64,
+    source: Option<Pubkey>,
+    escrow: Option<Pubkey>,
+    recipient: Option<Pubkey>,
+    multisig_pubkeys: &[Pubkey],
+    bulk_signers: Vec<Arc<dyn Signer>>,
+) -> Result<RpcClientResponse, Box<dyn Error>> {
+    let wrapped_token_program_id = get_mint_account_checked(program_client, wrapped_mint)
+        .await?
+        .owner;
+    let unwrapped_mint =
+        get_unwrapped_mint_checked(program_client, wrapped_mint, &wrapped_token_program_id).await?;
+    let unwrapped_token_program_id = get_mint_account_checked(program_client, &unwrapped_mint)
+        .await?
+        .owner;
+    let wrapped_mint_authority = get_wrapped_mint_authority(wrapped_mint);
+
+    let mut instructions = vec![];
+    let escrow = escrow.unwrap_or_else(|| {
+        get_associated_token_address_with_program_id(
+            &wrapped_mint_authority,
+            &unwrapped_mint,
+            &unwrapped_token_program_id,
+        )
+    });
+    let recipient = recipient.unwrap_or_else(|| {
+        instructions.push(create_associated_token_account_idempotent(
+            &payer.pubkey(),
+            &owner.pubkey(),
+            &unwrapped_mint,
+            &unwrapped_token_program_id,
+        ));
+        get_associated_token_address_with_program_id(
+            &owner.pubkey(),
+            &unwrapped_mint,
+            &unwrapped_token_program_id,
+        )
+    });
+    let source = source.unwrap_or_else(|| {
+        get_associated_token_address_with_program_id(
+            &owner.pubkey(),
+            wrapped_mint,
+            &wrapped_token_program_id,
+        )
+    });
+
+    println!(
+        "Unwrapping {} tokens from account {} into account {}",
+        amount, source, recipient
+    );
+    instructions.push(unwrap(
+        &spl_token_wrap::id(),
+        &source,
+        wrapped_mint,
+        &escrow,
+        &recipient,
+        &unwrapped_mint,
+        &wrapped_token_program_id,
+        &unwrapped_token_program_id,
+        &owner.pubkey(),
+        &multisig_pubkeys.iter().collect::<Vec<_>>(),
+        amount,
+    ));
+
+    let token = Token::new(
+        program_client.clone(),
+        &wrapped_token_program_id,
+        wrapped_mint,
+        None,
+        payer.clone(),
+    );
+    token
+        .process_ixs(&instructions, &bulk_signers)
+        .await
+        .map_err(|e| e.into())
+}
+
+async fn process_info(
+    rpc_client: &RpcClient,
+    program_client: &RpcProgramClient,
+    wrapped_mint: &Pubkey,
+) -> Result<WrappedMintInfo, Box<dyn Error>> {
+    let wrapped_mint_account = get_mint_account_checked(program_client, wrapped_mint).await?;
+    let wrapped_token_program_id = wrapped_mint_account.owner;
+    let wrapped_mint_state = StateWithExtensions::<Mint>::unpack(&wrapped_mint_account.data)?;
+    let unwrapped_mint =
+        get_unwrapped_mint_checked(program_client, wrapped_mint, &wrapped_token_program_id).await?;
+    let unwrapped_token_program_id = get_mint_account_checked(program_client, &unwrapped_mint)
+        .await?
+        .owner;
+    let wrapped_mint_authority = get_wrapped_mint_authority(wrapped_mint);
+
+    // The program accepts any token account owned by the wrapped mint
+    // authority as escrow, so all of them back the wrapped supply
+    let mut escrows = vec![];
+    for keyed_account in rpc_client
+        .get_token_accounts_by_owner(
+            &wrapped_mint_authority,
+            TokenAccountsFilter::Mint(unwrapped_mint),
+        )
+        .await?
+    {
+        let escrow = keyed_account.pubkey.parse::<Pubkey>()?;
+        let amount = match keyed_account.account.data {
+            UiAccountData::Json(parsed_account) => {
+                match serde_json::from_value(parsed_account.parsed)? {
+                    TokenAccountType::Account(ui_token_account) => {
+                        ui_token_account.token_amount.amount.parse::<u64>()?
+                    }
+                    _ => return Err(format!("Account {} is not a valid account", escrow).into()),
+                }
+            }
+            _ => return Err(format!("Could not parse escrow account {}", escrow).into()),
+        };
+        escrows.push((escrow, amount));
+    }
+    escrows.sort();
+    let escrow_amount = escrows
+        .iter()
+        .try_fold(0u64, |total, (_, amount)| total.checked_add(*amount))
+        .ok_or("Escrow balance overflow")?;
+
+    let info = WrappedMintInfo {
+        wrapped_mint: *wrapped_mint,
+        wrapped_token_program_id,
+        unwrapped_mint,
+        unwrapped_token_program_id,
+        escrows,
+        escrow_amount,
+        wrapped_supply: wrapped_mint_state.base.supply,
+        decimals: wrapped_mint_state.base.decimals,
+    };
+    let display_amount =
+        |amount| spl_token::amount_to_ui_amount_string_trimmed(amount, info.decimals);
+
+    println!("Wrapped mint: {}", info.wrapped_mint);
+    println!("  Wrapped token program: {}", info.wrapped_token_program_id);
+    println!("  Unwrapped mint: {}", info.unwrapped_mint);
+    println!(
+        "  Unwrapped token program: {}",
+        info.unwrapped_token_program_id
+    );
+    println!("  Wrapped mint authority: {}", wrapped_mint_authority);
+    for (escrow, amount) in &info.escrows {
+        println!("  Escrow {}: {}", escrow, display_amount(*amount));
+    }
+    println!("  Escrow balance: {}", display_amount(info.escrow_amount));
+    println!("  Wrapped supply: {}", display_amount(info.wrapped_supply));
+    println!("  Fully backed: {}", info.is_fully_backed());
+    Ok(info)
+}
+
+/// Get the transfer authority of a wrap or unwrap, along with any multisig
+/// signers, adding all of them to the transaction signers
+fn parse_transfer_authority(
+    matches: &ArgMatches,
+    payer: &Arc<dyn Signer>,
+    wallet_manager: &mut Option<Arc<RemoteWalletManager>>,
+) -> (Arc<dyn Signer>, Vec<Pubkey>, Vec<Arc<dyn Signer>>) {
+    let mut bulk_signers = vec![payer.clone()];
+    let mut multisig_pubkeys = vec![];
+    if let Some(values) = matches.values_of("multisig_signer") {
+        for (i, value) in values.enumerate() {
+            let name = format!("{}-{}", "multisig_signer", i.saturating_add(1));
+            let signer =
+                signer_from_path(matches, value, &name, wallet_manager).unwrap_or_else(|e| {
+                    eprintln!("error parsing multisig signer: {}", e);
+                    exit(1);
+                });
+            let signer_pubkey = signer.pubkey();
+            let signer = Arc::from(signer);
+            if !bulk_signers.contains(&signer) {
+                bulk_signers.push(signer);
+            }
+            if !multisig_pubkeys.contains(&signer_pubkey) {
+                multisig_pubkeys.push(signer_pubkey);
+            }
+        }
+    }
+
+    let signer_config = SignerFromPathConfig {
+        allow_null_signer: !multisig_pubkeys.is_empty(),
+    };
+    let owner = matches
+        .value_of("owner")
+        .map_or(Ok(payer.clone()), |path| {
+            signer_from_path_with_config(matches, path, "owner", wallet_manager, &signer_config)
+                .map(Arc::from)
+        })
+        .unwrap_or_else(|err| {
+            eprintln!("error: owner signer: {}", err);
+            exit(1);
+        });
+    if !signer_config.allow_null_signer && !bulk_signers.contains(&owner) {
+        bulk_signers.push(owner.clone());
+    }
+    (owner, multisig_pubkeys, bulk_signers)
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let app_matches = Command::new(crate_name!())
+        .about(crate_description!())
+        .version(crate_version!())
+        .subcommand_required(true)
+        .arg_required_else_help(true)
+        .arg({
+            let arg = Arg::new("config_file")
+                .short('C')
+                .long("config")
+                .value_name("PATH")
+                .takes_value(true)
+                .global(true)
+                .help("Configuration file to use");
+            if let Some(ref config_file) = *solana_cli_config::CONFIG_FILE {
+                arg.default_value(config_file)
+            } else {
+                arg
+            }
+        })
+        .arg(
+            Arg::new("payer")
+                .long("payer")
+                .value_name("KEYPAIR")
+                .validator(|s| is_valid_signer(s))
+                .takes_value(true)
+                .global(true)
+                .help("Filepath or URL to a keypair [default: client keypair]"),
+        )
+        .arg(
+            Arg::new("verbose")
+                .long("verbose")
+                .short('v')
+                .takes_value(false)
+                .global(true)
+                .help("Show additional information"),
+        )
+        .arg(
+            Arg::new("json_rpc_url")
+                .short('u')
+                .long("url")
+                .value_name("URL")
+                .takes_value(true)
+                .global(true)
+                .validator(|s| is_url_or_moniker(s))
+                .help("JSON RPC URL for the cluster [default: value from configuration file]"),
+        )
+        .subcommand(
+            Command::new("create-mint").about("Create the wrapped mint for an unwrapped mint")
+            .arg(
+                Arg::new("unwrapped_mint")
+                    .validator(|s| is_valid_pubkey(s))
+                    .value_name("ADDRESS")
+                    .required(true)
+                    .takes_value(true)
+                    .index(1)
+                    .help("Unwrapped mint address, whose tokens will be held in escrow")
+            )
+            .arg(
+                Arg::new("wrapped_token_program")
+                    .long("wrapped-token-program")
+                    .validator(|s| is_valid_pubkey(s))
+                    .value_name("PROGRAM_ID")
+                    .takes_value(true)
+                    .help("Token program of the wrapped mint [default: Token-2022 program]")
+            )
+            .arg(
+                Arg::new("idempotent")
+                    .long("idempotent")
+                    .takes_value(false)
+                    .help("Succeed without doing anything if the wrapped mint already exists")
+            )
+            .arg(
+                Arg::new("transfer_hook")
+                    .long("transfer-hook")
+                    .validator(|s| is_valid_pubkey(s))
+                    .value_name("PROGRAM_ID")
+                    .takes_value(true)
+                    .help("Transfer hook program called on every transfer of wrapped tokens, only for Token-2022")
+            )
+            .arg(
+                Arg::new("mirror_metadata")
+                    .long("mirror-metadata")
+                    .takes_value(false)
+                    .help("Copy the metadata of the unwrapped mint into the wrapped mint, only for Token-2022")
+            )
+            .arg(
+                Arg::new("confidential_transfers")
+                    .long("confidential-transfers")
+                    .takes_value(false)
+                    .help("Allow confidential transfers of wrapped tokens, only for Token-2022")
+            )
+            .arg(
+                Arg::new("mint_authority")
+                    .long("mint-authority")
+                    .value_name("MINT_AUTHORITY_KEYPAIR")
+                    .validator(|s| is_valid_signer(s))
+                    .takes_value(true)
+                    .help("Specify the mint authority of the unwrapped mint, which must sign to add any extension. This may be a keypair file or the ASK keyword. [default: fee payer]"),
+            )
+        )
+        .subcommand(
+            Command::new("wrap").about("Wrap tokens, moving them into escrow and receiving wrapped tokens")
+            .arg(
+                Arg::new("unwrapped_mint")
+                    .validator(|s| is_valid_pubkey(s))
+                    .value_name("ADDRESS")
+                    .required(true)
+                    .takes_value(true)
+                    .index(1)
+                    .help("Unwrapped mint address, whose tokens will be held in escrow")
+            )
+            .arg(
+                Arg::new("amount")
+                    .validator(is_parsable::<u64>)
+                    .value_name("AMOUNT")
+                    .required(true)
+                    .takes_value(true)
+                    .index(2)
+                    .help("Amount of tokens to wrap, in base units")
+            )
+            .arg(
+                Arg::new("wrapped_token_program")
+                    .long("wrapped-token-program")
+                    .validator(|s| is_valid_pubkey(s))
+                    .value_name("PROGRAM_ID")
+                    .takes_value(true)
+                    .help("Token program of the wrapped mint [default: Token-2022 program]")
+            )
+            .arg(
+                Arg::new("owner")
+                    .long("owner")
+                    .value_name("OWNER_KEYPAIR")
+                    .validator(|s| is_valid_signer(s))
+                    .takes_value(true)
+                    .help("Specify the owner or delegate of the unwrapped token account. This may be a keypair file or the ASK keyword. [default: fee payer]"),
+            )
+            .arg(
+                Arg::new("from")
+                    .long("from")
+                    .value_name("TOKEN_ACCOUNT_ADDRESS")
+                    .validator(|s| is_valid_pubkey(s))
+                    .takes_value(true)
+                    .help("Specify the unwrapped token account to wrap from. [default: associated token account for owner on unwrapped mint]"),
+            )
+            .arg(
+                Arg::new("escrow")
+                    .long("escrow")
+                    .value_name("ESCROW_TOKEN_ACCOUNT_ADDRESS")
+                    .validator(|s| is_valid_pubkey(s))
+                    .takes_value(true)
+                    .help("Specify the escrow account to transfer unwrapped tokens to. [default: associated token account for the wrapped mint authority, created if needed]"),
+            )
+            .arg(
+                Arg::new("recipient")
+                    .long("recipient")
+                    .value_name("RECIPIENT_ACCOUNT_ADDRESS")
+                    .validator(|s| is_valid_pubkey(s))
+                    .takes_value(true)
+                    .help("Specify the account to receive wrapped tokens. [default: associated token account for owner on wrapped mint, created if needed]"),
+            )
+            .arg(
+                Arg::new("multisig_signer")
+                    .long("multisig-signer")
+                    .validator(|s| is_valid_signer(s))
+                    .value_name("MULTISIG_SIGNER")
+                    .takes_value(true)
+                    .multiple(true)
+                    .min_values(0)
+                    .max_values(spl_token_2022::instruction::MAX_SIGNERS)
+                    .help("Member signer of a multisig account")
+            )
+        )
+        .subcommand(
+            Command::new("unwrap").about("Unwrap tokens, burning wrapped tokens and receiving tokens from escrow")
+            .arg(
+                Arg::new("wrapped_mint")
+                    .validator(|s| is_valid_pubkey(s))
+                    .value_name("ADDRESS")
+                    .required(true)
+                    .takes_value(true)
+                    .index(1)
+                    .help("Wrapped mint address, whose tokens will be burned")
+            )
+            .arg(
+                Arg::new("amount")
+                    .validator(is_parsable::<u64>)
+                    .value_name("AMOUNT")
+                    .required(true)
+                    .takes_value(true)
+                    .index(2)
+                    .help("Amount of tokens to unwrap, in base units")
+            )
+            .arg(
+                Arg::new("owner")
+                    .long("owner")
+                    .value_name("OWNER_KEYPAIR")
+                    .validator(|s| is_valid_signer(s))
+                    .takes_value(true)
+                    .help("Specify the owner or delegate of the wrapped token account. This may be a keypair file or the ASK keyword. [default: fee payer]"),
+            )
+            .arg(
+                Arg::new("from")
+                    .long("from")
+                    .value_name("TOKEN_ACCOUNT_ADDRESS")
+                    .validator(|s| is_valid_pubkey(s))
+                    .takes_value(true)
+                    .help("Specify the wrapped token account to unwrap from. [default: associated token account for owner on wrapped mint]"),
+            )
+            .arg(
+                Arg::new("escrow")
+                    .long("escrow")
+                    .value_name("ESCROW_TOKEN_ACCOUNT_ADDRESS")
+                    .validator(|s| is_valid_pubkey(s))
+                    .takes_value(true)
+                    .help("Specify the escrow account to transfer unwrapped tokens from. [default: associated token account for the wrapped mint authority]"),
+            )
+            .arg(
+                Arg::new("recipient")
+                    .long("recipient")
+                    .value_name("RECIPIENT_ACCOUNT_ADDRESS")
+                    .validator(|s| is_valid_pubkey(s))
+                    .takes_value(true)
+                    .help("Specify the account to receive unwrapped tokens. [default: associated token account for owner on unwrapped mint, created if needed]"),
+            )
+            .arg(
+                Arg::new("multisig_signer")
+                    .long("multisig-signer")
+                    .validator(|s| is_valid_signer(s))
+                    .value_name("MULTISIG_SIGNER")
+                    .takes_value(true)
+                    .multiple(true)
+                    .min_values(0)
+                    .max_values(spl_token_2022::instruction::MAX_SIGNERS)
+                    .help("Member signer of a multisig account")
+            )
+        )
+        .subcommand(
+            Command::new("info").about("Display a wrapped mint, and check that its supply is fully backed by the escrows owned by the wrapped mint authority")
+            .arg(
+                Arg::new("wrapped_mint")
+                    .validator(|s| is_valid_pubkey(s))
+                    .value_name("ADDRESS")
+                    .required(true)
+                    .takes_value(true)
+                    .index(1)
+                    .help("Wrapped mint address")
+            )
+        )
+        .get_matches();
+
+    let (command, matches) = app_matches.subcommand().unwrap();
+    let mut wallet_manager: Option<Arc<RemoteWalletManager>> = None;
+
+    let config = {
+        let cli_config = if let Some(config_file) = matches.value_of("config_file") {
+            solana_cli_config::Config::load(config_file).unwrap_or_default()
+        } else {
+            solana_cli_config::Config::default()
+        };
+
+        let payer = DefaultSigner::new(
+            "payer",
+            matches
+                .value_of("payer")
+                .map(|s| s.to_string())
+                .unwrap_or_else(|| cli_config.keypair_path.clone()),
+        );
+
+        let json_rpc_url = normalize_to_url_if_moniker(
+            matches
+                .value_of("json_rpc_url")
+                .unwrap_or(&cli_config.json_rpc_url),
+        );
+
+        Config {
+            commitment_config: CommitmentConfig::confirmed(),
+            payer: Arc::from(
+                payer
+                    .signer_from_path(matches, &mut wallet_manager)
+                    .unwrap_or_else(|err| {
+                        eprintln!("error: {}", err);
+                        exit(1);
+                    }),
+            ),
+            json_rpc_url,
+            verbose: matches.is_present("verbose"),
+        }
+    };
+    solana_logger::setup_with_default("solana=info");
+
+    if config.verbose {
+        println!("JSON RPC URL: {}", config.json_rpc_url);
+    }
+    let rpc_client = Arc::new(RpcClient::new_with_commitment(
+        config.json_rpc_url.clone(),
+        config.commitment_config,
+    ));
+    let program_client: RpcProgramClient = Arc::new(ProgramRpcClient::new(
+        rpc_client.clone(),
+        ProgramRpcClientSendTransaction,
+    ));
+
+    match (command, matches) {
+        ("create-mint", arg_matches) => {
+            let unwrapped_mint = pubkey_of(arg_matches, "unwrapped_mint").unwrap();
+            let wrapped_token_program_id =
+                pubkey_of(arg_matches, "wrapped_token_program").unwrap_or_else(spl_token_2022::id);
+            let transfer_hook_program_id =
+                OptionalNonZeroPubkey::try_from(pubkey_of(arg_matches, "transfer_hook"))
+                    .unwrap_or_else(|err| {
+                        eprintln!("error: transfer hook program: {}", err);
+                        exit(1);
+                    });
+            let extensions = WrappedMintExtensions {
+                transfer_hook_program_id,
+                mirror_metadata: arg_matches.is_present("mirror_metadata").into(),
+                confidential_transfers: arg_matches.is_present("confidential_transfers").into(),
+            };
+            let mint_authority = arg_matches
+                .value_of("mint_authority")
+                .map_or(Ok(config.payer.clone()), |path| {
+                    signer_from_path(arg_matches, path, "mint_authority", &mut wallet_manager)
+                        .map(Arc::from)
+                })
+                .unwrap_or_else(|err| {
+                    eprintln!("error: mint authority signer: {}", err);
+                    exit(1);
+                });
+            let response = process_create_mint(
+                &program_client,
+                &config.payer,
+                &mint_authority,
+                &unwrapped_mint,
+                &wrapped_token_program_id,
+                arg_matches.is_present("idempotent"),
+                &extensions,
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("error: create mint: {}", err);
+                exit(1);
+            });
+            println!("{}", response);
+        }
+        ("wrap", arg_matches) => {
+            let (owner, multisig_pubkeys, bulk_signers) =
+                parse_transfer_authority(arg_matches, &config.payer, &mut wallet_manager);
+            let unwrapped_mint = pubkey_of(arg_matches, "unwrapped_mint").unwrap();
+            let amount = value_of::<u64>(arg_matches, "amount").unwrap();
+            let wrapped_token_program_id =
+                pubkey_of(arg_matches, "wrapped_token_program").unwrap_or_else(spl_token_2022::id);
+            let source = pubkey_of(arg_matches, "from");
+            let escrow = pubkey_of(arg_matches, "escrow");
+            let recipient = pubkey_of(arg_matches, "recipient");
+
+            let response = process_wrap(
+                &program_client,
+                &config.payer,
+                &unwrapped_mint,
+                &wrapped_token_program_id,
+                &owner,
+                amount,
+                source,
+                escrow,
+                recipient,
+                &multisig_pubkeys,
+                bulk_signers,
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("error: wrap: {}", err);
+                exit(1);
+            });
+            println!("{}", response);
+        }
+        ("unwrap", arg_matches) => {
+            let (owner, multisig_pubkeys, bulk_signers) =
+                parse_transfer_authority(arg_matches, &config.payer, &mut wallet_manager);
+            let wrapped_mint = pubkey_of(arg_matches, "wrapped_mint").unwrap();
+            let amount = value_of::<u64>(arg_matches, "amount").unwrap();
+            let source = pubkey_of(arg_matches, "from");
+            let escrow = pubkey_of(arg_matches, "escrow");
+            let recipient = pubkey_of(arg_matches, "recipient");
+
+            let response = process_unwrap(
+                &program_client,
+                &config.payer,
+                &wrapped_mint,
+                &owner,
+                amount,
+                source,
+                escrow,
+                recipient,
+                &multisig_pubkeys,
+                bulk_signers,
+            )
+            .await
+            .unwrap_or_else(|err| {
+                eprintln!("error: unwrap: {}", err);
+                exit(1);
+            });
+            println!("{}", response);
+        }
+        ("info", arg_matches) => {
+            let wrapped_mint = pubkey_of(arg_matches, "wrapped_mint").unwrap();
+            let info = process_info(&rpc_client, &program_client, &wrapped_mint)
+                .await
+                .unwrap_or_else(|err| {
+                    eprintln!("error: info: {}", err);
+                    exit(1);
+                });
+            if !info.is_fully_backed() {
+                eprintln!("error: wrapped supply is greater than the escrow balance");
+                exit(1);
+            }
+        }
+        _ => unreachable!(),
+    };
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use {
+        super::*,
+        solana_sdk::{bpf_loader_upgradeable, signer::keypair::Keypair},
+        solana_test_validator::{TestValidator, TestValidatorGenesis, UpgradeableProgramInfo},
+        std::path::PathBuf,
+    };
+
+    async fn new_validator_for_test() -> (TestValidator, Keypair) {
+        solana_logger::setup();
+        let mut test_validator_genesis = TestValidatorGenesis::default();
+        test_validator_genesis.add_upgradeable_programs_with_path(&[
+            UpgradeableProgramInfo {
+                program_id: spl_token::id(),
+                loader: bpf_loader_upgradeable::id(),
+                program_path: PathBuf::from("../../target/deploy/spl_token.so"),
+                upgrade_authority: Pubkey::new_unique(),
+            },
+            UpgradeableProgramInfo {
+                program_id: spl_associated_token_account::id(),
+                loader: bpf_loader_upgradeable::id(),
+                program_path: PathBuf::from("../../target/deploy/spl_associated_token_account.so"),
+                upgrade_authority: Pubkey::new_unique(),
+            },
+            UpgradeableProgramInfo {
+                program_id: spl_token_2022::id(),
+                loader: bpf_loader_upgradeable::id(),
+                program_path: PathBuf::from("../../target/deploy/spl_token_2022.so"),
+                upgrade_authority: Pubkey::new_unique(),
+            },
+            UpgradeableProgramInfo {
+                program_id: spl_token_wrap::id(),
+                loader: bpf_loader_upgradeable::id(),
+                program_path: PathBuf::from("../../target/deploy/spl_token_wrap.so"),
+                upgrade_authority: Pubkey::new_unique(),
+            },
+        ]);
+        test_validator_genesis.start_async().await
+    }
+
+    async fn setup_mint(
+        program_id: &Pubkey,
+        mint_authority: &Pubkey,
+        decimals: u8,
+        payer: Arc<dyn Signer>,
+        client: RpcProgramClient,
+    ) -> Token<ProgramRpcClientSendTransaction> {
+        let mint_account = Keypair::new();
+        let token = Token::new(
+            client,
+            program_id,
+            &mint_account.pubkey(),
+            Some(decimals),
+            payer,
+        );
+        token
+            .create_mint(mint_authority, None, vec![], &[&mint_account])
+            .await
+            .unwrap();
+        token
+    }
+
+    #[tokio::test]
+    async fn success_create_mint() {
+        let (test_validator, payer) = new_validator_for_test().await;
+        let payer: Arc<dyn Signer> = Arc::new(payer);
+        let rpc_client = Arc::new(test_validator.get_async_rpc_client());
+        let client: RpcProgramClient = Arc::new(ProgramRpcClient::new(
+            rpc_client.clone(),
+            ProgramRpcClientSendTransaction,
+        ));
+
+        let mint_authority = Keypair::new();
+        let unwrapped_token = setup_mint(
+            &spl_token::id(),
+            &mint_authority.pubkey(),
+            6,
+            payer.clone(),
+            client.clone(),
+        )
+        .await;
+
+        process_create_mint(
+            &client,
+            &payer,
+            &payer,
+            unwrapped_token.get_address(),
+            &spl_token_2022::id(),
+            false,
+            &WrappedMintExtensions::default(),
+        )
+        .await
+        .unwrap();
+
+        // creating again only works if idempotent
+        assert!(process_create_mint(
+            &client,
+            &payer,
+            &payer,
+            unwrapped_token.get_address(),
+            &spl_token_2022::id(),
+            false,
+            &WrappedMintExtensions::default(),
+        )
+        .await
+        .is_err());
+        process_create_mint(
+            &client,
+            &payer,
+            &payer,
+            unwrapped_token.get_address(),
+            &spl_token_2022::id(),
+            true,
+            &WrappedMintExtensions::default(),
+        )
+        .await
+        .unwrap();
+
+        let wrapped_mint =
+            get_wrapped_mint_address(unwrapped_token.get_address(), &spl_token_2022::id());
+        let info = process_info(&rpc_client, &client, &wrapped_mint)
+            .await
+            .unwrap();
+        assert_eq!(
+            info,
+            WrappedMintInfo {
+                wrapped_mint,
+                wrapped_token_program_id: spl_token_2022::id(),
+                unwrapped_mint: *unwrapped_token.get_address(),
+                unwrapped_token_program_id: spl_token::id(),
+                escrows: vec![],
+                escrow_amount: 0,
+                wrapped_supply: 0,
+                decimals: 6,
+            }
+        );
+        assert!(info.is_fully_backed());
+
+        // not a wrapped mint
+        assert!(
+            process_info(&rpc_client, &client, unwrapped_token.get_address())
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn success_wrap_and_unwrap() {
+        let (test_validator, payer) = new_validator_for_test().await;
+        let payer: Arc<dyn Signer> = Arc::new(payer);
+        let rpc_client = Arc::new(test_validator.get_async_rpc_client());
+        let client: RpcProgramClient = Arc::new(ProgramRpcClient::new(
+            rpc_client.clone(),
+            ProgramRpcClientSendTransaction,
+        ));
+
+        let mint_authority = Keypair::new();
+        let unwrapped_token = setup_mint(
+            &spl_token::id(),
+            &mint_authority.pubkey(),
+            6,
+            payer.clone(),
+            client.clone(),
+        )
+        .await;
+        process_create_mint(
+            &client,
+            &payer,
+            &payer,
+            unwrapped_token.get_address(),
+            &spl_token_2022::id(),
+            false,
+            &WrappedMintExtensions::default(),
+        )
+        .await
+        .unwrap();
+        let wrapped_mint =
+            get_wrapped_mint_address(unwrapped_token.get_address(), &spl_token_2022::id());
+
+        let user = Keypair::new();
+        let amount = 1_000_000;
+        unwrapped_token
+            .create_associated_token_account(&user.pubkey())
+            .await
+            .unwrap();
+        let unwrapped_account = unwrapped_token.get_associated_token_address(&user.pubkey());
+        unwrapped_token
+            .mint_to(
+                &unwrapped_account,
+                &mint_authority.pubkey(),
+                amount,
+                &[&mint_authority],
+            )
+            .await
+            .unwrap();
+
+        // escrow and recipient are created as needed
+        let user: Arc<dyn Signer> = Arc::new(user);
+        process_wrap(
+            &client,
+            &payer,
+            unwrapped_token.get_address(),
+            &spl_token_2022::id(),
+            &user,
+            amount,
+            None,
+            None,
+            None,
+            &[],
+            vec![payer.clone(), user.clone()],
+        )
+        .await
+        .unwrap();
+
+        let wrapped_token = Token::new(
+            client.clone(),
+            &spl_token_2022::id(),
+            &wrapped_mint,
+            None,
+            payer.clone(),
+        );
+        let wrapped_account = wrapped_token.get_associated_token_address(&user.pubkey());
+        let account = wrapped_token
+            .get_account_info(&wrapped_account)
+            .await
+            .unwrap();
+        assert_eq!(account.base.amount, amount);
+        let account = unwrapped_token
+            .get_account_info(&unwrapped_account)
+            .await
+            .unwrap();
+        assert_eq!(account.base.amount, 0);
+
+        let info = process_info(&rpc_client, &client, &wrapped_mint)
+            .await
+            .unwrap();
+        assert_eq!(
+            info.escrows,
+            vec![(
+                unwrapped_token
+                    .get_associated_token_address(&get_wrapped_mint_authority(&wrapped_mint)),
+                amount
+            )]
+        );
+        assert_eq!(info.escrow_amount, amount);
+        assert_eq!(info.wrapped_supply, amount);
+        assert!(info.is_fully_backed());
+
+        let unwrap_amount = 250_000;
+        let remaining_amount = 750_000;
+        process_unwrap(
+            &client,
+            &payer,
+            &wrapped_mint,
+            &user,
+            unwrap_amount,
+            None,
+            None,
+            None,
+            &[],
+            vec![payer.clone(), user.clone()],
+        )
+        .await
+        .unwrap();
+
+        let account = wrapped_token
+            .get_account_info(&wrapped_account)
+            .await
+            .unwrap();
+        assert_eq!(account.base.amount, remaining_amount);
+        let account = unwrapped_token
+            .get_account_info(&unwrapped_account)
+            .await
+            .unwrap();
+        assert_eq!(account.base.amount, unwrap_amount);
+
+        let info = process_info(&rpc_client, &client, &wrapped_mint)
+            .await
+            .unwrap();
+        assert_eq!(info.escrow_amount, remaining_amount);
+        assert_eq!(info.wrapped_supply, remaining_amount);
+        assert!(info.is_fully_backed());
+
+        // wrapping through another escrow owned by the wrapped mint authority
+        // also backs the supply
+        let wrapped_mint_authority = get_wrapped_mint_authority(&wrapped_mint);
+        let escrow = unwrapped_token.get_associated_token_address(&wrapped_mint_authority);
+        let other_escrow = Keypair::new();
+        unwrapped_token
+            .create_auxiliary_token_account(&other_escrow, &wrapped_mint_authority)
+            .await
+            .unwrap();
+        let other_escrow = other_escrow.pubkey();
+        let other_amount = unwrap_amount;
+        process_wrap(
+            &client,
+            &payer,
+            unwrapped_token.get_address(),
+            &spl_token_2022::id(),
+            &user,
+            other_amount,
+            None,
+            Some(other_escrow),
+            None,
+            &[],
+            vec![payer.clone(), user.clone()],
+        )
+        .await
+        .unwrap();
+
+        let info = process_info(&rpc_client, &client, &wrapped_mint)
+            .await
+            .unwrap();
+        let mut escrows = vec![(escrow, remaining_amount), (other_escrow, other_amount)];
+        escrows.sort();
+        assert_eq!(info.escrows, escrows);
+        assert_eq!(info.escrow_amount, amount);
+        assert_eq!(info.wrapped_supply, amount);
+        assert!(info.is_fully_backed());
+    }
+
+    #[tokio::test]
+    async fn fail_wrap_without_wrapped_mint() {
+        let (test_validator, payer) = new_validator_for_test().await;
+        let payer: Arc<dyn Signer> = Arc::new(payer);
+        let rpc_client = Arc::new(test_validator.get_async_rpc_client());
+        let client: RpcProgramClient = Arc::new(ProgramRpcClient::new(
+            rpc_client.clone(),
+            ProgramRpcClientSendTransaction,
+        ));
+
+        let mint_authority = Keypair::new();
+        let unwrapped_token = setup_mint(
+            &spl_token::id(),
+            &mint_authority.pubkey(),
+            6,
+            payer.clone(),
+            client.clone(),
+        )
+        .await;
+
+        assert!(process_wrap(
+            &client,
+            &payer,
+            unwrapped_token.get_address(),
+            &spl_token_2022::id(),
+            &payer,
+            1,
+            None,
+            None,
+            None,
+            &[],
+            vec![payer.clone()],
+        )
+        .await
+        .is_err());
+    }
+}
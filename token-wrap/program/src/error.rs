@@ -0,0 +1,69 @@
+//! Error types
+
+use {
+    num_derive::FromPrimitive,
+    solana_program::{decode_error::DecodeError, program_error::ProgramError},
+    thiserror::Error,
+};
+
+/// Errors that may be returned by the program.
+#[derive(Clone, Debug, Eq, Error, FromPrimitive, PartialEq)]
+pub enum TokenWrapError {
+    // 0
+    /// Wrapped mint account address does not match expected PDA
+    #[error("Wrapped mint account address does not match expected PDA")]
+    WrappedMintMismatch,
+    /// Wrapped backpointer account address does not match expected PDA
+    #[error("Wrapped backpointer account address does not match expected PDA")]
+    BackpointerMismatch,
+    /// Wrap amount should be positive
+    #[error("Wrap amount should be positive")]
+    ZeroWrapAmount,
+    /// Wrapped mint authority does not match expected PDA
+    #[error("Wrapped mint authority does not match expected PDA")]
+    MintAuthorityMismatch,
+    /// Unwrapped escrow token owner is not set to expected PDA
+    #[error("Unwrapped escrow token owner is not set to expected PDA")]
+    EscrowOwnerMismatch,
+
+    // 5
+    /// Wrapped mint account owner is not the expected token program
+    #[error("Wrapped mint account owner is not the expected token program")]
+    InvalidWrappedMintOwner,
+    /// Wrapped backpointer account owner is not the expected token wrap program
+    #[error("Wrapped backpointer account owner is not the expected token wrap program")]
+    InvalidBackpointerOwner,
+    /// Extensions can only be added to wrapped mints in Token-2022
+    #[error("Extensions can only be added to wrapped mints in Token-2022")]
+    ExtensionsRequireToken2022,
+    /// Unwrapped mint does not point to any metadata
+    #[error("Unwrapped mint does not point to any metadata")]
+    MissingUnwrappedMetadata,
+    /// Unwrapped metadata account does not match the unwrapped mint
+    #[error("Unwrapped metadata account does not match the unwrapped mint")]
+    UnwrappedMetadataMismatch,
+
+    // 10
+    /// Wrapped mint does not mirror the unwrapped mint's metadata
+    #[error("Wrapped mint does not mirror the unwrapped mint's metadata")]
+    MetadataNotMirrored,
+    /// Unwrapped mint authority does not match the provided account
+    #[error("Unwrapped mint authority does not match the provided account")]
+    UnwrappedMintAuthorityMismatch,
+    /// Existing wrapped mint does not have the requested extensions
+    #[error("Existing wrapped mint does not have the requested extensions")]
+    ExtensionsMismatch,
+    /// Unwrapped mints with a transfer hook cannot be wrapped or unwrapped
+    #[error("Unwrapped mints with a transfer hook cannot be wrapped or unwrapped")]
+    UnwrappedTransferHookUnsupported,
+}
+impl From<TokenWrapError> for ProgramError {
+    fn from(e: TokenWrapError) -> Self {
+        ProgramError::Custom(e as u32)
+    }
+}
+impl<T> DecodeError<T> for TokenWrapError {
+    fn type_of() -> &'static str {
+        "TokenWrapError"
+    }
+}
@@ -1,6 +1,19 @@
 //! Program instructions
 
-use num_enum::{IntoPrimitive, TryFromPrimitive};
+use {
+    crate::{
+        get_wrapped_mint_authority, get_wrapped_mint_backpointer_address,
+        state::WrappedMintExtensions,
+    },
+    bytemuck::{Pod, Zeroable},
+    num_enum::{IntoPrimitive, TryFromPrimitive},
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        pubkey::Pubkey,
+        system_program,
+    },
+    spl_token_2022::pod::{pod_bytes_of, PodBool, PodU64},
+};
 
 /// Instructions supported by the Token Wrap program
 #[derive(Clone, Debug, PartialEq, TryFromPrimitive, IntoPrimitive)]
@@ -18,9 +31,22 @@ pub enum TokenWrapInstruction {
     /// 3. `[]` Existing unwrapped mint
     /// 4. `[]` System program
     /// 5. `[]` SPL Token program for wrapped mint
+    /// 6. `[signer]` (Optional) Unwrapped mint authority, required if any
+    ///     extension is requested
+    /// 7. `[]` (Optional) Wrapped mint authority, required if mirroring
+    ///     metadata: `get_wrapped_mint_authority(wrapped_mint)`
+    /// 8. `[]` (Optional) Unwrapped metadata account, required if mirroring
+    ///     metadata that is not stored in the unwrapped mint itself
+    ///
+    /// Anyone may create a wrapped mint without extensions. Since there is
+    /// only one wrapped mint per unwrapped mint and token program, only the
+    /// unwrapped mint authority may choose its extensions. If the wrapped
+    /// mint exists and creation is idempotent, the requested extensions must
+    /// match the existing ones.
     ///
     /// Data expected by this instruction:
-    ///   * bool: true = idempotent creation, false = non-idempotent creation
+    ///   * `CreateMintData`: whether creation is idempotent, and the
+    ///     Token-2022 extensions to initialize on the wrapped mint
     ///
     CreateMint,
 
@@ -29,6 +55,11 @@ pub enum TokenWrapInstruction {
     /// Move a user's unwrapped tokens into an escrow account and mint the same
     /// number of wrapped tokens into the provided account.
     ///
+    /// If the unwrapped mint charges a transfer fee, only the amount received
+    /// by the escrow is minted, so that wrapped tokens are always fully backed.
+    /// Unwrapped mints with a transfer hook are not supported, since the
+    /// hook's extra accounts are not passed to the transfer.
+    ///
     /// Accounts expected by this instruction:
     ///
     /// 0. `[writeable]` Unwrapped token account to wrap
@@ -42,8 +73,9 @@ pub enum TokenWrapInstruction {
     ///     `get_wrapped_mint_authority(wrapped_mint)`
     /// 6. `[]` SPL Token program for unwrapped mint
     /// 7. `[]` SPL Token program for wrapped mint
-    /// 8. `[signer]` Transfer authority on unwrapped token account
-    /// 8..8+M. `[signer]` (Optional) M multisig signers on unwrapped token account
+    /// 8. `[signer]` Transfer authority on unwrapped token account, not a
+    ///     signer if it is a multisig
+    /// 9..9+M. `[signer]` (Optional) M multisig signers on unwrapped token account
     ///
     /// Data expected by this instruction:
     ///   * little-endian u64 representing the amount to wrap
@@ -55,6 +87,10 @@ pub enum TokenWrapInstruction {
     /// Burn user wrapped tokens and transfer the same amount of unwrapped tokens
     /// from the escrow account to the provided account.
     ///
+    /// If the unwrapped mint charges a transfer fee, it is taken from the
+    /// unwrapped tokens received. Unwrapped mints with a transfer hook are
+    /// not supported.
+    ///
     /// Accounts expected by this instruction:
     ///
     /// 0. `[writeable]` Wrapped token account to unwrap
@@ -68,11 +104,220 @@ pub enum TokenWrapInstruction {
     ///     `get_wrapped_mint_authority(wrapped_mint)`
     /// 6. `[]` SPL Token program for wrapped mint
     /// 7. `[]` SPL Token program for unwrapped mint
-    /// 8. `[signer]` Transfer authority on wrapped token account
-    /// 8..8+M. `[signer]` (Optional) M multisig signers on wrapped token account
+    /// 8. `[signer]` Transfer authority on wrapped token account, not a
+    ///     signer if it is a multisig
+    /// 9..9+M. `[signer]` (Optional) M multisig signers on wrapped token account
     ///
     /// Data expected by this instruction:
     ///   * little-endian u64 representing the amount to unwrap
     ///
     Unwrap,
+
+    /// Refresh the metadata of a wrapped mint from the unwrapped mint's
+    /// metadata
+    ///
+    /// Only works for Token-2022 wrapped mints created with
+    /// `mirror_metadata`. Anyone may call it, and the funding account pays for
+    /// any additional space needed by the metadata.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    /// 0. `[writeable]` Wrapped mint, address must be:
+    ///     `get_wrapped_mint_address(unwrapped_mint_address, wrapped_token_program_id)`
+    /// 1. `[]` Wrapped mint authority, address must be:
+    ///     `get_wrapped_mint_authority(wrapped_mint)`
+    /// 2. `[]` Unwrapped mint
+    /// 3. `[]` SPL Token-2022 program for wrapped mint
+    /// 4. `[writeable,signer]` Funding account for additional rent (must be a
+    ///     system account)
+    /// 5. `[]` System program
+    /// 6. `[]` (Optional) Unwrapped metadata account, if the metadata is not
+    ///     stored in the unwrapped mint itself
+    ///
+    /// Data expected by this instruction:
+    ///   None
+    ///
+    SyncMetadata,
+}
+
+/// Data expected by `CreateMint`
+#[derive(Copy, Clone, Debug, Default, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct CreateMintData {
+    /// If true, succeed without doing anything if the wrapped mint exists
+    pub idempotent: PodBool,
+    /// Token-2022 extensions to initialize on the wrapped mint
+    pub extensions: WrappedMintExtensions,
+}
+
+/// Create a `CreateMint` instruction
+///
+/// `unwrapped_mint_authority` is only needed when requesting extensions, and
+/// `unwrapped_metadata` is only needed when mirroring metadata that is not
+/// stored in the unwrapped mint itself.
+#[allow(clippy::too_many_arguments)]
+pub fn create_mint(
+    program_id: &Pubkey,
+    wrapped_mint: &Pubkey,
+    unwrapped_mint: &Pubkey,
+    wrapped_token_program_id: &Pubkey,
+    funding_account: &Pubkey,
+    idempotent: bool,
+    extensions: &WrappedMintExtensions,
+    unwrapped_mint_authority: Option<&Pubkey>,
+    unwrapped_metadata: Option<&Pubkey>,
+) -> Instruction {
+    let backpointer = get_wrapped_mint_backpointer_address(wrapped_mint);
+    let mut accounts = vec![
+        AccountMeta::new(*funding_account, true),
+        AccountMeta::new(*wrapped_mint, false),
+        AccountMeta::new(backpointer, false),
+        AccountMeta::new_readonly(*unwrapped_mint, false),
+        AccountMeta::new_readonly(system_program::id(), false),
+        AccountMeta::new_readonly(*wrapped_token_program_id, false),
+    ];
+    if !extensions.is_empty() {
+        if let Some(unwrapped_mint_authority) = unwrapped_mint_authority {
+            accounts.push(AccountMeta::new_readonly(*unwrapped_mint_authority, true));
+        }
+    }
+    if bool::from(extensions.mirror_metadata) {
+        accounts.push(AccountMeta::new_readonly(
+            get_wrapped_mint_authority(wrapped_mint),
+            false,
+        ));
+        if let Some(unwrapped_metadata) = unwrapped_metadata {
+            accounts.push(AccountMeta::new_readonly(*unwrapped_metadata, false));
+        }
+    }
+    let mut data = vec![TokenWrapInstruction::CreateMint.into()];
+    data.extend_from_slice(pod_bytes_of(&CreateMintData {
+        idempotent: idempotent.into(),
+        extensions: *extensions,
+    }));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Create a `Wrap` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn wrap(
+    program_id: &Pubkey,
+    unwrapped_token_account: &Pubkey,
+    unwrapped_escrow: &Pubkey,
+    unwrapped_mint: &Pubkey,
+    wrapped_mint: &Pubkey,
+    recipient_wrapped_token_account: &Pubkey,
+    unwrapped_token_program_id: &Pubkey,
+    wrapped_token_program_id: &Pubkey,
+    transfer_authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    amount: u64,
+) -> Instruction {
+    let wrapped_mint_authority = get_wrapped_mint_authority(wrapped_mint);
+    let mut accounts = Vec::with_capacity(9usize.saturating_add(multisig_signers.len()));
+    accounts.push(AccountMeta::new(*unwrapped_token_account, false));
+    accounts.push(AccountMeta::new(*unwrapped_escrow, false));
+    accounts.push(AccountMeta::new_readonly(*unwrapped_mint, false));
+    accounts.push(AccountMeta::new(*wrapped_mint, false));
+    accounts.push(AccountMeta::new(*recipient_wrapped_token_account, false));
+    accounts.push(AccountMeta::new_readonly(wrapped_mint_authority, false));
+    accounts.push(AccountMeta::new_readonly(
+        *unwrapped_token_program_id,
+        false,
+    ));
+    accounts.push(AccountMeta::new_readonly(*wrapped_token_program_id, false));
+    accounts.push(AccountMeta::new_readonly(
+        *transfer_authority,
+        multisig_signers.is_empty(),
+    ));
+    for signer_pubkey in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    let mut data = vec![TokenWrapInstruction::Wrap.into()];
+    data.extend_from_slice(pod_bytes_of(&PodU64::from(amount)));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Create an `Unwrap` instruction
+#[allow(clippy::too_many_arguments)]
+pub fn unwrap(
+    program_id: &Pubkey,
+    wrapped_token_account: &Pubkey,
+    wrapped_mint: &Pubkey,
+    unwrapped_escrow: &Pubkey,
+    recipient_unwrapped_token_account: &Pubkey,
+    unwrapped_mint: &Pubkey,
+    wrapped_token_program_id: &Pubkey,
+    unwrapped_token_program_id: &Pubkey,
+    transfer_authority: &Pubkey,
+    multisig_signers: &[&Pubkey],
+    amount: u64,
+) -> Instruction {
+    let wrapped_mint_authority = get_wrapped_mint_authority(wrapped_mint);
+    let mut accounts = Vec::with_capacity(9usize.saturating_add(multisig_signers.len()));
+    accounts.push(AccountMeta::new(*wrapped_token_account, false));
+    accounts.push(AccountMeta::new(*wrapped_mint, false));
+    accounts.push(AccountMeta::new(*unwrapped_escrow, false));
+    accounts.push(AccountMeta::new(*recipient_unwrapped_token_account, false));
+    accounts.push(AccountMeta::new_readonly(*unwrapped_mint, false));
+    accounts.push(AccountMeta::new_readonly(wrapped_mint_authority, false));
+    accounts.push(AccountMeta::new_readonly(*wrapped_token_program_id, false));
+    accounts.push(AccountMeta::new_readonly(
+        *unwrapped_token_program_id,
+        false,
+    ));
+    accounts.push(AccountMeta::new_readonly(
+        *transfer_authority,
+        multisig_signers.is_empty(),
+    ));
+    for signer_pubkey in multisig_signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+
+    let mut data = vec![TokenWrapInstruction::Unwrap.into()];
+    data.extend_from_slice(pod_bytes_of(&PodU64::from(amount)));
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Create a `SyncMetadata` instruction
+///
+/// `unwrapped_metadata` is only needed if the metadata is not stored in the
+/// unwrapped mint itself.
+pub fn sync_metadata(
+    program_id: &Pubkey,
+    wrapped_mint: &Pubkey,
+    unwrapped_mint: &Pubkey,
+    wrapped_token_program_id: &Pubkey,
+    funding_account: &Pubkey,
+    unwrapped_metadata: Option<&Pubkey>,
+) -> Instruction {
+    let mut accounts = vec![
+        AccountMeta::new(*wrapped_mint, false),
+        AccountMeta::new_readonly(get_wrapped_mint_authority(wrapped_mint), false),
+        AccountMeta::new_readonly(*unwrapped_mint, false),
+        AccountMeta::new_readonly(*wrapped_token_program_id, false),
+        AccountMeta::new(*funding_account, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    if let Some(unwrapped_metadata) = unwrapped_metadata {
+        accounts.push(AccountMeta::new_readonly(*unwrapped_metadata, false));
+    }
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data: vec![TokenWrapInstruction::SyncMetadata.into()],
+    }
 }
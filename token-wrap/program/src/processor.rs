@@ -1,26 +1,847 @@
 //! Program state processor
 
 use {
-    crate::instruction::TokenWrapInstruction,
-    solana_program::{account_info::AccountInfo, entrypoint::ProgramResult, pubkey::Pubkey},
-    spl_token_2022::instruction::decode_instruction_type,
+    crate::{
+        error::TokenWrapError,
+        get_wrapped_mint_address_with_seed, get_wrapped_mint_authority_signer_seeds,
+        get_wrapped_mint_authority_with_seed, get_wrapped_mint_backpointer_address_signer_seeds,
+        get_wrapped_mint_backpointer_address_with_seed, get_wrapped_mint_signer_seeds,
+        instruction::{CreateMintData, TokenWrapInstruction},
+        state::{Backpointer, WrappedMintExtensions},
+    },
+    bytemuck::Zeroable,
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        instruction::{AccountMeta, Instruction},
+        msg,
+        program::{invoke, invoke_signed},
+        program_error::ProgramError,
+        program_option::COption,
+        pubkey::Pubkey,
+        rent::Rent,
+        system_instruction,
+        sysvar::Sysvar,
+    },
+    spl_token_2022::{
+        check_spl_token_program_account,
+        extension::{
+            confidential_transfer::{
+                instruction::{ConfidentialTransferInstruction, InitializeMintData},
+                ConfidentialTransferMint,
+            },
+            metadata_pointer::{self, MetadataPointer},
+            transfer_hook::{self, TransferHook},
+            BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+        },
+        instruction::{decode_instruction_data, decode_instruction_type, TokenInstruction},
+        pod::{pod_bytes_of, PodU64},
+        state::{Account, Mint},
+    },
+    spl_token_metadata_interface::state::{
+        Field, FieldUpdate, OptionalNonZeroPubkey, TokenMetadata,
+    },
+    spl_type_length_value::state::{TlvState, TlvStateBorrowed},
+    std::{convert::TryFrom, mem::size_of},
 };
 
+/// Transfer enough lamports for the account to be rent-exempt at the given
+/// size, if it does not have enough already
+fn transfer_rent<'a>(
+    funding_info: &AccountInfo<'a>,
+    account_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    space: usize,
+) -> ProgramResult {
+    let required_lamports = Rent::get()?
+        .minimum_balance(space)
+        .saturating_sub(account_info.lamports());
+    if required_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(funding_info.key, account_info.key, required_lamports),
+            &[
+                funding_info.clone(),
+                account_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// Create a program-derived account, which may already hold some lamports
+fn create_pda_account<'a>(
+    funding_info: &AccountInfo<'a>,
+    new_account_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    space: usize,
+    owner: &Pubkey,
+    signer_seeds: &[&[u8]],
+) -> ProgramResult {
+    transfer_rent(funding_info, new_account_info, system_program_info, space)?;
+    invoke_signed(
+        &system_instruction::allocate(new_account_info.key, space as u64),
+        &[new_account_info.clone(), system_program_info.clone()],
+        &[signer_seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(new_account_info.key, owner),
+        &[new_account_info.clone(), system_program_info.clone()],
+        &[signer_seeds],
+    )
+}
+
+/// Check the unwrapped mint and return its decimals
+///
+/// Mints with a transfer hook are rejected, since the extra accounts required
+/// by the hook are not passed to the transfer.
+fn get_unwrapped_mint_decimals(
+    mint_info: &AccountInfo,
+    token_program_info: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    check_spl_token_program_account(token_program_info.key)?;
+    if mint_info.owner != token_program_info.key {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+    if transfer_hook::get_program_id(&mint).is_some() {
+        return Err(TokenWrapError::UnwrappedTransferHookUnsupported.into());
+    }
+    Ok(mint.base.decimals)
+}
+
+fn check_wrapped_mint(
+    wrapped_mint_info: &AccountInfo,
+    unwrapped_mint: &Pubkey,
+    wrapped_token_program_info: &AccountInfo,
+) -> ProgramResult {
+    check_spl_token_program_account(wrapped_token_program_info.key)?;
+    let (expected_wrapped_mint, _) =
+        get_wrapped_mint_address_with_seed(unwrapped_mint, wrapped_token_program_info.key);
+    if expected_wrapped_mint != *wrapped_mint_info.key {
+        msg!(
+            "Expected wrapped mint {}, received {}",
+            &expected_wrapped_mint,
+            wrapped_mint_info.key
+        );
+        return Err(TokenWrapError::WrappedMintMismatch.into());
+    }
+    if wrapped_mint_info.owner != wrapped_token_program_info.key {
+        return Err(TokenWrapError::InvalidWrappedMintOwner.into());
+    }
+    Ok(())
+}
+
+fn check_wrapped_mint_authority(
+    wrapped_mint: &Pubkey,
+    wrapped_mint_authority_info: &AccountInfo,
+) -> Result<u8, ProgramError> {
+    let (expected_authority, bump_seed) = get_wrapped_mint_authority_with_seed(wrapped_mint);
+    if expected_authority != *wrapped_mint_authority_info.key {
+        msg!(
+            "Expected wrapped mint authority {}, received {}",
+            &expected_authority,
+            wrapped_mint_authority_info.key
+        );
+        return Err(TokenWrapError::MintAuthorityMismatch.into());
+    }
+    Ok(bump_seed)
+}
+
+/// Check the escrow and return its balance
+fn get_escrow_amount(
+    escrow_info: &AccountInfo,
+    unwrapped_token_program_info: &AccountInfo,
+    wrapped_mint_authority: &Pubkey,
+) -> Result<u64, ProgramError> {
+    if escrow_info.owner != unwrapped_token_program_info.key {
+        return Err(ProgramError::IncorrectProgramId);
+    }
+    let escrow_data = escrow_info.try_borrow_data()?;
+    let escrow = StateWithExtensions::<Account>::unpack(&escrow_data)?;
+    if escrow.base.owner != *wrapped_mint_authority {
+        msg!(
+            "Expected escrow owned by {}, but it is owned by {}",
+            wrapped_mint_authority,
+            escrow.base.owner
+        );
+        return Err(TokenWrapError::EscrowOwnerMismatch.into());
+    }
+    Ok(escrow.base.amount)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn transfer_tokens<'a>(
+    token_program: AccountInfo<'a>,
+    source: AccountInfo<'a>,
+    mint: AccountInfo<'a>,
+    destination: AccountInfo<'a>,
+    authority: AccountInfo<'a>,
+    multisig_signers: &[AccountInfo<'a>],
+    amount: u64,
+    decimals: u8,
+) -> ProgramResult {
+    let multisig_pubkeys = multisig_signers.iter().map(|s| s.key).collect::<Vec<_>>();
+    let ix = spl_token_2022::instruction::transfer_checked(
+        token_program.key,
+        source.key,
+        mint.key,
+        destination.key,
+        authority.key,
+        &multisig_pubkeys,
+        amount,
+        decimals,
+    )?;
+    let mut account_infos = vec![source, mint, destination, authority];
+    account_infos.extend_from_slice(multisig_signers);
+    invoke(&ix, &account_infos)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn transfer_from_escrow<'a>(
+    token_program: AccountInfo<'a>,
+    source: AccountInfo<'a>,
+    mint: AccountInfo<'a>,
+    destination: AccountInfo<'a>,
+    authority: AccountInfo<'a>,
+    authority_seeds: &[&[u8]],
+    amount: u64,
+    decimals: u8,
+) -> ProgramResult {
+    let ix = spl_token_2022::instruction::transfer_checked(
+        token_program.key,
+        source.key,
+        mint.key,
+        destination.key,
+        authority.key,
+        &[],
+        amount,
+        decimals,
+    )?;
+    invoke_signed(
+        &ix,
+        &[source, mint, destination, authority],
+        &[authority_seeds],
+    )
+}
+
+fn burn_tokens<'a>(
+    token_program: AccountInfo<'a>,
+    source: AccountInfo<'a>,
+    mint: AccountInfo<'a>,
+    authority: AccountInfo<'a>,
+    multisig_signers: &[AccountInfo<'a>],
+    amount: u64,
+    decimals: u8,
+) -> ProgramResult {
+    let multisig_pubkeys = multisig_signers.iter().map(|s| s.key).collect::<Vec<_>>();
+    let ix = spl_token_2022::instruction::burn_checked(
+        token_program.key,
+        source.key,
+        mint.key,
+        authority.key,
+        &multisig_pubkeys,
+        amount,
+        decimals,
+    )?;
+    let mut account_infos = vec![source, mint, authority];
+    account_infos.extend_from_slice(multisig_signers);
+    invoke(&ix, &account_infos)
+}
+
+fn mint_wrapped_tokens<'a>(
+    token_program: AccountInfo<'a>,
+    mint: AccountInfo<'a>,
+    destination: AccountInfo<'a>,
+    authority: AccountInfo<'a>,
+    authority_seeds: &[&[u8]],
+    amount: u64,
+    decimals: u8,
+) -> ProgramResult {
+    let ix = spl_token_2022::instruction::mint_to_checked(
+        token_program.key,
+        mint.key,
+        destination.key,
+        authority.key,
+        &[],
+        amount,
+        decimals,
+    )?;
+    invoke_signed(&ix, &[mint, destination, authority], &[authority_seeds])
+}
+
+/// Build a confidential transfer `InitializeMint` instruction without any
+/// authority or auditor, since the token-2022 helper is not available on-chain
+fn initialize_confidential_transfer_mint(token_program_id: &Pubkey, mint: &Pubkey) -> Instruction {
+    let mut data = TokenInstruction::ConfidentialTransferExtension.pack();
+    data.push(ConfidentialTransferInstruction::InitializeMint.into());
+    data.extend_from_slice(pod_bytes_of(&InitializeMintData {
+        auto_approve_new_accounts: true.into(),
+        ..Zeroable::zeroed()
+    }));
+    Instruction {
+        program_id: *token_program_id,
+        accounts: vec![AccountMeta::new(*mint, false)],
+        data,
+    }
+}
+
+/// Read the metadata of the unwrapped mint, either from the mint itself or from
+/// the account designated by its metadata pointer
+fn get_unwrapped_metadata(
+    unwrapped_mint_info: &AccountInfo,
+    unwrapped_metadata_info: Option<&AccountInfo>,
+) -> Result<TokenMetadata, ProgramError> {
+    let unwrapped_mint_data = unwrapped_mint_info.try_borrow_data()?;
+    let unwrapped_mint = StateWithExtensions::<Mint>::unpack(&unwrapped_mint_data)?;
+    let metadata_address = unwrapped_mint
+        .get_extension::<MetadataPointer>()
+        .ok()
+        .and_then(|pointer| Option::<Pubkey>::from(pointer.metadata_address))
+        .ok_or(TokenWrapError::MissingUnwrappedMetadata)?;
+    let token_metadata = if metadata_address == *unwrapped_mint_info.key {
+        unwrapped_mint.get_variable_len_extension::<TokenMetadata>()?
+    } else {
+        let unwrapped_metadata_info =
+            unwrapped_metadata_info.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if *unwrapped_metadata_info.key != metadata_address {
+            msg!(
+                "Expected unwrapped metadata {}, received {}",
+                &metadata_address,
+                unwrapped_metadata_info.key
+            );
+            return Err(TokenWrapError::UnwrappedMetadataMismatch.into());
+        }
+        let unwrapped_metadata_data = unwrapped_metadata_info.try_borrow_data()?;
+        TlvStateBorrowed::unpack(&unwrapped_metadata_data)?
+            .get_first_variable_len_value::<TokenMetadata>()?
+    };
+    // The metadata must also point back to the mint to avoid spoofing
+    if token_metadata.mint != *unwrapped_mint_info.key {
+        return Err(TokenWrapError::UnwrappedMetadataMismatch.into());
+    }
+    Ok(token_metadata)
+}
+
+/// Get the updates needed for the wrapped metadata to match the unwrapped
+/// metadata, ignoring the mint and update authority
+fn get_metadata_updates(wrapped: &TokenMetadata, unwrapped: &TokenMetadata) -> Vec<FieldUpdate> {
+    let mut updates = vec![];
+    let fields = vec![
+        (Field::Name, &wrapped.name, &unwrapped.name),
+        (Field::Symbol, &wrapped.symbol, &unwrapped.symbol),
+        (Field::Uri, &wrapped.uri, &unwrapped.uri),
+    ];
+    for (field, wrapped_value, unwrapped_value) in fields {
+        if wrapped_value != unwrapped_value {
+            updates.push(FieldUpdate::Set {
+                field,
+                value: unwrapped_value.clone(),
+            });
+        }
+    }
+    for (key, value) in unwrapped.additional_metadata.iter() {
+        if !wrapped
+            .additional_metadata
+            .iter()
+            .any(|(k, v)| k == key && v == value)
+        {
+            updates.push(FieldUpdate::Set {
+                field: Field::Key(key.clone()),
+                value: value.clone(),
+            });
+        }
+    }
+    for (key, _) in wrapped.additional_metadata.iter() {
+        if !unwrapped.additional_metadata.iter().any(|(k, _)| k == key) {
+            updates.push(FieldUpdate::Remove {
+                key: key.clone(),
+                idempotent: false,
+            });
+        }
+    }
+    updates
+}
+
+/// Get the extensions initialized on an existing wrapped mint
+fn get_wrapped_mint_extensions(
+    wrapped_mint_info: &AccountInfo,
+) -> Result<WrappedMintExtensions, ProgramError> {
+    let wrapped_mint_data = wrapped_mint_info.try_borrow_data()?;
+    let wrapped_mint = StateWithExtensions::<Mint>::unpack(&wrapped_mint_data)?;
+    Ok(WrappedMintExtensions {
+        transfer_hook_program_id: wrapped_mint
+            .get_extension::<TransferHook>()
+            .map(|transfer_hook| transfer_hook.program_id)
+            .unwrap_or_default(),
+        mirror_metadata: wrapped_mint
+            .get_extension::<MetadataPointer>()
+            .is_ok()
+            .into(),
+        confidential_transfers: wrapped_mint
+            .get_extension::<ConfidentialTransferMint>()
+            .is_ok()
+            .into(),
+    })
+}
+
+fn process_create_mint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: &CreateMintData,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let funding_info = next_account_info(account_info_iter)?;
+    let wrapped_mint_info = next_account_info(account_info_iter)?;
+    let backpointer_info = next_account_info(account_info_iter)?;
+    let unwrapped_mint_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let wrapped_token_program_info = next_account_info(account_info_iter)?;
+    let extensions = &data.extensions;
+    let unwrapped_mint_authority_info = if extensions.is_empty() {
+        None
+    } else {
+        account_info_iter.next()
+    };
+    let wrapped_mint_authority_info = account_info_iter.next();
+    let unwrapped_metadata_info = account_info_iter.next();
+
+    check_spl_token_program_account(wrapped_token_program_info.key)?;
+    let (expected_wrapped_mint, wrapped_mint_bump_seed) =
+        get_wrapped_mint_address_with_seed(unwrapped_mint_info.key, wrapped_token_program_info.key);
+    if expected_wrapped_mint != *wrapped_mint_info.key {
+        msg!(
+            "Expected wrapped mint {}, received {}",
+            &expected_wrapped_mint,
+            wrapped_mint_info.key
+        );
+        return Err(TokenWrapError::WrappedMintMismatch.into());
+    }
+    let (expected_backpointer, backpointer_bump_seed) =
+        get_wrapped_mint_backpointer_address_with_seed(wrapped_mint_info.key);
+    if expected_backpointer != *backpointer_info.key {
+        msg!(
+            "Expected backpointer {}, received {}",
+            &expected_backpointer,
+            backpointer_info.key
+        );
+        return Err(TokenWrapError::BackpointerMismatch.into());
+    }
+
+    // The mint and backpointer are always created together
+    if wrapped_mint_info.data_len() > 0 || backpointer_info.data_len() > 0 {
+        if !bool::from(data.idempotent) {
+            return Err(ProgramError::AccountAlreadyInitialized);
+        }
+        if wrapped_mint_info.owner != wrapped_token_program_info.key {
+            return Err(TokenWrapError::InvalidWrappedMintOwner.into());
+        }
+        if backpointer_info.owner != program_id {
+            return Err(TokenWrapError::InvalidBackpointerOwner.into());
+        }
+        if get_wrapped_mint_extensions(wrapped_mint_info)? != *extensions {
+            return Err(TokenWrapError::ExtensionsMismatch.into());
+        }
+        return Ok(());
+    }
+
+    let unwrapped_token_program_id = *unwrapped_mint_info.owner;
+    check_spl_token_program_account(&unwrapped_token_program_id)?;
+    let (decimals, unwrapped_mint_authority) = {
+        let unwrapped_mint_data = unwrapped_mint_info.try_borrow_data()?;
+        let unwrapped_mint = StateWithExtensions::<Mint>::unpack(&unwrapped_mint_data)?;
+        (
+            unwrapped_mint.base.decimals,
+            unwrapped_mint.base.mint_authority,
+        )
+    };
+
+    let extension_types = extensions.extension_types();
+    if !extension_types.is_empty() {
+        if *wrapped_token_program_info.key != spl_token_2022::id() {
+            return Err(TokenWrapError::ExtensionsRequireToken2022.into());
+        }
+        // Only the unwrapped mint authority chooses the extensions
+        let unwrapped_mint_authority_info =
+            unwrapped_mint_authority_info.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        if unwrapped_mint_authority != COption::Some(*unwrapped_mint_authority_info.key) {
+            return Err(TokenWrapError::UnwrappedMintAuthorityMismatch.into());
+        }
+        if !unwrapped_mint_authority_info.is_signer {
+            return Err(ProgramError::MissingRequiredSignature);
+        }
+    }
+    let (wrapped_mint_authority, wrapped_mint_authority_bump_seed) =
+        get_wrapped_mint_authority_with_seed(wrapped_mint_info.key);
+    let mirrored_metadata = if bool::from(extensions.mirror_metadata) {
+        let wrapped_mint_authority_info =
+            wrapped_mint_authority_info.ok_or(ProgramError::NotEnoughAccountKeys)?;
+        check_wrapped_mint_authority(wrapped_mint_info.key, wrapped_mint_authority_info)?;
+        let unwrapped_metadata =
+            get_unwrapped_metadata(unwrapped_mint_info, unwrapped_metadata_info)?;
+        Some((
+            TokenMetadata {
+                update_authority: OptionalNonZeroPubkey::try_from(Some(wrapped_mint_authority))?,
+                mint: *wrapped_mint_info.key,
+                ..unwrapped_metadata
+            },
+            wrapped_mint_authority_info,
+        ))
+    } else {
+        None
+    };
+
+    // Create and initialize the wrapped mint
+    let bump_seed = [wrapped_mint_bump_seed];
+    let signer_seeds = get_wrapped_mint_signer_seeds(
+        unwrapped_mint_info.key,
+        wrapped_token_program_info.key,
+        &bump_seed,
+    );
+    let space = ExtensionType::try_calculate_account_len::<Mint>(&extension_types)?;
+    create_pda_account(
+        funding_info,
+        wrapped_mint_info,
+        system_program_info,
+        space,
+        wrapped_token_program_info.key,
+        &signer_seeds,
+    )?;
+    if let Some(transfer_hook_program_id) =
+        Option::<Pubkey>::from(extensions.transfer_hook_program_id)
+    {
+        invoke(
+            &transfer_hook::instruction::initialize(
+                wrapped_token_program_info.key,
+                wrapped_mint_info.key,
+                None,
+                Some(transfer_hook_program_id),
+            )?,
+            &[wrapped_mint_info.clone()],
+        )?;
+    }
+    if mirrored_metadata.is_some() {
+        invoke(
+            &metadata_pointer::instruction::initialize(
+                wrapped_token_program_info.key,
+                wrapped_mint_info.key,
+                None,
+                Some(*wrapped_mint_info.key),
+            )?,
+            &[wrapped_mint_info.clone()],
+        )?;
+    }
+    if bool::from(extensions.confidential_transfers) {
+        invoke(
+            &initialize_confidential_transfer_mint(
+                wrapped_token_program_info.key,
+                wrapped_mint_info.key,
+            ),
+            &[wrapped_mint_info.clone()],
+        )?;
+    }
+    invoke(
+        &spl_token_2022::instruction::initialize_mint2(
+            wrapped_token_program_info.key,
+            wrapped_mint_info.key,
+            &wrapped_mint_authority,
+            None,
+            decimals,
+        )?,
+        &[wrapped_mint_info.clone()],
+    )?;
+
+    // Copy the metadata, which needs more space
+    if let Some((token_metadata, wrapped_mint_authority_info)) = mirrored_metadata {
+        let metadata_space = space
+            .checked_add(token_metadata.tlv_size_of()?)
+            .ok_or(ProgramError::InvalidAccountData)?;
+        transfer_rent(
+            funding_info,
+            wrapped_mint_info,
+            system_program_info,
+            metadata_space,
+        )?;
+        let bump_seed = [wrapped_mint_authority_bump_seed];
+        let authority_seeds =
+            get_wrapped_mint_authority_signer_seeds(wrapped_mint_info.key, &bump_seed);
+        let account_infos = [
+            wrapped_mint_info.clone(),
+            wrapped_mint_authority_info.clone(),
+        ];
+        invoke_signed(
+            &spl_token_metadata_interface::instruction::initialize(
+                wrapped_token_program_info.key,
+                wrapped_mint_info.key,
+                &wrapped_mint_authority,
+                wrapped_mint_info.key,
+                &wrapped_mint_authority,
+                token_metadata.name.clone(),
+                token_metadata.symbol.clone(),
+                token_metadata.uri.clone(),
+            ),
+            &account_infos,
+            &[&authority_seeds],
+        )?;
+        let initialized_metadata = TokenMetadata {
+            additional_metadata: vec![],
+            ..token_metadata.clone()
+        };
+        let updates = get_metadata_updates(&initialized_metadata, &token_metadata);
+        if !updates.is_empty() {
+            invoke_signed(
+                &spl_token_metadata_interface::instruction::batch_update_fields(
+                    wrapped_token_program_info.key,
+                    wrapped_mint_info.key,
+                    &wrapped_mint_authority,
+                    updates,
+                ),
+                &account_infos,
+                &[&authority_seeds],
+            )?;
+        }
+    }
+
+    // Create the backpointer
+    let bump_seed = [backpointer_bump_seed];
+    let signer_seeds =
+        get_wrapped_mint_backpointer_address_signer_seeds(wrapped_mint_info.key, &bump_seed);
+    create_pda_account(
+        funding_info,
+        backpointer_info,
+        system_program_info,
+        size_of::<Backpointer>(),
+        program_id,
+        &signer_seeds,
+    )?;
+    let mut backpointer_data = backpointer_info.try_borrow_mut_data()?;
+    let backpointer = bytemuck::try_from_bytes_mut::<Backpointer>(&mut backpointer_data)
+        .map_err(|_| ProgramError::InvalidAccountData)?;
+    backpointer.unwrapped_mint = *unwrapped_mint_info.key;
+
+    Ok(())
+}
+
+fn process_wrap(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    if amount == 0 {
+        return Err(TokenWrapError::ZeroWrapAmount.into());
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let unwrapped_token_account_info = next_account_info(account_info_iter)?;
+    let unwrapped_escrow_info = next_account_info(account_info_iter)?;
+    let unwrapped_mint_info = next_account_info(account_info_iter)?;
+    let wrapped_mint_info = next_account_info(account_info_iter)?;
+    let recipient_wrapped_token_account_info = next_account_info(account_info_iter)?;
+    let wrapped_mint_authority_info = next_account_info(account_info_iter)?;
+    let unwrapped_token_program_info = next_account_info(account_info_iter)?;
+    let wrapped_token_program_info = next_account_info(account_info_iter)?;
+    let transfer_authority_info = next_account_info(account_info_iter)?;
+    let multisig_signer_infos = account_info_iter.as_slice();
+
+    check_wrapped_mint(
+        wrapped_mint_info,
+        unwrapped_mint_info.key,
+        wrapped_token_program_info,
+    )?;
+    let bump_seed =
+        check_wrapped_mint_authority(wrapped_mint_info.key, wrapped_mint_authority_info)?;
+    let decimals = get_unwrapped_mint_decimals(unwrapped_mint_info, unwrapped_token_program_info)?;
+
+    let escrow_amount_before = get_escrow_amount(
+        unwrapped_escrow_info,
+        unwrapped_token_program_info,
+        wrapped_mint_authority_info.key,
+    )?;
+    transfer_tokens(
+        unwrapped_token_program_info.clone(),
+        unwrapped_token_account_info.clone(),
+        unwrapped_mint_info.clone(),
+        unwrapped_escrow_info.clone(),
+        transfer_authority_info.clone(),
+        multisig_signer_infos,
+        amount,
+        decimals,
+    )?;
+    let escrow_amount_after = get_escrow_amount(
+        unwrapped_escrow_info,
+        unwrapped_token_program_info,
+        wrapped_mint_authority_info.key,
+    )?;
+    // Only mint what the escrow actually received, in case of transfer fees
+    let wrapped_amount = escrow_amount_after
+        .checked_sub(escrow_amount_before)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    if wrapped_amount == 0 {
+        return Err(TokenWrapError::ZeroWrapAmount.into());
+    }
+
+    let bump_seed = [bump_seed];
+    let authority_seeds =
+        get_wrapped_mint_authority_signer_seeds(wrapped_mint_info.key, &bump_seed);
+    mint_wrapped_tokens(
+        wrapped_token_program_info.clone(),
+        wrapped_mint_info.clone(),
+        recipient_wrapped_token_account_info.clone(),
+        wrapped_mint_authority_info.clone(),
+        &authority_seeds,
+        wrapped_amount,
+        decimals,
+    )
+}
+
+fn process_unwrap(accounts: &[AccountInfo], amount: u64) -> ProgramResult {
+    if amount == 0 {
+        return Err(TokenWrapError::ZeroWrapAmount.into());
+    }
+
+    let account_info_iter = &mut accounts.iter();
+
+    let wrapped_token_account_info = next_account_info(account_info_iter)?;
+    let wrapped_mint_info = next_account_info(account_info_iter)?;
+    let unwrapped_escrow_info = next_account_info(account_info_iter)?;
+    let recipient_unwrapped_token_account_info = next_account_info(account_info_iter)?;
+    let unwrapped_mint_info = next_account_info(account_info_iter)?;
+    let wrapped_mint_authority_info = next_account_info(account_info_iter)?;
+    let wrapped_token_program_info = next_account_info(account_info_iter)?;
+    let unwrapped_token_program_info = next_account_info(account_info_iter)?;
+    let transfer_authority_info = next_account_info(account_info_iter)?;
+    let multisig_signer_infos = account_info_iter.as_slice();
+
+    check_wrapped_mint(
+        wrapped_mint_info,
+        unwrapped_mint_info.key,
+        wrapped_token_program_info,
+    )?;
+    let bump_seed =
+        check_wrapped_mint_authority(wrapped_mint_info.key, wrapped_mint_authority_info)?;
+    let decimals = get_unwrapped_mint_decimals(unwrapped_mint_info, unwrapped_token_program_info)?;
+    get_escrow_amount(
+        unwrapped_escrow_info,
+        unwrapped_token_program_info,
+        wrapped_mint_authority_info.key,
+    )?;
+
+    burn_tokens(
+        wrapped_token_program_info.clone(),
+        wrapped_token_account_info.clone(),
+        wrapped_mint_info.clone(),
+        transfer_authority_info.clone(),
+        multisig_signer_infos,
+        amount,
+        decimals,
+    )?;
+
+    let bump_seed = [bump_seed];
+    let authority_seeds =
+        get_wrapped_mint_authority_signer_seeds(wrapped_mint_info.key, &bump_seed);
+    transfer_from_escrow(
+        unwrapped_token_program_info.clone(),
+        unwrapped_escrow_info.clone(),
+        unwrapped_mint_info.clone(),
+        recipient_unwrapped_token_account_info.clone(),
+        wrapped_mint_authority_info.clone(),
+        &authority_seeds,
+        amount,
+        decimals,
+    )
+}
+
+fn process_sync_metadata(accounts: &[AccountInfo]) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let wrapped_mint_info = next_account_info(account_info_iter)?;
+    let wrapped_mint_authority_info = next_account_info(account_info_iter)?;
+    let unwrapped_mint_info = next_account_info(account_info_iter)?;
+    let wrapped_token_program_info = next_account_info(account_info_iter)?;
+    let funding_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+    let unwrapped_metadata_info = account_info_iter.next();
+
+    check_wrapped_mint(
+        wrapped_mint_info,
+        unwrapped_mint_info.key,
+        wrapped_token_program_info,
+    )?;
+    let bump_seed =
+        check_wrapped_mint_authority(wrapped_mint_info.key, wrapped_mint_authority_info)?;
+
+    let wrapped_metadata = {
+        let wrapped_mint_data = wrapped_mint_info.try_borrow_data()?;
+        let wrapped_mint = StateWithExtensions::<Mint>::unpack(&wrapped_mint_data)?;
+        let metadata_address = wrapped_mint
+            .get_extension::<MetadataPointer>()
+            .ok()
+            .and_then(|pointer| Option::<Pubkey>::from(pointer.metadata_address));
+        if metadata_address != Some(*wrapped_mint_info.key) {
+            return Err(TokenWrapError::MetadataNotMirrored.into());
+        }
+        wrapped_mint
+            .get_variable_len_extension::<TokenMetadata>()
+            .map_err(|_| TokenWrapError::MetadataNotMirrored)?
+    };
+    let unwrapped_metadata = get_unwrapped_metadata(unwrapped_mint_info, unwrapped_metadata_info)?;
+
+    let updates = get_metadata_updates(&wrapped_metadata, &unwrapped_metadata);
+    if updates.is_empty() {
+        return Ok(());
+    }
+
+    // Pay for the new size of the metadata before it gets reallocated
+    let mut new_metadata = wrapped_metadata.clone();
+    new_metadata.apply_updates(updates.clone())?;
+    let new_space = wrapped_mint_info
+        .data_len()
+        .checked_sub(wrapped_metadata.tlv_size_of()?)
+        .and_then(|len| len.checked_add(new_metadata.tlv_size_of().ok()?))
+        .ok_or(ProgramError::InvalidAccountData)?;
+    transfer_rent(
+        funding_info,
+        wrapped_mint_info,
+        system_program_info,
+        new_space,
+    )?;
+
+    let bump_seed = [bump_seed];
+    let authority_seeds =
+        get_wrapped_mint_authority_signer_seeds(wrapped_mint_info.key, &bump_seed);
+    invoke_signed(
+        &spl_token_metadata_interface::instruction::batch_update_fields(
+            wrapped_token_program_info.key,
+            wrapped_mint_info.key,
+            wrapped_mint_authority_info.key,
+            updates,
+        ),
+        &[
+            wrapped_mint_info.clone(),
+            wrapped_mint_authority_info.clone(),
+        ],
+        &[&authority_seeds],
+    )
+}
+
 /// Instruction processor
 pub fn process_instruction(
-    _program_id: &Pubkey,
-    _accounts: &[AccountInfo],
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
     input: &[u8],
 ) -> ProgramResult {
     match decode_instruction_type(input)? {
         TokenWrapInstruction::CreateMint => {
-            unimplemented!();
+            let data = decode_instruction_data::<CreateMintData>(input)?;
+            process_create_mint(program_id, accounts, data)
         }
         TokenWrapInstruction::Wrap => {
-            unimplemented!();
+            let amount = decode_instruction_data::<PodU64>(input)?;
+            process_wrap(accounts, u64::from(*amount))
         }
         TokenWrapInstruction::Unwrap => {
-            unimplemented!();
+            let amount = decode_instruction_data::<PodU64>(input)?;
+            process_unwrap(accounts, u64::from(*amount))
         }
+        TokenWrapInstruction::SyncMetadata => process_sync_metadata(accounts),
     }
 }
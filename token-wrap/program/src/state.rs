@@ -3,6 +3,10 @@
 use {
     bytemuck::{Pod, Zeroable},
     solana_program::pubkey::Pubkey,
+    spl_token_2022::{
+        extension::ExtensionType,
+        pod::{OptionalNonZeroPubkey, PodBool},
+    },
 };
 
 /// Backpointer
@@ -22,3 +26,43 @@ pub struct Backpointer {
     /// Address that the wrapped mint is wrapping
     pub unwrapped_mint: Pubkey,
 }
+
+/// Token-2022 extensions to initialize on a wrapped mint
+///
+/// All extensions are created without an authority, so the wrapped mint
+/// remains fully controlled by the token wrap program.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Pod, Zeroable)]
+#[repr(C)]
+pub struct WrappedMintExtensions {
+    /// Program called on every transfer of wrapped tokens, if set
+    pub transfer_hook_program_id: OptionalNonZeroPubkey,
+    /// If true, the wrapped mint points to itself for its metadata, which is
+    /// copied from the unwrapped mint's metadata and refreshed through
+    /// `SyncMetadata`
+    pub mirror_metadata: PodBool,
+    /// If true, wrapped tokens may be transferred confidentially, with new
+    /// accounts approved automatically and no auditor
+    pub confidential_transfers: PodBool,
+}
+impl WrappedMintExtensions {
+    /// Returns true if no extension is requested
+    pub fn is_empty(&self) -> bool {
+        self.extension_types().is_empty()
+    }
+
+    /// Get the fixed-length extension types to allocate on the wrapped mint.
+    /// The metadata itself is variable-length, so it is not included.
+    pub fn extension_types(&self) -> Vec<ExtensionType> {
+        let mut extension_types = vec![];
+        if Option::<Pubkey>::from(self.transfer_hook_program_id).is_some() {
+            extension_types.push(ExtensionType::TransferHook);
+        }
+        if bool::from(self.mirror_metadata) {
+            extension_types.push(ExtensionType::MetadataPointer);
+        }
+        if bool::from(self.confidential_transfers) {
+            extension_types.push(ExtensionType::ConfidentialTransferMint);
+        }
+        extension_types
+    }
+}
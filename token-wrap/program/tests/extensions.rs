@@ -0,0 +1,720 @@
+// Mark this test as SBF-only due to current `ProgramTest` limitations when CPIing into the system program
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use {
+    common::{process_instruction, setup},
+    solana_program_test::{
+        tokio::{self, sync::Mutex},
+        ProgramTestContext,
+    },
+    solana_sdk::{
+        account::Account,
+        borsh::get_instance_packed_len,
+        instruction::InstructionError,
+        pubkey::Pubkey,
+        signature::Signer,
+        signer::keypair::Keypair,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token_2022::extension::{
+        metadata_pointer::MetadataPointer, transfer_hook::TransferHook, BaseStateWithExtensions,
+        ExtensionType,
+    },
+    spl_token_client::{
+        client::{ProgramClient, SendTransaction, SimulateTransaction},
+        token::{ExtensionInitializationParams, Token},
+    },
+    spl_token_metadata_interface::state::{Field, TokenMetadata},
+    spl_token_wrap::{
+        error::TokenWrapError,
+        get_wrapped_mint_address, get_wrapped_mint_authority,
+        instruction::{create_mint, sync_metadata},
+        state::WrappedMintExtensions,
+    },
+    spl_type_length_value::state::TlvStateMut,
+    std::{convert::TryInto, sync::Arc},
+};
+
+/// Create a token-2022 mint holding its own metadata
+async fn setup_mint_with_metadata<T: SendTransaction + SimulateTransaction>(
+    authority: &Keypair,
+    decimals: u8,
+    payer: Arc<Keypair>,
+    client: Arc<dyn ProgramClient<T>>,
+) -> Token<T> {
+    let mint_account = Keypair::new();
+    let token = Token::new(
+        client,
+        &spl_token_2022::id(),
+        &mint_account.pubkey(),
+        Some(decimals),
+        payer.clone(),
+    );
+    token
+        .create_mint(
+            &authority.pubkey(),
+            None,
+            vec![ExtensionInitializationParams::MetadataPointer {
+                authority: Some(authority.pubkey()),
+                metadata_address: Some(mint_account.pubkey()),
+            }],
+            &[&mint_account],
+        )
+        .await
+        .unwrap();
+    token
+        .token_metadata_initialize_with_rent_transfer(
+            &payer.pubkey(),
+            &authority.pubkey(),
+            &authority.pubkey(),
+            "Original".to_string(),
+            "ORIG".to_string(),
+            "https://example.com/original.json".to_string(),
+            &[authority],
+        )
+        .await
+        .unwrap();
+    token
+        .token_metadata_update_field_with_rent_transfer(
+            &payer.pubkey(),
+            &authority.pubkey(),
+            Field::Key("color".to_string()),
+            "blue".to_string(),
+            &[authority],
+        )
+        .await
+        .unwrap();
+    token
+}
+
+/// Write token-metadata into an account outside of the mint, as a separate
+/// metadata program would
+async fn set_metadata_account(
+    context: &Mutex<ProgramTestContext>,
+    address: &Pubkey,
+    token_metadata: &TokenMetadata,
+) {
+    let mut data = vec![0; token_metadata.tlv_size_of().unwrap()];
+    let mut state = TlvStateMut::unpack(&mut data).unwrap();
+    state
+        .alloc::<TokenMetadata>(get_instance_packed_len(token_metadata).unwrap(), false)
+        .unwrap();
+    state.pack_first_variable_len_value(token_metadata).unwrap();
+
+    let mut context = context.lock().await;
+    let rent = context.banks_client.get_rent().await.unwrap();
+    context.set_account(
+        address,
+        &Account {
+            lamports: rent.minimum_balance(data.len()),
+            data,
+            owner: Pubkey::new_unique(),
+            executable: false,
+            rent_epoch: 0,
+        }
+        .into(),
+    );
+}
+
+fn assert_mirrored(wrapped: &TokenMetadata, unwrapped: &TokenMetadata, wrapped_mint: &Pubkey) {
+    assert_eq!(
+        Option::<Pubkey>::from(wrapped.update_authority.clone()),
+        Some(get_wrapped_mint_authority(wrapped_mint))
+    );
+    assert_eq!(wrapped.mint, *wrapped_mint);
+    assert_eq!(wrapped.name, unwrapped.name);
+    assert_eq!(wrapped.symbol, unwrapped.symbol);
+    assert_eq!(wrapped.uri, unwrapped.uri);
+    assert_eq!(wrapped.additional_metadata, unwrapped.additional_metadata);
+}
+
+#[tokio::test]
+async fn success_create_mint_with_extensions_and_sync() {
+    let (context, client, payer) = setup().await;
+    let authority = Keypair::new();
+    let decimals = 6;
+    let unwrapped_token =
+        setup_mint_with_metadata(&authority, decimals, payer.clone(), client.clone()).await;
+
+    let wrapped_token_program_id = spl_token_2022::id();
+    let wrapped_mint =
+        get_wrapped_mint_address(unwrapped_token.get_address(), &wrapped_token_program_id);
+    let transfer_hook_program_id = Pubkey::new_unique();
+    let extensions = WrappedMintExtensions {
+        transfer_hook_program_id: Some(transfer_hook_program_id).try_into().unwrap(),
+        mirror_metadata: true.into(),
+        confidential_transfers: true.into(),
+    };
+    process_instruction(
+        &context,
+        create_mint(
+            &spl_token_wrap::id(),
+            &wrapped_mint,
+            unwrapped_token.get_address(),
+            &wrapped_token_program_id,
+            &payer.pubkey(),
+            false,
+            &extensions,
+            Some(&authority.pubkey()),
+            None,
+        ),
+        &[&authority],
+    )
+    .await
+    .unwrap();
+
+    let wrapped_token = Token::new(
+        client.clone(),
+        &wrapped_token_program_id,
+        &wrapped_mint,
+        Some(decimals),
+        payer.clone(),
+    );
+    let wrapped_mint_info = wrapped_token.get_mint_info().await.unwrap();
+    assert_eq!(wrapped_mint_info.base.decimals, decimals);
+    let extension_types = wrapped_mint_info.get_extension_types().unwrap();
+    for extension_type in [
+        ExtensionType::TransferHook,
+        ExtensionType::MetadataPointer,
+        ExtensionType::ConfidentialTransferMint,
+        ExtensionType::TokenMetadata,
+    ] {
+        assert!(extension_types.contains(&extension_type));
+    }
+    let transfer_hook = wrapped_mint_info.get_extension::<TransferHook>().unwrap();
+    assert_eq!(
+        Option::<Pubkey>::from(transfer_hook.program_id),
+        Some(transfer_hook_program_id)
+    );
+    assert_eq!(Option::<Pubkey>::from(transfer_hook.authority), None);
+    let metadata_pointer = wrapped_mint_info
+        .get_extension::<MetadataPointer>()
+        .unwrap();
+    assert_eq!(
+        Option::<Pubkey>::from(metadata_pointer.metadata_address),
+        Some(wrapped_mint)
+    );
+
+    let unwrapped_metadata = unwrapped_token
+        .get_mint_info()
+        .await
+        .unwrap()
+        .get_variable_len_extension::<TokenMetadata>()
+        .unwrap();
+    let wrapped_metadata = wrapped_mint_info
+        .get_variable_len_extension::<TokenMetadata>()
+        .unwrap();
+    assert_mirrored(&wrapped_metadata, &unwrapped_metadata, &wrapped_mint);
+
+    // change the source metadata, growing it
+    unwrapped_token
+        .token_metadata_update_field_with_rent_transfer(
+            &payer.pubkey(),
+            &authority.pubkey(),
+            Field::Name,
+            "Original token with a much longer name".to_string(),
+            &[&authority],
+        )
+        .await
+        .unwrap();
+    unwrapped_token
+        .token_metadata_update_field_with_rent_transfer(
+            &payer.pubkey(),
+            &authority.pubkey(),
+            Field::Key("size".to_string()),
+            "large".to_string(),
+            &[&authority],
+        )
+        .await
+        .unwrap();
+    unwrapped_token
+        .token_metadata_remove_key(
+            &authority.pubkey(),
+            "color".to_string(),
+            false,
+            &[&authority],
+        )
+        .await
+        .unwrap();
+
+    // anyone can sync
+    let funding = Keypair::new();
+    {
+        let mut context = context.lock().await;
+        let transaction = Transaction::new_signed_with_payer(
+            &[solana_sdk::system_instruction::transfer(
+                &context.payer.pubkey(),
+                &funding.pubkey(),
+                1_000_000_000,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.last_blockhash,
+        );
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+    process_instruction(
+        &context,
+        sync_metadata(
+            &spl_token_wrap::id(),
+            &wrapped_mint,
+            unwrapped_token.get_address(),
+            &wrapped_token_program_id,
+            &funding.pubkey(),
+            None,
+        ),
+        &[&funding],
+    )
+    .await
+    .unwrap();
+
+    let unwrapped_metadata = unwrapped_token
+        .get_mint_info()
+        .await
+        .unwrap()
+        .get_variable_len_extension::<TokenMetadata>()
+        .unwrap();
+    let wrapped_metadata = wrapped_token
+        .get_mint_info()
+        .await
+        .unwrap()
+        .get_variable_len_extension::<TokenMetadata>()
+        .unwrap();
+    assert_mirrored(&wrapped_metadata, &unwrapped_metadata, &wrapped_mint);
+}
+
+#[tokio::test]
+async fn success_mirror_metadata_from_separate_account() {
+    let (context, client, payer) = setup().await;
+    let authority = Keypair::new();
+    let mint_account = Keypair::new();
+    let metadata_address = Pubkey::new_unique();
+    let unwrapped_token = Token::new(
+        client.clone(),
+        &spl_token_2022::id(),
+        &mint_account.pubkey(),
+        Some(0),
+        payer.clone(),
+    );
+    unwrapped_token
+        .create_mint(
+            &authority.pubkey(),
+            None,
+            vec![ExtensionInitializationParams::MetadataPointer {
+                authority: Some(authority.pubkey()),
+                metadata_address: Some(metadata_address),
+            }],
+            &[&mint_account],
+        )
+        .await
+        .unwrap();
+    let mut unwrapped_metadata = TokenMetadata {
+        update_authority: Some(authority.pubkey()).try_into().unwrap(),
+        mint: mint_account.pubkey(),
+        name: "Separate".to_string(),
+        symbol: "SEP".to_string(),
+        uri: "https://example.com/separate.json".to_string(),
+        additional_metadata: vec![("color".to_string(), "blue".to_string())],
+    };
+    set_metadata_account(&context, &metadata_address, &unwrapped_metadata).await;
+
+    let wrapped_token_program_id = spl_token_2022::id();
+    let wrapped_mint =
+        get_wrapped_mint_address(unwrapped_token.get_address(), &wrapped_token_program_id);
+    let extensions = WrappedMintExtensions {
+        mirror_metadata: true.into(),
+        ..Default::default()
+    };
+
+    // the metadata account must be provided, and must be the one pointed to
+    for (unwrapped_metadata_address, expected_error) in [
+        (None, InstructionError::NotEnoughAccountKeys),
+        (
+            Some(Pubkey::new_unique()),
+            InstructionError::Custom(TokenWrapError::UnwrappedMetadataMismatch as u32),
+        ),
+    ] {
+        let error = process_instruction(
+            &context,
+            create_mint(
+                &spl_token_wrap::id(),
+                &wrapped_mint,
+                unwrapped_token.get_address(),
+                &wrapped_token_program_id,
+                &payer.pubkey(),
+                false,
+                &extensions,
+                Some(&authority.pubkey()),
+                unwrapped_metadata_address.as_ref(),
+            ),
+            &[&authority],
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(error, TransactionError::InstructionError(0, expected_error));
+    }
+
+    process_instruction(
+        &context,
+        create_mint(
+            &spl_token_wrap::id(),
+            &wrapped_mint,
+            unwrapped_token.get_address(),
+            &wrapped_token_program_id,
+            &payer.pubkey(),
+            false,
+            &extensions,
+            Some(&authority.pubkey()),
+            Some(&metadata_address),
+        ),
+        &[&authority],
+    )
+    .await
+    .unwrap();
+
+    let wrapped_token = Token::new(
+        client,
+        &wrapped_token_program_id,
+        &wrapped_mint,
+        Some(0),
+        payer.clone(),
+    );
+    let wrapped_metadata = wrapped_token
+        .get_mint_info()
+        .await
+        .unwrap()
+        .get_variable_len_extension::<TokenMetadata>()
+        .unwrap();
+    assert_mirrored(&wrapped_metadata, &unwrapped_metadata, &wrapped_mint);
+
+    // change the source metadata, then sync from the same account
+    unwrapped_metadata.name = "Separate token with a much longer name".to_string();
+    unwrapped_metadata.additional_metadata = vec![("size".to_string(), "large".to_string())];
+    set_metadata_account(&context, &metadata_address, &unwrapped_metadata).await;
+    process_instruction(
+        &context,
+        sync_metadata(
+            &spl_token_wrap::id(),
+            &wrapped_mint,
+            unwrapped_token.get_address(),
+            &wrapped_token_program_id,
+            &payer.pubkey(),
+            Some(&metadata_address),
+        ),
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let wrapped_metadata = wrapped_token
+        .get_mint_info()
+        .await
+        .unwrap()
+        .get_variable_len_extension::<TokenMetadata>()
+        .unwrap();
+    assert_mirrored(&wrapped_metadata, &unwrapped_metadata, &wrapped_mint);
+}
+
+#[tokio::test]
+async fn fail_extensions_on_token() {
+    let (context, client, payer) = setup().await;
+    let authority = Keypair::new();
+    let unwrapped_token = setup_mint_with_metadata(&authority, 0, payer.clone(), client).await;
+
+    let wrapped_token_program_id = spl_token::id();
+    let wrapped_mint =
+        get_wrapped_mint_address(unwrapped_token.get_address(), &wrapped_token_program_id);
+    let error = process_instruction(
+        &context,
+        create_mint(
+            &spl_token_wrap::id(),
+            &wrapped_mint,
+            unwrapped_token.get_address(),
+            &wrapped_token_program_id,
+            &payer.pubkey(),
+            false,
+            &WrappedMintExtensions {
+                confidential_transfers: true.into(),
+                ..Default::default()
+            },
+            Some(&authority.pubkey()),
+            None,
+        ),
+        &[&authority],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TokenWrapError::ExtensionsRequireToken2022 as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_mirror_without_metadata() {
+    let (context, client, payer) = setup().await;
+    let authority = Keypair::new();
+    let mint_account = Keypair::new();
+    let unwrapped_token = Token::new(
+        client,
+        &spl_token::id(),
+        &mint_account.pubkey(),
+        Some(0),
+        payer.clone(),
+    );
+    unwrapped_token
+        .create_mint(&authority.pubkey(), None, vec![], &[&mint_account])
+        .await
+        .unwrap();
+
+    let wrapped_token_program_id = spl_token_2022::id();
+    let wrapped_mint =
+        get_wrapped_mint_address(unwrapped_token.get_address(), &wrapped_token_program_id);
+    let error = process_instruction(
+        &context,
+        create_mint(
+            &spl_token_wrap::id(),
+            &wrapped_mint,
+            unwrapped_token.get_address(),
+            &wrapped_token_program_id,
+            &payer.pubkey(),
+            false,
+            &WrappedMintExtensions {
+                mirror_metadata: true.into(),
+                ..Default::default()
+            },
+            Some(&authority.pubkey()),
+            None,
+        ),
+        &[&authority],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TokenWrapError::MissingUnwrappedMetadata as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_sync_not_mirrored() {
+    let (context, client, payer) = setup().await;
+    let authority = Keypair::new();
+    let unwrapped_token = setup_mint_with_metadata(&authority, 0, payer.clone(), client).await;
+
+    let wrapped_token_program_id = spl_token_2022::id();
+    let wrapped_mint =
+        get_wrapped_mint_address(unwrapped_token.get_address(), &wrapped_token_program_id);
+    process_instruction(
+        &context,
+        create_mint(
+            &spl_token_wrap::id(),
+            &wrapped_mint,
+            unwrapped_token.get_address(),
+            &wrapped_token_program_id,
+            &payer.pubkey(),
+            false,
+            &WrappedMintExtensions::default(),
+            None,
+            None,
+        ),
+        &[],
+    )
+    .await
+    .unwrap();
+
+    let error = process_instruction(
+        &context,
+        sync_metadata(
+            &spl_token_wrap::id(),
+            &wrapped_mint,
+            unwrapped_token.get_address(),
+            &wrapped_token_program_id,
+            &payer.pubkey(),
+            None,
+        ),
+        &[],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TokenWrapError::MetadataNotMirrored as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_extensions_without_unwrapped_mint_authority() {
+    let (context, client, payer) = setup().await;
+    let authority = Keypair::new();
+    let unwrapped_token = setup_mint_with_metadata(&authority, 0, payer.clone(), client).await;
+
+    let wrapped_token_program_id = spl_token_2022::id();
+    let wrapped_mint =
+        get_wrapped_mint_address(unwrapped_token.get_address(), &wrapped_token_program_id);
+    let extensions = WrappedMintExtensions {
+        transfer_hook_program_id: Some(Pubkey::new_unique()).try_into().unwrap(),
+        ..Default::default()
+    };
+    let create_mint_with_authority = |unwrapped_mint_authority: Option<&Pubkey>| {
+        create_mint(
+            &spl_token_wrap::id(),
+            &wrapped_mint,
+            unwrapped_token.get_address(),
+            &wrapped_token_program_id,
+            &payer.pubkey(),
+            false,
+            &extensions,
+            unwrapped_mint_authority,
+            None,
+        )
+    };
+
+    // missing authority
+    let error = process_instruction(&context, create_mint_with_authority(None), &[])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(0, InstructionError::NotEnoughAccountKeys)
+    );
+
+    // wrong authority
+    let wrong_authority = Keypair::new();
+    let error = process_instruction(
+        &context,
+        create_mint_with_authority(Some(&wrong_authority.pubkey())),
+        &[&wrong_authority],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TokenWrapError::UnwrappedMintAuthorityMismatch as u32)
+        )
+    );
+
+    // authority does not sign
+    let mut instruction = create_mint_with_authority(Some(&authority.pubkey()));
+    instruction.accounts[6].is_signer = false;
+    let error = process_instruction(&context, instruction, &[])
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(0, InstructionError::MissingRequiredSignature)
+    );
+}
+
+#[tokio::test]
+async fn fail_idempotent_create_mint_with_different_extensions() {
+    let (context, client, payer) = setup().await;
+    let authority = Keypair::new();
+    let unwrapped_token = setup_mint_with_metadata(&authority, 0, payer.clone(), client).await;
+
+    let wrapped_token_program_id = spl_token_2022::id();
+    let wrapped_mint =
+        get_wrapped_mint_address(unwrapped_token.get_address(), &wrapped_token_program_id);
+    let extensions = WrappedMintExtensions {
+        transfer_hook_program_id: Some(Pubkey::new_unique()).try_into().unwrap(),
+        confidential_transfers: true.into(),
+        ..Default::default()
+    };
+    process_instruction(
+        &context,
+        create_mint(
+            &spl_token_wrap::id(),
+            &wrapped_mint,
+            unwrapped_token.get_address(),
+            &wrapped_token_program_id,
+            &payer.pubkey(),
+            false,
+            &extensions,
+            Some(&authority.pubkey()),
+            None,
+        ),
+        &[&authority],
+    )
+    .await
+    .unwrap();
+
+    // the same extensions succeed
+    context
+        .lock()
+        .await
+        .get_new_latest_blockhash()
+        .await
+        .unwrap();
+    process_instruction(
+        &context,
+        create_mint(
+            &spl_token_wrap::id(),
+            &wrapped_mint,
+            unwrapped_token.get_address(),
+            &wrapped_token_program_id,
+            &payer.pubkey(),
+            true,
+            &extensions,
+            Some(&authority.pubkey()),
+            None,
+        ),
+        &[&authority],
+    )
+    .await
+    .unwrap();
+
+    // different extensions fail, even without any
+    for (other_extensions, unwrapped_mint_authority, signers) in [
+        (
+            WrappedMintExtensions {
+                confidential_transfers: true.into(),
+                ..Default::default()
+            },
+            Some(authority.pubkey()),
+            vec![&authority],
+        ),
+        (WrappedMintExtensions::default(), None, vec![]),
+    ] {
+        let error = process_instruction(
+            &context,
+            create_mint(
+                &spl_token_wrap::id(),
+                &wrapped_mint,
+                unwrapped_token.get_address(),
+                &wrapped_token_program_id,
+                &payer.pubkey(),
+                true,
+                &other_extensions,
+                unwrapped_mint_authority.as_ref(),
+                None,
+            ),
+            &signers,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            error,
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenWrapError::ExtensionsMismatch as u32)
+            )
+        );
+    }
+}
@@ -0,0 +1,849 @@
+// Mark this test as SBF-only due to current `ProgramTest` limitations when CPIing into the system program
+#![cfg(feature = "test-sbf")]
+
+mod common;
+
+use {
+    common::{process_instruction, setup},
+    solana_program_test::{
+        tokio::{self, sync::Mutex},
+        ProgramTestContext,
+    },
+    solana_sdk::{
+        instruction::InstructionError, program_option::COption, pubkey::Pubkey, signature::Signer,
+        signer::keypair::Keypair, transaction::TransactionError,
+    },
+    spl_token_client::{
+        client::{ProgramClient, SendTransaction, SimulateTransaction},
+        token::{ExtensionInitializationParams, Token},
+    },
+    spl_token_wrap::{
+        error::TokenWrapError,
+        get_wrapped_mint_address, get_wrapped_mint_authority, get_wrapped_mint_backpointer_address,
+        instruction::{create_mint, unwrap, wrap},
+        state::{Backpointer, WrappedMintExtensions},
+    },
+    std::sync::Arc,
+    test_case::test_case,
+};
+
+async fn setup_mint<T: SendTransaction + SimulateTransaction>(
+    program_id: &Pubkey,
+    mint_authority: &Pubkey,
+    decimals: u8,
+    payer: Arc<Keypair>,
+    client: Arc<dyn ProgramClient<T>>,
+) -> Token<T> {
+    let mint_account = Keypair::new();
+    let token = Token::new(
+        client,
+        program_id,
+        &mint_account.pubkey(),
+        Some(decimals),
+        payer,
+    );
+    token
+        .create_mint(mint_authority, None, vec![], &[&mint_account])
+        .await
+        .unwrap();
+    token
+}
+
+/// Create the wrapped mint for the given unwrapped token, and return the
+/// wrapped token
+async fn setup_wrapped_mint<T: SendTransaction + SimulateTransaction>(
+    context: &Mutex<ProgramTestContext>,
+    unwrapped_token: &Token<T>,
+    wrapped_token_program_id: &Pubkey,
+    decimals: u8,
+    payer: Arc<Keypair>,
+    client: Arc<dyn ProgramClient<T>>,
+) -> Token<T> {
+    let wrapped_mint =
+        get_wrapped_mint_address(unwrapped_token.get_address(), wrapped_token_program_id);
+    process_instruction(
+        context,
+        create_mint(
+            &spl_token_wrap::id(),
+            &wrapped_mint,
+            unwrapped_token.get_address(),
+            wrapped_token_program_id,
+            &payer.pubkey(),
+            false,
+            &WrappedMintExtensions::default(),
+            None,
+            None,
+        ),
+        &[],
+    )
+    .await
+    .unwrap();
+    Token::new(
+        client,
+        wrapped_token_program_id,
+        &wrapped_mint,
+        Some(decimals),
+        payer,
+    )
+}
+
+#[test_case(spl_token::id(), spl_token_2022::id() ; "token to token-2022")]
+#[test_case(spl_token_2022::id(), spl_token::id() ; "token-2022 to token")]
+#[test_case(spl_token_2022::id(), spl_token_2022::id() ; "token-2022 to token-2022")]
+#[tokio::test]
+async fn success_create_mint(unwrapped_token_program_id: Pubkey, wrapped_token_program_id: Pubkey) {
+    let (context, client, payer) = setup().await;
+    let mint_authority = Pubkey::new_unique();
+    let decimals = 5;
+
+    let unwrapped_token = setup_mint(
+        &unwrapped_token_program_id,
+        &mint_authority,
+        decimals,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+    let wrapped_token = setup_wrapped_mint(
+        &context,
+        &unwrapped_token,
+        &wrapped_token_program_id,
+        decimals,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+
+    let wrapped_mint = wrapped_token.get_mint_info().await.unwrap();
+    assert_eq!(wrapped_mint.base.decimals, decimals);
+    assert_eq!(wrapped_mint.base.supply, 0);
+    assert_eq!(
+        wrapped_mint.base.mint_authority,
+        COption::Some(get_wrapped_mint_authority(wrapped_token.get_address()))
+    );
+    assert_eq!(wrapped_mint.base.freeze_authority, COption::None);
+
+    let backpointer_address = get_wrapped_mint_backpointer_address(wrapped_token.get_address());
+    {
+        let mut context = context.lock().await;
+        let account = context
+            .banks_client
+            .get_account(backpointer_address)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(account.owner, spl_token_wrap::id());
+        let backpointer = bytemuck::from_bytes::<Backpointer>(&account.data);
+        assert_eq!(backpointer.unwrapped_mint, *unwrapped_token.get_address());
+    }
+
+    // idempotent creation succeeds
+    process_instruction(
+        &context,
+        create_mint(
+            &spl_token_wrap::id(),
+            wrapped_token.get_address(),
+            unwrapped_token.get_address(),
+            &wrapped_token_program_id,
+            &payer.pubkey(),
+            true,
+            &WrappedMintExtensions::default(),
+            None,
+            None,
+        ),
+        &[],
+    )
+    .await
+    .unwrap();
+
+    // non-idempotent creation fails
+    context
+        .lock()
+        .await
+        .get_new_latest_blockhash()
+        .await
+        .unwrap();
+    let error = process_instruction(
+        &context,
+        create_mint(
+            &spl_token_wrap::id(),
+            wrapped_token.get_address(),
+            unwrapped_token.get_address(),
+            &wrapped_token_program_id,
+            &payer.pubkey(),
+            false,
+            &WrappedMintExtensions::default(),
+            None,
+            None,
+        ),
+        &[],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(0, InstructionError::AccountAlreadyInitialized)
+    );
+}
+
+#[tokio::test]
+async fn fail_create_mint_wrong_address() {
+    let (context, client, payer) = setup().await;
+    let unwrapped_token = setup_mint(
+        &spl_token::id(),
+        &Pubkey::new_unique(),
+        0,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+
+    // address derived for the other token program
+    let wrapped_mint = get_wrapped_mint_address(unwrapped_token.get_address(), &spl_token::id());
+    let error = process_instruction(
+        &context,
+        create_mint(
+            &spl_token_wrap::id(),
+            &wrapped_mint,
+            unwrapped_token.get_address(),
+            &spl_token_2022::id(),
+            &payer.pubkey(),
+            false,
+            &WrappedMintExtensions::default(),
+            None,
+            None,
+        ),
+        &[],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TokenWrapError::WrappedMintMismatch as u32)
+        )
+    );
+}
+
+#[test_case(spl_token::id(), spl_token_2022::id() ; "token to token-2022")]
+#[test_case(spl_token_2022::id(), spl_token::id() ; "token-2022 to token")]
+#[test_case(spl_token_2022::id(), spl_token_2022::id() ; "token-2022 to token-2022")]
+#[tokio::test]
+async fn success_wrap_and_unwrap(
+    unwrapped_token_program_id: Pubkey,
+    wrapped_token_program_id: Pubkey,
+) {
+    let (context, client, payer) = setup().await;
+    let mint_authority = Keypair::new();
+    let decimals = 2;
+
+    let unwrapped_token = setup_mint(
+        &unwrapped_token_program_id,
+        &mint_authority.pubkey(),
+        decimals,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+    let wrapped_token = setup_wrapped_mint(
+        &context,
+        &unwrapped_token,
+        &wrapped_token_program_id,
+        decimals,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+    let wrapped_mint_authority = get_wrapped_mint_authority(wrapped_token.get_address());
+
+    unwrapped_token
+        .create_associated_token_account(&wrapped_mint_authority)
+        .await
+        .unwrap();
+    let escrow = unwrapped_token.get_associated_token_address(&wrapped_mint_authority);
+
+    let user = Keypair::new();
+    let amount = 1_000;
+    unwrapped_token
+        .create_associated_token_account(&user.pubkey())
+        .await
+        .unwrap();
+    let unwrapped_account = unwrapped_token.get_associated_token_address(&user.pubkey());
+    unwrapped_token
+        .mint_to(
+            &unwrapped_account,
+            &mint_authority.pubkey(),
+            amount,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    wrapped_token
+        .create_associated_token_account(&user.pubkey())
+        .await
+        .unwrap();
+    let wrapped_account = wrapped_token.get_associated_token_address(&user.pubkey());
+
+    process_instruction(
+        &context,
+        wrap(
+            &spl_token_wrap::id(),
+            &unwrapped_account,
+            &escrow,
+            unwrapped_token.get_address(),
+            wrapped_token.get_address(),
+            &wrapped_account,
+            &unwrapped_token_program_id,
+            &wrapped_token_program_id,
+            &user.pubkey(),
+            &[],
+            amount,
+        ),
+        &[&user],
+    )
+    .await
+    .unwrap();
+
+    let unwrapped_account_info = unwrapped_token
+        .get_account_info(&unwrapped_account)
+        .await
+        .unwrap();
+    assert_eq!(unwrapped_account_info.base.amount, 0);
+    let escrow_info = unwrapped_token.get_account_info(&escrow).await.unwrap();
+    assert_eq!(escrow_info.base.amount, amount);
+    let wrapped_account_info = wrapped_token
+        .get_account_info(&wrapped_account)
+        .await
+        .unwrap();
+    assert_eq!(wrapped_account_info.base.amount, amount);
+    let wrapped_mint = wrapped_token.get_mint_info().await.unwrap();
+    assert_eq!(wrapped_mint.base.supply, amount);
+
+    let unwrap_amount = 400;
+    process_instruction(
+        &context,
+        unwrap(
+            &spl_token_wrap::id(),
+            &wrapped_account,
+            wrapped_token.get_address(),
+            &escrow,
+            &unwrapped_account,
+            unwrapped_token.get_address(),
+            &wrapped_token_program_id,
+            &unwrapped_token_program_id,
+            &user.pubkey(),
+            &[],
+            unwrap_amount,
+        ),
+        &[&user],
+    )
+    .await
+    .unwrap();
+
+    let unwrapped_account_info = unwrapped_token
+        .get_account_info(&unwrapped_account)
+        .await
+        .unwrap();
+    assert_eq!(unwrapped_account_info.base.amount, unwrap_amount);
+    let escrow_info = unwrapped_token.get_account_info(&escrow).await.unwrap();
+    assert_eq!(escrow_info.base.amount, amount - unwrap_amount);
+    let wrapped_account_info = wrapped_token
+        .get_account_info(&wrapped_account)
+        .await
+        .unwrap();
+    assert_eq!(wrapped_account_info.base.amount, amount - unwrap_amount);
+    let wrapped_mint = wrapped_token.get_mint_info().await.unwrap();
+    assert_eq!(wrapped_mint.base.supply, amount - unwrap_amount);
+}
+
+#[tokio::test]
+async fn success_wrap_and_unwrap_with_multisig() {
+    let (context, client, payer) = setup().await;
+    let unwrapped_token_program_id = spl_token::id();
+    let wrapped_token_program_id = spl_token_2022::id();
+    let mint_authority = Keypair::new();
+    let decimals = 0;
+
+    let unwrapped_token = setup_mint(
+        &unwrapped_token_program_id,
+        &mint_authority.pubkey(),
+        decimals,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+    let wrapped_token = setup_wrapped_mint(
+        &context,
+        &unwrapped_token,
+        &wrapped_token_program_id,
+        decimals,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+    let wrapped_mint_authority = get_wrapped_mint_authority(wrapped_token.get_address());
+    unwrapped_token
+        .create_associated_token_account(&wrapped_mint_authority)
+        .await
+        .unwrap();
+    let escrow = unwrapped_token.get_associated_token_address(&wrapped_mint_authority);
+
+    // 2 of 3 multisig owns the unwrapped tokens, and receives the wrapped tokens
+    let signers = [Keypair::new(), Keypair::new(), Keypair::new()];
+    let signer_pubkeys = signers.iter().map(|s| s.pubkey()).collect::<Vec<_>>();
+    let multisig = Keypair::new();
+    unwrapped_token
+        .create_multisig(&multisig, &signer_pubkeys.iter().collect::<Vec<_>>(), 2)
+        .await
+        .unwrap();
+    let multisig = multisig.pubkey();
+
+    let amount = 10;
+    unwrapped_token
+        .create_associated_token_account(&multisig)
+        .await
+        .unwrap();
+    let unwrapped_account = unwrapped_token.get_associated_token_address(&multisig);
+    unwrapped_token
+        .mint_to(
+            &unwrapped_account,
+            &mint_authority.pubkey(),
+            amount,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    wrapped_token
+        .create_associated_token_account(&multisig)
+        .await
+        .unwrap();
+    let wrapped_account = wrapped_token.get_associated_token_address(&multisig);
+
+    process_instruction(
+        &context,
+        wrap(
+            &spl_token_wrap::id(),
+            &unwrapped_account,
+            &escrow,
+            unwrapped_token.get_address(),
+            wrapped_token.get_address(),
+            &wrapped_account,
+            &unwrapped_token_program_id,
+            &wrapped_token_program_id,
+            &multisig,
+            &[&signer_pubkeys[0], &signer_pubkeys[2]],
+            amount,
+        ),
+        &[&signers[0], &signers[2]],
+    )
+    .await
+    .unwrap();
+    let wrapped_account_info = wrapped_token
+        .get_account_info(&wrapped_account)
+        .await
+        .unwrap();
+    assert_eq!(wrapped_account_info.base.amount, amount);
+}
+
+#[tokio::test]
+async fn success_wrap_and_unwrap_with_transfer_fee() {
+    let (context, client, payer) = setup().await;
+    let unwrapped_token_program_id = spl_token_2022::id();
+    let wrapped_token_program_id = spl_token_2022::id();
+    let mint_authority = Keypair::new();
+    let decimals = 2;
+
+    // 1% fee on every transfer of unwrapped tokens
+    let mint_account = Keypair::new();
+    let unwrapped_token = Token::new(
+        client.clone(),
+        &unwrapped_token_program_id,
+        &mint_account.pubkey(),
+        Some(decimals),
+        payer.clone(),
+    );
+    unwrapped_token
+        .create_mint(
+            &mint_authority.pubkey(),
+            None,
+            vec![ExtensionInitializationParams::TransferFeeConfig {
+                transfer_fee_config_authority: None,
+                withdraw_withheld_authority: None,
+                transfer_fee_basis_points: 100,
+                maximum_fee: u64::MAX,
+            }],
+            &[&mint_account],
+        )
+        .await
+        .unwrap();
+    let wrapped_token = setup_wrapped_mint(
+        &context,
+        &unwrapped_token,
+        &wrapped_token_program_id,
+        decimals,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+    let wrapped_mint_authority = get_wrapped_mint_authority(wrapped_token.get_address());
+
+    unwrapped_token
+        .create_associated_token_account(&wrapped_mint_authority)
+        .await
+        .unwrap();
+    let escrow = unwrapped_token.get_associated_token_address(&wrapped_mint_authority);
+
+    let user = Keypair::new();
+    let amount = 1_000;
+    unwrapped_token
+        .create_associated_token_account(&user.pubkey())
+        .await
+        .unwrap();
+    let unwrapped_account = unwrapped_token.get_associated_token_address(&user.pubkey());
+    unwrapped_token
+        .mint_to(
+            &unwrapped_account,
+            &mint_authority.pubkey(),
+            amount,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    wrapped_token
+        .create_associated_token_account(&user.pubkey())
+        .await
+        .unwrap();
+    let wrapped_account = wrapped_token.get_associated_token_address(&user.pubkey());
+
+    process_instruction(
+        &context,
+        wrap(
+            &spl_token_wrap::id(),
+            &unwrapped_account,
+            &escrow,
+            unwrapped_token.get_address(),
+            wrapped_token.get_address(),
+            &wrapped_account,
+            &unwrapped_token_program_id,
+            &wrapped_token_program_id,
+            &user.pubkey(),
+            &[],
+            amount,
+        ),
+        &[&user],
+    )
+    .await
+    .unwrap();
+
+    // only what the escrow received is wrapped
+    let wrapped_amount = 990;
+    let escrow_info = unwrapped_token.get_account_info(&escrow).await.unwrap();
+    assert_eq!(escrow_info.base.amount, wrapped_amount);
+    let wrapped_account_info = wrapped_token
+        .get_account_info(&wrapped_account)
+        .await
+        .unwrap();
+    assert_eq!(wrapped_account_info.base.amount, wrapped_amount);
+    let wrapped_mint = wrapped_token.get_mint_info().await.unwrap();
+    assert_eq!(wrapped_mint.base.supply, escrow_info.base.amount);
+
+    // the fee comes out of the unwrapped tokens received
+    let unwrap_amount = 400;
+    process_instruction(
+        &context,
+        unwrap(
+            &spl_token_wrap::id(),
+            &wrapped_account,
+            wrapped_token.get_address(),
+            &escrow,
+            &unwrapped_account,
+            unwrapped_token.get_address(),
+            &wrapped_token_program_id,
+            &unwrapped_token_program_id,
+            &user.pubkey(),
+            &[],
+            unwrap_amount,
+        ),
+        &[&user],
+    )
+    .await
+    .unwrap();
+
+    let unwrapped_account_info = unwrapped_token
+        .get_account_info(&unwrapped_account)
+        .await
+        .unwrap();
+    assert_eq!(unwrapped_account_info.base.amount, 396);
+    let escrow_info = unwrapped_token.get_account_info(&escrow).await.unwrap();
+    assert_eq!(escrow_info.base.amount, wrapped_amount - unwrap_amount);
+    let wrapped_mint = wrapped_token.get_mint_info().await.unwrap();
+    assert_eq!(wrapped_mint.base.supply, escrow_info.base.amount);
+}
+
+#[tokio::test]
+async fn fail_wrap_and_unwrap_with_transfer_hook() {
+    let (context, client, payer) = setup().await;
+    let unwrapped_token_program_id = spl_token_2022::id();
+    let wrapped_token_program_id = spl_token_2022::id();
+    let mint_authority = Keypair::new();
+    let decimals = 0;
+
+    let mint_account = Keypair::new();
+    let unwrapped_token = Token::new(
+        client.clone(),
+        &unwrapped_token_program_id,
+        &mint_account.pubkey(),
+        Some(decimals),
+        payer.clone(),
+    );
+    unwrapped_token
+        .create_mint(
+            &mint_authority.pubkey(),
+            None,
+            vec![ExtensionInitializationParams::TransferHook {
+                authority: None,
+                program_id: Some(Pubkey::new_unique()),
+            }],
+            &[&mint_account],
+        )
+        .await
+        .unwrap();
+    let wrapped_token = setup_wrapped_mint(
+        &context,
+        &unwrapped_token,
+        &wrapped_token_program_id,
+        decimals,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+    let wrapped_mint_authority = get_wrapped_mint_authority(wrapped_token.get_address());
+
+    unwrapped_token
+        .create_associated_token_account(&wrapped_mint_authority)
+        .await
+        .unwrap();
+    let escrow = unwrapped_token.get_associated_token_address(&wrapped_mint_authority);
+
+    let user = Keypair::new();
+    let amount = 10;
+    unwrapped_token
+        .create_associated_token_account(&user.pubkey())
+        .await
+        .unwrap();
+    let unwrapped_account = unwrapped_token.get_associated_token_address(&user.pubkey());
+    unwrapped_token
+        .mint_to(
+            &unwrapped_account,
+            &mint_authority.pubkey(),
+            amount,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    wrapped_token
+        .create_associated_token_account(&user.pubkey())
+        .await
+        .unwrap();
+    let wrapped_account = wrapped_token.get_associated_token_address(&user.pubkey());
+
+    let expected_error = TransactionError::InstructionError(
+        0,
+        InstructionError::Custom(TokenWrapError::UnwrappedTransferHookUnsupported as u32),
+    );
+    let error = process_instruction(
+        &context,
+        wrap(
+            &spl_token_wrap::id(),
+            &unwrapped_account,
+            &escrow,
+            unwrapped_token.get_address(),
+            wrapped_token.get_address(),
+            &wrapped_account,
+            &unwrapped_token_program_id,
+            &wrapped_token_program_id,
+            &user.pubkey(),
+            &[],
+            amount,
+        ),
+        &[&user],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error, expected_error);
+
+    let error = process_instruction(
+        &context,
+        unwrap(
+            &spl_token_wrap::id(),
+            &wrapped_account,
+            wrapped_token.get_address(),
+            &escrow,
+            &unwrapped_account,
+            unwrapped_token.get_address(),
+            &wrapped_token_program_id,
+            &unwrapped_token_program_id,
+            &user.pubkey(),
+            &[],
+            amount,
+        ),
+        &[&user],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(error, expected_error);
+}
+
+#[tokio::test]
+async fn fail_wrap() {
+    let (context, client, payer) = setup().await;
+    let unwrapped_token_program_id = spl_token_2022::id();
+    let wrapped_token_program_id = spl_token::id();
+    let mint_authority = Keypair::new();
+    let decimals = 0;
+
+    let unwrapped_token = setup_mint(
+        &unwrapped_token_program_id,
+        &mint_authority.pubkey(),
+        decimals,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+    let wrapped_token = setup_wrapped_mint(
+        &context,
+        &unwrapped_token,
+        &wrapped_token_program_id,
+        decimals,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+
+    let user = Keypair::new();
+    let amount = 10;
+    unwrapped_token
+        .create_associated_token_account(&user.pubkey())
+        .await
+        .unwrap();
+    let unwrapped_account = unwrapped_token.get_associated_token_address(&user.pubkey());
+    unwrapped_token
+        .mint_to(
+            &unwrapped_account,
+            &mint_authority.pubkey(),
+            amount,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    wrapped_token
+        .create_associated_token_account(&user.pubkey())
+        .await
+        .unwrap();
+    let wrapped_account = wrapped_token.get_associated_token_address(&user.pubkey());
+
+    // escrow not owned by the wrapped mint authority
+    let not_escrow = Keypair::new();
+    unwrapped_token
+        .create_auxiliary_token_account(&not_escrow, &user.pubkey())
+        .await
+        .unwrap();
+    let error = process_instruction(
+        &context,
+        wrap(
+            &spl_token_wrap::id(),
+            &unwrapped_account,
+            &not_escrow.pubkey(),
+            unwrapped_token.get_address(),
+            wrapped_token.get_address(),
+            &wrapped_account,
+            &unwrapped_token_program_id,
+            &wrapped_token_program_id,
+            &user.pubkey(),
+            &[],
+            amount,
+        ),
+        &[&user],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TokenWrapError::EscrowOwnerMismatch as u32)
+        )
+    );
+
+    let wrapped_mint_authority = get_wrapped_mint_authority(wrapped_token.get_address());
+    unwrapped_token
+        .create_associated_token_account(&wrapped_mint_authority)
+        .await
+        .unwrap();
+    let escrow = unwrapped_token.get_associated_token_address(&wrapped_mint_authority);
+
+    // zero amount
+    let error = process_instruction(
+        &context,
+        wrap(
+            &spl_token_wrap::id(),
+            &unwrapped_account,
+            &escrow,
+            unwrapped_token.get_address(),
+            wrapped_token.get_address(),
+            &wrapped_account,
+            &unwrapped_token_program_id,
+            &wrapped_token_program_id,
+            &user.pubkey(),
+            &[],
+            0,
+        ),
+        &[&user],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TokenWrapError::ZeroWrapAmount as u32)
+        )
+    );
+
+    // wrapped mint for another unwrapped mint
+    let other_token = setup_mint(
+        &unwrapped_token_program_id,
+        &mint_authority.pubkey(),
+        decimals,
+        payer.clone(),
+        client.clone(),
+    )
+    .await;
+    let error = process_instruction(
+        &context,
+        wrap(
+            &spl_token_wrap::id(),
+            &unwrapped_account,
+            &escrow,
+            other_token.get_address(),
+            wrapped_token.get_address(),
+            &wrapped_account,
+            &unwrapped_token_program_id,
+            &wrapped_token_program_id,
+            &user.pubkey(),
+            &[],
+            amount,
+        ),
+        &[&user],
+    )
+    .await
+    .unwrap_err();
+    assert_eq!(
+        error,
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TokenWrapError::WrappedMintMismatch as u32)
+        )
+    );
+}
@@ -70,10 +70,16 @@ fn main() {
             .join("associated-token-account")
             .join("program");
         rerun_if_changed(&spl_associated_token_account_dir);
+        let spl_transfer_hook_example_dir = cwd
+            .parent()
+            .expect("Unable to get parent directory of current working dir")
+            .join("transfer-hook-example");
+        rerun_if_changed(&spl_transfer_hook_example_dir);
 
         build_bpf(&spl_token_dir);
         build_bpf(&spl_token_2022_dir);
         build_bpf(&spl_associated_token_account_dir);
+        build_bpf(&spl_transfer_hook_example_dir);
     }
     println!("cargo:rerun-if-changed=build.rs");
 }
@@ -0,0 +1,277 @@
+//! Listing and rotation of every authority configured on a mint
+use {
+    crate::{config::Config, Error},
+    solana_sdk::{
+        account::Account as RawAccount, instruction::Instruction, message::Message,
+        packet::PACKET_DATA_SIZE, pubkey::Pubkey,
+    },
+    spl_token_2022::{
+        extension::{
+            confidential_transfer::ConfidentialTransferMint,
+            confidential_transfer_fee::ConfidentialTransferFeeConfig,
+            interest_bearing_mint::InterestBearingConfig, metadata_pointer::MetadataPointer,
+            mint_close_authority::MintCloseAuthority, permanent_delegate::PermanentDelegate,
+            transfer_fee::TransferFeeConfig, transfer_hook::TransferHook, BaseStateWithExtensions,
+            StateWithExtensionsOwned,
+        },
+        instruction::{set_authority, AuthorityType},
+        state::Mint,
+    },
+    spl_token_client::client::{ProgramClient, ProgramRpcClient, ProgramRpcClientSendTransaction},
+    spl_token_metadata_interface::{instruction::update_authority, state::TokenMetadata},
+    spl_type_length_value::state::{TlvState, TlvStateBorrowed},
+    std::sync::Arc,
+};
+
+/// Name used for the token-metadata update authority, which is changed through
+/// the token-metadata interface rather than `SetAuthority`
+const METADATA_UPDATE_AUTHORITY: &str = "metadata-update";
+
+/// How an authority is moved to a new key
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum MintAuthorityKind {
+    /// Changed with the token program's `SetAuthority`
+    Token(AuthorityType),
+    /// Changed with the token-metadata interface's `UpdateAuthority`, on
+    /// metadata stored at `metadata` and owned by `program_id`
+    TokenMetadata {
+        program_id: Pubkey,
+        metadata: Pubkey,
+    },
+}
+
+/// One authority slot on a mint, with its current holder
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MintAuthority {
+    pub(crate) name: &'static str,
+    pub(crate) kind: MintAuthorityKind,
+    pub(crate) authority: Option<Pubkey>,
+}
+
+impl MintAuthority {
+    fn token(name: &'static str, authority_type: AuthorityType, authority: Option<Pubkey>) -> Self {
+        Self {
+            name,
+            kind: MintAuthorityKind::Token(authority_type),
+            authority,
+        }
+    }
+
+    /// Build the instruction moving this authority from `current_authority`
+    /// to `new_authority`
+    pub(crate) fn rotate_instruction(
+        &self,
+        token_program_id: &Pubkey,
+        mint: &Pubkey,
+        current_authority: &Pubkey,
+        new_authority: &Pubkey,
+        multisig_signers: &[&Pubkey],
+    ) -> Result<Instruction, Error> {
+        match &self.kind {
+            MintAuthorityKind::Token(authority_type) => Ok(set_authority(
+                token_program_id,
+                mint,
+                Some(new_authority),
+                authority_type.clone(),
+                current_authority,
+                multisig_signers,
+            )?),
+            MintAuthorityKind::TokenMetadata {
+                program_id,
+                metadata,
+            } => Ok(update_authority(
+                program_id,
+                metadata,
+                current_authority,
+                Some(*new_authority).try_into()?,
+            )),
+        }
+    }
+}
+
+/// Fetch an account to look up authorities. Signing offline still needs the
+/// current authorities, so with `--sign-only` they are read from the cluster
+/// rather than through the offline client.
+async fn get_authority_account(
+    config: &Config<'_>,
+    address: Pubkey,
+) -> Result<Option<RawAccount>, Error> {
+    let program_client: Arc<dyn ProgramClient<ProgramRpcClientSendTransaction>> =
+        if config.sign_only {
+            Arc::new(ProgramRpcClient::new(
+                config.rpc_client.clone(),
+                ProgramRpcClientSendTransaction,
+            ))
+        } else {
+            config.program_client.clone()
+        };
+    Ok(program_client.get_account(address).await?)
+}
+
+/// Fetch and unpack a mint whose authorities are to be looked up
+pub(crate) async fn get_authority_mint(
+    config: &Config<'_>,
+    mint_address: &Pubkey,
+) -> Result<StateWithExtensionsOwned<Mint>, Error> {
+    let account = get_authority_account(config, *mint_address)
+        .await?
+        .ok_or_else(|| format!("Account {} not found", mint_address))?;
+    if account.owner != config.program_id {
+        return Err(format!(
+            "Account {} is owned by {}, not configured program id {}",
+            mint_address, account.owner, config.program_id
+        )
+        .into());
+    }
+    StateWithExtensionsOwned::<Mint>::unpack(account.data)
+        .map_err(|_| format!("Could not deserialize token mint {}", mint_address).into())
+}
+
+/// Collect every authority present on the mint and its extensions.
+///
+/// Authorities belonging to extensions the mint doesn't have are left out;
+/// authorities that have been disabled are included with no holder. If the
+/// metadata pointer points to an account holding token-metadata, its update
+/// authority is included as well.
+pub(crate) async fn get_mint_authorities(
+    config: &Config<'_>,
+    mint_address: &Pubkey,
+    mint: &StateWithExtensionsOwned<Mint>,
+) -> Result<Vec<MintAuthority>, Error> {
+    let mut authorities = vec![
+        MintAuthority::token(
+            "mint",
+            AuthorityType::MintTokens,
+            mint.base.mint_authority.into(),
+        ),
+        MintAuthority::token(
+            "freeze",
+            AuthorityType::FreezeAccount,
+            mint.base.freeze_authority.into(),
+        ),
+    ];
+
+    if let Ok(extension) = mint.get_extension::<MintCloseAuthority>() {
+        authorities.push(MintAuthority::token(
+            "close-mint",
+            AuthorityType::CloseMint,
+            extension.close_authority.into(),
+        ));
+    }
+    if let Ok(extension) = mint.get_extension::<TransferFeeConfig>() {
+        authorities.push(MintAuthority::token(
+            "transfer-fee-config",
+            AuthorityType::TransferFeeConfig,
+            extension.transfer_fee_config_authority.into(),
+        ));
+        authorities.push(MintAuthority::token(
+            "withheld-withdraw",
+            AuthorityType::WithheldWithdraw,
+            extension.withdraw_withheld_authority.into(),
+        ));
+    }
+    if let Ok(extension) = mint.get_extension::<InterestBearingConfig>() {
+        authorities.push(MintAuthority::token(
+            "interest-rate",
+            AuthorityType::InterestRate,
+            extension.rate_authority.into(),
+        ));
+    }
+    if let Ok(extension) = mint.get_extension::<PermanentDelegate>() {
+        authorities.push(MintAuthority::token(
+            "permanent-delegate",
+            AuthorityType::PermanentDelegate,
+            extension.delegate.into(),
+        ));
+    }
+    if let Ok(extension) = mint.get_extension::<ConfidentialTransferMint>() {
+        authorities.push(MintAuthority::token(
+            "confidential-transfer-mint",
+            AuthorityType::ConfidentialTransferMint,
+            extension.authority.into(),
+        ));
+    }
+    if let Ok(extension) = mint.get_extension::<ConfidentialTransferFeeConfig>() {
+        authorities.push(MintAuthority::token(
+            "confidential-transfer-fee",
+            AuthorityType::ConfidentialTransferFeeConfig,
+            extension.authority.into(),
+        ));
+    }
+    if let Ok(extension) = mint.get_extension::<TransferHook>() {
+        authorities.push(MintAuthority::token(
+            "transfer-hook-program-id",
+            AuthorityType::TransferHookProgramId,
+            extension.authority.into(),
+        ));
+    }
+    if let Ok(extension) = mint.get_extension::<MetadataPointer>() {
+        authorities.push(MintAuthority::token(
+            "metadata-pointer",
+            AuthorityType::MetadataPointer,
+            extension.authority.into(),
+        ));
+
+        let metadata_address: Option<Pubkey> = extension.metadata_address.into();
+        if let Some(metadata_address) = metadata_address {
+            let metadata = if metadata_address == *mint_address {
+                mint.get_variable_len_extension::<TokenMetadata>()
+                    .ok()
+                    .map(|metadata| (config.program_id, metadata))
+            } else {
+                let account = get_authority_account(config, metadata_address).await?;
+                account.and_then(|account| {
+                    let state = TlvStateBorrowed::unpack(&account.data).ok()?;
+                    let metadata = state.get_first_variable_len_value::<TokenMetadata>().ok()?;
+                    Some((account.owner, metadata))
+                })
+            };
+            if let Some((program_id, metadata)) = metadata {
+                authorities.push(MintAuthority {
+                    name: METADATA_UPDATE_AUTHORITY,
+                    kind: MintAuthorityKind::TokenMetadata {
+                        program_id,
+                        metadata: metadata_address,
+                    },
+                    authority: metadata.update_authority.into(),
+                });
+            }
+        }
+    }
+
+    Ok(authorities)
+}
+
+/// Split instructions into as few transactions as possible, keeping their
+/// order and staying under the packet size limit
+pub(crate) fn pack_instructions(
+    instructions: Vec<Instruction>,
+    payer: &Pubkey,
+) -> Result<Vec<Vec<Instruction>>, Error> {
+    let serialized_size = |instructions: &[Instruction]| {
+        let message = Message::new(instructions, Some(payer));
+        // signature count as a compact-u16 fits in a single byte here
+        1 + message.header.num_required_signatures as usize * 64 + message.serialize().len()
+    };
+
+    let mut batches: Vec<Vec<Instruction>> = vec![];
+    let mut batch = vec![];
+    for instruction in instructions {
+        batch.push(instruction);
+        if serialized_size(&batch) > PACKET_DATA_SIZE && batch.len() > 1 {
+            let instruction = batch.pop().unwrap();
+            batches.push(std::mem::replace(&mut batch, vec![instruction]));
+        }
+        if serialized_size(&batch) > PACKET_DATA_SIZE {
+            return Err(format!(
+                "Instruction for program {} does not fit in a transaction",
+                batch[0].program_id
+            )
+            .into());
+        }
+    }
+    if !batch.is_empty() {
+        batches.push(batch);
+    }
+    Ok(batches)
+}
@@ -1,7 +1,11 @@
 /// The `bench` subcommand
 use {
-    crate::{config::Config, owner_address_arg, CommandResult, Error},
-    clap::{value_t_or_exit, App, AppSettings, Arg, ArgMatches, SubCommand},
+    crate::{
+        config::Config,
+        output::{println_display, CliBenchResult, CliBenchStats, CliBenchSummary},
+        owner_address_arg, CommandResult, Error,
+    },
+    clap::{value_t_or_exit, values_t_or_exit, App, AppSettings, Arg, ArgMatches, SubCommand},
     solana_clap_utils::{
         input_parsers::pubkey_of_signer,
         input_validators::{is_amount, is_parsable, is_valid_pubkey},
@@ -12,16 +16,42 @@ use {
     },
     solana_remote_wallet::remote_wallet::RemoteWalletManager,
     solana_sdk::{
-        message::Message, native_token::Sol, program_pack::Pack, pubkey::Pubkey, signature::Signer,
+        hash::Hash,
+        instruction::{AccountMeta, Instruction},
+        message::Message,
+        native_token::Sol,
+        program_pack::Pack,
+        pubkey::Pubkey,
+        signature::{Keypair, Signature, Signer},
         system_instruction,
+        transaction::Transaction,
     },
     spl_associated_token_account::*,
+    spl_tlv_account_resolution::state::ExtraAccountMetas,
     spl_token_2022::{
-        extension::StateWithExtensions,
+        extension::{
+            confidential_transfer::{self, ConfidentialTransferAccount},
+            transfer_fee::{self, TransferFeeConfig},
+            BaseStateWithExtensions, ExtensionType, StateWithExtensions,
+        },
         instruction,
+        proof::ProofLocation,
+        solana_zk_token_sdk::encryption::{
+            auth_encryption::AeKey,
+            elgamal::{ElGamalKeypair, ElGamalPubkey},
+        },
         state::{Account, Mint},
     },
-    std::{sync::Arc, time::Instant},
+    spl_token_client::{
+        client::ProgramRpcClientSendTransaction,
+        token::{ExtensionInitializationParams, Token},
+    },
+    spl_transfer_hook_interface::{
+        get_extra_account_metas_address,
+        instruction::{execute, initialize_extra_account_metas, ExecuteInstruction},
+    },
+    std::{collections::HashSet, str::FromStr, sync::Arc, time::Instant},
+    strum_macros::{EnumString, IntoStaticStr},
 };
 
 pub(crate) trait BenchSubCommand {
@@ -160,11 +190,81 @@ impl BenchSubCommand for App<'_, '_> {
                                 .help("The recipient token account address [default: associated token account for --owner]")
                         )
                         .arg(owner_address_arg()),
+                )
+                .subcommand(
+                    SubCommand::with_name("run")
+                        .about("Run a mix of transfers against freshly created mints, \
+                            recording compute units and confirmation latency")
+                        .arg(
+                            Arg::with_name("mix")
+                                .long("mix")
+                                .validator(is_bench_mix)
+                                .value_name("KIND:COUNT")
+                                .takes_value(true)
+                                .multiple(true)
+                                .number_of_values(1)
+                                .required(true)
+                                .help(
+                                    "Number of operations of the given kind to run. \
+                                    Operations of different kinds are interleaved. \
+                                    Possible kinds are \"transfer\", \"transfer-with-fee\", \
+                                    \"confidential-transfer\" and \"transfer-with-hook\". \
+                                    This argument may be specified multiple times."
+                                ),
+                        )
+                        .arg(
+                            Arg::with_name("amount")
+                                .long("amount")
+                                .validator(is_amount)
+                                .value_name("TOKEN_AMOUNT")
+                                .takes_value(true)
+                                .default_value("1")
+                                .help("Amount to send in each operation, in tokens"),
+                        )
+                        .arg(
+                            Arg::with_name("transfer_hook_program_id")
+                                .long("transfer-hook-program-id")
+                                .validator(is_valid_pubkey)
+                                .value_name("TRANSFER_HOOK_PROGRAM_ID")
+                                .takes_value(true)
+                                .help(
+                                    "Transfer hook program used by \"transfer-with-hook\" \
+                                    operations. The program must accept an empty list of \
+                                    extra account metas."
+                                ),
+                        )
+                        .arg(owner_address_arg()),
                 ),
         )
     }
 }
 
+/// Kinds of operations that `bench run` knows how to measure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, EnumString, IntoStaticStr)]
+#[strum(serialize_all = "kebab-case")]
+enum BenchKind {
+    Transfer,
+    TransferWithFee,
+    ConfidentialTransfer,
+    TransferWithHook,
+}
+
+fn parse_bench_mix(string: &str) -> Result<(BenchKind, usize), String> {
+    let (kind, count) = string
+        .split_once(':')
+        .ok_or_else(|| format!("Invalid mix `{}`, expected <KIND>:<COUNT>", string))?;
+    let kind =
+        BenchKind::from_str(kind).map_err(|_| format!("Unknown operation kind `{}`", kind))?;
+    let count = count
+        .parse::<usize>()
+        .map_err(|err| format!("Invalid operation count `{}`: {}", count, err))?;
+    Ok((kind, count))
+}
+
+fn is_bench_mix(string: String) -> Result<(), String> {
+    parse_bench_mix(&string).map(|_| ())
+}
+
 pub(crate) async fn bench_process_command(
     matches: &ArgMatches<'_>,
     config: &Config<'_>,
@@ -227,6 +327,29 @@ pub(crate) async fn bench_process_command(
             )
             .await?;
         }
+        ("run", Some(arg_matches)) => {
+            let mix = values_t_or_exit!(arg_matches, "mix", String)
+                .iter()
+                .map(|mix| parse_bench_mix(mix).unwrap())
+                .collect::<Vec<_>>();
+            let ui_amount = value_t_or_exit!(arg_matches, "amount", f64);
+            let transfer_hook_program_id =
+                pubkey_of_signer(arg_matches, "transfer_hook_program_id", wallet_manager).unwrap();
+            let (owner_signer, owner) =
+                config.signer_or_default(arg_matches, "owner", wallet_manager);
+            signers.push(owner_signer.clone());
+
+            return command_run(
+                config,
+                signers,
+                owner_signer,
+                &owner,
+                &mix,
+                ui_amount,
+                transfer_hook_program_id,
+            )
+            .await;
+        }
         _ => unreachable!(),
     }
 
@@ -489,3 +612,511 @@ async fn send_messages(
 
     Ok(())
 }
+
+/// Decimals of the mints created by `bench run`
+const BENCH_DECIMALS: u8 = 6;
+/// Transfer fee of the mints created for "transfer-with-fee" operations
+const BENCH_TRANSFER_FEE_BASIS_POINTS: u16 = 100;
+
+struct BenchConfidentialKeys {
+    elgamal_keypair: ElGamalKeypair,
+    aes_key: AeKey,
+    destination_elgamal_pubkey: ElGamalPubkey,
+}
+
+/// A mint with a funded source account and a destination account, ready to
+/// run operations of a single kind
+struct BenchFixture {
+    kind: BenchKind,
+    token: Token<ProgramRpcClientSendTransaction>,
+    program_id: Pubkey,
+    source: Pubkey,
+    destination: Pubkey,
+    fee: u64,
+    transfer_hook_accounts: Vec<AccountMeta>,
+    confidential_keys: Option<BenchConfidentialKeys>,
+}
+
+#[derive(Default)]
+struct BenchSamples {
+    compute_units: Vec<u64>,
+    confirmation_latency_ms: Vec<u64>,
+    failed: usize,
+    last_error: Option<String>,
+}
+
+fn bench_stats(mut values: Vec<u64>) -> Option<CliBenchStats> {
+    if values.is_empty() {
+        return None;
+    }
+    values.sort_unstable();
+    let percentile = |p: usize| values[(values.len() - 1) * p / 100];
+    Some(CliBenchStats {
+        min: values[0],
+        max: values[values.len() - 1],
+        mean: values.iter().sum::<u64>() as f64 / values.len() as f64,
+        p50: percentile(50),
+        p90: percentile(90),
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn create_bench_fixture(
+    config: &Config<'_>,
+    program_id: &Pubkey,
+    kind: BenchKind,
+    owner_signer: &Arc<dyn Signer>,
+    owner: &Pubkey,
+    amount: u64,
+    count: usize,
+    transfer_hook_program_id: Option<Pubkey>,
+) -> Result<BenchFixture, Error> {
+    let mint_signer: Arc<dyn Signer> = Arc::new(Keypair::new());
+    let mint = mint_signer.pubkey();
+    let mint_signers = vec![mint_signer];
+    let token = Token::new(
+        config.program_client.clone(),
+        program_id,
+        &mint,
+        Some(BENCH_DECIMALS),
+        config.fee_payer()?,
+    );
+    let owner_signers = vec![owner_signer.clone()];
+
+    let extension_initialization_params = match kind {
+        BenchKind::Transfer => vec![],
+        BenchKind::TransferWithFee => vec![ExtensionInitializationParams::TransferFeeConfig {
+            transfer_fee_config_authority: None,
+            withdraw_withheld_authority: None,
+            transfer_fee_basis_points: BENCH_TRANSFER_FEE_BASIS_POINTS,
+            maximum_fee: u64::MAX,
+        }],
+        BenchKind::ConfidentialTransfer => {
+            vec![ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: None,
+                auto_approve_new_accounts: true,
+                auditor_elgamal_pubkey: None,
+            }]
+        }
+        BenchKind::TransferWithHook => vec![ExtensionInitializationParams::TransferHook {
+            authority: None,
+            program_id: transfer_hook_program_id,
+        }],
+    };
+    token
+        .create_mint(owner, None, extension_initialization_params, &mint_signers)
+        .await?;
+
+    // The destination owner never signs for transfers, but confidential
+    // transfers need it to configure the destination account
+    let destination_owner_signer: Arc<dyn Signer> = Arc::new(Keypair::new());
+    let destination_owner = destination_owner_signer.pubkey();
+    let destination_owner_signers = vec![destination_owner_signer];
+
+    token.create_associated_token_account(owner).await?;
+    token
+        .create_associated_token_account(&destination_owner)
+        .await?;
+    let source = token.get_associated_token_address(owner);
+    let destination = token.get_associated_token_address(&destination_owner);
+
+    let total_amount = amount
+        .checked_mul(count as u64)
+        .ok_or("Total benchmark amount overflows")?;
+    token
+        .mint_to(&source, owner, total_amount, &owner_signers)
+        .await?;
+
+    let mut fee = 0;
+    let mut transfer_hook_accounts = vec![];
+    let mut confidential_keys = None;
+    match kind {
+        BenchKind::Transfer => {}
+        BenchKind::TransferWithFee => {
+            let epoch = config.rpc_client.get_epoch_info().await?.epoch;
+            let mint_state = token.get_mint_info().await?;
+            fee = mint_state
+                .get_extension::<TransferFeeConfig>()?
+                .calculate_epoch_fee(epoch, amount)
+                .ok_or("Transfer fee calculation failed")?;
+        }
+        BenchKind::ConfidentialTransfer => {
+            let elgamal_keypair = ElGamalKeypair::new_rand();
+            let aes_key = AeKey::new_rand();
+            let destination_elgamal_keypair = ElGamalKeypair::new_rand();
+            let destination_aes_key = AeKey::new_rand();
+            let extension_types = [ExtensionType::ConfidentialTransferAccount];
+
+            token
+                .reallocate(&source, owner, &extension_types, &owner_signers)
+                .await?;
+            token
+                .confidential_transfer_configure_token_account(
+                    &source,
+                    owner,
+                    None,
+                    None,
+                    &elgamal_keypair,
+                    &aes_key,
+                    &owner_signers,
+                )
+                .await?;
+            token
+                .reallocate(
+                    &destination,
+                    &destination_owner,
+                    &extension_types,
+                    &destination_owner_signers,
+                )
+                .await?;
+            token
+                .confidential_transfer_configure_token_account(
+                    &destination,
+                    &destination_owner,
+                    None,
+                    None,
+                    &destination_elgamal_keypair,
+                    &destination_aes_key,
+                    &destination_owner_signers,
+                )
+                .await?;
+
+            token
+                .confidential_transfer_deposit(
+                    &source,
+                    owner,
+                    total_amount,
+                    BENCH_DECIMALS,
+                    &owner_signers,
+                )
+                .await?;
+            token
+                .confidential_transfer_apply_pending_balance(
+                    &source,
+                    owner,
+                    None,
+                    elgamal_keypair.secret(),
+                    &aes_key,
+                    &owner_signers,
+                )
+                .await?;
+
+            confidential_keys = Some(BenchConfidentialKeys {
+                elgamal_keypair,
+                aes_key,
+                destination_elgamal_pubkey: *destination_elgamal_keypair.pubkey(),
+            });
+        }
+        BenchKind::TransferWithHook => {
+            let transfer_hook_program_id = transfer_hook_program_id.unwrap();
+            let extra_account_metas_address =
+                get_extra_account_metas_address(&mint, &transfer_hook_program_id);
+
+            // The interface does not fund the validation account, so do it here
+            let required_lamports = config
+                .program_client
+                .get_minimum_balance_for_rent_exemption(ExtraAccountMetas::size_of(0)?)
+                .await?;
+            token
+                .process_ixs(
+                    &[
+                        system_instruction::transfer(
+                            &config.fee_payer()?.pubkey(),
+                            &extra_account_metas_address,
+                            required_lamports,
+                        ),
+                        initialize_extra_account_metas(
+                            &transfer_hook_program_id,
+                            &extra_account_metas_address,
+                            &mint,
+                            owner,
+                            &[],
+                        ),
+                    ],
+                    &owner_signers,
+                )
+                .await?;
+
+            // Resolve the extra accounts once, since every operation uses the
+            // same source, destination and amount
+            let validation_account = config
+                .program_client
+                .get_account(extra_account_metas_address)
+                .await?
+                .ok_or("Transfer hook validation account was not created")?;
+            let mut execute_instruction = execute(
+                &transfer_hook_program_id,
+                &source,
+                &mint,
+                &destination,
+                owner,
+                &extra_account_metas_address,
+                amount,
+            );
+            let initial_accounts_len = execute_instruction.accounts.len();
+            ExtraAccountMetas::add_to_instruction::<ExecuteInstruction>(
+                &mut execute_instruction,
+                &validation_account.data,
+            )?;
+            transfer_hook_accounts = execute_instruction.accounts.split_off(initial_accounts_len);
+            transfer_hook_accounts.push(AccountMeta::new_readonly(transfer_hook_program_id, false));
+            transfer_hook_accounts.push(AccountMeta::new_readonly(
+                extra_account_metas_address,
+                false,
+            ));
+        }
+    }
+
+    Ok(BenchFixture {
+        kind,
+        token,
+        program_id: *program_id,
+        source,
+        destination,
+        fee,
+        transfer_hook_accounts,
+        confidential_keys,
+    })
+}
+
+async fn bench_instructions(
+    fixture: &BenchFixture,
+    owner: &Pubkey,
+    amount: u64,
+) -> Result<Vec<Instruction>, Error> {
+    let mint = fixture.token.get_address();
+    match fixture.kind {
+        BenchKind::Transfer | BenchKind::TransferWithHook => {
+            let mut instruction = instruction::transfer_checked(
+                &fixture.program_id,
+                &fixture.source,
+                mint,
+                &fixture.destination,
+                owner,
+                &[],
+                amount,
+                BENCH_DECIMALS,
+            )?;
+            instruction
+                .accounts
+                .extend(fixture.transfer_hook_accounts.iter().cloned());
+            Ok(vec![instruction])
+        }
+        BenchKind::TransferWithFee => {
+            Ok(vec![transfer_fee::instruction::transfer_checked_with_fee(
+                &fixture.program_id,
+                &fixture.source,
+                mint,
+                &fixture.destination,
+                owner,
+                &[],
+                amount,
+                BENCH_DECIMALS,
+                fixture.fee,
+            )?])
+        }
+        BenchKind::ConfidentialTransfer => {
+            let keys = fixture
+                .confidential_keys
+                .as_ref()
+                .ok_or("Missing confidential transfer keys")?;
+            // The available balance changes with every transfer, so the proof
+            // must be generated against the latest account state
+            let account_info = fixture
+                .token
+                .get_account_info(&fixture.source)
+                .await?
+                .get_extension::<ConfidentialTransferAccount>()?
+                .transfer_account_info();
+            let proof_data = account_info.generate_transfer_proof_data(
+                amount,
+                &keys.elgamal_keypair,
+                &keys.aes_key,
+                &keys.destination_elgamal_pubkey,
+                None,
+            )?;
+            let new_decryptable_available_balance =
+                account_info.new_decryptable_available_balance(amount, &keys.aes_key)?;
+            Ok(confidential_transfer::instruction::transfer(
+                &fixture.program_id,
+                &fixture.source,
+                &fixture.destination,
+                mint,
+                new_decryptable_available_balance,
+                owner,
+                &[],
+                ProofLocation::InstructionOffset(1.try_into().unwrap(), &proof_data),
+            )?)
+        }
+    }
+}
+
+/// Simulates and then sends a single operation, returning the compute units
+/// consumed in simulation and the confirmation latency in milliseconds
+async fn run_bench_operation(
+    config: &Config<'_>,
+    signers: &[Arc<dyn Signer>],
+    instructions: &[Instruction],
+    sent_signatures: &mut HashSet<Signature>,
+    blockhash: &mut Hash,
+) -> Result<(u64, u64), Error> {
+    let rpc_client = &config.rpc_client;
+    let mut transaction =
+        Transaction::new_with_payer(instructions, Some(&config.fee_payer()?.pubkey()));
+    let signer_keys = transaction.message.signer_keys();
+    let signers = signers
+        .iter()
+        .filter(|signer| signer_keys.contains(&&signer.pubkey()))
+        .cloned()
+        .collect::<Vec<_>>();
+
+    // Identical operations would produce identical transactions within the
+    // same blockhash, so wait for a new one instead of getting a duplicate
+    transaction.try_sign(&signers, *blockhash)?;
+    if sent_signatures.contains(&transaction.signatures[0]) {
+        *blockhash = rpc_client.get_new_latest_blockhash(blockhash).await?;
+        transaction.try_sign(&signers, *blockhash)?;
+    }
+
+    let simulation = rpc_client.simulate_transaction(&transaction).await?.value;
+    if let Some(err) = simulation.err {
+        return Err(format!("Simulation failed: {}", err).into());
+    }
+    let compute_units = simulation.units_consumed.unwrap_or_default();
+
+    sent_signatures.insert(transaction.signatures[0]);
+    let start = Instant::now();
+    rpc_client
+        .send_and_confirm_transaction(&transaction)
+        .await?;
+    let confirmation_latency_ms = start.elapsed().as_millis() as u64;
+
+    Ok((compute_units, confirmation_latency_ms))
+}
+
+async fn command_run(
+    config: &Config<'_>,
+    signers: Vec<Arc<dyn Signer>>,
+    owner_signer: Arc<dyn Signer>,
+    owner: &Pubkey,
+    mix: &[(BenchKind, usize)],
+    ui_amount: f64,
+    transfer_hook_program_id: Option<Pubkey>,
+) -> CommandResult {
+    // Extensions only exist in Token-2022, so use it unless another program
+    // was explicitly requested
+    let program_id = if config.program_id == spl_token::id() {
+        spl_token_2022::id()
+    } else {
+        config.program_id
+    };
+    let amount = spl_token::ui_amount_to_amount(ui_amount, BENCH_DECIMALS);
+    if transfer_hook_program_id.is_none()
+        && mix
+            .iter()
+            .any(|(kind, _)| *kind == BenchKind::TransferWithHook)
+    {
+        return Err(
+            "Operations of kind `transfer-with-hook` require --transfer-hook-program-id".into(),
+        );
+    }
+
+    let mut fixtures = vec![];
+    for (kind, count) in mix {
+        println_display(
+            config,
+            format!(
+                "Creating mint and accounts for {} {} operations...",
+                count,
+                <&str>::from(kind)
+            ),
+        );
+        fixtures.push(
+            create_bench_fixture(
+                config,
+                &program_id,
+                *kind,
+                &owner_signer,
+                owner,
+                amount,
+                *count,
+                transfer_hook_program_id,
+            )
+            .await?,
+        );
+    }
+
+    // Interleave the operations so that every kind sees the same cluster load
+    let mut remaining = mix.iter().map(|(_, count)| *count).collect::<Vec<_>>();
+    let mut schedule = vec![];
+    while remaining.iter().any(|count| *count > 0) {
+        for (i, count) in remaining.iter_mut().enumerate() {
+            if *count > 0 {
+                *count -= 1;
+                schedule.push(i);
+            }
+        }
+    }
+
+    println_display(config, format!("Running {} operations...", schedule.len()));
+    let mut samples = fixtures
+        .iter()
+        .map(|_| BenchSamples::default())
+        .collect::<Vec<_>>();
+    let mut sent_signatures = HashSet::new();
+    let mut blockhash = config.rpc_client.get_latest_blockhash().await?;
+    let start = Instant::now();
+    for i in schedule {
+        let fixture = &fixtures[i];
+        let sample = &mut samples[i];
+        let result = match bench_instructions(fixture, owner, amount).await {
+            Ok(instructions) => {
+                run_bench_operation(
+                    config,
+                    &signers,
+                    &instructions,
+                    &mut sent_signatures,
+                    &mut blockhash,
+                )
+                .await
+            }
+            Err(err) => Err(err),
+        };
+        match result {
+            Ok((compute_units, confirmation_latency_ms)) => {
+                sample.compute_units.push(compute_units);
+                sample.confirmation_latency_ms.push(confirmation_latency_ms);
+            }
+            Err(err) => {
+                sample.failed += 1;
+                sample.last_error = Some(err.to_string());
+            }
+        }
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+
+    let succeeded = samples
+        .iter()
+        .map(|sample| sample.compute_units.len())
+        .sum::<usize>();
+    let results = fixtures
+        .iter()
+        .zip(samples)
+        .map(|(fixture, sample)| CliBenchResult {
+            kind: <&str>::from(fixture.kind).to_string(),
+            mint: fixture.token.get_address().to_string(),
+            succeeded: sample.compute_units.len(),
+            failed: sample.failed,
+            compute_units: bench_stats(sample.compute_units),
+            confirmation_latency_ms: bench_stats(sample.confirmation_latency_ms),
+            last_error: sample.last_error,
+        })
+        .collect();
+
+    let cli_output = CliBenchSummary {
+        elapsed_seconds: elapsed,
+        transactions_per_second: succeeded as f64 / elapsed,
+        results,
+    };
+    Ok(config.output_format.formatted_string(&cli_output))
+}
@@ -0,0 +1,507 @@
+//! Decoding of token, associated token account and memo instructions into
+//! named fields and accounts
+use {
+    crate::output::{CliDecodedAccount, CliDecodedInstruction},
+    borsh::BorshDeserialize,
+    serde_json::{json, Value},
+    solana_sdk::{program_error::ProgramError, pubkey::Pubkey},
+    spl_associated_token_account::instruction::AssociatedTokenAccountInstruction,
+    spl_token_2022::{
+        extension::{
+            confidential_transfer::instruction::ConfidentialTransferInstruction,
+            confidential_transfer_fee::instruction::ConfidentialTransferFeeInstruction,
+            cpi_guard::instruction::CpiGuardInstruction,
+            default_account_state::instruction as default_account_state_instruction,
+            interest_bearing_mint::{
+                instruction::{
+                    InitializeInstructionData as InterestBearingMintInitializeData,
+                    InterestBearingMintInstruction,
+                },
+                BasisPoints,
+            },
+            memo_transfer::instruction::RequiredMemoTransfersInstruction,
+            metadata_pointer::instruction::{
+                InitializeInstructionData as MetadataPointerInitializeData,
+                MetadataPointerInstruction, UpdateInstructionData as MetadataPointerUpdateData,
+            },
+            transfer_fee::instruction::TransferFeeInstruction,
+            transfer_hook::instruction::{
+                EventsInstructionData as TransferHookEventsData,
+                InitializeInstructionData as TransferHookInitializeData, TransferHookInstruction,
+                UpdateInstructionData as TransferHookUpdateData,
+            },
+            ExtensionType,
+        },
+        instruction::{decode_instruction_data, decode_instruction_type, TokenInstruction},
+    },
+    std::fmt::Debug,
+};
+
+/// Name given to accounts beyond the ones an instruction names, like
+/// multisig signers or transfer hook accounts
+const ADDITIONAL_ACCOUNT: &str = "additional account";
+
+/// An instruction decoded into its type, fields and the names of its accounts
+struct DecodedInstruction {
+    program: &'static str,
+    instruction_type: String,
+    info: Option<Value>,
+    account_names: &'static [&'static str],
+    additional_account_name: &'static str,
+}
+
+impl DecodedInstruction {
+    fn new(
+        program: &'static str,
+        instruction_type: String,
+        info: Option<Value>,
+        account_names: &'static [&'static str],
+    ) -> Self {
+        Self {
+            program,
+            instruction_type,
+            info,
+            account_names,
+            additional_account_name: ADDITIONAL_ACCOUNT,
+        }
+    }
+}
+
+/// Decode an instruction of one of the supported programs. Accounts are named
+/// in place; instructions of other programs are returned as "Unknown".
+pub(crate) fn decode_instruction(
+    program_id: &Pubkey,
+    data: &[u8],
+    mut accounts: Vec<CliDecodedAccount>,
+) -> CliDecodedInstruction {
+    let decoded = if *program_id == spl_token_2022::id() {
+        decode_token_instruction("spl-token-2022", data)
+    } else if *program_id == spl_token::id() {
+        // Token-2022 is a superset of Token with the same layout, so only
+        // check that the instruction is valid for Token before decoding it
+        spl_token::instruction::TokenInstruction::unpack(data)
+            .and_then(|_| decode_token_instruction("spl-token", data))
+    } else if *program_id == spl_associated_token_account::id() {
+        decode_associated_token_account_instruction(data)
+    } else if *program_id == spl_memo::id() || *program_id == spl_memo::v1::id() {
+        decode_memo_instruction(data)
+    } else {
+        return CliDecodedInstruction {
+            program: "unknown".to_string(),
+            program_id: program_id.to_string(),
+            instruction_type: "Unknown".to_string(),
+            info: None,
+            accounts,
+            error: None,
+        };
+    };
+
+    match decoded {
+        Ok(decoded) => {
+            for (i, account) in accounts.iter_mut().enumerate() {
+                account.name = decoded
+                    .account_names
+                    .get(i)
+                    .unwrap_or(&decoded.additional_account_name)
+                    .to_string();
+            }
+            CliDecodedInstruction {
+                program: decoded.program.to_string(),
+                program_id: program_id.to_string(),
+                instruction_type: decoded.instruction_type,
+                info: decoded.info,
+                accounts,
+                error: None,
+            }
+        }
+        Err(err) => CliDecodedInstruction {
+            program: program_name(program_id).to_string(),
+            program_id: program_id.to_string(),
+            instruction_type: "Invalid".to_string(),
+            info: None,
+            accounts,
+            error: Some(err.to_string()),
+        },
+    }
+}
+
+fn program_name(program_id: &Pubkey) -> &'static str {
+    if *program_id == spl_token_2022::id() {
+        "spl-token-2022"
+    } else if *program_id == spl_token::id() {
+        "spl-token"
+    } else if *program_id == spl_associated_token_account::id() {
+        "spl-associated-token-account"
+    } else {
+        "spl-memo"
+    }
+}
+
+/// Split an externally tagged enum value into its variant name and fields
+fn split_variant(value: Value) -> (String, Option<Value>) {
+    match value {
+        Value::String(name) => (name, None),
+        Value::Object(map) if map.len() == 1 => {
+            let (name, fields) = map.into_iter().next().unwrap();
+            (name, Some(fields))
+        }
+        value => ("Unknown".to_string(), Some(value)),
+    }
+}
+
+fn extension_instruction_type<T: Debug>(extension: &str, instruction: T) -> String {
+    format!("{}::{:?}", extension, instruction)
+}
+
+fn decode_token_instruction(
+    program: &'static str,
+    data: &[u8],
+) -> Result<DecodedInstruction, ProgramError> {
+    let instruction = TokenInstruction::unpack(data)?;
+    let extension_data = &data[1..];
+    let value =
+        serde_json::to_value(&instruction).map_err(|_| ProgramError::InvalidInstructionData)?;
+    let (instruction_type, info) = split_variant(value);
+
+    let account_names: &'static [&'static str] = match &instruction {
+        TokenInstruction::InitializeMint { .. } => &["mint", "rent sysvar"],
+        TokenInstruction::InitializeAccount => &["account", "mint", "owner", "rent sysvar"],
+        TokenInstruction::InitializeMultisig { .. } => &["multisig", "rent sysvar"],
+        TokenInstruction::Transfer { .. } => &["source", "destination", "authority"],
+        TokenInstruction::Approve { .. } => &["source", "delegate", "owner"],
+        TokenInstruction::Revoke => &["source", "owner"],
+        TokenInstruction::SetAuthority { .. } => &["account", "current authority"],
+        TokenInstruction::MintTo { .. } | TokenInstruction::MintToChecked { .. } => {
+            &["mint", "destination", "mint authority"]
+        }
+        TokenInstruction::Burn { .. } | TokenInstruction::BurnChecked { .. } => {
+            &["account", "mint", "authority"]
+        }
+        TokenInstruction::CloseAccount => &["account", "destination", "authority"],
+        TokenInstruction::FreezeAccount | TokenInstruction::ThawAccount => {
+            &["account", "mint", "freeze authority"]
+        }
+        TokenInstruction::TransferChecked { .. } => &["source", "mint", "destination", "authority"],
+        TokenInstruction::ApproveChecked { .. } => &["source", "mint", "delegate", "owner"],
+        TokenInstruction::InitializeAccount2 { .. } => &["account", "mint", "rent sysvar"],
+        TokenInstruction::InitializeAccount3 { .. } => &["account", "mint"],
+        TokenInstruction::SyncNative | TokenInstruction::InitializeImmutableOwner => &["account"],
+        TokenInstruction::InitializeMultisig2 { .. } => &["multisig"],
+        TokenInstruction::InitializeMint2 { .. }
+        | TokenInstruction::GetAccountDataSize { .. }
+        | TokenInstruction::AmountToUiAmount { .. }
+        | TokenInstruction::UiAmountToAmount { .. }
+        | TokenInstruction::InitializeMintCloseAuthority { .. }
+        | TokenInstruction::InitializeNonTransferableMint
+        | TokenInstruction::InitializePermanentDelegate { .. } => &["mint"],
+        TokenInstruction::Reallocate { .. } => &["account", "payer", "system program", "owner"],
+        TokenInstruction::CreateNativeMint => &["payer", "native mint", "system program"],
+        TokenInstruction::WithdrawExcessLamports => &["source", "destination", "authority"],
+        TokenInstruction::TransferFeeExtension(transfer_fee_instruction) => {
+            return Ok(decode_transfer_fee_instruction(
+                program,
+                transfer_fee_instruction,
+                info,
+            ));
+        }
+        TokenInstruction::ConfidentialTransferExtension => {
+            // Confidential instructions carry ciphertexts and proof offsets,
+            // so only name them
+            let instruction_type = extension_instruction_type(
+                "ConfidentialTransferExtension",
+                decode_instruction_type::<ConfidentialTransferInstruction>(extension_data)?,
+            );
+            return Ok(DecodedInstruction::new(
+                program,
+                instruction_type,
+                None,
+                &[],
+            ));
+        }
+        TokenInstruction::ConfidentialTransferFeeExtension => {
+            let instruction_type = extension_instruction_type(
+                "ConfidentialTransferFeeExtension",
+                decode_instruction_type::<ConfidentialTransferFeeInstruction>(extension_data)?,
+            );
+            return Ok(DecodedInstruction::new(
+                program,
+                instruction_type,
+                None,
+                &[],
+            ));
+        }
+        TokenInstruction::DefaultAccountStateExtension => {
+            let (default_account_state_type, state) =
+                default_account_state_instruction::decode_instruction(extension_data)?;
+            return Ok(DecodedInstruction::new(
+                program,
+                extension_instruction_type(
+                    "DefaultAccountStateExtension",
+                    default_account_state_type,
+                ),
+                Some(json!({ "state": format!("{:?}", state) })),
+                &["mint", "freeze authority"],
+            ));
+        }
+        TokenInstruction::MemoTransferExtension => {
+            return Ok(DecodedInstruction::new(
+                program,
+                extension_instruction_type(
+                    "MemoTransferExtension",
+                    decode_instruction_type::<RequiredMemoTransfersInstruction>(extension_data)?,
+                ),
+                None,
+                &["account", "owner"],
+            ));
+        }
+        TokenInstruction::CpiGuardExtension => {
+            return Ok(DecodedInstruction::new(
+                program,
+                extension_instruction_type(
+                    "CpiGuardExtension",
+                    decode_instruction_type::<CpiGuardInstruction>(extension_data)?,
+                ),
+                None,
+                &["account", "owner"],
+            ));
+        }
+        TokenInstruction::InterestBearingMintExtension => {
+            let interest_bearing_instruction =
+                decode_instruction_type::<InterestBearingMintInstruction>(extension_data)?;
+            let info = match interest_bearing_instruction {
+                InterestBearingMintInstruction::Initialize => {
+                    let data = decode_instruction_data::<InterestBearingMintInitializeData>(
+                        extension_data,
+                    )?;
+                    json!({
+                        "rate_authority": optional_pubkey_string(data.rate_authority.into()),
+                        "rate": i16::from(data.rate),
+                    })
+                }
+                InterestBearingMintInstruction::UpdateRate => {
+                    let rate = decode_instruction_data::<BasisPoints>(extension_data)?;
+                    json!({ "rate": i16::from(*rate) })
+                }
+            };
+            return Ok(DecodedInstruction::new(
+                program,
+                extension_instruction_type(
+                    "InterestBearingMintExtension",
+                    interest_bearing_instruction,
+                ),
+                Some(info),
+                &["mint", "rate authority"],
+            ));
+        }
+        TokenInstruction::TransferHookExtension => {
+            let transfer_hook_instruction =
+                decode_instruction_type::<TransferHookInstruction>(extension_data)?;
+            let info = match transfer_hook_instruction {
+                TransferHookInstruction::Initialize => {
+                    let data =
+                        decode_instruction_data::<TransferHookInitializeData>(extension_data)?;
+                    json!({
+                        "authority": optional_pubkey_string(data.authority.into()),
+                        "program_id": optional_pubkey_string(data.program_id.into()),
+                    })
+                }
+                TransferHookInstruction::Update => {
+                    let data = decode_instruction_data::<TransferHookUpdateData>(extension_data)?;
+                    json!({ "program_id": optional_pubkey_string(data.program_id.into()) })
+                }
+                TransferHookInstruction::InitializeEvents
+                | TransferHookInstruction::UpdateEvents => {
+                    let data = decode_instruction_data::<TransferHookEventsData>(extension_data)?;
+                    json!({
+                        "hook_mint": bool::from(data.hook_mint),
+                        "hook_burn": bool::from(data.hook_burn),
+                    })
+                }
+            };
+            return Ok(DecodedInstruction::new(
+                program,
+                extension_instruction_type("TransferHookExtension", transfer_hook_instruction),
+                Some(info),
+                &["mint", "authority"],
+            ));
+        }
+        TokenInstruction::MetadataPointerExtension => {
+            let metadata_pointer_instruction =
+                decode_instruction_type::<MetadataPointerInstruction>(extension_data)?;
+            let info = match metadata_pointer_instruction {
+                MetadataPointerInstruction::Initialize => {
+                    let data =
+                        decode_instruction_data::<MetadataPointerInitializeData>(extension_data)?;
+                    json!({
+                        "authority": optional_pubkey_string(data.authority.into()),
+                        "metadata_address": optional_pubkey_string(data.metadata_address.into()),
+                    })
+                }
+                MetadataPointerInstruction::Update => {
+                    let data =
+                        decode_instruction_data::<MetadataPointerUpdateData>(extension_data)?;
+                    json!({
+                        "metadata_address": optional_pubkey_string(data.metadata_address.into()),
+                    })
+                }
+            };
+            return Ok(DecodedInstruction::new(
+                program,
+                extension_instruction_type(
+                    "MetadataPointerExtension",
+                    metadata_pointer_instruction,
+                ),
+                Some(info),
+                &["mint", "authority"],
+            ));
+        }
+    };
+
+    Ok(DecodedInstruction::new(
+        program,
+        instruction_type,
+        info,
+        account_names,
+    ))
+}
+
+fn optional_pubkey_string(pubkey: Option<Pubkey>) -> Option<String> {
+    pubkey.map(|pubkey| pubkey.to_string())
+}
+
+fn decode_transfer_fee_instruction(
+    program: &'static str,
+    transfer_fee_instruction: &TransferFeeInstruction,
+    info: Option<Value>,
+) -> DecodedInstruction {
+    let (instruction_type, info) = match info {
+        Some(info) => split_variant(info),
+        None => ("Unknown".to_string(), None),
+    };
+    let mut decoded = DecodedInstruction::new(
+        program,
+        format!("TransferFeeExtension::{}", instruction_type),
+        info,
+        &[],
+    );
+    match transfer_fee_instruction {
+        TransferFeeInstruction::InitializeTransferFeeConfig { .. } => {
+            decoded.account_names = &["mint"];
+        }
+        TransferFeeInstruction::TransferCheckedWithFee { .. } => {
+            decoded.account_names = &["source", "mint", "destination", "authority"];
+        }
+        TransferFeeInstruction::WithdrawWithheldTokensFromMint => {
+            decoded.account_names = &["mint", "destination", "withdraw withheld authority"];
+        }
+        TransferFeeInstruction::WithdrawWithheldTokensFromAccounts { .. } => {
+            decoded.account_names = &["mint", "destination", "withdraw withheld authority"];
+        }
+        TransferFeeInstruction::HarvestWithheldTokensToMint => {
+            decoded.account_names = &["mint"];
+            decoded.additional_account_name = "source";
+        }
+        TransferFeeInstruction::SetTransferFee { .. } => {
+            decoded.account_names = &["mint", "transfer fee config authority"];
+        }
+    }
+    decoded
+}
+
+fn decode_associated_token_account_instruction(
+    data: &[u8],
+) -> Result<DecodedInstruction, ProgramError> {
+    // An empty instruction is treated as `Create` for backwards compatibility
+    let instruction = if data.is_empty() {
+        AssociatedTokenAccountInstruction::Create
+    } else {
+        AssociatedTokenAccountInstruction::try_from_slice(data)
+            .map_err(|_| ProgramError::InvalidInstructionData)?
+    };
+    let account_names: &'static [&'static str] = match instruction {
+        AssociatedTokenAccountInstruction::Create
+        | AssociatedTokenAccountInstruction::CreateIdempotent
+        | AssociatedTokenAccountInstruction::CreateWithExtensions { .. } => &[
+            "funding account",
+            "associated token account",
+            "wallet",
+            "mint",
+            "system program",
+            "token program",
+        ],
+        AssociatedTokenAccountInstruction::RecoverNested => &[
+            "nested associated token account",
+            "nested mint",
+            "destination associated token account",
+            "owner associated token account",
+            "owner mint",
+            "wallet",
+            "token program",
+        ],
+        AssociatedTokenAccountInstruction::CreateIdempotentBatch => {
+            &["funding account", "system program", "token program"]
+        }
+        AssociatedTokenAccountInstruction::CloseAndRecoverRent => &[
+            "associated token account",
+            "mint",
+            "destination",
+            "wallet",
+            "token program",
+        ],
+        AssociatedTokenAccountInstruction::RecoverNestedPath => &[
+            "nested associated token account",
+            "nested mint",
+            "destination associated token account",
+            "wallet",
+            "token program",
+        ],
+    };
+    let info = match &instruction {
+        AssociatedTokenAccountInstruction::CreateWithExtensions { extension_types } => {
+            Some(json!({
+                "extensionTypes": extension_types
+                    .iter()
+                    .map(|extension_type| match ExtensionType::try_from(*extension_type) {
+                        Ok(extension_type) => format!("{:?}", extension_type),
+                        Err(_) => extension_type.to_string(),
+                    })
+                    .collect::<Vec<_>>(),
+            }))
+        }
+        _ => None,
+    };
+    let instruction_type = match &instruction {
+        AssociatedTokenAccountInstruction::CreateWithExtensions { .. } => {
+            "CreateWithExtensions".to_string()
+        }
+        _ => format!("{:?}", instruction),
+    };
+    let mut decoded = DecodedInstruction::new(
+        "spl-associated-token-account",
+        instruction_type,
+        info,
+        account_names,
+    );
+    match instruction {
+        AssociatedTokenAccountInstruction::CreateIdempotentBatch => {
+            decoded.additional_account_name = "wallet, mint or associated token account";
+        }
+        AssociatedTokenAccountInstruction::RecoverNestedPath => {
+            decoded.additional_account_name = "owner associated token account or mint";
+        }
+        _ => {}
+    }
+    Ok(decoded)
+}
+
+fn decode_memo_instruction(data: &[u8]) -> Result<DecodedInstruction, ProgramError> {
+    let memo = std::str::from_utf8(data).map_err(|_| ProgramError::InvalidInstructionData)?;
+    let mut decoded = DecodedInstruction::new(
+        "spl-memo",
+        "Memo".to_string(),
+        Some(json!({ "memo": memo })),
+        &[],
+    );
+    decoded.additional_account_name = "signer";
+    Ok(decoded)
+}
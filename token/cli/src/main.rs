@@ -1,4 +1,5 @@
 #![allow(clippy::integer_arithmetic)]
+use base64::{prelude::BASE64_STANDARD, Engine};
 use clap::{
     crate_description, crate_name, crate_version, value_t, value_t_or_exit, App, AppSettings, Arg,
     ArgMatches, SubCommand,
@@ -25,17 +26,23 @@ use solana_cli_output::{
     return_signers_data, CliSignOnlyData, CliSignature, OutputFormat, QuietDisplay,
     ReturnSignersConfig, VerboseDisplay,
 };
-use solana_client::rpc_request::TokenAccountsFilter;
+use solana_client::{rpc_config::RpcTransactionConfig, rpc_request::TokenAccountsFilter};
 use solana_remote_wallet::remote_wallet::RemoteWalletManager;
 use solana_sdk::{
     instruction::AccountMeta,
     native_token::*,
     program_option::COption,
     pubkey::Pubkey,
-    signature::{Keypair, Signer},
-    system_program,
+    signature::{Keypair, Signature, Signer},
+    system_instruction, system_program,
+    transaction::VersionedTransaction,
+};
+use solana_transaction_status::{
+    option_serializer::OptionSerializer, EncodedTransaction, TransactionBinaryEncoding,
+    UiTransactionEncoding,
 };
 use spl_associated_token_account::get_associated_token_address_with_program_id;
+use spl_tlv_account_resolution::{pod::PodAccountMeta, seeds::Seed, state::ExtraAccountMetas};
 use spl_token_2022::{
     extension::{
         confidential_transfer::ConfidentialTransferMint,
@@ -48,7 +55,7 @@ use spl_token_2022::{
         mint_close_authority::MintCloseAuthority,
         permanent_delegate::PermanentDelegate,
         transfer_fee::{TransferFeeAmount, TransferFeeConfig},
-        transfer_hook::TransferHook,
+        transfer_hook::{self, TransferHook},
         BaseStateWithExtensions, ExtensionType, StateWithExtensionsOwned,
     },
     instruction::*,
@@ -58,6 +65,14 @@ use spl_token_client::{
     client::{ProgramRpcClientSendTransaction, RpcClientResponse},
     token::{ExtensionInitializationParams, Token},
 };
+use spl_transfer_hook_interface::{
+    get_extra_account_metas_address,
+    instruction::{
+        execute, initialize_extra_account_metas, update_extra_account_metas_with_pod_account_metas,
+        ExecuteInstruction,
+    },
+};
+use spl_type_length_value::state::TlvStateBorrowed;
 use std::{collections::HashMap, fmt, fmt::Display, process::exit, str::FromStr, sync::Arc};
 use strum_macros::{EnumString, IntoStaticStr};
 
@@ -73,6 +88,15 @@ use sort::{sort_and_parse_token_accounts, AccountFilter};
 mod bench;
 use bench::*;
 
+mod decode;
+use decode::decode_instruction;
+
+mod mint_spec;
+use mint_spec::{MintSpec, MintSpecParams};
+
+mod authorities;
+use authorities::{get_authority_mint, get_mint_authorities, pack_instructions, MintAuthorityKind};
+
 pub const OWNER_ADDRESS_ARG: ArgConstant<'static> = ArgConstant {
     name: "owner",
     long: "owner",
@@ -121,6 +145,8 @@ pub enum CommandName {
     CreateAccount,
     CreateMultisig,
     Authorize,
+    Authorities,
+    RotateAuthorities,
     SetInterestRate,
     Transfer,
     Burn,
@@ -138,6 +164,7 @@ pub enum CommandName {
     AccountInfo,
     MultisigInfo,
     Display,
+    Decode,
     Gc,
     SyncNative,
     EnableRequiredTransferMemos,
@@ -150,6 +177,10 @@ pub enum CommandName {
     SetTransferFee,
     WithdrawExcessLamports,
     SetTransferHookProgram,
+    CreateTransferHookMetas,
+    UpdateTransferHookMetas,
+    DisplayTransferHookMetas,
+    ResolveTransferHookAccounts,
 }
 impl fmt::Display for CommandName {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -203,6 +234,86 @@ where
     }
 }
 
+impl AccountMetaRole {
+    fn from_flags(is_signer: bool, is_writable: bool) -> Self {
+        match (is_signer, is_writable) {
+            (false, false) => AccountMetaRole::Readonly,
+            (false, true) => AccountMetaRole::Writable,
+            (true, false) => AccountMetaRole::ReadonlySigner,
+            (true, true) => AccountMetaRole::WritableSigner,
+        }
+    }
+    fn is_signer(&self) -> bool {
+        matches!(
+            self,
+            AccountMetaRole::ReadonlySigner | AccountMetaRole::WritableSigner
+        )
+    }
+    fn is_writable(&self) -> bool {
+        matches!(
+            self,
+            AccountMetaRole::Writable | AccountMetaRole::WritableSigner
+        )
+    }
+}
+fn parse_seed(string: &str) -> Result<Seed, String> {
+    match string.split_once('=') {
+        Some(("literal", literal)) => Ok(Seed::Literal {
+            bytes: literal.as_bytes().to_vec(),
+        }),
+        Some(("account-key", index)) => Ok(Seed::AccountKey {
+            index: index.parse::<u8>().map_err(|e| format!("{e}"))?,
+        }),
+        Some(("instruction-data", range)) => match range.split_once('/') {
+            Some((index, length)) => Ok(Seed::InstructionData {
+                index: index.parse::<u8>().map_err(|e| format!("{e}"))?,
+                length: length.parse::<u8>().map_err(|e| format!("{e}"))?,
+            }),
+            None => Err(format!(
+                "Instruction data seed must be present as instruction-data=<INDEX>/<LENGTH>, got {string}"
+            )),
+        },
+        _ => Err(format!(
+            "Seed must be one of literal=<STRING>, account-key=<INDEX>, or \
+            instruction-data=<INDEX>/<LENGTH>, got {string}"
+        )),
+    }
+}
+fn format_seed(seed: &Seed) -> String {
+    match seed {
+        Seed::Uninitialized => "uninitialized".to_string(),
+        Seed::Literal { bytes } => match std::str::from_utf8(bytes) {
+            Ok(literal) => format!("literal={literal}"),
+            Err(_) => format!("literal={bytes:?}"),
+        },
+        Seed::InstructionData { index, length } => format!("instruction-data={index}/{length}"),
+        Seed::AccountKey { index } => format!("account-key={index}"),
+    }
+}
+fn parse_transfer_hook_seeds<T>(string: T) -> Result<PodAccountMeta, String>
+where
+    T: AsRef<str> + Display,
+{
+    match string.as_ref().rsplit_once(':') {
+        Some((seeds, role)) => {
+            let seeds = seeds
+                .split(',')
+                .map(parse_seed)
+                .collect::<Result<Vec<_>, _>>()?;
+            let role = AccountMetaRole::from_str(role).map_err(|e| format!("{e}"))?;
+            PodAccountMeta::new_with_seeds(&seeds, role.is_signer(), role.is_writable())
+                .map_err(|e| format!("{e}"))
+        }
+        None => Err("Transfer hook seeds must be present as <SEED>[,<SEED>...]:<ROLE>".to_string()),
+    }
+}
+fn validate_transfer_hook_seeds<T>(string: T) -> Result<(), String>
+where
+    T: AsRef<str> + Display,
+{
+    parse_transfer_hook_seeds(string).map(|_| ())
+}
+
 pub fn owner_address_arg<'a, 'b>() -> Arg<'a, 'b> {
     Arg::with_name(OWNER_ADDRESS_ARG.name)
         .long(OWNER_ADDRESS_ARG.long)
@@ -472,16 +583,6 @@ async fn command_create_token(
     transfer_hook_program_id: Option<Pubkey>,
     bulk_signers: Vec<Arc<dyn Signer>>,
 ) -> CommandResult {
-    println_display(
-        config,
-        format!(
-            "Creating token {} under program {}",
-            token_pubkey, config.program_id
-        ),
-    );
-
-    let token = token_client_from_config(config, &token_pubkey, Some(decimals))?;
-
     let freeze_authority = if enable_freeze { Some(authority) } else { None };
 
     let mut extensions = vec![];
@@ -541,10 +642,6 @@ async fn command_create_token(
         });
     }
 
-    if let Some(text) = memo {
-        token.with_memo(text, vec![config.default_signer()?.pubkey()]);
-    }
-
     if metadata_address.is_some() {
         extensions.push(ExtensionInitializationParams::MetadataPointer {
             authority: Some(authority),
@@ -552,9 +649,65 @@ async fn command_create_token(
         });
     }
 
+    command_create_token_with_params(
+        config,
+        token_pubkey,
+        MintSpecParams {
+            decimals,
+            mint_authority: authority,
+            freeze_authority,
+            extensions,
+        },
+        memo,
+        bulk_signers,
+    )
+    .await
+}
+
+async fn command_create_token_with_params(
+    config: &Config<'_>,
+    token_pubkey: Pubkey,
+    params: MintSpecParams,
+    memo: Option<String>,
+    bulk_signers: Vec<Arc<dyn Signer>>,
+) -> CommandResult {
+    let MintSpecParams {
+        decimals,
+        mint_authority,
+        freeze_authority,
+        extensions,
+    } = params;
+
+    let extension_types = extensions
+        .iter()
+        .map(|extension| extension.extension())
+        .collect::<Vec<_>>();
+    ExtensionType::check_for_invalid_mint_extension_combinations(&extension_types).map_err(
+        |err| {
+            format!(
+                "Invalid combination of extensions {:?}: {}",
+                extension_types, err
+            )
+        },
+    )?;
+
+    println_display(
+        config,
+        format!(
+            "Creating token {} under program {}",
+            token_pubkey, config.program_id
+        ),
+    );
+
+    let token = token_client_from_config(config, &token_pubkey, Some(decimals))?;
+
+    if let Some(text) = memo {
+        token.with_memo(text, vec![config.default_signer()?.pubkey()]);
+    }
+
     let res = token
         .create_mint(
-            &authority,
+            &mint_authority,
             freeze_authority.as_ref(),
             extensions,
             &bulk_signers,
@@ -698,6 +851,310 @@ async fn command_set_transfer_hook_program(
     })
 }
 
+async fn get_transfer_hook_program_id(
+    config: &Config<'_>,
+    token_pubkey: &Pubkey,
+) -> Result<Pubkey, Error> {
+    let mint_account = config.get_account_checked(token_pubkey).await?;
+    let mint_state = StateWithExtensionsOwned::<Mint>::unpack(mint_account.data)
+        .map_err(|_| format!("Could not deserialize token mint {}", token_pubkey))?;
+    transfer_hook::get_program_id(&mint_state).ok_or_else(|| {
+        format!(
+            "Mint {} does not have a transfer hook program",
+            token_pubkey
+        )
+        .into()
+    })
+}
+
+fn cli_extra_account_meta(pod_account_meta: &PodAccountMeta) -> Result<CliExtraAccountMeta, Error> {
+    let role: &str = AccountMetaRole::from_flags(
+        pod_account_meta.is_signer.into(),
+        pod_account_meta.is_writable.into(),
+    )
+    .into();
+    Ok(if pod_account_meta.discriminator == 0 {
+        CliExtraAccountMeta {
+            address: Some(AccountMeta::try_from(pod_account_meta)?.pubkey.to_string()),
+            seeds: None,
+            role: role.to_string(),
+        }
+    } else {
+        CliExtraAccountMeta {
+            address: None,
+            seeds: Some(
+                Seed::unpack_address_config(&pod_account_meta.address_config)?
+                    .iter()
+                    .map(format_seed)
+                    .collect(),
+            ),
+            role: role.to_string(),
+        }
+    })
+}
+
+async fn command_create_transfer_hook_metas(
+    config: &Config<'_>,
+    token_pubkey: Pubkey,
+    mint_authority: Pubkey,
+    transfer_hook_accounts: Vec<AccountMeta>,
+    transfer_hook_seeds: Vec<PodAccountMeta>,
+    bulk_signers: BulkSigners,
+) -> CommandResult {
+    let token = token_client_from_config(config, &token_pubkey, None)?;
+    let program_id = get_transfer_hook_program_id(config, &token_pubkey).await?;
+    let extra_account_metas_address = get_extra_account_metas_address(&token_pubkey, &program_id);
+
+    if config
+        .program_client
+        .get_account(extra_account_metas_address)
+        .await?
+        .is_some()
+    {
+        return Err(format!(
+            "Extra account metas for mint {} already exist at {}",
+            token_pubkey, extra_account_metas_address
+        )
+        .into());
+    }
+
+    // The interface does not fund the validation account, so do it here
+    let account_size =
+        ExtraAccountMetas::size_of(transfer_hook_accounts.len() + transfer_hook_seeds.len())?;
+    let required_lamports = config
+        .program_client
+        .get_minimum_balance_for_rent_exemption(account_size)
+        .await?;
+    if !config.sign_only {
+        check_fee_payer_balance(config, required_lamports).await?;
+    }
+
+    println_display(
+        config,
+        format!(
+            "Creating extra account metas for transfer hook program {} at {}",
+            program_id, extra_account_metas_address
+        ),
+    );
+
+    let mut instructions = vec![
+        system_instruction::transfer(
+            &config.fee_payer()?.pubkey(),
+            &extra_account_metas_address,
+            required_lamports,
+        ),
+        initialize_extra_account_metas(
+            &program_id,
+            &extra_account_metas_address,
+            &token_pubkey,
+            &mint_authority,
+            &transfer_hook_accounts,
+        ),
+    ];
+    // `InitializeExtraAccountMetas` only takes fixed accounts, so PDAs are
+    // written by replacing the whole list right after
+    if !transfer_hook_seeds.is_empty() {
+        instructions.push(update_extra_account_metas_with_pod_account_metas(
+            &program_id,
+            &extra_account_metas_address,
+            &token_pubkey,
+            &mint_authority,
+            &transfer_hook_accounts,
+            &transfer_hook_seeds,
+        ));
+    }
+    let res = token.process_ixs(&instructions, &bulk_signers).await?;
+
+    let tx_return = finish_tx(config, &res, false).await?;
+    Ok(match tx_return {
+        TransactionReturnData::CliSignature(signature) => {
+            config.output_format.formatted_string(&signature)
+        }
+        TransactionReturnData::CliSignOnlyData(sign_only_data) => {
+            config.output_format.formatted_string(&sign_only_data)
+        }
+    })
+}
+
+async fn command_update_transfer_hook_metas(
+    config: &Config<'_>,
+    token_pubkey: Pubkey,
+    mint_authority: Pubkey,
+    transfer_hook_accounts: Vec<AccountMeta>,
+    transfer_hook_seeds: Vec<PodAccountMeta>,
+    bulk_signers: BulkSigners,
+) -> CommandResult {
+    let token = token_client_from_config(config, &token_pubkey, None)?;
+    let program_id = get_transfer_hook_program_id(config, &token_pubkey).await?;
+    let extra_account_metas_address = get_extra_account_metas_address(&token_pubkey, &program_id);
+    let account = config
+        .program_client
+        .get_account(extra_account_metas_address)
+        .await?
+        .ok_or_else(|| {
+            format!(
+                "Extra account metas for mint {} not found at {}",
+                token_pubkey, extra_account_metas_address
+            )
+        })?;
+
+    // Only the `Execute` entry is replaced, entries for other events are kept
+    let previous_entry_size = {
+        let state = TlvStateBorrowed::unpack(&account.data)?;
+        let previous_extra_account_metas =
+            ExtraAccountMetas::unpack_with_tlv_state::<ExecuteInstruction>(&state)?;
+        ExtraAccountMetas::size_of(previous_extra_account_metas.data().len())?
+    };
+    let new_entry_size =
+        ExtraAccountMetas::size_of(transfer_hook_accounts.len() + transfer_hook_seeds.len())?;
+    let account_size = account
+        .data
+        .len()
+        .saturating_sub(previous_entry_size)
+        .saturating_add(new_entry_size);
+
+    // The interface does not fund the validation account, so top it up here
+    let required_lamports = config
+        .program_client
+        .get_minimum_balance_for_rent_exemption(account_size)
+        .await?
+        .saturating_sub(account.lamports);
+    if !config.sign_only && required_lamports > 0 {
+        check_fee_payer_balance(config, required_lamports).await?;
+    }
+
+    println_display(
+        config,
+        format!(
+            "Updating extra account metas for transfer hook program {} at {}",
+            program_id, extra_account_metas_address
+        ),
+    );
+
+    let mut instructions = vec![];
+    if required_lamports > 0 {
+        instructions.push(system_instruction::transfer(
+            &config.fee_payer()?.pubkey(),
+            &extra_account_metas_address,
+            required_lamports,
+        ));
+    }
+    instructions.push(update_extra_account_metas_with_pod_account_metas(
+        &program_id,
+        &extra_account_metas_address,
+        &token_pubkey,
+        &mint_authority,
+        &transfer_hook_accounts,
+        &transfer_hook_seeds,
+    ));
+    let res = token.process_ixs(&instructions, &bulk_signers).await?;
+
+    let tx_return = finish_tx(config, &res, false).await?;
+    Ok(match tx_return {
+        TransactionReturnData::CliSignature(signature) => {
+            config.output_format.formatted_string(&signature)
+        }
+        TransactionReturnData::CliSignOnlyData(sign_only_data) => {
+            config.output_format.formatted_string(&sign_only_data)
+        }
+    })
+}
+
+async fn command_display_transfer_hook_metas(
+    config: &Config<'_>,
+    token_pubkey: Pubkey,
+) -> CommandResult {
+    let program_id = get_transfer_hook_program_id(config, &token_pubkey).await?;
+    let extra_account_metas_address = get_extra_account_metas_address(&token_pubkey, &program_id);
+    let account = config
+        .program_client
+        .get_account(extra_account_metas_address)
+        .await?
+        .ok_or_else(|| {
+            format!(
+                "Extra account metas for mint {} not found at {}",
+                token_pubkey, extra_account_metas_address
+            )
+        })?;
+
+    let state = TlvStateBorrowed::unpack(&account.data)?;
+    let pod_account_metas = ExtraAccountMetas::unpack_with_tlv_state::<ExecuteInstruction>(&state)?;
+    let extra_account_metas = pod_account_metas
+        .data()
+        .iter()
+        .map(cli_extra_account_meta)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let cli_output = CliTransferHookExtraAccountMetas {
+        address: extra_account_metas_address.to_string(),
+        mint: token_pubkey.to_string(),
+        program_id: program_id.to_string(),
+        extra_account_metas,
+    };
+    Ok(config.output_format.formatted_string(&cli_output))
+}
+
+async fn command_resolve_transfer_hook_accounts(
+    config: &Config<'_>,
+    token_pubkey: Pubkey,
+    source: Pubkey,
+    destination: Pubkey,
+    authority: Pubkey,
+    ui_amount: f64,
+    mint_decimals: Option<u8>,
+) -> CommandResult {
+    let mint_info = config.get_mint_info(&token_pubkey, mint_decimals).await?;
+    let amount = spl_token::ui_amount_to_amount(ui_amount, mint_info.decimals);
+    let program_id = get_transfer_hook_program_id(config, &token_pubkey).await?;
+    let extra_account_metas_address = get_extra_account_metas_address(&token_pubkey, &program_id);
+    let account = config
+        .program_client
+        .get_account(extra_account_metas_address)
+        .await?
+        .ok_or_else(|| {
+            format!(
+                "Extra account metas for mint {} not found at {}",
+                token_pubkey, extra_account_metas_address
+            )
+        })?;
+
+    // Resolve against the `Execute` instruction that the token program will
+    // send to the hook program, since seeds refer to its accounts and data
+    let mut instruction = execute(
+        &program_id,
+        &source,
+        &token_pubkey,
+        &destination,
+        &authority,
+        &extra_account_metas_address,
+        amount,
+    );
+    let initial_accounts_len = instruction.accounts.len();
+    ExtraAccountMetas::add_to_instruction::<ExecuteInstruction>(&mut instruction, &account.data)?;
+
+    let state = TlvStateBorrowed::unpack(&account.data)?;
+    let pod_account_metas = ExtraAccountMetas::unpack_with_tlv_state::<ExecuteInstruction>(&state)?;
+    let extra_account_metas = instruction
+        .accounts
+        .iter()
+        .skip(initial_accounts_len)
+        .zip(pod_account_metas.data())
+        .map(|(meta, pod_account_meta)| {
+            let mut cli_meta = cli_extra_account_meta(pod_account_meta)?;
+            cli_meta.address = Some(meta.pubkey.to_string());
+            Ok(cli_meta)
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let cli_output = CliTransferHookExtraAccountMetas {
+        address: extra_account_metas_address.to_string(),
+        mint: token_pubkey.to_string(),
+        program_id: program_id.to_string(),
+        extra_account_metas,
+    };
+    Ok(config.output_format.formatted_string(&cli_output))
+}
+
 async fn command_set_transfer_fee(
     config: &Config<'_>,
     token_pubkey: Pubkey,
@@ -1133,6 +1590,107 @@ async fn command_authorize(
     })
 }
 
+async fn command_authorities(config: &Config<'_>, mint_address: Pubkey) -> CommandResult {
+    let mint_account = config.get_account_checked(&mint_address).await?;
+    let mint_state = StateWithExtensionsOwned::<Mint>::unpack(mint_account.data)
+        .map_err(|_| format!("Could not deserialize token mint {}", mint_address))?;
+
+    let authorities = get_mint_authorities(config, &mint_address, &mint_state)
+        .await?
+        .into_iter()
+        .map(|mint_authority| CliMintAuthority {
+            authority_type: mint_authority.name.to_string(),
+            authority: mint_authority.authority.map(|pubkey| pubkey.to_string()),
+        })
+        .collect();
+
+    Ok(config.output_format.formatted_string(&CliMintAuthorities {
+        address: mint_address.to_string(),
+        authorities,
+    }))
+}
+
+async fn command_rotate_authorities(
+    config: &Config<'_>,
+    mint_address: Pubkey,
+    authority: Pubkey,
+    new_authority: Pubkey,
+    bulk_signers: BulkSigners,
+) -> CommandResult {
+    let mint_state = get_authority_mint(config, &mint_address).await?;
+
+    let mut instructions = vec![];
+    let mut rotated = vec![];
+    let mut skipped = vec![];
+    for mint_authority in get_mint_authorities(config, &mint_address, &mint_state).await? {
+        let held_by_authority = mint_authority.authority == Some(authority);
+        // the token-metadata interface has no notion of multisig signers
+        let rotatable = match mint_authority.kind {
+            MintAuthorityKind::Token(_) => true,
+            MintAuthorityKind::TokenMetadata { .. } => config.multisigner_pubkeys.is_empty(),
+        };
+        if held_by_authority && rotatable {
+            instructions.push(mint_authority.rotate_instruction(
+                &config.program_id,
+                &mint_address,
+                &authority,
+                &new_authority,
+                &config.multisigner_pubkeys,
+            )?);
+            rotated.push(mint_authority.name.to_string());
+        } else if mint_authority.authority.is_some()
+            && mint_authority.authority != Some(new_authority)
+        {
+            skipped.push(CliMintAuthority {
+                authority_type: mint_authority.name.to_string(),
+                authority: mint_authority.authority.map(|pubkey| pubkey.to_string()),
+            });
+        }
+    }
+
+    let token = token_client_from_config(config, &mint_address, None)?;
+    let batches = pack_instructions(instructions, &config.fee_payer()?.pubkey())?;
+
+    println_display(
+        config,
+        format!(
+            "Rotating {} authorities on {} to {} in {} transaction(s)",
+            rotated.len(),
+            mint_address,
+            new_authority,
+            batches.len()
+        ),
+    );
+
+    let mut signatures = vec![];
+    let mut sign_only_results = vec![];
+    for batch in batches {
+        let res = token.process_ixs(&batch, &bulk_signers).await?;
+        match finish_tx(config, &res, false).await? {
+            TransactionReturnData::CliSignature(signature) => {
+                signatures.push(signature.signature);
+            }
+            TransactionReturnData::CliSignOnlyData(sign_only_data) => {
+                sign_only_results.push(config.output_format.formatted_string(&sign_only_data));
+            }
+        }
+    }
+    if config.sign_only {
+        return Ok(sign_only_results.join(""));
+    }
+
+    let cli_rotate_authorities = CliRotateAuthorities {
+        address: mint_address.to_string(),
+        new_authority: new_authority.to_string(),
+        rotated,
+        skipped,
+        signatures,
+    };
+    Ok(config
+        .output_format
+        .formatted_string(&cli_rotate_authorities))
+}
+
 #[allow(clippy::too_many_arguments)]
 async fn command_transfer(
     config: &Config<'_>,
@@ -2060,6 +2618,104 @@ async fn command_display(config: &Config<'_>, address: Pubkey) -> CommandResult
     }
 }
 
+async fn command_export_mint_spec(
+    config: &Config<'_>,
+    address: Pubkey,
+    spec_file: &str,
+) -> CommandResult {
+    let mint_account = config.get_account_checked(&address).await?;
+    let mint_state = StateWithExtensionsOwned::<Mint>::unpack(mint_account.data)
+        .map_err(|_| format!("Could not deserialize token mint {}", address))?;
+
+    MintSpec::from_mint(&mint_state)?.to_file(spec_file)?;
+    Ok(format!("Wrote mint spec for {} to {}", address, spec_file))
+}
+
+async fn command_decode(config: &Config<'_>, transaction: &str) -> CommandResult {
+    let signature = Signature::from_str(transaction).ok();
+    let (transaction, loaded_addresses) = if let Some(signature) = signature {
+        let confirmed_transaction = config
+            .rpc_client
+            .get_transaction_with_config(
+                &signature,
+                RpcTransactionConfig {
+                    encoding: Some(UiTransactionEncoding::Base64),
+                    commitment: Some(config.rpc_client.commitment()),
+                    max_supported_transaction_version: Some(0),
+                },
+            )
+            .await?;
+
+        // Accounts loaded from lookup tables are only known from the meta
+        let loaded_addresses = match confirmed_transaction
+            .transaction
+            .meta
+            .map(|meta| meta.loaded_addresses)
+        {
+            Some(OptionSerializer::Some(loaded_addresses)) => loaded_addresses
+                .writable
+                .iter()
+                .chain(loaded_addresses.readonly.iter())
+                .map(|address| Pubkey::from_str(address))
+                .collect::<Result<Vec<_>, _>>()?,
+            _ => vec![],
+        };
+        let transaction = confirmed_transaction
+            .transaction
+            .transaction
+            .decode()
+            .ok_or_else(|| format!("Could not decode transaction {}", signature))?;
+        (transaction, loaded_addresses)
+    } else {
+        let transaction = [
+            TransactionBinaryEncoding::Base64,
+            TransactionBinaryEncoding::Base58,
+        ]
+        .into_iter()
+        .find_map(|encoding| EncodedTransaction::Binary(transaction.to_string(), encoding).decode())
+        .ok_or("Expected a transaction signature, or a base64 or base58 serialized transaction")?;
+        (transaction, vec![])
+    };
+
+    let message = &transaction.message;
+    let account_keys = message
+        .static_account_keys()
+        .iter()
+        .chain(loaded_addresses.iter())
+        .collect::<Vec<_>>();
+    let instructions = message
+        .instructions()
+        .iter()
+        .map(|instruction| {
+            let program_id = account_keys
+                .get(instruction.program_id_index as usize)
+                .ok_or("Invalid program id index")?;
+            let accounts = instruction
+                .accounts
+                .iter()
+                .map(|index| {
+                    let index = *index as usize;
+                    CliDecodedAccount {
+                        name: String::new(),
+                        address: account_keys
+                            .get(index)
+                            .map(|address| address.to_string())
+                            .unwrap_or_else(|| "unresolved lookup table address".to_string()),
+                        signer: message.is_signer(index),
+                        writable: message.is_maybe_writable(index),
+                    }
+                })
+                .collect();
+            Ok(decode_instruction(program_id, &instruction.data, accounts))
+        })
+        .collect::<Result<Vec<_>, Error>>()?;
+
+    let cli_output = CliDecodedTransaction {
+        signature: signature.map(|signature| signature.to_string()),
+        instructions,
+    };
+    Ok(config.output_format.formatted_string(&cli_output))
+}
 async fn command_gc(
     config: &Config<'_>,
     owner: Pubkey,
@@ -2846,6 +3502,30 @@ fn app<'a, 'b>(
                         .takes_value(true)
                         .help("Enable the mint authority to set the transfer hook program for this mint"),
                 )
+                .arg(
+                    Arg::with_name("spec")
+                        .long("spec")
+                        .value_name("SPEC_FILE")
+                        .takes_value(true)
+                        .conflicts_with_all(&[
+                            "enable_freeze",
+                            "enable_close",
+                            "interest_rate",
+                            "metadata_address",
+                            "enable_non_transferable",
+                            "default_account_state",
+                            "transfer_fee",
+                            "enable_permanent_delegate",
+                            "enable_confidential_transfers",
+                            "transfer_hook",
+                        ])
+                        .help(
+                            "Create the token from a mint spec file, which declares the \
+                            decimals, authorities and extensions of the mint. The file is \
+                            read as TOML if it has a \".toml\" extension, and as JSON \
+                            otherwise. The mint authority defaults to --mint-authority."
+                        ),
+                )
                 .nonce_args(true)
                 .arg(memo_arg())
         )
@@ -2917,20 +3597,180 @@ fn app<'a, 'b>(
                 )
         )
         .subcommand(
-            SubCommand::with_name(CommandName::CreateAccount.into())
-                .about("Create a new token account")
+            SubCommand::with_name(CommandName::CreateTransferHookMetas.into())
+                .about("Create the account holding the extra accounts required by a token's transfer hook program")
                 .arg(
                     Arg::with_name("token")
                         .validator(is_valid_pubkey)
                         .value_name("TOKEN_MINT_ADDRESS")
                         .takes_value(true)
-                        .index(1)
                         .required(true)
-                        .help("The token that the account will hold"),
+                        .index(1)
+                        .help("The token address with a transfer hook program"),
                 )
                 .arg(
-                    Arg::with_name("account_keypair")
-                        .value_name("ACCOUNT_KEYPAIR")
+                    Arg::with_name("transfer_hook_account")
+                        .long("transfer-hook-account")
+                        .validator(validate_transfer_hook_account)
+                        .value_name("PUBKEY:ROLE")
+                        .takes_value(true)
+                        .multiple(true)
+                        .min_values(0u64)
+                        .help("Additional pubkey(s) required by the transfer hook and their \
+                            role, in the format \"<PUBKEY>:<ROLE>\". The role must be \
+                            \"readonly\", \"writable\". \"readonly-signer\", or \"writable-signer\".")
+                )
+                .arg(
+                    Arg::with_name("transfer_hook_seeds")
+                        .long("transfer-hook-seeds")
+                        .validator(validate_transfer_hook_seeds)
+                        .value_name("SEEDS:ROLE")
+                        .takes_value(true)
+                        .multiple(true)
+                        .min_values(0u64)
+                        .help("Additional PDA(s) required by the transfer hook, derived from the \
+                            transfer hook program id, and their role, in the format \
+                            \"<SEED>[,<SEED>...]:<ROLE>\". Each seed must be \"literal=<STRING>\", \
+                            \"account-key=<INDEX>\", or \"instruction-data=<INDEX>/<LENGTH>\", \
+                            where indices refer to the accounts and data of the transfer hook's \
+                            execute instruction. Written after any --transfer-hook-account, \
+                            through UpdateExtraAccountMetas, which the program must implement.")
+                )
+                .arg(
+                    Arg::with_name("mint_authority")
+                        .long("mint-authority")
+                        .value_name("KEYPAIR")
+                        .validator(is_valid_signer)
+                        .takes_value(true)
+                        .help(
+                            "Specify the mint authority keypair. \
+                             This may be a keypair file or the ASK keyword. \
+                             Defaults to the client keypair."
+                        ),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name(CommandName::UpdateTransferHookMetas.into())
+                .about("Replace the extra accounts required by a token's transfer hook program")
+                .arg(
+                    Arg::with_name("token")
+                        .validator(is_valid_pubkey)
+                        .value_name("TOKEN_MINT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .index(1)
+                        .help("The token address with a transfer hook program"),
+                )
+                .arg(
+                    Arg::with_name("transfer_hook_account")
+                        .long("transfer-hook-account")
+                        .validator(validate_transfer_hook_account)
+                        .value_name("PUBKEY:ROLE")
+                        .takes_value(true)
+                        .multiple(true)
+                        .min_values(0u64)
+                        .help("Additional pubkey(s) required by the transfer hook and their \
+                            role, in the format \"<PUBKEY>:<ROLE>\". The role must be \
+                            \"readonly\", \"writable\". \"readonly-signer\", or \"writable-signer\".")
+                )
+                .arg(
+                    Arg::with_name("transfer_hook_seeds")
+                        .long("transfer-hook-seeds")
+                        .validator(validate_transfer_hook_seeds)
+                        .value_name("SEEDS:ROLE")
+                        .takes_value(true)
+                        .multiple(true)
+                        .min_values(0u64)
+                        .help("Additional PDA(s) required by the transfer hook, derived from the \
+                            transfer hook program id, and their role, in the format \
+                            \"<SEED>[,<SEED>...]:<ROLE>\". Each seed must be \"literal=<STRING>\", \
+                            \"account-key=<INDEX>\", or \"instruction-data=<INDEX>/<LENGTH>\", \
+                            where indices refer to the accounts and data of the transfer hook's \
+                            execute instruction. Written after any --transfer-hook-account.")
+                )
+                .arg(
+                    Arg::with_name("mint_authority")
+                        .long("mint-authority")
+                        .value_name("KEYPAIR")
+                        .validator(is_valid_signer)
+                        .takes_value(true)
+                        .help(
+                            "Specify the mint authority keypair. \
+                             This may be a keypair file or the ASK keyword. \
+                             Defaults to the client keypair."
+                        ),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name(CommandName::DisplayTransferHookMetas.into())
+                .about("Query the extra accounts required by a token's transfer hook program")
+                .arg(
+                    Arg::with_name("token")
+                        .validator(is_valid_pubkey)
+                        .value_name("TOKEN_MINT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .index(1)
+                        .help("The token address with a transfer hook program"),
+                )
+        )
+        .subcommand(
+            SubCommand::with_name(CommandName::ResolveTransferHookAccounts.into())
+                .about("Resolve the extra accounts required by a token's transfer hook program for a transfer, without sending it")
+                .arg(
+                    Arg::with_name("token")
+                        .validator(is_valid_pubkey)
+                        .value_name("TOKEN_MINT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .index(1)
+                        .help("The token address with a transfer hook program"),
+                )
+                .arg(
+                    Arg::with_name("amount")
+                        .validator(is_amount)
+                        .value_name("TOKEN_AMOUNT")
+                        .takes_value(true)
+                        .required(true)
+                        .index(2)
+                        .help("Amount to transfer, in tokens"),
+                )
+                .arg(
+                    Arg::with_name("source")
+                        .validator(is_valid_pubkey)
+                        .value_name("SOURCE_TOKEN_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .index(3)
+                        .help("The source token account address"),
+                )
+                .arg(
+                    Arg::with_name("destination")
+                        .validator(is_valid_pubkey)
+                        .value_name("DESTINATION_TOKEN_ACCOUNT_ADDRESS")
+                        .takes_value(true)
+                        .required(true)
+                        .index(4)
+                        .help("The destination token account address"),
+                )
+                .arg(owner_address_arg())
+                .arg(mint_decimals_arg())
+        )
+        .subcommand(
+            SubCommand::with_name(CommandName::CreateAccount.into())
+                .about("Create a new token account")
+                .arg(
+                    Arg::with_name("token")
+                        .validator(is_valid_pubkey)
+                        .value_name("TOKEN_MINT_ADDRESS")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("The token that the account will hold"),
+                )
+                .arg(
+                    Arg::with_name("account_keypair")
+                        .value_name("ACCOUNT_KEYPAIR")
                         .validator(is_valid_signer)
                         .takes_value(true)
                         .index(2)
@@ -3055,6 +3895,56 @@ fn app<'a, 'b>(
                 .nonce_args(true)
                 .offline_args(),
         )
+        .subcommand(
+            SubCommand::with_name(CommandName::Authorities.into())
+                .about("List every authority on a token mint and its extensions")
+                .arg(
+                    Arg::with_name("address")
+                        .validator(is_valid_pubkey)
+                        .value_name("TOKEN_MINT_ADDRESS")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the token mint"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CommandName::RotateAuthorities.into())
+                .about("Move every authority held by one key on a token mint and its extensions to a new key")
+                .arg(
+                    Arg::with_name("address")
+                        .validator(is_valid_pubkey)
+                        .value_name("TOKEN_MINT_ADDRESS")
+                        .takes_value(true)
+                        .index(1)
+                        .required(true)
+                        .help("The address of the token mint"),
+                )
+                .arg(
+                    Arg::with_name("new_authority")
+                        .validator(is_valid_pubkey)
+                        .value_name("AUTHORITY_ADDRESS")
+                        .takes_value(true)
+                        .index(2)
+                        .required(true)
+                        .help("The address of the new authority"),
+                )
+                .arg(
+                    Arg::with_name("authority")
+                        .long("authority")
+                        .value_name("KEYPAIR")
+                        .validator(is_valid_signer)
+                        .takes_value(true)
+                        .help(
+                            "Specify the current authority keypair. \
+                             Only authorities held by this key are rotated. \
+                             Defaults to the client keypair."
+                        ),
+                )
+                .arg(multisig_signer_arg())
+                .nonce_args(true)
+                .offline_args(),
+        )
         .subcommand(
             SubCommand::with_name(CommandName::Transfer.into())
                 .about("Transfer tokens between accounts")
@@ -3691,6 +4581,30 @@ fn app<'a, 'b>(
                     .index(1)
                     .required(true)
                     .help("The address of the SPL Token mint, account, or multisig to query"),
+                )
+                .arg(
+                    Arg::with_name("export_spec")
+                    .long("export-spec")
+                    .value_name("SPEC_FILE")
+                    .takes_value(true)
+                    .help(
+                        "Write the configuration of the mint to a spec file usable with \
+                        `create-token --spec`, as TOML if the file has a \".toml\" \
+                        extension, and as JSON otherwise"
+                    ),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name(CommandName::Decode.into())
+                .about("Decode the token, associated token account and memo instructions of a transaction")
+                .arg(
+                    Arg::with_name("transaction")
+                    .value_name("SIGNATURE_OR_TRANSACTION")
+                    .takes_value(true)
+                    .index(1)
+                    .required(true)
+                    .help("The signature of a confirmed transaction, or a base64 or base58 \
+                        serialized transaction"),
                 ),
         )
         .subcommand(
@@ -4077,6 +4991,25 @@ async fn process_command<'a>(
                 .value_of("enable_confidential_transfers")
                 .map(|b| b == "auto");
 
+            if let Some(spec_file) = arg_matches.value_of("spec") {
+                let spec = MintSpec::from_file(spec_file)?;
+                if arg_matches.occurrences_of("decimals") > 0 && spec.decimals != decimals {
+                    return Err(format!(
+                        "Decimals {} was provided, but the mint spec declares {}",
+                        decimals, spec.decimals
+                    )
+                    .into());
+                }
+                return command_create_token_with_params(
+                    config,
+                    token,
+                    spec.into_params(mint_authority)?,
+                    memo,
+                    bulk_signers,
+                )
+                .await;
+            }
+
             command_create_token(
                 config,
                 decimals,
@@ -4134,6 +5067,93 @@ async fn process_command<'a>(
             )
             .await
         }
+        (CommandName::CreateTransferHookMetas, arg_matches) => {
+            let token_pubkey = pubkey_of_signer(arg_matches, "token", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let transfer_hook_accounts = arg_matches
+                .values_of("transfer_hook_account")
+                .unwrap_or_default()
+                .map(|s| parse_transfer_hook_account(s).unwrap())
+                .collect::<Vec<_>>();
+            let transfer_hook_seeds = arg_matches
+                .values_of("transfer_hook_seeds")
+                .unwrap_or_default()
+                .map(|s| parse_transfer_hook_seeds(s).unwrap())
+                .collect::<Vec<_>>();
+            let (mint_authority_signer, mint_authority) =
+                config.signer_or_default(arg_matches, "mint_authority", &mut wallet_manager);
+            let bulk_signers = vec![mint_authority_signer];
+
+            command_create_transfer_hook_metas(
+                config,
+                token_pubkey,
+                mint_authority,
+                transfer_hook_accounts,
+                transfer_hook_seeds,
+                bulk_signers,
+            )
+            .await
+        }
+        (CommandName::UpdateTransferHookMetas, arg_matches) => {
+            let token_pubkey = pubkey_of_signer(arg_matches, "token", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let transfer_hook_accounts = arg_matches
+                .values_of("transfer_hook_account")
+                .unwrap_or_default()
+                .map(|s| parse_transfer_hook_account(s).unwrap())
+                .collect::<Vec<_>>();
+            let transfer_hook_seeds = arg_matches
+                .values_of("transfer_hook_seeds")
+                .unwrap_or_default()
+                .map(|s| parse_transfer_hook_seeds(s).unwrap())
+                .collect::<Vec<_>>();
+            let (mint_authority_signer, mint_authority) =
+                config.signer_or_default(arg_matches, "mint_authority", &mut wallet_manager);
+            let bulk_signers = vec![mint_authority_signer];
+
+            command_update_transfer_hook_metas(
+                config,
+                token_pubkey,
+                mint_authority,
+                transfer_hook_accounts,
+                transfer_hook_seeds,
+                bulk_signers,
+            )
+            .await
+        }
+        (CommandName::DisplayTransferHookMetas, arg_matches) => {
+            let token_pubkey = pubkey_of_signer(arg_matches, "token", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            command_display_transfer_hook_metas(config, token_pubkey).await
+        }
+        (CommandName::ResolveTransferHookAccounts, arg_matches) => {
+            let token_pubkey = pubkey_of_signer(arg_matches, "token", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let amount = value_t_or_exit!(arg_matches, "amount", f64);
+            let source = pubkey_of_signer(arg_matches, "source", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let destination = pubkey_of_signer(arg_matches, "destination", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let owner = config.pubkey_or_default(arg_matches, "owner", &mut wallet_manager)?;
+            let mint_decimals = value_of::<u8>(arg_matches, MINT_DECIMALS_ARG.name);
+
+            command_resolve_transfer_hook_accounts(
+                config,
+                token_pubkey,
+                source,
+                destination,
+                owner,
+                amount,
+                mint_decimals,
+            )
+            .await
+        }
         (CommandName::CreateAccount, arg_matches) => {
             let token = pubkey_of_signer(arg_matches, "token", &mut wallet_manager)
                 .unwrap()
@@ -4219,18 +5239,41 @@ async fn process_command<'a>(
             )
             .await
         }
-        (CommandName::Transfer, arg_matches) => {
-            let token = pubkey_of_signer(arg_matches, "token", &mut wallet_manager)
+        (CommandName::Authorities, arg_matches) => {
+            let address = pubkey_of_signer(arg_matches, "address", &mut wallet_manager)
                 .unwrap()
                 .unwrap();
-            let amount = match arg_matches.value_of("amount").unwrap() {
-                "ALL" => None,
-                amount => Some(amount.parse::<f64>().unwrap()),
-            };
-            let recipient = pubkey_of_signer(arg_matches, "recipient", &mut wallet_manager)
+            command_authorities(config, address).await
+        }
+        (CommandName::RotateAuthorities, arg_matches) => {
+            let address = pubkey_of_signer(arg_matches, "address", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let new_authority = pubkey_of_signer(arg_matches, "new_authority", &mut wallet_manager)
                 .unwrap()
                 .unwrap();
-            let sender = pubkey_of_signer(arg_matches, "from", &mut wallet_manager).unwrap();
+
+            let (authority_signer, authority) =
+                config.signer_or_default(arg_matches, "authority", &mut wallet_manager);
+            if config.multisigner_pubkeys.is_empty() {
+                push_signer_with_dedup(authority_signer, &mut bulk_signers);
+            }
+
+            command_rotate_authorities(config, address, authority, new_authority, bulk_signers)
+                .await
+        }
+        (CommandName::Transfer, arg_matches) => {
+            let token = pubkey_of_signer(arg_matches, "token", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let amount = match arg_matches.value_of("amount").unwrap() {
+                "ALL" => None,
+                amount => Some(amount.parse::<f64>().unwrap()),
+            };
+            let recipient = pubkey_of_signer(arg_matches, "recipient", &mut wallet_manager)
+                .unwrap()
+                .unwrap();
+            let sender = pubkey_of_signer(arg_matches, "from", &mut wallet_manager).unwrap();
 
             let (owner_signer, owner) =
                 config.signer_or_default(arg_matches, "owner", &mut wallet_manager);
@@ -4548,6 +5591,10 @@ async fn process_command<'a>(
                 .await?;
             command_display(config, address).await
         }
+        (CommandName::Decode, arg_matches) => {
+            let transaction = arg_matches.value_of("transaction").unwrap();
+            command_decode(config, transaction).await
+        }
         (CommandName::MultisigInfo, arg_matches) => {
             let address = pubkey_of_signer(arg_matches, "address", &mut wallet_manager)
                 .unwrap()
@@ -4558,7 +5605,11 @@ async fn process_command<'a>(
             let address = pubkey_of_signer(arg_matches, "address", &mut wallet_manager)
                 .unwrap()
                 .unwrap();
-            command_display(config, address).await
+            if let Some(spec_file) = arg_matches.value_of("export_spec") {
+                command_export_mint_spec(config, address, spec_file).await
+            } else {
+                command_display(config, address).await
+            }
         }
         (CommandName::Gc, arg_matches) => {
             match config.output_format {
@@ -4775,6 +5826,39 @@ enum TransactionReturnData {
     CliSignOnlyData(CliSignOnlyData),
 }
 
+/// Same as `return_signers_data`, for v0 transactions
+fn return_versioned_signers_data(
+    transaction: &VersionedTransaction,
+    dump_transaction_message: bool,
+) -> CliSignOnlyData {
+    let mut signers = vec![];
+    let mut absent = vec![];
+    let mut bad_sig = vec![];
+    for ((signature, key), verified) in transaction
+        .signatures
+        .iter()
+        .zip(transaction.message.static_account_keys())
+        .zip(transaction.verify_with_results())
+    {
+        if verified {
+            signers.push(format!("{}={}", key, signature));
+        } else if *signature == Signature::default() {
+            absent.push(key.to_string());
+        } else {
+            bad_sig.push(key.to_string());
+        }
+    }
+
+    CliSignOnlyData {
+        blockhash: transaction.message.recent_blockhash().to_string(),
+        message: dump_transaction_message
+            .then(|| BASE64_STANDARD.encode(transaction.message.serialize())),
+        signers,
+        absent,
+        bad_sig,
+    }
+}
+
 async fn finish_tx<'a>(
     config: &Config<'a>,
     rpc_response: &RpcClientResponse,
@@ -4789,6 +5873,11 @@ async fn finish_tx<'a>(
                 },
             )))
         }
+        RpcClientResponse::VersionedTransaction(transaction) => {
+            Ok(TransactionReturnData::CliSignOnlyData(
+                return_versioned_signers_data(transaction, config.dump_transaction_message),
+            ))
+        }
         RpcClientResponse::Signature(signature) if no_wait => {
             Ok(TransactionReturnData::CliSignature(CliSignature {
                 signature: signature.to_string(),
@@ -4845,8 +5934,15 @@ mod tests {
     const TEST_DECIMALS: u8 = 0;
 
     async fn new_validator_for_test() -> (TestValidator, Keypair) {
+        new_validator_for_test_with_programs(&[]).await
+    }
+
+    async fn new_validator_for_test_with_programs(
+        extra_programs: &[UpgradeableProgramInfo],
+    ) -> (TestValidator, Keypair) {
         solana_logger::setup();
         let mut test_validator_genesis = TestValidatorGenesis::default();
+        test_validator_genesis.add_upgradeable_programs_with_path(extra_programs);
         test_validator_genesis.add_upgradeable_programs_with_path(&[
             UpgradeableProgramInfo {
                 program_id: spl_token::id(),
@@ -7975,4 +9071,592 @@ mod tests {
 
         assert_eq!(extension.program_id, None.try_into().unwrap());
     }
+
+    #[tokio::test]
+    #[serial]
+    async fn transfer_hook_extra_account_metas() {
+        let transfer_hook_program_id = Pubkey::new_unique();
+        let (test_validator, payer) =
+            new_validator_for_test_with_programs(&[UpgradeableProgramInfo {
+                program_id: transfer_hook_program_id,
+                loader: bpf_loader_upgradeable::id(),
+                program_path: PathBuf::from("../../target/deploy/spl_transfer_hook_example.so"),
+                upgrade_authority: Pubkey::new_unique(),
+            }])
+            .await;
+        let program_id = spl_token_2022::id();
+        let config = test_config_with_default_signer(&test_validator, &payer, &program_id);
+
+        let result = process_test_command(
+            &config,
+            &payer,
+            &[
+                "spl-token",
+                CommandName::CreateToken.into(),
+                "--program-id",
+                &program_id.to_string(),
+                "--transfer-hook",
+                &transfer_hook_program_id.to_string(),
+            ],
+        )
+        .await;
+        let value: serde_json::Value = serde_json::from_str(&result.unwrap()).unwrap();
+        let mint = Pubkey::from_str(value["commandOutput"]["address"].as_str().unwrap()).unwrap();
+
+        let extra_account = Pubkey::new_unique();
+        process_test_command(
+            &config,
+            &payer,
+            &[
+                "spl-token",
+                CommandName::CreateTransferHookMetas.into(),
+                &mint.to_string(),
+                "--transfer-hook-account",
+                &format!("{}:readonly", extra_account),
+                "--transfer-hook-seeds",
+                "literal=seed,account-key=0:writable",
+            ],
+        )
+        .await
+        .unwrap();
+
+        let extra_account_metas_address =
+            get_extra_account_metas_address(&mint, &transfer_hook_program_id);
+        let account = config
+            .rpc_client
+            .get_account(&extra_account_metas_address)
+            .await
+            .unwrap();
+        assert_eq!(account.owner, transfer_hook_program_id);
+
+        // creating twice fails
+        process_test_command(
+            &config,
+            &payer,
+            &[
+                "spl-token",
+                CommandName::CreateTransferHookMetas.into(),
+                &mint.to_string(),
+            ],
+        )
+        .await
+        .unwrap_err();
+
+        let result = process_test_command(
+            &config,
+            &payer,
+            &[
+                "spl-token",
+                CommandName::DisplayTransferHookMetas.into(),
+                &mint.to_string(),
+            ],
+        )
+        .await
+        .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(
+            value["address"],
+            serde_json::json!(extra_account_metas_address.to_string())
+        );
+        assert_eq!(
+            value["extraAccountMetas"],
+            serde_json::json!([
+                {
+                    "address": extra_account.to_string(),
+                    "role": "readonly",
+                },
+                {
+                    "seeds": ["literal=seed", "account-key=0"],
+                    "role": "writable",
+                },
+            ])
+        );
+
+        let source = Pubkey::new_unique();
+        let destination = Pubkey::new_unique();
+        let result = process_test_command(
+            &config,
+            &payer,
+            &[
+                "spl-token",
+                CommandName::ResolveTransferHookAccounts.into(),
+                &mint.to_string(),
+                "10",
+                &source.to_string(),
+                &destination.to_string(),
+            ],
+        )
+        .await
+        .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&result).unwrap();
+        let pda =
+            Pubkey::find_program_address(&[b"seed", source.as_ref()], &transfer_hook_program_id).0;
+        assert_eq!(
+            value["extraAccountMetas"],
+            serde_json::json!([
+                {
+                    "address": extra_account.to_string(),
+                    "role": "readonly",
+                },
+                {
+                    "address": pda.to_string(),
+                    "seeds": ["literal=seed", "account-key=0"],
+                    "role": "writable",
+                },
+            ])
+        );
+
+        // replace the extra accounts, growing the account
+        let other_account = Pubkey::new_unique();
+        process_test_command(
+            &config,
+            &payer,
+            &[
+                "spl-token",
+                CommandName::UpdateTransferHookMetas.into(),
+                &mint.to_string(),
+                "--transfer-hook-account",
+                &format!("{}:readonly", extra_account),
+                &format!("{}:writable", other_account),
+                "--transfer-hook-seeds",
+                "literal=seed,account-key=0:writable",
+            ],
+        )
+        .await
+        .unwrap();
+
+        let result = process_test_command(
+            &config,
+            &payer,
+            &[
+                "spl-token",
+                CommandName::DisplayTransferHookMetas.into(),
+                &mint.to_string(),
+            ],
+        )
+        .await
+        .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(
+            value["extraAccountMetas"],
+            serde_json::json!([
+                {
+                    "address": extra_account.to_string(),
+                    "role": "readonly",
+                },
+                {
+                    "address": other_account.to_string(),
+                    "role": "writable",
+                },
+                {
+                    "seeds": ["literal=seed", "account-key=0"],
+                    "role": "writable",
+                },
+            ])
+        );
+
+        // and shrink it down to nothing
+        process_test_command(
+            &config,
+            &payer,
+            &[
+                "spl-token",
+                CommandName::UpdateTransferHookMetas.into(),
+                &mint.to_string(),
+            ],
+        )
+        .await
+        .unwrap();
+
+        let result = process_test_command(
+            &config,
+            &payer,
+            &[
+                "spl-token",
+                CommandName::DisplayTransferHookMetas.into(),
+                &mint.to_string(),
+            ],
+        )
+        .await
+        .unwrap();
+        let value: serde_json::Value = serde_json::from_str(&result).unwrap();
+        assert_eq!(value["extraAccountMetas"], serde_json::json!([]));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn bench_run() {
+        let transfer_hook_program_id = Pubkey::new_unique();
+        let (test_validator, payer) =
+            new_validator_for_test_with_programs(&[UpgradeableProgramInfo {
+                program_id: transfer_hook_program_id,
+                loader: bpf_loader_upgradeable::id(),
+                program_path: PathBuf::from("../../target/deploy/spl_transfer_hook_example.so"),
+                upgrade_authority: Pubkey::new_unique(),
+            }])
+            .await;
+        let program_id = spl_token_2022::id();
+        let config = test_config_with_default_signer(&test_validator, &payer, &program_id);
+
+        let result = process_test_command(
+            &config,
+            &payer,
+            &[
+                "spl-token",
+                CommandName::Bench.into(),
+                "run",
+                "--mix",
+                "transfer:2",
+                "--mix",
+                "transfer-with-fee:2",
+                "--mix",
+                "transfer-with-hook:2",
+                "--mix",
+                "confidential-transfer:2",
+                "--transfer-hook-program-id",
+                &transfer_hook_program_id.to_string(),
+            ],
+        )
+        .await;
+        let value: serde_json::Value = serde_json::from_str(&result.unwrap()).unwrap();
+        let results = value["results"].as_array().unwrap();
+        assert_eq!(results.len(), 4);
+        let kinds = [
+            "transfer",
+            "transfer-with-fee",
+            "transfer-with-hook",
+            "confidential-transfer",
+        ];
+        for (result, kind) in results.iter().zip(kinds) {
+            assert_eq!(result["kind"], kind);
+            assert_eq!(result["succeeded"], 2);
+            assert_eq!(result["failed"], 0);
+            assert!(result["computeUnits"]["min"].as_u64().unwrap() > 0);
+        }
+
+        // the hook program is invoked on every transfer
+        assert!(
+            results[2]["computeUnits"]["min"].as_u64().unwrap()
+                > results[0]["computeUnits"]["max"].as_u64().unwrap()
+        );
+
+        // confidential transfers verify proofs, so they cost more than plain ones
+        assert!(
+            results[3]["computeUnits"]["min"].as_u64().unwrap()
+                > results[0]["computeUnits"]["max"].as_u64().unwrap()
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn decode() {
+        let (test_validator, payer) = new_validator_for_test().await;
+        let program_id = spl_token_2022::id();
+        let config = test_config_with_default_signer(&test_validator, &payer, &program_id);
+
+        let result = process_test_command(
+            &config,
+            &payer,
+            &[
+                "spl-token",
+                CommandName::CreateToken.into(),
+                "--program-id",
+                &program_id.to_string(),
+                "--transfer-fee",
+                "50",
+                "1000",
+            ],
+        )
+        .await;
+        let value: serde_json::Value = serde_json::from_str(&result.unwrap()).unwrap();
+        let mint = value["commandOutput"]["address"]
+            .as_str()
+            .unwrap()
+            .to_string();
+        let signature = value["commandOutput"]["transactionData"]["signature"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let result = process_test_command(
+            &config,
+            &payer,
+            &["spl-token", CommandName::Decode.into(), &signature],
+        )
+        .await;
+        let value: serde_json::Value = serde_json::from_str(&result.unwrap()).unwrap();
+        let instructions = value["instructions"].as_array().unwrap();
+        assert_eq!(instructions.len(), 3);
+        assert_eq!(instructions[0]["program"], "unknown");
+
+        let transfer_fee = &instructions[1];
+        assert_eq!(transfer_fee["program"], "spl-token-2022");
+        assert_eq!(
+            transfer_fee["instructionType"],
+            "TransferFeeExtension::InitializeTransferFeeConfig"
+        );
+        assert_eq!(transfer_fee["info"]["transfer_fee_basis_points"], 50);
+        assert_eq!(transfer_fee["accounts"][0]["name"], "mint");
+        assert_eq!(transfer_fee["accounts"][0]["address"], mint);
+
+        let initialize_mint = &instructions[2];
+        assert_eq!(initialize_mint["instructionType"], "InitializeMint");
+        assert_eq!(initialize_mint["info"]["decimals"], 9);
+        assert_eq!(
+            initialize_mint["info"]["mint_authority"],
+            payer.pubkey().to_string()
+        );
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn mint_spec() {
+        let (test_validator, payer) = new_validator_for_test().await;
+        let program_id = spl_token_2022::id();
+        let config = test_config_with_default_signer(&test_validator, &payer, &program_id);
+        let authority = payer.pubkey().to_string();
+
+        let spec_file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+        std::fs::write(
+            spec_file.path(),
+            format!(
+                r#"decimals = 6
+freeze-authority = "{authority}"
+
+[extensions.mint-close-authority]
+close-authority = "{authority}"
+
+[extensions.interest-bearing-config]
+rate-authority = "{authority}"
+rate = 10
+
+[extensions.default-account-state]
+state = "frozen"
+
+[extensions.transfer-fee-config]
+transfer-fee-config-authority = "{authority}"
+withdraw-withheld-authority = "{authority}"
+transfer-fee-basis-points = 100
+maximum-fee = "18446744073709551615"
+
+[extensions.metadata-pointer]
+authority = "{authority}"
+"#
+            ),
+        )
+        .unwrap();
+        let spec_path = spec_file.path().to_str().unwrap();
+
+        let result = process_test_command(
+            &config,
+            &payer,
+            &[
+                "spl-token",
+                CommandName::CreateToken.into(),
+                "--spec",
+                spec_path,
+            ],
+        )
+        .await;
+        let value: serde_json::Value = serde_json::from_str(&result.unwrap()).unwrap();
+        let mint = value["commandOutput"]["address"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let export_file = tempfile::Builder::new().suffix(".json").tempfile().unwrap();
+        let export_path = export_file.path().to_str().unwrap();
+        process_test_command(
+            &config,
+            &payer,
+            &[
+                "spl-token",
+                CommandName::Display.into(),
+                &mint,
+                "--export-spec",
+                export_path,
+            ],
+        )
+        .await
+        .unwrap();
+
+        // the mint authority defaults to the client keypair
+        let mut expected_spec = MintSpec::from_file(spec_path).unwrap();
+        expected_spec.mint_authority = Some(authority);
+        assert_eq!(MintSpec::from_file(export_path).unwrap(), expected_spec);
+
+        // transfer fees and confidential transfers also require the
+        // confidential transfer fee extension
+        std::fs::write(
+            spec_file.path(),
+            r#"decimals = 6
+
+[extensions.transfer-fee-config]
+transfer-fee-basis-points = 100
+maximum-fee = "1000"
+
+[extensions.confidential-transfer-mint]
+auto-approve-new-accounts = true
+"#,
+        )
+        .unwrap();
+        let result = process_test_command(
+            &config,
+            &payer,
+            &[
+                "spl-token",
+                CommandName::CreateToken.into(),
+                "--spec",
+                spec_path,
+            ],
+        )
+        .await;
+        assert!(result
+            .unwrap_err()
+            .to_string()
+            .starts_with("Invalid combination of extensions"));
+    }
+
+    #[tokio::test]
+    #[serial]
+    async fn rotate_authorities() {
+        let (test_validator, payer) = new_validator_for_test().await;
+        let program_id = spl_token_2022::id();
+        let config = test_config_with_default_signer(&test_validator, &payer, &program_id);
+        let authority = payer.pubkey().to_string();
+        let other_authority = Pubkey::new_unique().to_string();
+        let new_authority = Pubkey::new_unique().to_string();
+
+        let spec_file = tempfile::Builder::new().suffix(".toml").tempfile().unwrap();
+        std::fs::write(
+            spec_file.path(),
+            format!(
+                r#"decimals = 6
+freeze-authority = "{authority}"
+
+[extensions.mint-close-authority]
+close-authority = "{authority}"
+
+[extensions.interest-bearing-config]
+rate-authority = "{authority}"
+rate = 10
+
+[extensions.transfer-fee-config]
+transfer-fee-config-authority = "{authority}"
+withdraw-withheld-authority = "{other_authority}"
+transfer-fee-basis-points = 100
+maximum-fee = "1000"
+
+[extensions.metadata-pointer]
+authority = "{authority}"
+"#
+            ),
+        )
+        .unwrap();
+        let result = process_test_command(
+            &config,
+            &payer,
+            &[
+                "spl-token",
+                CommandName::CreateToken.into(),
+                "--spec",
+                spec_file.path().to_str().unwrap(),
+            ],
+        )
+        .await;
+        let value: serde_json::Value = serde_json::from_str(&result.unwrap()).unwrap();
+        let mint = value["commandOutput"]["address"]
+            .as_str()
+            .unwrap()
+            .to_string();
+
+        let result = process_test_command(
+            &config,
+            &payer,
+            &["spl-token", CommandName::Authorities.into(), &mint],
+        )
+        .await;
+        let value: serde_json::Value = serde_json::from_str(&result.unwrap()).unwrap();
+        let authorities = value["authorities"].as_array().unwrap();
+        let authority_types = authorities
+            .iter()
+            .map(|authority| authority["authorityType"].as_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            authority_types,
+            [
+                "mint",
+                "freeze",
+                "close-mint",
+                "transfer-fee-config",
+                "withheld-withdraw",
+                "interest-rate",
+                "metadata-pointer",
+            ]
+        );
+
+        // signing offline still looks up the authorities on the cluster, and
+        // returns the transaction to be signed instead of sending it
+        let blockhash = config.rpc_client.get_latest_blockhash().await.unwrap();
+        let mut offline_config =
+            test_config_with_default_signer(&test_validator, &payer, &program_id);
+        offline_config.program_client = Arc::new(ProgramOfflineClient::new(
+            blockhash,
+            ProgramRpcClientSendTransaction,
+        ));
+        let result = exec_test_cmd(
+            &offline_config,
+            &[
+                "spl-token",
+                CommandName::RotateAuthorities.into(),
+                &mint,
+                &new_authority,
+                "--authority",
+                &authority,
+                "--fee-payer",
+                &authority,
+                "--program-id",
+                &program_id.to_string(),
+                "--blockhash",
+                &blockhash.to_string(),
+                "--sign-only",
+            ],
+        )
+        .await
+        .unwrap();
+        let absent_signers_position = result.find("Absent Signers").unwrap();
+        let absent_signers = result.get(absent_signers_position..).unwrap();
+        assert!(absent_signers.contains(&authority));
+
+        let result = process_test_command(
+            &config,
+            &payer,
+            &[
+                "spl-token",
+                CommandName::RotateAuthorities.into(),
+                &mint,
+                &new_authority,
+            ],
+        )
+        .await;
+        let value: serde_json::Value = serde_json::from_str(&result.unwrap()).unwrap();
+        assert_eq!(value["rotated"].as_array().unwrap().len(), 6);
+        assert_eq!(value["skipped"][0]["authorityType"], "withheld-withdraw");
+        assert_eq!(value["signatures"].as_array().unwrap().len(), 1);
+
+        let result = process_test_command(
+            &config,
+            &payer,
+            &["spl-token", CommandName::Authorities.into(), &mint],
+        )
+        .await;
+        let value: serde_json::Value = serde_json::from_str(&result.unwrap()).unwrap();
+        for authority in value["authorities"].as_array().unwrap() {
+            if authority["authorityType"] == "withheld-withdraw" {
+                assert_eq!(authority["authority"], other_authority);
+            } else {
+                assert_eq!(authority["authority"], new_authority);
+            }
+        }
+    }
 }
@@ -0,0 +1,399 @@
+use crate::Error;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use solana_sdk::pubkey::Pubkey;
+use spl_token_2022::{
+    extension::{
+        confidential_transfer::ConfidentialTransferMint,
+        default_account_state::DefaultAccountState, interest_bearing_mint::InterestBearingConfig,
+        metadata_pointer::MetadataPointer, mint_close_authority::MintCloseAuthority,
+        permanent_delegate::PermanentDelegate, transfer_fee::TransferFeeConfig,
+        transfer_hook::TransferHook, BaseStateWithExtensions, ExtensionType,
+        StateWithExtensionsOwned,
+    },
+    solana_zk_token_sdk::zk_token_elgamal::pod::ElGamalPubkey as PodElGamalPubkey,
+    state::{AccountState, Mint},
+};
+use spl_token_client::token::ExtensionInitializationParams;
+use std::{fs, path::Path, str::FromStr};
+
+/// A reviewable definition of a mint, its authorities and its extensions,
+/// which can be written as TOML or JSON
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct MintSpec {
+    pub(crate) decimals: u8,
+    /// Defaults to the `--mint-authority` of `create-token`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) mint_authority: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) freeze_authority: Option<String>,
+    #[serde(default)]
+    pub(crate) extensions: MintExtensionsSpec,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct MintExtensionsSpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) mint_close_authority: Option<MintCloseAuthoritySpec>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) permanent_delegate: Option<PermanentDelegateSpec>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) interest_bearing_config: Option<InterestBearingConfigSpec>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) non_transferable: Option<NonTransferableSpec>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) default_account_state: Option<DefaultAccountStateSpec>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) transfer_fee_config: Option<TransferFeeConfigSpec>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) confidential_transfer_mint: Option<ConfidentialTransferMintSpec>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) transfer_hook: Option<TransferHookSpec>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) metadata_pointer: Option<MetadataPointerSpec>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct MintCloseAuthoritySpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) close_authority: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct PermanentDelegateSpec {
+    pub(crate) delegate: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct InterestBearingConfigSpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) rate_authority: Option<String>,
+    /// Interest rate in basis points
+    pub(crate) rate: i16,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct NonTransferableSpec {}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct DefaultAccountStateSpec {
+    /// Either "initialized" or "frozen"
+    pub(crate) state: String,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct TransferFeeConfigSpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) transfer_fee_config_authority: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) withdraw_withheld_authority: Option<String>,
+    pub(crate) transfer_fee_basis_points: u16,
+    /// Maximum fee in base units. Written as a string since TOML integers
+    /// cannot hold every `u64`.
+    #[serde(serialize_with = "serialize_u64", deserialize_with = "deserialize_u64")]
+    pub(crate) maximum_fee: u64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct ConfidentialTransferMintSpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) authority: Option<String>,
+    pub(crate) auto_approve_new_accounts: bool,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct TransferHookSpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) authority: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) program_id: Option<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub(crate) struct MetadataPointerSpec {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) authority: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) metadata_address: Option<String>,
+}
+
+fn serialize_u64<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&value.to_string())
+}
+
+fn deserialize_u64<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+    let value = String::deserialize(deserializer)?;
+    value.parse().map_err(serde::de::Error::custom)
+}
+
+/// Mint creation parameters obtained from a validated `MintSpec`
+pub(crate) struct MintSpecParams {
+    pub(crate) decimals: u8,
+    pub(crate) mint_authority: Pubkey,
+    pub(crate) freeze_authority: Option<Pubkey>,
+    pub(crate) extensions: Vec<ExtensionInitializationParams>,
+}
+
+fn parse_pubkey(field: &str, value: &str) -> Result<Pubkey, Error> {
+    Pubkey::from_str(value).map_err(|err| format!("Invalid `{}` {}: {}", field, value, err).into())
+}
+
+fn parse_optional_pubkey(field: &str, value: &Option<String>) -> Result<Option<Pubkey>, Error> {
+    value
+        .as_ref()
+        .map(|value| parse_pubkey(field, value))
+        .transpose()
+}
+
+fn optional_string(pubkey: Option<Pubkey>) -> Option<String> {
+    pubkey.map(|pubkey| pubkey.to_string())
+}
+
+fn is_toml(path: &str) -> bool {
+    Path::new(path)
+        .extension()
+        .map(|extension| extension.eq_ignore_ascii_case("toml"))
+        .unwrap_or(false)
+}
+
+impl MintSpec {
+    /// Read a spec from a file, as TOML if it has a `.toml` extension and as
+    /// JSON otherwise
+    pub(crate) fn from_file(path: &str) -> Result<Self, Error> {
+        let contents = fs::read_to_string(path)
+            .map_err(|err| format!("Unable to read mint spec {}: {}", path, err))?;
+        let spec = if is_toml(path) {
+            toml::from_str(&contents).map_err(|err| err.to_string())
+        } else {
+            serde_json::from_str(&contents).map_err(|err| err.to_string())
+        };
+        spec.map_err(|err| format!("Invalid mint spec {}: {}", path, err).into())
+    }
+
+    /// Write a spec to a file, using the same format rules as `from_file`
+    pub(crate) fn to_file(&self, path: &str) -> Result<(), Error> {
+        let contents = if is_toml(path) {
+            toml::to_string(self)?
+        } else {
+            serde_json::to_string_pretty(self)?
+        };
+        fs::write(path, contents)
+            .map_err(|err| format!("Unable to write mint spec {}: {}", path, err).into())
+    }
+
+    /// Build a spec describing an existing mint. Fails if the mint has
+    /// configuration that a spec cannot express.
+    pub(crate) fn from_mint(mint: &StateWithExtensionsOwned<Mint>) -> Result<Self, Error> {
+        let mut extensions = MintExtensionsSpec::default();
+        for extension_type in mint.get_extension_types()? {
+            match extension_type {
+                ExtensionType::MintCloseAuthority => {
+                    let extension = mint.get_extension::<MintCloseAuthority>()?;
+                    extensions.mint_close_authority = Some(MintCloseAuthoritySpec {
+                        close_authority: optional_string(extension.close_authority.into()),
+                    });
+                }
+                ExtensionType::PermanentDelegate => {
+                    let extension = mint.get_extension::<PermanentDelegate>()?;
+                    let delegate = Option::<Pubkey>::from(extension.delegate)
+                        .ok_or("Permanent delegate extension has no delegate")?;
+                    extensions.permanent_delegate = Some(PermanentDelegateSpec {
+                        delegate: delegate.to_string(),
+                    });
+                }
+                ExtensionType::InterestBearingConfig => {
+                    let extension = mint.get_extension::<InterestBearingConfig>()?;
+                    extensions.interest_bearing_config = Some(InterestBearingConfigSpec {
+                        rate_authority: optional_string(extension.rate_authority.into()),
+                        rate: extension.current_rate.into(),
+                    });
+                }
+                ExtensionType::NonTransferable => {
+                    extensions.non_transferable = Some(NonTransferableSpec {});
+                }
+                ExtensionType::DefaultAccountState => {
+                    let extension = mint.get_extension::<DefaultAccountState>()?;
+                    let state = match AccountState::try_from(extension.state) {
+                        Ok(AccountState::Initialized) => "initialized",
+                        Ok(AccountState::Frozen) => "frozen",
+                        _ => return Err("Invalid default account state".into()),
+                    };
+                    extensions.default_account_state = Some(DefaultAccountStateSpec {
+                        state: state.to_string(),
+                    });
+                }
+                ExtensionType::TransferFeeConfig => {
+                    let extension = mint.get_extension::<TransferFeeConfig>()?;
+                    let transfer_fee = extension.newer_transfer_fee;
+                    extensions.transfer_fee_config = Some(TransferFeeConfigSpec {
+                        transfer_fee_config_authority: optional_string(
+                            extension.transfer_fee_config_authority.into(),
+                        ),
+                        withdraw_withheld_authority: optional_string(
+                            extension.withdraw_withheld_authority.into(),
+                        ),
+                        transfer_fee_basis_points: transfer_fee.transfer_fee_basis_points.into(),
+                        maximum_fee: transfer_fee.maximum_fee.into(),
+                    });
+                }
+                ExtensionType::ConfidentialTransferMint => {
+                    let extension = mint.get_extension::<ConfidentialTransferMint>()?;
+                    let auditor_elgamal_pubkey: Option<PodElGamalPubkey> =
+                        extension.auditor_elgamal_pubkey.into();
+                    if auditor_elgamal_pubkey.is_some() {
+                        return Err(
+                            "Confidential transfer auditors cannot be expressed in a mint spec"
+                                .into(),
+                        );
+                    }
+                    extensions.confidential_transfer_mint = Some(ConfidentialTransferMintSpec {
+                        authority: optional_string(extension.authority.into()),
+                        auto_approve_new_accounts: extension.auto_approve_new_accounts.into(),
+                    });
+                }
+                ExtensionType::TransferHook => {
+                    let extension = mint.get_extension::<TransferHook>()?;
+                    extensions.transfer_hook = Some(TransferHookSpec {
+                        authority: optional_string(extension.authority.into()),
+                        program_id: optional_string(extension.program_id.into()),
+                    });
+                }
+                ExtensionType::MetadataPointer => {
+                    let extension = mint.get_extension::<MetadataPointer>()?;
+                    extensions.metadata_pointer = Some(MetadataPointerSpec {
+                        authority: optional_string(extension.authority.into()),
+                        metadata_address: optional_string(extension.metadata_address.into()),
+                    });
+                }
+                extension_type => {
+                    return Err(format!(
+                        "Extension {:?} cannot be expressed in a mint spec",
+                        extension_type
+                    )
+                    .into());
+                }
+            }
+        }
+
+        Ok(Self {
+            decimals: mint.base.decimals,
+            mint_authority: optional_string(mint.base.mint_authority.into()),
+            freeze_authority: optional_string(mint.base.freeze_authority.into()),
+            extensions,
+        })
+    }
+
+    /// Check the spec and convert it into mint creation parameters, using
+    /// `default_mint_authority` if the spec does not name one
+    pub(crate) fn into_params(
+        self,
+        default_mint_authority: Pubkey,
+    ) -> Result<MintSpecParams, Error> {
+        let mint_authority = parse_optional_pubkey("mint-authority", &self.mint_authority)?
+            .unwrap_or(default_mint_authority);
+        let freeze_authority = parse_optional_pubkey("freeze-authority", &self.freeze_authority)?;
+        let spec = self.extensions;
+
+        let mut extensions = vec![];
+        if let Some(mint_close_authority) = spec.mint_close_authority {
+            extensions.push(ExtensionInitializationParams::MintCloseAuthority {
+                close_authority: parse_optional_pubkey(
+                    "close-authority",
+                    &mint_close_authority.close_authority,
+                )?,
+            });
+        }
+        if let Some(permanent_delegate) = spec.permanent_delegate {
+            extensions.push(ExtensionInitializationParams::PermanentDelegate {
+                delegate: parse_pubkey("delegate", &permanent_delegate.delegate)?,
+            });
+        }
+        if let Some(interest_bearing_config) = spec.interest_bearing_config {
+            extensions.push(ExtensionInitializationParams::InterestBearingConfig {
+                rate_authority: parse_optional_pubkey(
+                    "rate-authority",
+                    &interest_bearing_config.rate_authority,
+                )?,
+                rate: interest_bearing_config.rate,
+            });
+        }
+        if spec.non_transferable.is_some() {
+            extensions.push(ExtensionInitializationParams::NonTransferable);
+        }
+        if let Some(default_account_state) = spec.default_account_state {
+            let state = match default_account_state.state.as_str() {
+                "initialized" => AccountState::Initialized,
+                "frozen" => AccountState::Frozen,
+                state => {
+                    return Err(format!(
+                    "Invalid default account state `{}`, expected \"initialized\" or \"frozen\"",
+                    state
+                )
+                    .into())
+                }
+            };
+            if freeze_authority.is_none() {
+                return Err(
+                    "Token requires a freeze authority to have a default account state".into(),
+                );
+            }
+            extensions.push(ExtensionInitializationParams::DefaultAccountState { state });
+        }
+        if let Some(transfer_fee_config) = spec.transfer_fee_config {
+            extensions.push(ExtensionInitializationParams::TransferFeeConfig {
+                transfer_fee_config_authority: parse_optional_pubkey(
+                    "transfer-fee-config-authority",
+                    &transfer_fee_config.transfer_fee_config_authority,
+                )?,
+                withdraw_withheld_authority: parse_optional_pubkey(
+                    "withdraw-withheld-authority",
+                    &transfer_fee_config.withdraw_withheld_authority,
+                )?,
+                transfer_fee_basis_points: transfer_fee_config.transfer_fee_basis_points,
+                maximum_fee: transfer_fee_config.maximum_fee,
+            });
+        }
+        if let Some(confidential_transfer_mint) = spec.confidential_transfer_mint {
+            extensions.push(ExtensionInitializationParams::ConfidentialTransferMint {
+                authority: parse_optional_pubkey(
+                    "authority",
+                    &confidential_transfer_mint.authority,
+                )?,
+                auto_approve_new_accounts: confidential_transfer_mint.auto_approve_new_accounts,
+                auditor_elgamal_pubkey: None,
+            });
+        }
+        if let Some(transfer_hook) = spec.transfer_hook {
+            extensions.push(ExtensionInitializationParams::TransferHook {
+                authority: parse_optional_pubkey("authority", &transfer_hook.authority)?,
+                program_id: parse_optional_pubkey("program-id", &transfer_hook.program_id)?,
+            });
+        }
+        if let Some(metadata_pointer) = spec.metadata_pointer {
+            extensions.push(ExtensionInitializationParams::MetadataPointer {
+                authority: parse_optional_pubkey("authority", &metadata_pointer.authority)?,
+                metadata_address: parse_optional_pubkey(
+                    "metadata-address",
+                    &metadata_pointer.metadata_address,
+                )?,
+            });
+        }
+
+        Ok(MintSpecParams {
+            decimals: self.decimals,
+            mint_authority,
+            freeze_authority,
+            extensions,
+        })
+    }
+}
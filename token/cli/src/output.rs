@@ -543,6 +543,326 @@ impl fmt::Display for CliTokenAccounts {
     }
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliExtraAccountMeta {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) seeds: Option<Vec<String>>,
+    pub(crate) role: String,
+}
+
+impl fmt::Display for CliExtraAccountMeta {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (&self.address, &self.seeds) {
+            (Some(address), Some(seeds)) => {
+                write!(f, "{} ({}) [{}]", address, self.role, seeds.join(", "))
+            }
+            (Some(address), None) => write!(f, "{} ({})", address, self.role),
+            (None, Some(seeds)) => write!(f, "PDA [{}] ({})", seeds.join(", "), self.role),
+            (None, None) => write!(f, "({})", self.role),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliTransferHookExtraAccountMetas {
+    pub(crate) address: String,
+    pub(crate) mint: String,
+    pub(crate) program_id: String,
+    pub(crate) extra_account_metas: Vec<CliExtraAccountMeta>,
+}
+
+impl QuietDisplay for CliTransferHookExtraAccountMetas {}
+impl VerboseDisplay for CliTransferHookExtraAccountMetas {}
+
+impl fmt::Display for CliTransferHookExtraAccountMetas {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f)?;
+        writeln!(
+            f,
+            "{}",
+            style("SPL Transfer Hook Extra Account Metas").bold()
+        )?;
+        writeln_name_value(f, "  Address:", &self.address)?;
+        writeln_name_value(f, "  Mint:", &self.mint)?;
+        writeln_name_value(f, "  Program:", &self.program_id)?;
+        if self.extra_account_metas.is_empty() {
+            writeln_name_value(f, "  Extra accounts:", "None")?;
+        } else {
+            writeln!(f, "  {}", style("Extra accounts:").bold())?;
+            let width = if self.extra_account_metas.len() >= 9 {
+                4
+            } else {
+                3
+            };
+            for (i, extra_account_meta) in self.extra_account_metas.iter().enumerate() {
+                let title = format!("  {1:>0$}:", width, i + 1);
+                writeln_name_value(f, &title, &extra_account_meta.to_string())?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliBenchSummary {
+    pub(crate) elapsed_seconds: f64,
+    pub(crate) transactions_per_second: f64,
+    pub(crate) results: Vec<CliBenchResult>,
+}
+
+impl QuietDisplay for CliBenchSummary {}
+impl VerboseDisplay for CliBenchSummary {}
+
+impl fmt::Display for CliBenchSummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f)?;
+        writeln!(f, "{}", style("Benchmark Results").bold())?;
+        for result in &self.results {
+            writeln!(f)?;
+            write!(f, "{}", result)?;
+        }
+        writeln!(f)?;
+        writeln_name_value(
+            f,
+            "Elapsed time:",
+            &format!("{:.2} seconds", self.elapsed_seconds),
+        )?;
+        writeln_name_value(
+            f,
+            "Average TPS:",
+            &format!("{:.2}", self.transactions_per_second),
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliBenchResult {
+    pub(crate) kind: String,
+    pub(crate) mint: String,
+    pub(crate) succeeded: usize,
+    pub(crate) failed: usize,
+    pub(crate) compute_units: Option<CliBenchStats>,
+    pub(crate) confirmation_latency_ms: Option<CliBenchStats>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) last_error: Option<String>,
+}
+
+impl fmt::Display for CliBenchResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{}", style(&self.kind).bold())?;
+        writeln_name_value(f, "  Mint:", &self.mint)?;
+        writeln_name_value(f, "  Succeeded:", &self.succeeded.to_string())?;
+        writeln_name_value(f, "  Failed:", &self.failed.to_string())?;
+        if let Some(compute_units) = &self.compute_units {
+            writeln_name_value(f, "  Compute units:", &compute_units.to_string())?;
+        }
+        if let Some(confirmation_latency_ms) = &self.confirmation_latency_ms {
+            writeln_name_value(
+                f,
+                "  Confirmation latency (ms):",
+                &confirmation_latency_ms.to_string(),
+            )?;
+        }
+        if let Some(last_error) = &self.last_error {
+            writeln_name_value(f, "  Last error:", last_error)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliBenchStats {
+    pub(crate) min: u64,
+    pub(crate) max: u64,
+    pub(crate) mean: f64,
+    pub(crate) p50: u64,
+    pub(crate) p90: u64,
+}
+
+impl fmt::Display for CliBenchStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "min {}, mean {:.1}, p50 {}, p90 {}, max {}",
+            self.min, self.mean, self.p50, self.p90, self.max
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliDecodedTransaction {
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) signature: Option<String>,
+    pub(crate) instructions: Vec<CliDecodedInstruction>,
+}
+
+impl QuietDisplay for CliDecodedTransaction {}
+impl VerboseDisplay for CliDecodedTransaction {}
+
+impl fmt::Display for CliDecodedTransaction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(signature) = &self.signature {
+            writeln!(f)?;
+            writeln_name_value(f, "Signature:", signature)?;
+        }
+        for (i, instruction) in self.instructions.iter().enumerate() {
+            writeln!(f)?;
+            writeln!(f, "{}", style(format!("Instruction {}", i + 1)).bold())?;
+            write!(f, "{}", instruction)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliDecodedInstruction {
+    pub(crate) program: String,
+    pub(crate) program_id: String,
+    pub(crate) instruction_type: String,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) info: Option<serde_json::Value>,
+    pub(crate) accounts: Vec<CliDecodedAccount>,
+    #[serde(skip_serializing_if = "Option::is_none", default)]
+    pub(crate) error: Option<String>,
+}
+
+impl fmt::Display for CliDecodedInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln_name_value(
+            f,
+            "  Program:",
+            &format!("{} ({})", self.program, self.program_id),
+        )?;
+        writeln_name_value(f, "  Instruction:", &self.instruction_type)?;
+        if let Some(error) = &self.error {
+            writeln_name_value(f, "  Error:", error)?;
+        }
+        if let Some(serde_json::Value::Object(info)) = &self.info {
+            for (name, value) in info {
+                let value = match value {
+                    serde_json::Value::String(value) => value.clone(),
+                    serde_json::Value::Null => "None".to_string(),
+                    value => value.to_string(),
+                };
+                writeln_name_value(f, &format!("  {}:", name), &value)?;
+            }
+        }
+        if !self.accounts.is_empty() {
+            writeln!(f, "  {}", style("Accounts:").bold())?;
+            for account in &self.accounts {
+                writeln!(f, "    {}", account)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliDecodedAccount {
+    pub(crate) name: String,
+    pub(crate) address: String,
+    pub(crate) signer: bool,
+    pub(crate) writable: bool,
+}
+
+impl fmt::Display for CliDecodedAccount {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let role = match (self.signer, self.writable) {
+            (true, true) => "writable signer",
+            (true, false) => "signer",
+            (false, true) => "writable",
+            (false, false) => "readonly",
+        };
+        write!(f, "{}: {} ({})", self.name, self.address, role)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliMintAuthorities {
+    pub(crate) address: String,
+    pub(crate) authorities: Vec<CliMintAuthority>,
+}
+
+impl QuietDisplay for CliMintAuthorities {}
+impl VerboseDisplay for CliMintAuthorities {}
+
+impl fmt::Display for CliMintAuthorities {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f)?;
+        writeln_name_value(f, "Address:", &self.address)?;
+        writeln!(f, "{}", style("Authorities:").bold())?;
+        for authority in &self.authorities {
+            writeln!(f, "  {}", authority)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliMintAuthority {
+    pub(crate) authority_type: String,
+    pub(crate) authority: Option<String>,
+}
+
+impl fmt::Display for CliMintAuthority {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {}",
+            self.authority_type,
+            self.authority.as_deref().unwrap_or("disabled")
+        )
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct CliRotateAuthorities {
+    pub(crate) address: String,
+    pub(crate) new_authority: String,
+    pub(crate) rotated: Vec<String>,
+    pub(crate) skipped: Vec<CliMintAuthority>,
+    pub(crate) signatures: Vec<String>,
+}
+
+impl QuietDisplay for CliRotateAuthorities {}
+impl VerboseDisplay for CliRotateAuthorities {}
+
+impl fmt::Display for CliRotateAuthorities {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f)?;
+        writeln_name_value(f, "Address:", &self.address)?;
+        writeln_name_value(f, "New authority:", &self.new_authority)?;
+        if self.rotated.is_empty() {
+            writeln_name_value(f, "Rotated:", "None")?;
+        } else {
+            writeln_name_value(f, "Rotated:", &self.rotated.join(", "))?;
+        }
+        if !self.skipped.is_empty() {
+            writeln!(f, "{}", style("Skipped:").bold())?;
+            for authority in &self.skipped {
+                writeln!(f, "  {}", authority)?;
+            }
+        }
+        for signature in &self.signatures {
+            writeln_name_value(f, "Signature:", signature)?;
+        }
+        Ok(())
+    }
+}
+
 fn display_ui_extension(
     f: &mut fmt::Formatter,
     epoch: u64,
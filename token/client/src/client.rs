@@ -5,8 +5,14 @@ use {
     solana_rpc_client::nonblocking::rpc_client::RpcClient,
     solana_rpc_client_api::response::RpcSimulateTransactionResult,
     solana_sdk::{
-        account::Account, hash::Hash, pubkey::Pubkey, signature::Signature,
-        transaction::Transaction,
+        account::Account,
+        clock::Slot,
+        hash::Hash,
+        instruction::{AccountMeta, CompiledInstruction, Instruction},
+        message::MessageHeader,
+        pubkey::Pubkey,
+        signature::Signature,
+        transaction::{Transaction, VersionedTransaction},
     },
     std::{fmt, future::Future, pin::Pin, sync::Arc},
 };
@@ -20,7 +26,41 @@ pub trait SendTransaction {
 
 /// Basic trait for simulating transactions in a validator.
 pub trait SimulateTransaction {
-    type SimulationOutput;
+    type SimulationOutput: SimulationResult;
+}
+
+/// Trait for the output of a simulation, giving the number of compute units
+/// the transaction consumed.
+pub trait SimulationResult {
+    fn get_compute_units_consumed(&self) -> ProgramClientResult<u64>;
+}
+
+impl SimulationResult for BanksTransactionResultWithSimulation {
+    fn get_compute_units_consumed(&self) -> ProgramClientResult<u64> {
+        if let Some(Err(error)) = &self.result {
+            return Err(error.clone().into());
+        }
+        self.simulation_details
+            .as_ref()
+            .map(|details| details.units_consumed)
+            .ok_or_else(|| "No simulation details returned".into())
+    }
+}
+
+impl SimulationResult for RpcClientResponse {
+    fn get_compute_units_consumed(&self) -> ProgramClientResult<u64> {
+        match self {
+            Self::Simulation(result) => {
+                if let Some(error) = &result.err {
+                    return Err(error.clone().into());
+                }
+                result
+                    .units_consumed
+                    .ok_or_else(|| "No compute units returned by simulation".into())
+            }
+            _ => Err("Not a simulation result".into()),
+        }
+    }
 }
 
 /// Extends basic `SendTransaction` trait with function `send` where client is `&mut BanksClient`.
@@ -31,6 +71,15 @@ pub trait SendTransactionBanksClient: SendTransaction {
         client: &'a mut BanksClient,
         transaction: Transaction,
     ) -> BoxFuture<'a, ProgramClientResult<Self::Output>>;
+
+    /// Versioned transactions are not supported unless implemented
+    fn send_versioned<'a>(
+        &self,
+        _client: &'a mut BanksClient,
+        _transaction: VersionedTransaction,
+    ) -> BoxFuture<'a, ProgramClientResult<Self::Output>> {
+        Box::pin(async move { Err("Versioned transactions are not supported".into()) })
+    }
 }
 
 /// Extends basic `SimulateTransaction` trait with function `simulation` where client is `&mut BanksClient`.
@@ -41,6 +90,15 @@ pub trait SimulateTransactionBanksClient: SimulateTransaction {
         client: &'a mut BanksClient,
         transaction: Transaction,
     ) -> BoxFuture<'a, ProgramClientResult<Self::SimulationOutput>>;
+
+    /// Versioned transactions are not supported unless implemented
+    fn simulate_versioned<'a>(
+        &self,
+        _client: &'a mut BanksClient,
+        _transaction: VersionedTransaction,
+    ) -> BoxFuture<'a, ProgramClientResult<Self::SimulationOutput>> {
+        Box::pin(async move { Err("Versioned transactions are not supported".into()) })
+    }
 }
 
 /// Send transaction to validator using `BanksClient::process_transaction`.
@@ -64,6 +122,19 @@ impl SendTransactionBanksClient for ProgramBanksClientProcessTransaction {
                 .map_err(Into::into)
         })
     }
+
+    fn send_versioned<'a>(
+        &self,
+        client: &'a mut BanksClient,
+        transaction: VersionedTransaction,
+    ) -> BoxFuture<'a, ProgramClientResult<Self::Output>> {
+        Box::pin(async move {
+            client
+                .process_transaction(transaction)
+                .await
+                .map_err(Into::into)
+        })
+    }
 }
 
 impl SimulateTransaction for ProgramBanksClientProcessTransaction {
@@ -83,6 +154,19 @@ impl SimulateTransactionBanksClient for ProgramBanksClientProcessTransaction {
                 .map_err(Into::into)
         })
     }
+
+    fn simulate_versioned<'a>(
+        &self,
+        client: &'a mut BanksClient,
+        transaction: VersionedTransaction,
+    ) -> BoxFuture<'a, ProgramClientResult<Self::SimulationOutput>> {
+        Box::pin(async move {
+            client
+                .simulate_transaction(transaction)
+                .await
+                .map_err(Into::into)
+        })
+    }
 }
 
 /// Extends basic `SendTransaction` trait with function `send` where client is `&RpcClient`.
@@ -93,6 +177,15 @@ pub trait SendTransactionRpc: SendTransaction {
         client: &'a RpcClient,
         transaction: &'a Transaction,
     ) -> BoxFuture<'a, ProgramClientResult<Self::Output>>;
+
+    /// Versioned transactions are not supported unless implemented
+    fn send_versioned<'a>(
+        &self,
+        _client: &'a RpcClient,
+        _transaction: &'a VersionedTransaction,
+    ) -> BoxFuture<'a, ProgramClientResult<Self::Output>> {
+        Box::pin(async move { Err("Versioned transactions are not supported".into()) })
+    }
 }
 
 /// Extends basic `SimulateTransaction` trait with function `simulate` where client is `&RpcClient`.
@@ -103,6 +196,15 @@ pub trait SimulateTransactionRpc: SimulateTransaction {
         client: &'a RpcClient,
         transaction: &'a Transaction,
     ) -> BoxFuture<'a, ProgramClientResult<Self::SimulationOutput>>;
+
+    /// Versioned transactions are not supported unless implemented
+    fn simulate_versioned<'a>(
+        &self,
+        _client: &'a RpcClient,
+        _transaction: &'a VersionedTransaction,
+    ) -> BoxFuture<'a, ProgramClientResult<Self::SimulationOutput>> {
+        Box::pin(async move { Err("Versioned transactions are not supported".into()) })
+    }
 }
 
 #[derive(Debug, Clone, Copy, Default)]
@@ -112,6 +214,7 @@ pub struct ProgramRpcClientSendTransaction;
 pub enum RpcClientResponse {
     Signature(Signature),
     Transaction(Transaction),
+    VersionedTransaction(VersionedTransaction),
     Simulation(RpcSimulateTransactionResult),
 }
 
@@ -137,6 +240,28 @@ impl SendTransactionRpc for ProgramRpcClientSendTransaction {
                 .map_err(Into::into)
         })
     }
+
+    fn send_versioned<'a>(
+        &self,
+        client: &'a RpcClient,
+        transaction: &'a VersionedTransaction,
+    ) -> BoxFuture<'a, ProgramClientResult<Self::Output>> {
+        Box::pin(async move {
+            if transaction
+                .signatures
+                .iter()
+                .any(|signature| *signature == Signature::default())
+            {
+                return Err("Cannot send transaction: not fully signed".into());
+            }
+
+            client
+                .send_and_confirm_transaction(transaction)
+                .await
+                .map(RpcClientResponse::Signature)
+                .map_err(Into::into)
+        })
+    }
 }
 
 impl SimulateTransaction for ProgramRpcClientSendTransaction {
@@ -157,6 +282,20 @@ impl SimulateTransactionRpc for ProgramRpcClientSendTransaction {
                 .map_err(Into::into)
         })
     }
+
+    fn simulate_versioned<'a>(
+        &self,
+        client: &'a RpcClient,
+        transaction: &'a VersionedTransaction,
+    ) -> BoxFuture<'a, ProgramClientResult<Self::SimulationOutput>> {
+        Box::pin(async move {
+            client
+                .simulate_transaction(transaction)
+                .await
+                .map(|r| RpcClientResponse::Simulation(r.value))
+                .map_err(Into::into)
+        })
+    }
 }
 
 pub type ProgramClientError = Box<dyn std::error::Error + Send + Sync>;
@@ -175,6 +314,12 @@ where
 
     async fn get_latest_blockhash(&self) -> ProgramClientResult<Hash>;
 
+    /// Only needed to create address lookup tables, so unsupported unless
+    /// implemented
+    async fn get_slot(&self) -> ProgramClientResult<Slot> {
+        Err("Getting the slot is not supported".into())
+    }
+
     async fn send_transaction(&self, transaction: &Transaction) -> ProgramClientResult<ST::Output>;
 
     async fn get_account(&self, address: Pubkey) -> ProgramClientResult<Option<Account>>;
@@ -183,6 +328,22 @@ where
         &self,
         transaction: &Transaction,
     ) -> ProgramClientResult<ST::SimulationOutput>;
+
+    /// Versioned transactions are not supported unless implemented
+    async fn send_versioned_transaction(
+        &self,
+        _transaction: &VersionedTransaction,
+    ) -> ProgramClientResult<ST::Output> {
+        Err("Versioned transactions are not supported".into())
+    }
+
+    /// Versioned transactions are not supported unless implemented
+    async fn simulate_versioned_transaction(
+        &self,
+        _transaction: &VersionedTransaction,
+    ) -> ProgramClientResult<ST::SimulationOutput> {
+        Err("Versioned transactions are not supported".into())
+    }
 }
 
 enum ProgramBanksClientContext {
@@ -257,6 +418,13 @@ where
         .await
     }
 
+    async fn get_slot(&self) -> ProgramClientResult<Slot> {
+        self.run_in_lock(|client| {
+            Box::pin(async move { client.get_root_slot().await.map_err(Into::into) })
+        })
+        .await
+    }
+
     async fn send_transaction(&self, transaction: &Transaction) -> ProgramClientResult<ST::Output> {
         self.run_in_lock(|client| {
             let transaction = transaction.clone();
@@ -276,6 +444,28 @@ where
         .await
     }
 
+    async fn send_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ProgramClientResult<ST::Output> {
+        self.run_in_lock(|client| {
+            let transaction = transaction.clone();
+            self.send.send_versioned(client, transaction)
+        })
+        .await
+    }
+
+    async fn simulate_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ProgramClientResult<ST::SimulationOutput> {
+        self.run_in_lock(|client| {
+            let transaction = transaction.clone();
+            self.send.simulate_versioned(client, transaction)
+        })
+        .await
+    }
+
     async fn get_account(&self, address: Pubkey) -> ProgramClientResult<Option<Account>> {
         self.run_in_lock(|client| {
             Box::pin(async move { client.get_account(address).await.map_err(Into::into) })
@@ -321,6 +511,10 @@ where
         self.client.get_latest_blockhash().await.map_err(Into::into)
     }
 
+    async fn get_slot(&self) -> ProgramClientResult<Slot> {
+        self.client.get_slot().await.map_err(Into::into)
+    }
+
     async fn send_transaction(&self, transaction: &Transaction) -> ProgramClientResult<ST::Output> {
         self.send.send(&self.client, transaction).await
     }
@@ -332,6 +526,22 @@ where
         self.send.simulate(&self.client, transaction).await
     }
 
+    async fn send_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ProgramClientResult<ST::Output> {
+        self.send.send_versioned(&self.client, transaction).await
+    }
+
+    async fn simulate_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ProgramClientResult<ST::SimulationOutput> {
+        self.send
+            .simulate_versioned(&self.client, transaction)
+            .await
+    }
+
     async fn get_account(&self, address: Pubkey) -> ProgramClientResult<Option<Account>> {
         Ok(self
             .client
@@ -381,6 +591,10 @@ where
         Ok(self.blockhash)
     }
 
+    async fn get_slot(&self) -> ProgramClientResult<Slot> {
+        Err("Unable to fetch slot in offline mode".into())
+    }
+
     async fn send_transaction(&self, transaction: &Transaction) -> ProgramClientResult<ST::Output> {
         Ok(RpcClientResponse::Transaction(transaction.clone()))
     }
@@ -392,7 +606,189 @@ where
         Ok(RpcClientResponse::Transaction(transaction.clone()))
     }
 
+    async fn send_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ProgramClientResult<ST::Output> {
+        Ok(RpcClientResponse::VersionedTransaction(transaction.clone()))
+    }
+
+    async fn simulate_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ProgramClientResult<ST::SimulationOutput> {
+        Ok(RpcClientResponse::VersionedTransaction(transaction.clone()))
+    }
+
     async fn get_account(&self, _address: Pubkey) -> ProgramClientResult<Option<Account>> {
         Err("Unable to fetch account in offline mode".into())
     }
 }
+
+/// Instructions built for a single operation instead of being sent, with the
+/// signers they need. The first signer is the fee payer of the `Token`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CollectedInstructions {
+    pub instructions: Vec<Instruction>,
+    pub signers: Vec<Pubkey>,
+}
+
+impl CollectedInstructions {
+    fn from_compiled(
+        header: &MessageHeader,
+        account_keys: &[Pubkey],
+        compiled_instructions: &[CompiledInstruction],
+    ) -> Self {
+        let num_required_signatures = header.num_required_signatures as usize;
+        let num_writable_signed =
+            num_required_signatures.saturating_sub(header.num_readonly_signed_accounts as usize);
+        let num_writable_unsigned = account_keys
+            .len()
+            .saturating_sub(num_required_signatures)
+            .saturating_sub(header.num_readonly_unsigned_accounts as usize);
+        let is_writable = |index: usize| {
+            if index < num_required_signatures {
+                index < num_writable_signed
+            } else {
+                index.saturating_sub(num_required_signatures) < num_writable_unsigned
+            }
+        };
+
+        let instructions = compiled_instructions
+            .iter()
+            .map(|compiled_instruction| Instruction {
+                program_id: account_keys[compiled_instruction.program_id_index as usize],
+                accounts: compiled_instruction
+                    .accounts
+                    .iter()
+                    .map(|index| {
+                        let index = *index as usize;
+                        AccountMeta {
+                            pubkey: account_keys[index],
+                            is_signer: index < num_required_signatures,
+                            is_writable: is_writable(index),
+                        }
+                    })
+                    .collect(),
+                data: compiled_instruction.data.clone(),
+            })
+            .collect();
+
+        Self {
+            instructions,
+            signers: account_keys[..num_required_signatures].to_vec(),
+        }
+    }
+}
+
+impl SimulationResult for CollectedInstructions {
+    fn get_compute_units_consumed(&self) -> ProgramClientResult<u64> {
+        Err("Unable to simulate transactions while collecting instructions".into())
+    }
+}
+
+/// Collect instructions into `CollectedInstructions` instead of sending them.
+/// Required for `ProgramInstructionsClient`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProgramInstructionsClientCollect;
+
+impl SendTransaction for ProgramInstructionsClientCollect {
+    type Output = CollectedInstructions;
+}
+
+impl SimulateTransaction for ProgramInstructionsClientCollect {
+    type SimulationOutput = CollectedInstructions;
+}
+
+/// Program client returning the instructions of each operation instead of
+/// sending them, so they can be combined with other instructions. Accounts
+/// are still read through the inner client.
+pub struct ProgramInstructionsClient<ST> {
+    inner: Arc<dyn ProgramClient<ST> + Send + Sync>,
+}
+
+impl<ST> fmt::Debug for ProgramInstructionsClient<ST> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgramInstructionsClient").finish()
+    }
+}
+
+impl<ST> ProgramInstructionsClient<ST> {
+    pub fn new(inner: Arc<dyn ProgramClient<ST> + Send + Sync>) -> Self {
+        Self { inner }
+    }
+}
+
+#[async_trait]
+impl<ST> ProgramClient<ProgramInstructionsClientCollect> for ProgramInstructionsClient<ST>
+where
+    ST: SendTransaction + SimulateTransaction + Send + Sync,
+{
+    async fn get_minimum_balance_for_rent_exemption(
+        &self,
+        data_len: usize,
+    ) -> ProgramClientResult<u64> {
+        self.inner
+            .get_minimum_balance_for_rent_exemption(data_len)
+            .await
+    }
+
+    async fn get_latest_blockhash(&self) -> ProgramClientResult<Hash> {
+        self.inner.get_latest_blockhash().await
+    }
+
+    async fn get_slot(&self) -> ProgramClientResult<Slot> {
+        self.inner.get_slot().await
+    }
+
+    async fn send_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> ProgramClientResult<CollectedInstructions> {
+        let message = &transaction.message;
+        Ok(CollectedInstructions::from_compiled(
+            &message.header,
+            &message.account_keys,
+            &message.instructions,
+        ))
+    }
+
+    async fn get_account(&self, address: Pubkey) -> ProgramClientResult<Option<Account>> {
+        self.inner.get_account(address).await
+    }
+
+    async fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> ProgramClientResult<CollectedInstructions> {
+        self.send_transaction(transaction).await
+    }
+
+    async fn send_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ProgramClientResult<CollectedInstructions> {
+        let message = &transaction.message;
+        if message
+            .address_table_lookups()
+            .map(|lookups| !lookups.is_empty())
+            .unwrap_or(false)
+        {
+            return Err(
+                "Unable to collect instructions loading accounts from lookup tables".into(),
+            );
+        }
+        Ok(CollectedInstructions::from_compiled(
+            message.header(),
+            message.static_account_keys(),
+            message.instructions(),
+        ))
+    }
+
+    async fn simulate_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ProgramClientResult<CollectedInstructions> {
+        self.send_versioned_transaction(transaction).await
+    }
+}
@@ -1,5 +1,6 @@
 #![allow(clippy::integer_arithmetic)]
 pub mod client;
+pub mod mock;
 pub mod output;
 pub mod token;
 
@@ -0,0 +1,700 @@
+//! In-memory program client, running the token programs natively.
+//!
+//! Accounts live in a map, and every instruction of a transaction is executed
+//! by calling the program's processor directly, without a bank or validator.
+//! Cross-program invocations, return data, logs, and the clock and rent
+//! sysvars are provided through the `solana_program` syscall stubs, which are
+//! installed for the duration of each transaction.
+//!
+//! The mock only checks what programs check themselves, plus signatures and
+//! privilege escalation on cross-program invocations. In particular, it does
+//! not meter compute units, nor enforce the runtime's rules on account
+//! ownership, rent and lamport balance.
+
+use {
+    crate::client::{
+        ProgramClient, ProgramClientResult, SendTransaction, SimulateTransaction, SimulationResult,
+    },
+    async_trait::async_trait,
+    solana_sdk::{
+        account::{create_account_for_test, Account},
+        account_info::AccountInfo,
+        clock::{Clock, Epoch, Slot},
+        compute_budget,
+        entrypoint::{ProcessInstruction, ProgramResult, MAX_PERMITTED_DATA_INCREASE, SUCCESS},
+        hash::Hash,
+        instruction::{CompiledInstruction, Instruction, InstructionError},
+        message::{Message, VersionedMessage},
+        native_loader,
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        rent::Rent,
+        signature::Signature,
+        system_instruction::{SystemError, SystemInstruction},
+        system_program, sysvar,
+        transaction::{self, Transaction, TransactionError, VersionedTransaction},
+        transport::TransportError,
+    },
+    spl_token_2022::solana_program::program_stubs::{set_syscall_stubs, SyscallStubs},
+    std::{
+        collections::HashMap,
+        fmt,
+        mem::size_of,
+        slice,
+        sync::{Mutex, RwLock},
+    },
+};
+
+/// Fee charged to the fee payer for every signature
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+
+/// Transactions executed by `ProgramMockClient` return their signature
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ProgramMockClientProcessTransaction;
+
+impl SendTransaction for ProgramMockClientProcessTransaction {
+    type Output = Signature;
+}
+
+impl SimulateTransaction for ProgramMockClientProcessTransaction {
+    type SimulationOutput = MockSimulationResult;
+}
+
+/// Outcome of a transaction simulated by `ProgramMockClient`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockSimulationResult {
+    pub result: transaction::Result<()>,
+    pub logs: Vec<String>,
+}
+
+impl SimulationResult for MockSimulationResult {
+    fn get_compute_units_consumed(&self) -> ProgramClientResult<u64> {
+        self.result.clone()?;
+        Err("Compute units are not metered by the mock client".into())
+    }
+}
+
+/// Program client keeping accounts in memory and executing instructions with
+/// the programs' processors.
+///
+/// The system, compute budget, token, token-2022, associated token account
+/// and memo programs are available by default, and other programs, such as
+/// transfer hooks, can be added with `with_program`. Every account starts out
+/// empty, so fee payers must be funded with `airdrop` first.
+pub struct ProgramMockClient {
+    accounts: RwLock<HashMap<Pubkey, Account>>,
+    programs: HashMap<Pubkey, ProcessInstruction>,
+    clock: RwLock<Clock>,
+}
+
+impl fmt::Debug for ProgramMockClient {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgramMockClient")
+            .field("programs", &self.programs.keys())
+            .field("clock", &self.clock.read().unwrap())
+            .finish()
+    }
+}
+
+impl Default for ProgramMockClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProgramMockClient {
+    pub fn new() -> Self {
+        Self {
+            accounts: RwLock::new(HashMap::new()),
+            programs: HashMap::new(),
+            clock: RwLock::new(Clock::default()),
+        }
+        .with_program(system_program::id(), process_system_instruction)
+        .with_program(compute_budget::id(), process_compute_budget_instruction)
+        .with_program(spl_token::id(), spl_token::processor::Processor::process)
+        .with_program(
+            spl_token_2022::id(),
+            spl_token_2022::processor::Processor::process,
+        )
+        .with_program(
+            spl_associated_token_account::id(),
+            spl_associated_token_account::processor::process_instruction,
+        )
+        .with_program(spl_memo::id(), spl_memo::processor::process_instruction)
+    }
+
+    /// Execute instructions for `program_id` with the given processor,
+    /// usually the one the program's entrypoint calls
+    pub fn with_program(
+        mut self,
+        program_id: Pubkey,
+        process_instruction: ProcessInstruction,
+    ) -> Self {
+        self.programs.insert(program_id, process_instruction);
+        self
+    }
+
+    /// Overwrite an account, or remove it if it has no lamports
+    pub fn set_account(&self, address: &Pubkey, account: Account) {
+        let mut accounts = self.accounts.write().unwrap();
+        if account.lamports == 0 {
+            accounts.remove(address);
+        } else {
+            accounts.insert(*address, account);
+        }
+    }
+
+    /// Add lamports to an account, creating it as a system account if needed
+    pub fn airdrop(&self, address: &Pubkey, lamports: u64) {
+        let mut accounts = self.accounts.write().unwrap();
+        let account = accounts.entry(*address).or_default();
+        account.lamports = account.lamports.saturating_add(lamports);
+    }
+
+    /// Set the clock seen by programs, and the slot returned by `get_slot`
+    pub fn set_clock(&self, clock: Clock) {
+        *self.clock.write().unwrap() = clock;
+    }
+
+    /// Execute a transaction whose signatures were already verified, keeping
+    /// its changes only if `commit` is set. As on-chain, the fee is charged
+    /// even if an instruction fails.
+    fn execute(&self, message: &Message, commit: bool) -> MockSimulationResult {
+        let _execution = EXECUTION_LOCK
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let mut accounts = self.accounts.read().unwrap().clone();
+        let fee_payer = message.account_keys[0];
+        let fee = LAMPORTS_PER_SIGNATURE * u64::from(message.header.num_required_signatures);
+        let Some(fee_payer_account) = accounts.get_mut(&fee_payer) else {
+            return MockSimulationResult {
+                result: Err(TransactionError::AccountNotFound),
+                logs: vec![],
+            };
+        };
+        if fee_payer_account.lamports < fee {
+            return MockSimulationResult {
+                result: Err(TransactionError::InsufficientFundsForFee),
+                logs: vec![],
+            };
+        }
+        fee_payer_account.lamports -= fee;
+        let fee_payer_account = fee_payer_account.clone();
+
+        *RUNTIME.lock().unwrap() = Some(MockRuntime {
+            programs: self.programs.clone(),
+            clock: self.clock.read().unwrap().clone(),
+            invoke_stack: vec![],
+            return_data: None,
+            logs: vec![],
+        });
+        let stubs = SyscallStubsGuard::install();
+        let mut result = Ok(());
+        for (index, instruction) in message.instructions.iter().enumerate() {
+            if let Err(error) = process_top_level_instruction(&mut accounts, message, instruction) {
+                result = Err(TransactionError::InstructionError(index as u8, error));
+                break;
+            }
+        }
+        drop(stubs);
+        let logs = RUNTIME.lock().unwrap().take().unwrap().logs;
+
+        if commit {
+            let mut stored_accounts = self.accounts.write().unwrap();
+            if result.is_ok() {
+                accounts.retain(|_, account| account.lamports > 0);
+                *stored_accounts = accounts;
+            } else if fee_payer_account.lamports > 0 {
+                stored_accounts.insert(fee_payer, fee_payer_account);
+            } else {
+                stored_accounts.remove(&fee_payer);
+            }
+        }
+        MockSimulationResult { result, logs }
+    }
+}
+
+#[async_trait]
+impl ProgramClient<ProgramMockClientProcessTransaction> for ProgramMockClient {
+    async fn get_minimum_balance_for_rent_exemption(
+        &self,
+        data_len: usize,
+    ) -> ProgramClientResult<u64> {
+        Ok(Rent::default().minimum_balance(data_len))
+    }
+
+    async fn get_latest_blockhash(&self) -> ProgramClientResult<Hash> {
+        Ok(Hash::default())
+    }
+
+    async fn get_slot(&self) -> ProgramClientResult<Slot> {
+        Ok(self.clock.read().unwrap().slot)
+    }
+
+    async fn send_transaction(&self, transaction: &Transaction) -> ProgramClientResult<Signature> {
+        verify_signatures(
+            &transaction.signatures,
+            &transaction.message,
+            &transaction.message_data(),
+        )?;
+        self.execute(&transaction.message, true)
+            .result
+            .map_err(TransportError::from)?;
+        Ok(transaction.signatures[0])
+    }
+
+    async fn get_account(&self, address: Pubkey) -> ProgramClientResult<Option<Account>> {
+        Ok(self.accounts.read().unwrap().get(&address).cloned())
+    }
+
+    async fn simulate_transaction(
+        &self,
+        transaction: &Transaction,
+    ) -> ProgramClientResult<MockSimulationResult> {
+        Ok(self.execute(&transaction.message, false))
+    }
+
+    async fn send_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ProgramClientResult<Signature> {
+        let message = legacy_message(transaction)?;
+        verify_signatures(
+            &transaction.signatures,
+            &message,
+            &transaction.message.serialize(),
+        )?;
+        self.execute(&message, true)
+            .result
+            .map_err(TransportError::from)?;
+        Ok(transaction.signatures[0])
+    }
+
+    async fn simulate_versioned_transaction(
+        &self,
+        transaction: &VersionedTransaction,
+    ) -> ProgramClientResult<MockSimulationResult> {
+        let message = legacy_message(transaction)?;
+        Ok(self.execute(&message, false))
+    }
+}
+
+/// Get the message of a versioned transaction as a legacy message, which is
+/// only possible if it doesn't load accounts from lookup tables
+fn legacy_message(transaction: &VersionedTransaction) -> ProgramClientResult<Message> {
+    match &transaction.message {
+        VersionedMessage::Legacy(message) => Ok(message.clone()),
+        VersionedMessage::V0(message) if message.address_table_lookups.is_empty() => Ok(Message {
+            header: message.header,
+            account_keys: message.account_keys.clone(),
+            recent_blockhash: message.recent_blockhash,
+            instructions: message.instructions.clone(),
+        }),
+        VersionedMessage::V0(_) => {
+            Err("Address lookup tables are not supported by the mock client".into())
+        }
+    }
+}
+
+fn verify_signatures(
+    signatures: &[Signature],
+    message: &Message,
+    message_data: &[u8],
+) -> Result<(), TransportError> {
+    let verified = signatures.len() == message.header.num_required_signatures as usize
+        && signatures
+            .iter()
+            .zip(&message.account_keys)
+            .all(|(signature, pubkey)| signature.verify(pubkey.as_ref(), message_data));
+    if verified {
+        Ok(())
+    } else {
+        Err(TransactionError::SignatureFailure.into())
+    }
+}
+
+/// Serializes transactions, since the syscall stubs are global
+static EXECUTION_LOCK: Mutex<()> = Mutex::new(());
+
+/// State of the transaction being executed, used by the syscall stubs
+static RUNTIME: Mutex<Option<MockRuntime>> = Mutex::new(None);
+
+struct MockRuntime {
+    programs: HashMap<Pubkey, ProcessInstruction>,
+    clock: Clock,
+    invoke_stack: Vec<Pubkey>,
+    return_data: Option<(Pubkey, Vec<u8>)>,
+    logs: Vec<String>,
+}
+
+fn with_runtime<R>(f: impl FnOnce(&mut MockRuntime) -> R) -> R {
+    let mut runtime = RUNTIME.lock().unwrap();
+    f(runtime
+        .as_mut()
+        .expect("No transaction executed by the mock client"))
+}
+
+/// Installs the mock's syscall stubs, restoring the previous ones when dropped
+struct SyscallStubsGuard(Option<Box<dyn SyscallStubs>>);
+
+impl SyscallStubsGuard {
+    fn install() -> Self {
+        Self(Some(set_syscall_stubs(Box::new(MockSyscallStubs))))
+    }
+}
+
+impl Drop for SyscallStubsGuard {
+    fn drop(&mut self) {
+        if let Some(previous) = self.0.take() {
+            set_syscall_stubs(previous);
+        }
+    }
+}
+
+struct MockSyscallStubs;
+
+impl SyscallStubs for MockSyscallStubs {
+    fn sol_log(&self, message: &str) {
+        with_runtime(|runtime| runtime.logs.push(format!("Program log: {}", message)));
+    }
+
+    fn sol_invoke_signed(
+        &self,
+        instruction: &Instruction,
+        account_infos: &[AccountInfo],
+        signers_seeds: &[&[&[u8]]],
+    ) -> ProgramResult {
+        let caller = with_runtime(|runtime| *runtime.invoke_stack.last().unwrap());
+        let signers = signers_seeds
+            .iter()
+            .map(|seeds| Pubkey::create_program_address(seeds, &caller))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| ProgramError::InvalidSeeds)?;
+
+        let callee_account_infos = instruction
+            .accounts
+            .iter()
+            .map(|meta| {
+                let account_info = account_infos
+                    .iter()
+                    .find(|account_info| *account_info.key == meta.pubkey)
+                    .ok_or(ProgramError::NotEnoughAccountKeys)?;
+                if meta.is_signer && !account_info.is_signer && !signers.contains(&meta.pubkey) {
+                    return Err(ProgramError::MissingRequiredSignature);
+                }
+                if meta.is_writable && !account_info.is_writable {
+                    return Err(ProgramError::InvalidArgument);
+                }
+                let mut account_info = account_info.clone();
+                account_info.is_signer = meta.is_signer;
+                account_info.is_writable = meta.is_writable;
+                Ok(account_info)
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        invoke_program(
+            &instruction.program_id,
+            &callee_account_infos,
+            &instruction.data,
+        )
+    }
+
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        let clock = with_runtime(|runtime| runtime.clock.clone());
+        unsafe {
+            *(var_addr as *mut Clock) = clock;
+        }
+        SUCCESS
+    }
+
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        unsafe {
+            *(var_addr as *mut Rent) = Rent::default();
+        }
+        SUCCESS
+    }
+
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        with_runtime(|runtime| runtime.return_data.clone())
+    }
+
+    fn sol_set_return_data(&self, data: &[u8]) {
+        with_runtime(|runtime| {
+            runtime.return_data = if data.is_empty() {
+                None
+            } else {
+                Some((*runtime.invoke_stack.last().unwrap(), data.to_vec()))
+            };
+        });
+    }
+
+    fn sol_get_stack_height(&self) -> u64 {
+        with_runtime(|runtime| runtime.invoke_stack.len() as u64)
+    }
+}
+
+fn invoke_program(
+    program_id: &Pubkey,
+    account_infos: &[AccountInfo],
+    data: &[u8],
+) -> ProgramResult {
+    let process_instruction = with_runtime(|runtime| {
+        runtime.invoke_stack.push(*program_id);
+        runtime.programs.get(program_id).copied()
+    });
+    let result = match process_instruction {
+        Some(process_instruction) => process_instruction(program_id, account_infos, data),
+        None => Err(ProgramError::IncorrectProgramId),
+    };
+    with_runtime(|runtime| runtime.invoke_stack.pop());
+    result
+}
+
+/// Execute a transaction's instruction, writing back the writable accounts
+fn process_top_level_instruction(
+    accounts: &mut HashMap<Pubkey, Account>,
+    message: &Message,
+    instruction: &CompiledInstruction,
+) -> Result<(), InstructionError> {
+    let program_id = message.account_keys[instruction.program_id_index as usize];
+
+    let mut indices: Vec<usize> = vec![];
+    for index in &instruction.accounts {
+        if !indices.contains(&(*index as usize)) {
+            indices.push(*index as usize);
+        }
+    }
+    let mut serialized_accounts = indices
+        .iter()
+        .map(|index| {
+            let key = &message.account_keys[*index];
+            let account = accounts
+                .get(key)
+                .cloned()
+                .unwrap_or_else(|| missing_account(key));
+            SerializedAccount::new(key, &account)
+        })
+        .collect::<Vec<_>>();
+    let unique_account_infos = serialized_accounts
+        .iter_mut()
+        .zip(&indices)
+        .map(|(account, index)| {
+            account.account_info(message.is_signer(*index), message.is_writable(*index))
+        })
+        .collect::<Vec<_>>();
+    let account_infos = instruction
+        .accounts
+        .iter()
+        .map(|index| {
+            let position = indices.iter().position(|i| *i == *index as usize).unwrap();
+            unique_account_infos[position].clone()
+        })
+        .collect::<Vec<_>>();
+
+    with_runtime(|runtime| runtime.return_data = None);
+    invoke_program(&program_id, &account_infos, &instruction.data)
+        .map_err(|error| InstructionError::from(u64::from(error)))?;
+
+    for (account_info, index) in unique_account_infos.iter().zip(&indices) {
+        if message.is_writable(*index) {
+            accounts.insert(
+                *account_info.key,
+                Account {
+                    lamports: account_info.lamports(),
+                    data: account_info.data.borrow().to_vec(),
+                    owner: *account_info.owner,
+                    executable: account_info.executable,
+                    rent_epoch: account_info.rent_epoch,
+                },
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Account used for an address not in the map: a sysvar, a program, or an
+/// empty system account
+fn missing_account(address: &Pubkey) -> Account {
+    with_runtime(|runtime| {
+        if *address == sysvar::rent::id() {
+            create_account_for_test(&Rent::default())
+        } else if *address == sysvar::clock::id() {
+            create_account_for_test(&runtime.clock)
+        } else if runtime.programs.contains_key(address) {
+            Account {
+                lamports: 1,
+                owner: native_loader::id(),
+                executable: true,
+                ..Account::default()
+            }
+        } else {
+            Account::default()
+        }
+    })
+}
+
+/// Account laid out the way the runtime serializes it for programs: the
+/// original data length right before the key, and the current data length
+/// right before the data, followed by room for the data to grow. This is what
+/// `AccountInfo::realloc` expects, so programs can resize accounts.
+struct SerializedAccount {
+    key: Vec<u32>,
+    lamports: u64,
+    data: Vec<u64>,
+    owner: Pubkey,
+    executable: bool,
+    rent_epoch: Epoch,
+}
+
+impl SerializedAccount {
+    fn new(key: &Pubkey, account: &Account) -> Self {
+        let data_len = account.data.len();
+
+        let mut serialized_key = vec![0u32; 1 + size_of::<Pubkey>() / size_of::<u32>()];
+        serialized_key[0] = data_len as u32;
+        as_bytes_mut(&mut serialized_key)[size_of::<u32>()..].copy_from_slice(key.as_ref());
+
+        let data_words =
+            (data_len + MAX_PERMITTED_DATA_INCREASE + size_of::<u64>() - 1) / size_of::<u64>();
+        let mut data = vec![0u64; 1 + data_words];
+        data[0] = data_len as u64;
+        as_bytes_mut(&mut data)[size_of::<u64>()..size_of::<u64>() + data_len]
+            .copy_from_slice(&account.data);
+
+        Self {
+            key: serialized_key,
+            lamports: account.lamports,
+            data,
+            owner: account.owner,
+            executable: account.executable,
+            rent_epoch: account.rent_epoch,
+        }
+    }
+
+    fn account_info(&mut self, is_signer: bool, is_writable: bool) -> AccountInfo<'_> {
+        let data_len = self.data[0] as usize;
+        // SAFETY: both buffers are large enough and aligned, and only borrowed
+        // through the returned account info
+        let (key, data) = unsafe {
+            (
+                &*(self.key.as_ptr().add(1) as *const Pubkey),
+                slice::from_raw_parts_mut(self.data.as_mut_ptr().add(1) as *mut u8, data_len),
+            )
+        };
+        AccountInfo::new(
+            key,
+            is_signer,
+            is_writable,
+            &mut self.lamports,
+            data,
+            &self.owner,
+            self.executable,
+            self.rent_epoch,
+        )
+    }
+}
+
+fn as_bytes_mut<T: Copy>(words: &mut [T]) -> &mut [u8] {
+    // SAFETY: only used on plain integers, which have no padding
+    unsafe {
+        slice::from_raw_parts_mut(words.as_mut_ptr() as *mut u8, std::mem::size_of_val(words))
+    }
+}
+
+/// Subset of the system program used to create and fund accounts
+fn process_system_instruction(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    let instruction: SystemInstruction =
+        bincode::deserialize(input).map_err(|_| ProgramError::InvalidInstructionData)?;
+    match instruction {
+        SystemInstruction::CreateAccount {
+            lamports,
+            space,
+            owner,
+        } => {
+            let [from, to, ..] = accounts else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            if to.lamports() > 0 || !to.data_is_empty() || *to.owner != system_program::id() {
+                return Err(ProgramError::Custom(
+                    SystemError::AccountAlreadyInUse as u32,
+                ));
+            }
+            system_transfer(from, to, lamports)?;
+            system_allocate(to, space)?;
+            to.assign(&owner);
+            Ok(())
+        }
+        SystemInstruction::Assign { owner } => {
+            let [account, ..] = accounts else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            if !account.is_signer {
+                return Err(ProgramError::MissingRequiredSignature);
+            }
+            account.assign(&owner);
+            Ok(())
+        }
+        SystemInstruction::Transfer { lamports } => {
+            let [from, to, ..] = accounts else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            system_transfer(from, to, lamports)
+        }
+        SystemInstruction::Allocate { space } => {
+            let [account, ..] = accounts else {
+                return Err(ProgramError::NotEnoughAccountKeys);
+            };
+            system_allocate(account, space)
+        }
+        _ => Err(ProgramError::InvalidInstructionData),
+    }
+}
+
+fn system_transfer(from: &AccountInfo, to: &AccountInfo, lamports: u64) -> ProgramResult {
+    if !from.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !from.data_is_empty() || *from.owner != system_program::id() {
+        return Err(ProgramError::InvalidArgument);
+    }
+    let from_lamports = from
+        .lamports()
+        .checked_sub(lamports)
+        .ok_or(ProgramError::Custom(
+            SystemError::ResultWithNegativeLamports as u32,
+        ))?;
+    **from.try_borrow_mut_lamports()? = from_lamports;
+    let to_lamports = to
+        .lamports()
+        .checked_add(lamports)
+        .ok_or(ProgramError::InvalidArgument)?;
+    **to.try_borrow_mut_lamports()? = to_lamports;
+    Ok(())
+}
+
+fn system_allocate(account: &AccountInfo, space: u64) -> ProgramResult {
+    if !account.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if !account.data_is_empty() || *account.owner != system_program::id() {
+        return Err(ProgramError::Custom(
+            SystemError::AccountAlreadyInUse as u32,
+        ));
+    }
+    let space = usize::try_from(space).map_err(|_| ProgramError::InvalidArgument)?;
+    account.realloc(space, true)
+}
+
+/// Compute budget instructions only matter to the runtime
+fn process_compute_budget_instruction(
+    _program_id: &Pubkey,
+    _accounts: &[AccountInfo],
+    _input: &[u8],
+) -> ProgramResult {
+    Ok(())
+}
@@ -1,18 +1,27 @@
 use {
-    crate::client::{ProgramClient, ProgramClientError, SendTransaction, SimulateTransaction},
-    futures_util::TryFutureExt,
+    crate::client::{
+        ProgramClient, ProgramClientError, SendTransaction, SimulateTransaction, SimulationResult,
+    },
+    solana_address_lookup_table_program::{
+        instruction::{create_lookup_table, extend_lookup_table},
+        state::{AddressLookupTable, LOOKUP_TABLE_MAX_ADDRESSES},
+    },
     solana_program_test::tokio::time,
     solana_sdk::{
         account::Account as BaseAccount,
+        address_lookup_table_account::AddressLookupTableAccount,
+        compute_budget::ComputeBudgetInstruction,
         hash::Hash,
         instruction::{AccountMeta, Instruction},
-        message::Message,
+        message::{v0, Message, VersionedMessage},
+        packet::PACKET_DATA_SIZE,
         program_error::ProgramError,
         program_pack::Pack,
         pubkey::Pubkey,
+        signature::Signature,
         signer::{signers::Signers, Signer, SignerError},
         system_instruction,
-        transaction::Transaction,
+        transaction::{Transaction, VersionedTransaction},
     },
     spl_associated_token_account::{
         get_associated_token_address_with_program_id, instruction::create_associated_token_account,
@@ -32,7 +41,7 @@ use {
             memo_transfer, metadata_pointer, transfer_fee, transfer_hook, BaseStateWithExtensions,
             ExtensionType, StateWithExtensionsOwned,
         },
-        instruction, offchain,
+        instruction,
         proof::ProofLocation,
         solana_zk_token_sdk::{
             encryption::{
@@ -43,8 +52,16 @@ use {
         },
         state::{Account, AccountState, Mint, Multisig},
     },
-    spl_token_metadata_interface::state::{Field, TokenMetadata},
+    spl_token_metadata_interface::state::{Field, FieldLocks, FieldUpdate, TokenMetadata},
+    spl_transfer_hook_interface::{
+        get_extra_account_metas_address,
+        offchain::{
+            add_extra_accounts_for_execute, add_extra_accounts_for_execute_on_burn,
+            add_extra_accounts_for_execute_on_mint,
+        },
+    },
     std::{
+        collections::HashMap,
         fmt, io,
         sync::{Arc, RwLock},
         time::{Duration, Instant},
@@ -153,6 +170,10 @@ pub enum ExtensionInitializationParams {
         authority: Option<Pubkey>,
         program_id: Option<Pubkey>,
     },
+    TransferHookEvents {
+        hook_mint: bool,
+        hook_burn: bool,
+    },
     MetadataPointer {
         authority: Option<Pubkey>,
         metadata_address: Option<Pubkey>,
@@ -174,6 +195,7 @@ impl ExtensionInitializationParams {
             Self::NonTransferable => ExtensionType::NonTransferable,
             Self::PermanentDelegate { .. } => ExtensionType::PermanentDelegate,
             Self::TransferHook { .. } => ExtensionType::TransferHook,
+            Self::TransferHookEvents { .. } => ExtensionType::TransferHookEvents,
             Self::MetadataPointer { .. } => ExtensionType::MetadataPointer,
             Self::ConfidentialTransferFeeConfig { .. } => {
                 ExtensionType::ConfidentialTransferFeeConfig
@@ -249,6 +271,15 @@ impl ExtensionInitializationParams {
                 authority,
                 program_id,
             ),
+            Self::TransferHookEvents {
+                hook_mint,
+                hook_burn,
+            } => transfer_hook::instruction::initialize_events(
+                token_program_id,
+                mint,
+                hook_mint,
+                hook_burn,
+            ),
             Self::MetadataPointer {
                 authority,
                 metadata_address,
@@ -275,6 +306,80 @@ impl ExtensionInitializationParams {
 
 pub type TokenResult<T> = Result<T, TokenError>;
 
+/// Highest compute unit limit a transaction may request
+const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+
+/// How the compute unit limit of a transaction is set
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ComputeUnitLimit {
+    /// Don't request a limit, leaving the runtime default
+    #[default]
+    Default,
+    /// Request a fixed limit
+    Static(u32),
+    /// Simulate the transaction first, and request the units it consumed
+    /// plus a margin, as a percentage of the consumed units
+    Simulated { margin_percent: u32 },
+}
+
+/// Compute budget instructions added to every transaction
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ComputeBudget {
+    pub unit_limit: ComputeUnitLimit,
+    /// Priority fee, in micro-lamports per compute unit
+    pub unit_price: Option<u64>,
+}
+impl ComputeBudget {
+    fn instructions(&self, unit_limit: Option<u32>) -> Vec<Instruction> {
+        let mut instructions = vec![];
+        if let Some(unit_limit) = unit_limit {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_limit(unit_limit));
+        }
+        if let Some(unit_price) = self.unit_price {
+            instructions.push(ComputeBudgetInstruction::set_compute_unit_price(unit_price));
+        }
+        instructions
+    }
+}
+
+/// Number of addresses added per `ExtendLookupTable` instruction, small
+/// enough to fit in a single transaction
+const MAX_ADDRESSES_PER_LOOKUP_TABLE_EXTEND: usize = 20;
+
+/// Transfer hook validation account data, by mint and hook program.
+///
+/// Clones share the same entries, so one cache can be given to several
+/// `Token`s. Entries are never refreshed on their own: invalidate a mint
+/// after its extra account metas are changed.
+#[derive(Clone, Debug, Default)]
+pub struct ExtraAccountMetasCache(Arc<RwLock<HashMap<(Pubkey, Pubkey), Vec<u8>>>>);
+impl ExtraAccountMetasCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Drop the entries of a mint, for every hook program
+    pub fn invalidate(&self, mint: &Pubkey) {
+        self.0
+            .write()
+            .unwrap()
+            .retain(|(entry_mint, _), _| entry_mint != mint);
+    }
+
+    /// Drop every entry
+    pub fn clear(&self) {
+        self.0.write().unwrap().clear();
+    }
+
+    fn get(&self, mint: &Pubkey, program_id: &Pubkey) -> Option<Vec<u8>> {
+        self.0.read().unwrap().get(&(*mint, *program_id)).cloned()
+    }
+
+    fn insert(&self, mint: &Pubkey, program_id: &Pubkey, data: Vec<u8>) {
+        self.0.write().unwrap().insert((*mint, *program_id), data);
+    }
+}
+
 #[derive(Debug)]
 struct TokenMemo {
     text: String,
@@ -300,6 +405,14 @@ pub struct Token<T> {
     nonce_blockhash: Option<Hash>,
     memo: Arc<RwLock<Option<TokenMemo>>>,
     transfer_hook_accounts: Option<Vec<AccountMeta>>,
+    mint_hook_accounts: Option<Vec<AccountMeta>>,
+    burn_hook_accounts: Option<Vec<AccountMeta>>,
+    extra_account_metas_cache: ExtraAccountMetasCache,
+    address_lookup_tables: Option<Vec<AddressLookupTableAccount>>,
+    auto_address_lookup_tables: bool,
+    created_address_lookup_tables: Arc<RwLock<Vec<AddressLookupTableAccount>>>,
+    compute_budget: ComputeBudget,
+    compute_budget_override: Arc<RwLock<Option<ComputeBudget>>>,
 }
 
 impl<T> fmt::Debug for Token<T> {
@@ -317,6 +430,23 @@ impl<T> fmt::Debug for Token<T> {
             .field("nonce_blockhash", &self.nonce_blockhash)
             .field("memo", &self.memo.read().unwrap())
             .field("transfer_hook_accounts", &self.transfer_hook_accounts)
+            .field("mint_hook_accounts", &self.mint_hook_accounts)
+            .field("burn_hook_accounts", &self.burn_hook_accounts)
+            .field("extra_account_metas_cache", &self.extra_account_metas_cache)
+            .field("address_lookup_tables", &self.address_lookup_tables)
+            .field(
+                "auto_address_lookup_tables",
+                &self.auto_address_lookup_tables,
+            )
+            .field(
+                "created_address_lookup_tables",
+                &self.created_address_lookup_tables.read().unwrap(),
+            )
+            .field("compute_budget", &self.compute_budget)
+            .field(
+                "compute_budget_override",
+                &self.compute_budget_override.read().unwrap(),
+            )
             .finish()
     }
 }
@@ -363,6 +493,14 @@ where
             nonce_blockhash: None,
             memo: Arc::new(RwLock::new(None)),
             transfer_hook_accounts: None,
+            mint_hook_accounts: None,
+            burn_hook_accounts: None,
+            extra_account_metas_cache: ExtraAccountMetasCache::new(),
+            address_lookup_tables: None,
+            auto_address_lookup_tables: false,
+            created_address_lookup_tables: Arc::new(RwLock::new(vec![])),
+            compute_budget: ComputeBudget::default(),
+            compute_budget_override: Arc::new(RwLock::new(None)),
         }
     }
 
@@ -404,6 +542,8 @@ where
         self.nonce_authority = Some(nonce_authority);
         self.nonce_blockhash = Some(*nonce_blockhash);
         self.transfer_hook_accounts = Some(vec![]);
+        self.mint_hook_accounts = Some(vec![]);
+        self.burn_hook_accounts = Some(vec![]);
         self
     }
 
@@ -412,6 +552,76 @@ where
         self
     }
 
+    /// Use the given accounts for the hook invoked on mints, instead of
+    /// resolving them from the hook's validation account
+    pub fn with_mint_hook_accounts(mut self, mint_hook_accounts: Vec<AccountMeta>) -> Self {
+        self.mint_hook_accounts = Some(mint_hook_accounts);
+        self
+    }
+
+    /// Use the given accounts for the hook invoked on burns, instead of
+    /// resolving them from the hook's validation account
+    pub fn with_burn_hook_accounts(mut self, burn_hook_accounts: Vec<AccountMeta>) -> Self {
+        self.burn_hook_accounts = Some(burn_hook_accounts);
+        self
+    }
+
+    /// Share a cache of transfer hook validation data with other `Token`s,
+    /// instead of the one created for this `Token`
+    pub fn with_extra_account_metas_cache(
+        mut self,
+        extra_account_metas_cache: ExtraAccountMetasCache,
+    ) -> Self {
+        self.extra_account_metas_cache = extra_account_metas_cache;
+        self
+    }
+
+    /// Cache of the transfer hook validation data used by this `Token`
+    pub fn extra_account_metas_cache(&self) -> &ExtraAccountMetasCache {
+        &self.extra_account_metas_cache
+    }
+
+    /// Send v0 transactions that load accounts through the given lookup
+    /// tables, instead of legacy transactions. An empty list still switches
+    /// to v0 transactions.
+    pub fn with_address_lookup_tables(
+        mut self,
+        address_lookup_tables: Vec<AddressLookupTableAccount>,
+    ) -> Self {
+        self.address_lookup_tables = Some(address_lookup_tables);
+        self
+    }
+
+    /// Send transactions that are too large for a legacy transaction as v0
+    /// transactions instead, loading their accounts through a lookup table
+    /// created for them and owned by the payer. Created tables are reused by
+    /// later transactions needing the same accounts.
+    ///
+    /// Ignored if lookup tables are given through `with_address_lookup_tables`.
+    pub fn with_auto_address_lookup_tables(mut self) -> Self {
+        self.auto_address_lookup_tables = true;
+        self
+    }
+
+    /// Lookup tables created for transactions too large for a legacy
+    /// transaction, see `with_auto_address_lookup_tables`
+    pub fn created_address_lookup_tables(&self) -> Vec<AddressLookupTableAccount> {
+        self.created_address_lookup_tables.read().unwrap().clone()
+    }
+
+    /// Add compute budget instructions to every transaction
+    pub fn with_compute_budget(mut self, compute_budget: ComputeBudget) -> Self {
+        self.compute_budget = compute_budget;
+        self
+    }
+
+    /// Use a different compute budget for the next transaction only
+    pub fn with_compute_budget_override(&self, compute_budget: ComputeBudget) -> &Self {
+        let mut w_compute_budget_override = self.compute_budget_override.write().unwrap();
+        *w_compute_budget_override = Some(compute_budget);
+        self
+    }
+
     pub fn with_memo<M: AsRef<str>>(&self, memo: M, signers: Vec<Pubkey>) -> &Self {
         let mut w_memo = self.memo.write().unwrap();
         *w_memo = Some(TokenMemo {
@@ -466,32 +676,76 @@ where
         }
     }
 
-    async fn construct_tx<S: Signers>(
+    fn take_memo_instruction<S: Signers>(
+        &self,
+        signing_keypairs: &S,
+    ) -> TokenResult<Option<Instruction>> {
+        let mut w_memo = self.memo.write().unwrap();
+        if let Some(memo) = w_memo.take() {
+            let signing_pubkeys = signing_keypairs.pubkeys();
+            if !memo
+                .signers
+                .iter()
+                .all(|signer| signing_pubkeys.contains(signer))
+            {
+                return Err(TokenError::MissingMemoSigner);
+            }
+
+            Ok(Some(memo.to_instruction()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Add the memo and compute budget instructions to `token_instructions`
+    async fn get_instructions<S: Signers>(
         &self,
         token_instructions: &[Instruction],
         signing_keypairs: &S,
-    ) -> TokenResult<Transaction> {
+    ) -> TokenResult<Vec<Instruction>> {
         let mut instructions = vec![];
-        let payer_key = self.payer.pubkey();
-        let fee_payer = Some(&payer_key);
-
-        {
-            let mut w_memo = self.memo.write().unwrap();
-            if let Some(memo) = w_memo.take() {
-                let signing_pubkeys = signing_keypairs.pubkeys();
-                if !memo
-                    .signers
-                    .iter()
-                    .all(|signer| signing_pubkeys.contains(signer))
-                {
-                    return Err(TokenError::MissingMemoSigner);
-                }
+        if let Some(memo_instruction) = self.take_memo_instruction(signing_keypairs)? {
+            instructions.push(memo_instruction);
+        }
+        instructions.extend_from_slice(token_instructions);
 
-                instructions.push(memo.to_instruction());
+        let compute_budget = self
+            .compute_budget_override
+            .write()
+            .unwrap()
+            .take()
+            .unwrap_or(self.compute_budget);
+        let unit_limit = match compute_budget.unit_limit {
+            ComputeUnitLimit::Default => None,
+            ComputeUnitLimit::Static(unit_limit) => Some(unit_limit),
+            ComputeUnitLimit::Simulated { margin_percent } => {
+                let mut simulation_instructions =
+                    compute_budget.instructions(Some(MAX_COMPUTE_UNIT_LIMIT));
+                simulation_instructions.extend_from_slice(&instructions);
+                let units_consumed = self
+                    .simulate_instructions(&simulation_instructions, signing_keypairs)
+                    .await?
+                    .get_compute_units_consumed()
+                    .map_err(TokenError::Client)?;
+                let unit_limit = units_consumed
+                    .saturating_mul(100u64.saturating_add(margin_percent.into()))
+                    .saturating_div(100);
+                Some(unit_limit.min(MAX_COMPUTE_UNIT_LIMIT.into()) as u32)
             }
-        }
+        };
 
-        instructions.extend_from_slice(token_instructions);
+        let mut compute_budget_instructions = compute_budget.instructions(unit_limit);
+        compute_budget_instructions.extend(instructions);
+        Ok(compute_budget_instructions)
+    }
+
+    async fn construct_tx<S: Signers>(
+        &self,
+        instructions: &[Instruction],
+        signing_keypairs: &S,
+    ) -> TokenResult<Transaction> {
+        let payer_key = self.payer.pubkey();
+        let fee_payer = Some(&payer_key);
 
         let (message, blockhash) =
             if let (Some(nonce_account), Some(nonce_authority), Some(nonce_blockhash)) = (
@@ -500,7 +754,7 @@ where
                 self.nonce_blockhash,
             ) {
                 let mut message = Message::new_with_nonce(
-                    token_instructions.to_vec(),
+                    instructions.to_vec(),
                     fee_payer,
                     &nonce_account,
                     &nonce_authority.pubkey(),
@@ -514,7 +768,7 @@ where
                     .await
                     .map_err(TokenError::Client)?;
                 (
-                    Message::new_with_blockhash(&instructions, fee_payer, &latest_blockhash),
+                    Message::new_with_blockhash(instructions, fee_payer, &latest_blockhash),
                     latest_blockhash,
                 )
             };
@@ -536,29 +790,173 @@ where
         Ok(transaction)
     }
 
+    async fn construct_versioned_tx<S: Signers>(
+        &self,
+        instructions: &[Instruction],
+        address_lookup_tables: &[AddressLookupTableAccount],
+        signing_keypairs: &S,
+    ) -> TokenResult<VersionedTransaction> {
+        let mut all_instructions = vec![];
+
+        let blockhash = if let (Some(nonce_account), Some(nonce_authority), Some(nonce_blockhash)) = (
+            self.nonce_account,
+            &self.nonce_authority,
+            self.nonce_blockhash,
+        ) {
+            all_instructions.push(system_instruction::advance_nonce_account(
+                &nonce_account,
+                &nonce_authority.pubkey(),
+            ));
+            nonce_blockhash
+        } else {
+            self.client
+                .get_latest_blockhash()
+                .await
+                .map_err(TokenError::Client)?
+        };
+
+        all_instructions.extend_from_slice(instructions);
+
+        let message = VersionedMessage::V0(
+            v0::Message::try_compile(
+                &self.payer.pubkey(),
+                &all_instructions,
+                address_lookup_tables,
+                blockhash,
+            )
+            .map_err(|error| TokenError::Client(error.into()))?,
+        );
+
+        // partially sign, as `Transaction::try_partial_sign` does for legacy
+        // transactions, so that offline and multisig signers can complete it
+        let message_data = message.serialize();
+        let num_required_signatures = message.header().num_required_signatures as usize;
+        let signer_keys = &message.static_account_keys()[..num_required_signatures];
+        let mut signatures = vec![Signature::default(); num_required_signatures];
+        let mut sign = |pubkeys: Vec<Pubkey>, new_signatures: Vec<Signature>| {
+            for (pubkey, signature) in pubkeys.into_iter().zip(new_signatures) {
+                let index = signer_keys
+                    .iter()
+                    .position(|key| *key == pubkey)
+                    .ok_or_else(|| TokenError::Client(SignerError::KeypairPubkeyMismatch.into()))?;
+                signatures[index] = signature;
+            }
+            Ok::<(), TokenError>(())
+        };
+
+        let mut default_signers = vec![self.payer.clone()];
+        if let Some(nonce_authority) = &self.nonce_authority {
+            default_signers.push(nonce_authority.clone());
+        }
+        let default_signatures = default_signers
+            .try_sign_message(&message_data)
+            .map_err(|error| TokenError::Client(error.into()))?;
+        sign(default_signers.pubkeys(), default_signatures)?;
+        let signing_signatures = signing_keypairs
+            .try_sign_message(&message_data)
+            .map_err(|error| TokenError::Client(error.into()))?;
+        sign(signing_keypairs.pubkeys(), signing_signatures)?;
+
+        Ok(VersionedTransaction {
+            signatures,
+            message,
+        })
+    }
+
+    async fn simulate_instructions<S: Signers>(
+        &self,
+        instructions: &[Instruction],
+        signing_keypairs: &S,
+    ) -> TokenResult<T::SimulationOutput> {
+        if let Some(address_lookup_tables) = self.get_address_lookup_tables(instructions) {
+            let transaction = self
+                .construct_versioned_tx(instructions, &address_lookup_tables, signing_keypairs)
+                .await?;
+
+            return self
+                .client
+                .simulate_versioned_transaction(&transaction)
+                .await
+                .map_err(TokenError::Client);
+        }
+
+        let transaction = self.construct_tx(instructions, signing_keypairs).await?;
+
+        self.client
+            .simulate_transaction(&transaction)
+            .await
+            .map_err(TokenError::Client)
+    }
+
     pub async fn simulate_ixs<S: Signers>(
         &self,
         token_instructions: &[Instruction],
         signing_keypairs: &S,
     ) -> TokenResult<T::SimulationOutput> {
-        let transaction = self
-            .construct_tx(token_instructions, signing_keypairs)
+        let instructions = self
+            .get_instructions(token_instructions, signing_keypairs)
+            .await?;
+        self.simulate_instructions(&instructions, signing_keypairs)
+            .await
+    }
+
+    pub async fn process_ixs<S: Signers>(
+        &self,
+        token_instructions: &[Instruction],
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let instructions = self
+            .get_instructions(token_instructions, signing_keypairs)
             .await?;
 
+        let mut address_lookup_tables = self.get_address_lookup_tables(&instructions);
+        if address_lookup_tables.is_none() && self.auto_address_lookup_tables {
+            let transaction = self.construct_tx(&instructions, signing_keypairs).await?;
+            let transaction_size = bincode::serialized_size(&transaction)
+                .map_err(|error| TokenError::Client(error.into()))?;
+            if transaction_size <= PACKET_DATA_SIZE as u64 {
+                return self
+                    .client
+                    .send_transaction(&transaction)
+                    .await
+                    .map_err(TokenError::Client);
+            }
+            address_lookup_tables = Some(vec![
+                self.create_auto_address_lookup_table(&instructions).await?,
+            ]);
+        }
+
+        if let Some(address_lookup_tables) = address_lookup_tables {
+            let transaction = self
+                .construct_versioned_tx(&instructions, &address_lookup_tables, signing_keypairs)
+                .await?;
+
+            return self
+                .client
+                .send_versioned_transaction(&transaction)
+                .await
+                .map_err(TokenError::Client);
+        }
+
+        let transaction = self.construct_tx(&instructions, signing_keypairs).await?;
+
         self.client
-            .simulate_transaction(&transaction)
+            .send_transaction(&transaction)
             .await
             .map_err(TokenError::Client)
     }
 
-    pub async fn process_ixs<S: Signers>(
+    /// Send the instructions in a legacy transaction, even if lookup tables
+    /// are used for other transactions
+    async fn process_legacy_ixs<S: Signers>(
         &self,
         token_instructions: &[Instruction],
         signing_keypairs: &S,
     ) -> TokenResult<T::Output> {
-        let transaction = self
-            .construct_tx(token_instructions, signing_keypairs)
+        let instructions = self
+            .get_instructions(token_instructions, signing_keypairs)
             .await?;
+        let transaction = self.construct_tx(&instructions, signing_keypairs).await?;
 
         self.client
             .send_transaction(&transaction)
@@ -566,6 +964,168 @@ where
             .map_err(TokenError::Client)
     }
 
+    /// Get the lookup tables to send the instructions with: the ones given
+    /// through `with_address_lookup_tables`, or else a created table holding
+    /// all of their accounts
+    fn get_address_lookup_tables(
+        &self,
+        instructions: &[Instruction],
+    ) -> Option<Vec<AddressLookupTableAccount>> {
+        if let Some(address_lookup_tables) = &self.address_lookup_tables {
+            return Some(address_lookup_tables.clone());
+        }
+        if !self.auto_address_lookup_tables {
+            return None;
+        }
+
+        let addresses = self.get_lookup_table_addresses(instructions);
+        self.created_address_lookup_tables
+            .read()
+            .unwrap()
+            .iter()
+            .find(|table| {
+                addresses
+                    .iter()
+                    .all(|address| table.addresses.contains(address))
+            })
+            .map(|table| vec![table.clone()])
+    }
+
+    /// Accounts of the instructions that can be loaded through a lookup table,
+    /// which excludes signers and invoked programs
+    fn get_lookup_table_addresses(&self, instructions: &[Instruction]) -> Vec<Pubkey> {
+        let program_ids = instructions
+            .iter()
+            .map(|instruction| instruction.program_id)
+            .collect::<Vec<_>>();
+        let mut addresses: Vec<Pubkey> = vec![];
+        for account_meta in instructions
+            .iter()
+            .flat_map(|instruction| instruction.accounts.iter())
+        {
+            if !account_meta.is_signer
+                && account_meta.pubkey != self.payer.pubkey()
+                && !program_ids.contains(&account_meta.pubkey)
+                && !addresses.contains(&account_meta.pubkey)
+            {
+                addresses.push(account_meta.pubkey);
+            }
+        }
+        addresses.truncate(LOOKUP_TABLE_MAX_ADDRESSES);
+        addresses
+    }
+
+    /// Create a lookup table owned by the payer for the accounts of the
+    /// instructions, and wait until its entries can be used
+    async fn create_auto_address_lookup_table(
+        &self,
+        instructions: &[Instruction],
+    ) -> TokenResult<AddressLookupTableAccount> {
+        let addresses = self.get_lookup_table_addresses(instructions);
+        let payer = self.payer.pubkey();
+        let lookup_table = self
+            .create_address_lookup_table::<[&dyn Signer; 0]>(&payer, &addresses, &[])
+            .await?;
+
+        // entries are only usable from the slot after they were added
+        let extended_slot = self.client.get_slot().await.map_err(TokenError::Client)?;
+        let start = Instant::now();
+        while start.elapsed().as_secs() < 5 {
+            let slot = self.client.get_slot().await.map_err(TokenError::Client)?;
+            if slot > extended_slot {
+                self.created_address_lookup_tables
+                    .write()
+                    .unwrap()
+                    .push(lookup_table.clone());
+                return Ok(lookup_table);
+            }
+
+            time::sleep(Duration::from_millis(200)).await;
+        }
+
+        Err(TokenError::Client(Box::new(io::Error::new(
+            io::ErrorKind::Other,
+            format!(
+                "Lookup table {} still not usable after {}ms, stuck at slot {}",
+                lookup_table.key,
+                start.elapsed().as_millis(),
+                extended_slot
+            ),
+        ))))
+    }
+
+    /// Create an address lookup table owned by `authority` holding
+    /// `addresses`, to be passed to `with_address_lookup_tables`.
+    ///
+    /// Lookup table entries can only be used from the slot after they were
+    /// added, so transactions relying on the new table must wait for the next
+    /// slot.
+    pub async fn create_address_lookup_table<S: Signers>(
+        &self,
+        authority: &Pubkey,
+        addresses: &[Pubkey],
+        signing_keypairs: &S,
+    ) -> TokenResult<AddressLookupTableAccount> {
+        let recent_slot = self.client.get_slot().await.map_err(TokenError::Client)?;
+        let payer = self.payer.pubkey();
+        let (create_instruction, key) = create_lookup_table(*authority, payer, recent_slot);
+
+        let mut chunks = addresses.chunks(MAX_ADDRESSES_PER_LOOKUP_TABLE_EXTEND);
+        let mut instructions = vec![create_instruction];
+        if let Some(chunk) = chunks.next() {
+            instructions.push(extend_lookup_table(
+                key,
+                *authority,
+                Some(payer),
+                chunk.to_vec(),
+            ));
+        }
+        self.process_legacy_ixs(&instructions, signing_keypairs)
+            .await?;
+
+        for chunk in chunks {
+            self.process_legacy_ixs(
+                &[extend_lookup_table(
+                    key,
+                    *authority,
+                    Some(payer),
+                    chunk.to_vec(),
+                )],
+                signing_keypairs,
+            )
+            .await?;
+        }
+
+        Ok(AddressLookupTableAccount {
+            key,
+            addresses: addresses.to_vec(),
+        })
+    }
+
+    /// Fetch an existing address lookup table, to be passed to
+    /// `with_address_lookup_tables`
+    pub async fn get_address_lookup_table(
+        &self,
+        address: &Pubkey,
+    ) -> TokenResult<AddressLookupTableAccount> {
+        let account = self
+            .client
+            .get_account(*address)
+            .await
+            .map_err(TokenError::Client)?
+            .ok_or(TokenError::AccountNotFound)?;
+        if account.owner != solana_address_lookup_table_program::id() {
+            return Err(TokenError::AccountInvalidOwner);
+        }
+        let lookup_table = AddressLookupTable::deserialize(&account.data)
+            .map_err(|error| TokenError::Client(error.into()))?;
+
+        Ok(AddressLookupTableAccount {
+            key: *address,
+            addresses: lookup_table.addresses.to_vec(),
+        })
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub async fn create_mint<'a, S: Signers>(
         &self,
@@ -837,8 +1397,8 @@ where
         let signing_pubkeys = signing_keypairs.pubkeys();
         let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
 
-        let instructions = if let Some(decimals) = self.decimals {
-            [instruction::mint_to_checked(
+        let mut instruction = if let Some(decimals) = self.decimals {
+            instruction::mint_to_checked(
                 &self.program_id,
                 &self.pubkey,
                 destination,
@@ -846,19 +1406,147 @@ where
                 &multisig_signers,
                 amount,
                 decimals,
-            )?]
+            )?
         } else {
-            [instruction::mint_to(
+            instruction::mint_to(
                 &self.program_id,
                 &self.pubkey,
                 destination,
                 authority,
                 &multisig_signers,
                 amount,
-            )?]
+            )?
         };
+        self.add_mint_hook_accounts(&mut instruction, destination, authority, amount)
+            .await?;
 
-        self.process_ixs(&instructions, signing_keypairs).await
+        self.process_ixs(&[instruction], signing_keypairs).await
+    }
+
+    /// Add the accounts required by the mint's transfer hook, if it has one,
+    /// to a transfer instruction. Accounts given through
+    /// `with_transfer_hook_accounts` are used as-is instead.
+    async fn add_transfer_hook_accounts(
+        &self,
+        instruction: &mut Instruction,
+        source: &Pubkey,
+        destination: &Pubkey,
+        authority: &Pubkey,
+        amount: u64,
+    ) -> TokenResult<()> {
+        if let Some(transfer_hook_accounts) = &self.transfer_hook_accounts {
+            instruction.accounts.extend(transfer_hook_accounts.clone());
+            return Ok(());
+        }
+
+        let mint = self.get_mint_info().await?;
+        if let Some(program_id) = transfer_hook::get_program_id(&mint) {
+            let validate_state_data = self.get_extra_account_metas_data(&program_id).await?;
+            add_extra_accounts_for_execute(
+                instruction,
+                &validate_state_data,
+                &program_id,
+                source,
+                &self.pubkey,
+                destination,
+                authority,
+                amount,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Get the mint to find its hook on mints and burns. Only Token-2022 mints
+    /// can have hooks, and hooks are skipped if the mint can't be fetched, for
+    /// example with an offline client, so that mints and burns can still be
+    /// signed.
+    async fn get_mint_info_for_hooks(&self) -> TokenResult<Option<StateWithExtensionsOwned<Mint>>> {
+        if self.program_id != spl_token_2022::id() {
+            return Ok(None);
+        }
+        match self.get_account(self.pubkey).await {
+            Ok(account) => self.unpack_mint_info(account).map(Some),
+            Err(_) => Ok(None),
+        }
+    }
+
+    /// Add the accounts required by the mint's transfer hook, if it is invoked
+    /// on mints, to a mint instruction. Accounts given through
+    /// `with_mint_hook_accounts` are used as-is instead.
+    async fn add_mint_hook_accounts(
+        &self,
+        instruction: &mut Instruction,
+        destination: &Pubkey,
+        authority: &Pubkey,
+        amount: u64,
+    ) -> TokenResult<()> {
+        if let Some(mint_hook_accounts) = &self.mint_hook_accounts {
+            instruction.accounts.extend(mint_hook_accounts.clone());
+            return Ok(());
+        }
+
+        let Some(mint) = self.get_mint_info_for_hooks().await? else {
+            return Ok(());
+        };
+        if let Some(program_id) = transfer_hook::get_mint_hook_program_id(&mint) {
+            let validate_state_data = self.get_extra_account_metas_data(&program_id).await?;
+            add_extra_accounts_for_execute_on_mint(
+                instruction,
+                &validate_state_data,
+                &program_id,
+                destination,
+                &self.pubkey,
+                authority,
+                amount,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Add the accounts required by the mint's transfer hook, if it is invoked
+    /// on burns, to a burn instruction. Accounts given through
+    /// `with_burn_hook_accounts` are used as-is instead.
+    async fn add_burn_hook_accounts(
+        &self,
+        instruction: &mut Instruction,
+        source: &Pubkey,
+        authority: &Pubkey,
+        amount: u64,
+    ) -> TokenResult<()> {
+        if let Some(burn_hook_accounts) = &self.burn_hook_accounts {
+            instruction.accounts.extend(burn_hook_accounts.clone());
+            return Ok(());
+        }
+
+        let Some(mint) = self.get_mint_info_for_hooks().await? else {
+            return Ok(());
+        };
+        if let Some(program_id) = transfer_hook::get_burn_hook_program_id(&mint) {
+            let validate_state_data = self.get_extra_account_metas_data(&program_id).await?;
+            add_extra_accounts_for_execute_on_burn(
+                instruction,
+                &validate_state_data,
+                &program_id,
+                source,
+                &self.pubkey,
+                authority,
+                amount,
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Get the validation account data of the mint's transfer hook, from the
+    /// cache if present
+    async fn get_extra_account_metas_data(&self, program_id: &Pubkey) -> TokenResult<Vec<u8>> {
+        if let Some(data) = self.extra_account_metas_cache.get(&self.pubkey, program_id) {
+            return Ok(data);
+        }
+        let address = get_extra_account_metas_address(&self.pubkey, program_id);
+        let account = self.get_account(address).await?;
+        self.extra_account_metas_cache
+            .insert(&self.pubkey, program_id, account.data.clone());
+        Ok(account.data)
     }
 
     /// Transfer tokens to another account
@@ -896,21 +1584,8 @@ where
                 amount,
             )?
         };
-        if let Some(transfer_hook_accounts) = &self.transfer_hook_accounts {
-            instruction.accounts.extend(transfer_hook_accounts.clone());
-        } else {
-            offchain::resolve_extra_transfer_account_metas(
-                &mut instruction,
-                |address| {
-                    self.client
-                        .get_account(address)
-                        .map_ok(|opt| opt.map(|acc| acc.data))
-                },
-                self.get_address(),
-            )
-            .await
-            .map_err(|_| TokenError::AccountNotFound)?;
-        };
+        self.add_transfer_hook_accounts(&mut instruction, source, destination, authority, amount)
+            .await?;
 
         self.process_ixs(&[instruction], signing_keypairs).await
     }
@@ -943,9 +1618,9 @@ where
             )),
         ];
 
-        if let Some(fee) = fee {
+        let mut instruction = if let Some(fee) = fee {
             let decimals = self.decimals.ok_or(TokenError::MissingDecimals)?;
-            instructions.push(transfer_fee::instruction::transfer_checked_with_fee(
+            transfer_fee::instruction::transfer_checked_with_fee(
                 &self.program_id,
                 source,
                 &self.pubkey,
@@ -955,9 +1630,9 @@ where
                 amount,
                 decimals,
                 fee,
-            )?);
+            )?
         } else if let Some(decimals) = self.decimals {
-            instructions.push(instruction::transfer_checked(
+            instruction::transfer_checked(
                 &self.program_id,
                 source,
                 &self.pubkey,
@@ -966,18 +1641,21 @@ where
                 &multisig_signers,
                 amount,
                 decimals,
-            )?);
+            )?
         } else {
             #[allow(deprecated)]
-            instructions.push(instruction::transfer(
+            instruction::transfer(
                 &self.program_id,
                 source,
                 destination,
                 authority,
                 &multisig_signers,
                 amount,
-            )?);
-        }
+            )?
+        };
+        self.add_transfer_hook_accounts(&mut instruction, source, destination, authority, amount)
+            .await?;
+        instructions.push(instruction);
 
         self.process_ixs(&instructions, signing_keypairs).await
     }
@@ -997,21 +1675,21 @@ where
         let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
         let decimals = self.decimals.ok_or(TokenError::MissingDecimals)?;
 
-        self.process_ixs(
-            &[transfer_fee::instruction::transfer_checked_with_fee(
-                &self.program_id,
-                source,
-                &self.pubkey,
-                destination,
-                authority,
-                &multisig_signers,
-                amount,
-                decimals,
-                fee,
-            )?],
-            signing_keypairs,
-        )
-        .await
+        let mut instruction = transfer_fee::instruction::transfer_checked_with_fee(
+            &self.program_id,
+            source,
+            &self.pubkey,
+            destination,
+            authority,
+            &multisig_signers,
+            amount,
+            decimals,
+            fee,
+        )?;
+        self.add_transfer_hook_accounts(&mut instruction, source, destination, authority, amount)
+            .await?;
+
+        self.process_ixs(&[instruction], signing_keypairs).await
     }
 
     /// Burn tokens from account
@@ -1025,8 +1703,8 @@ where
         let signing_pubkeys = signing_keypairs.pubkeys();
         let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
 
-        let instructions = if let Some(decimals) = self.decimals {
-            [instruction::burn_checked(
+        let mut instruction = if let Some(decimals) = self.decimals {
+            instruction::burn_checked(
                 &self.program_id,
                 source,
                 &self.pubkey,
@@ -1034,19 +1712,21 @@ where
                 &multisig_signers,
                 amount,
                 decimals,
-            )?]
+            )?
         } else {
-            [instruction::burn(
+            instruction::burn(
                 &self.program_id,
                 source,
                 &self.pubkey,
                 authority,
                 &multisig_signers,
                 amount,
-            )?]
+            )?
         };
+        self.add_burn_hook_accounts(&mut instruction, source, authority, amount)
+            .await?;
 
-        self.process_ixs(&instructions, signing_keypairs).await
+        self.process_ixs(&[instruction], signing_keypairs).await
     }
 
     /// Approve a delegate to spend tokens
@@ -1163,8 +1843,8 @@ where
 
         if !self.is_native() && account_state.base.amount > 0 {
             // if a separate close authority is being used, it must be a delegate also
-            if let Some(decimals) = self.decimals {
-                instructions.push(instruction::transfer_checked(
+            let mut instruction = if let Some(decimals) = self.decimals {
+                instruction::transfer_checked(
                     &self.program_id,
                     account_to_close,
                     &self.pubkey,
@@ -1173,18 +1853,27 @@ where
                     &multisig_signers,
                     account_state.base.amount,
                     decimals,
-                )?);
+                )?
             } else {
                 #[allow(deprecated)]
-                instructions.push(instruction::transfer(
+                instruction::transfer(
                     &self.program_id,
                     account_to_close,
                     tokens_destination,
                     authority,
                     &multisig_signers,
                     account_state.base.amount,
-                )?);
-            }
+                )?
+            };
+            self.add_transfer_hook_accounts(
+                &mut instruction,
+                account_to_close,
+                tokens_destination,
+                authority,
+                account_state.base.amount,
+            )
+            .await?;
+            instructions.push(instruction);
         }
 
         instructions.push(instruction::close_account(
@@ -1626,6 +2315,31 @@ where
         .await
     }
 
+    /// Update which events, other than transfers, invoke the transfer hook
+    pub async fn update_transfer_hook_events<S: Signers>(
+        &self,
+        authority: &Pubkey,
+        hook_mint: bool,
+        hook_burn: bool,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let signing_pubkeys = signing_keypairs.pubkeys();
+        let multisig_signers = self.get_multisig_signers(authority, &signing_pubkeys);
+
+        self.process_ixs(
+            &[transfer_hook::instruction::update_events(
+                &self.program_id,
+                self.get_address(),
+                authority,
+                &multisig_signers,
+                hook_mint,
+                hook_burn,
+            )?],
+            signing_keypairs,
+        )
+        .await
+    }
+
     /// Update metadata pointer address
     pub async fn update_metadata_address<S: Signers>(
         &self,
@@ -2686,14 +3400,13 @@ where
 
     async fn get_additional_rent_for_updated_metadata(
         &self,
-        field: Field,
-        value: String,
+        updates: Vec<FieldUpdate>,
     ) -> TokenResult<u64> {
         let account = self.get_account(self.pubkey).await?;
         let account_lamports = account.lamports;
         let mint_state = self.unpack_mint_info(account)?;
         let mut token_metadata = mint_state.get_variable_len_extension::<TokenMetadata>()?;
-        token_metadata.update(field, value);
+        token_metadata.apply_updates(updates)?;
         let new_account_len = mint_state.try_get_new_account_len(&token_metadata)?;
         let new_rent_exempt_minimum = self
             .client
@@ -2715,7 +3428,10 @@ where
         signing_keypairs: &S,
     ) -> TokenResult<T::Output> {
         let additional_lamports = self
-            .get_additional_rent_for_updated_metadata(field.clone(), value.clone())
+            .get_additional_rent_for_updated_metadata(vec![FieldUpdate::Set {
+                field: field.clone(),
+                value: value.clone(),
+            }])
             .await?;
         let mut instructions = vec![];
         if additional_lamports > 0 {
@@ -2735,6 +3451,122 @@ where
         self.process_ixs(&instructions, signing_keypairs).await
     }
 
+    /// Apply several token-metadata field updates and key removals on a mint
+    /// in one instruction
+    pub async fn token_metadata_batch_update_fields<S: Signers>(
+        &self,
+        update_authority: &Pubkey,
+        updates: Vec<FieldUpdate>,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        self.process_ixs(
+            &[
+                spl_token_metadata_interface::instruction::batch_update_fields(
+                    &self.program_id,
+                    &self.pubkey,
+                    update_authority,
+                    updates,
+                ),
+            ],
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Apply several token-metadata field updates and key removals on a mint
+    /// in one instruction. Includes a transfer for any additional rent-exempt
+    /// SOL required.
+    pub async fn token_metadata_batch_update_fields_with_rent_transfer<S: Signers>(
+        &self,
+        payer: &Pubkey,
+        update_authority: &Pubkey,
+        updates: Vec<FieldUpdate>,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let additional_lamports = self
+            .get_additional_rent_for_updated_metadata(updates.clone())
+            .await?;
+        let mut instructions = vec![];
+        if additional_lamports > 0 {
+            instructions.push(system_instruction::transfer(
+                payer,
+                &self.pubkey,
+                additional_lamports,
+            ));
+        }
+        instructions.push(
+            spl_token_metadata_interface::instruction::batch_update_fields(
+                &self.program_id,
+                &self.pubkey,
+                update_authority,
+                updates,
+            ),
+        );
+        self.process_ixs(&instructions, signing_keypairs).await
+    }
+
+    /// Permanently lock a token-metadata field on a mint
+    pub async fn token_metadata_lock_field<S: Signers>(
+        &self,
+        update_authority: &Pubkey,
+        field: Field,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        self.process_ixs(
+            &[spl_token_metadata_interface::instruction::lock_field(
+                &self.program_id,
+                &self.pubkey,
+                update_authority,
+                field,
+            )],
+            signing_keypairs,
+        )
+        .await
+    }
+
+    /// Permanently lock a token-metadata field on a mint. Includes a transfer
+    /// for any additional rent-exempt SOL required.
+    pub async fn token_metadata_lock_field_with_rent_transfer<S: Signers>(
+        &self,
+        payer: &Pubkey,
+        update_authority: &Pubkey,
+        field: Field,
+        signing_keypairs: &S,
+    ) -> TokenResult<T::Output> {
+        let account = self.get_account(self.pubkey).await?;
+        let account_lamports = account.lamports;
+        let mint_state = self.unpack_mint_info(account)?;
+        let mut field_locks = if mint_state.get_extension_bytes::<FieldLocks>().is_ok() {
+            mint_state.get_variable_len_extension::<FieldLocks>()?
+        } else {
+            FieldLocks::default()
+        };
+        field_locks.lock(field.clone());
+        let new_account_len = mint_state.try_get_new_account_len(&field_locks)?;
+        let new_rent_exempt_minimum = self
+            .client
+            .get_minimum_balance_for_rent_exemption(new_account_len)
+            .await
+            .map_err(TokenError::Client)?;
+        let additional_lamports = new_rent_exempt_minimum.saturating_sub(account_lamports);
+
+        let mut instructions = vec![];
+        if additional_lamports > 0 {
+            instructions.push(system_instruction::transfer(
+                payer,
+                &self.pubkey,
+                additional_lamports,
+            ));
+        }
+        instructions.push(spl_token_metadata_interface::instruction::lock_field(
+            &self.program_id,
+            &self.pubkey,
+            update_authority,
+            field,
+        ));
+        self.process_ixs(&instructions, signing_keypairs).await
+    }
+
     /// Update the token-metadata authority in a mint
     pub async fn token_metadata_update_authority<S: Signers>(
         &self,
@@ -0,0 +1,91 @@
+use {
+    solana_program_test::tokio,
+    solana_sdk::{
+        native_token::LAMPORTS_PER_SOL,
+        signer::{keypair::Keypair, Signer},
+    },
+    spl_token_client::{
+        client::ProgramClient,
+        mock::{ProgramMockClient, ProgramMockClientProcessTransaction},
+        token::{ExtensionInitializationParams, Token, TokenError},
+    },
+    std::sync::Arc,
+};
+
+#[tokio::test]
+async fn mint_and_transfer() {
+    let payer = Keypair::new();
+    let mock = ProgramMockClient::new();
+    mock.airdrop(&payer.pubkey(), LAMPORTS_PER_SOL);
+    let client: Arc<dyn ProgramClient<ProgramMockClientProcessTransaction>> = Arc::new(mock);
+
+    let mint_account = Keypair::new();
+    let mint_authority = Keypair::new();
+    let token = Token::new(
+        Arc::clone(&client),
+        &spl_token_2022::id(),
+        &mint_account.pubkey(),
+        Some(6),
+        Arc::new(payer),
+    );
+    token
+        .create_mint(
+            &mint_authority.pubkey(),
+            None,
+            vec![ExtensionInitializationParams::TransferFeeConfig {
+                transfer_fee_config_authority: None,
+                withdraw_withheld_authority: None,
+                transfer_fee_basis_points: 100,
+                maximum_fee: 1_000,
+            }],
+            &[&mint_account],
+        )
+        .await
+        .unwrap();
+
+    // associated accounts go through the associated token account program,
+    // which creates and initializes them with cross-program invocations
+    let alice = Keypair::new();
+    let bob = Keypair::new();
+    token
+        .create_associated_token_account(&alice.pubkey())
+        .await
+        .unwrap();
+    let alice_account = token.get_associated_token_address(&alice.pubkey());
+    let bob_account = token.get_associated_token_address(&bob.pubkey());
+
+    let amount = 1_000;
+    token
+        .mint_to(
+            &alice_account,
+            &mint_authority.pubkey(),
+            amount,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    token
+        .create_recipient_associated_account_and_transfer(
+            &alice_account,
+            &bob_account,
+            &bob.pubkey(),
+            &alice.pubkey(),
+            amount,
+            Some(10),
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    // a failed transaction leaves the accounts untouched
+    let error = token
+        .transfer(&alice_account, &bob_account, &alice.pubkey(), 1, &[&alice])
+        .await
+        .unwrap_err();
+    assert!(matches!(error, TokenError::Client(_)));
+
+    let alice_state = token.get_account_info(&alice_account).await.unwrap();
+    assert_eq!(alice_state.base.amount, 0);
+    let bob_state = token.get_account_info(&bob_account).await.unwrap();
+    assert_eq!(bob_state.base.amount, amount);
+}
@@ -0,0 +1,271 @@
+#![cfg(feature = "test-sbf")]
+
+mod program_test;
+use {
+    program_test::{keypair_clone, TestContext, TokenContext},
+    solana_program_test::{processor, tokio, ProgramTest},
+    solana_sdk::{
+        account::Account, instruction::AccountMeta, message::VersionedMessage, packet::Packet,
+        pubkey::Pubkey, signature::Signer, signer::keypair::Keypair,
+    },
+    spl_token_2022::processor::Processor,
+    spl_token_client::{
+        client::{
+            ProgramClient, ProgramOfflineClient, ProgramRpcClientSendTransaction, RpcClientResponse,
+        },
+        token::{ExtensionInitializationParams, Token},
+    },
+    spl_transfer_hook_interface::get_extra_account_metas_address,
+    std::{sync::Arc, time::Duration},
+};
+
+#[tokio::test]
+async fn transfer_with_lookup_table() {
+    let mut context = TestContext::new().await;
+    context.init_token_with_mint(vec![]).await.unwrap();
+    let TokenContext {
+        mint_authority,
+        token,
+        alice,
+        bob,
+        ..
+    } = context.token_context.take().unwrap();
+
+    let alice_account = Keypair::new();
+    token
+        .create_auxiliary_token_account(&alice_account, &alice.pubkey())
+        .await
+        .unwrap();
+    let alice_account = alice_account.pubkey();
+    let bob_account = Keypair::new();
+    token
+        .create_auxiliary_token_account(&bob_account, &bob.pubkey())
+        .await
+        .unwrap();
+    let bob_account = bob_account.pubkey();
+
+    let amount = 100;
+    token
+        .mint_to(
+            &alice_account,
+            &mint_authority.pubkey(),
+            amount,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+
+    // the lookup table is derived from a slot present in the slot hashes
+    context.context.lock().await.warp_to_slot(10).unwrap();
+    let lookup_table = token
+        .create_address_lookup_table(
+            &mint_authority.pubkey(),
+            &[
+                *token.get_address(),
+                alice_account,
+                bob_account,
+                spl_token_2022::id(),
+            ],
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        token
+            .get_address_lookup_table(&lookup_table.key)
+            .await
+            .unwrap(),
+        lookup_table
+    );
+
+    // new entries are only usable from the next slot
+    context.context.lock().await.warp_to_slot(20).unwrap();
+    let token = token.with_address_lookup_tables(vec![lookup_table]);
+    token
+        .transfer(
+            &alice_account,
+            &bob_account,
+            &alice.pubkey(),
+            amount,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+
+    let alice_state = token.get_account_info(&alice_account).await.unwrap();
+    assert_eq!(alice_state.base.amount, 0);
+    let bob_state = token.get_account_info(&bob_account).await.unwrap();
+    assert_eq!(bob_state.base.amount, amount);
+}
+
+#[tokio::test]
+async fn transfer_with_auto_lookup_table() {
+    let mint = Keypair::new();
+    let hook_program_id = Pubkey::new_unique();
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(false);
+    program_test.add_program(
+        "spl_token_2022",
+        spl_token_2022::id(),
+        processor!(Processor::process),
+    );
+    program_test.add_program(
+        "my_transfer_hook",
+        hook_program_id,
+        processor!(spl_transfer_hook_example::processor::process),
+    );
+
+    // too many extra accounts for a legacy transaction
+    let extra_account_metas = (0..40)
+        .map(|_| AccountMeta::new_readonly(Pubkey::new_unique(), false))
+        .collect::<Vec<_>>();
+    let validation_address = get_extra_account_metas_address(&mint.pubkey(), &hook_program_id);
+    program_test.add_account(
+        validation_address,
+        Account {
+            lamports: 1_000_000_000, // a lot, just to be safe
+            data: spl_transfer_hook_example::state::example_data(&extra_account_metas).unwrap(),
+            owner: hook_program_id,
+            ..Account::default()
+        },
+    );
+
+    let context = program_test.start_with_context().await;
+    let payer = keypair_clone(&context.payer);
+    let blockhash = context.last_blockhash;
+    let context = Arc::new(tokio::sync::Mutex::new(context));
+    let mut context = TestContext {
+        context,
+        token_context: None,
+    };
+    context
+        .init_token_with_mint_keypair_and_freeze_authority(
+            mint,
+            vec![ExtensionInitializationParams::TransferHook {
+                authority: None,
+                program_id: Some(hook_program_id),
+            }],
+            None,
+        )
+        .await
+        .unwrap();
+    let TokenContext {
+        decimals,
+        mint_authority,
+        token,
+        alice,
+        bob,
+        ..
+    } = context.token_context.take().unwrap();
+    let token = token.with_auto_address_lookup_tables();
+
+    let alice_account = Keypair::new();
+    token
+        .create_auxiliary_token_account(&alice_account, &alice.pubkey())
+        .await
+        .unwrap();
+    let alice_account = alice_account.pubkey();
+    let bob_account = Keypair::new();
+    token
+        .create_auxiliary_token_account(&bob_account, &bob.pubkey())
+        .await
+        .unwrap();
+    let bob_account = bob_account.pubkey();
+
+    let amount = 100;
+    token
+        .mint_to(
+            &alice_account,
+            &mint_authority.pubkey(),
+            amount,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    assert!(token.created_address_lookup_tables().is_empty());
+
+    // the lookup table is derived from a slot present in the slot hashes, and
+    // its entries are only usable from the next slot, so keep slots moving
+    context.context.lock().await.warp_to_slot(10).unwrap();
+    let warp_context = context.context.clone();
+    let warper = tokio::spawn(async move {
+        let mut slot = 10;
+        loop {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            slot += 1;
+            warp_context.lock().await.warp_to_slot(slot).unwrap();
+        }
+    });
+
+    token
+        .transfer(&alice_account, &bob_account, &alice.pubkey(), 1, &[&alice])
+        .await
+        .unwrap();
+    let lookup_tables = token.created_address_lookup_tables();
+    assert_eq!(lookup_tables.len(), 1);
+    for account_meta in &extra_account_metas {
+        assert!(lookup_tables[0].addresses.contains(&account_meta.pubkey));
+    }
+    assert_eq!(
+        token
+            .get_address_lookup_table(&lookup_tables[0].key)
+            .await
+            .unwrap(),
+        lookup_tables[0]
+    );
+
+    // the next transfer reuses the table
+    token
+        .transfer(&alice_account, &bob_account, &alice.pubkey(), 1, &[&alice])
+        .await
+        .unwrap();
+    warper.abort();
+    assert_eq!(token.created_address_lookup_tables(), lookup_tables);
+
+    let alice_state = token.get_account_info(&alice_account).await.unwrap();
+    assert_eq!(alice_state.base.amount, amount - 2);
+    let bob_state = token.get_account_info(&bob_account).await.unwrap();
+    assert_eq!(bob_state.base.amount, 2);
+
+    // build the same transfer offline: it only fits in a packet as a v0
+    // transaction loading its accounts through the table
+    let mut hook_accounts = extra_account_metas.clone();
+    hook_accounts.push(AccountMeta::new_readonly(hook_program_id, false));
+    hook_accounts.push(AccountMeta::new_readonly(validation_address, false));
+    let offline_client: Arc<dyn ProgramClient<ProgramRpcClientSendTransaction>> = Arc::new(
+        ProgramOfflineClient::new(blockhash, ProgramRpcClientSendTransaction),
+    );
+    let offline_token = Token::new(
+        offline_client,
+        &spl_token_2022::id(),
+        token.get_address(),
+        Some(decimals),
+        Arc::new(payer),
+    )
+    .with_transfer_hook_accounts(hook_accounts);
+
+    let legacy_transaction = match offline_token
+        .transfer(&alice_account, &bob_account, &alice.pubkey(), 1, &[&alice])
+        .await
+        .unwrap()
+    {
+        RpcClientResponse::Transaction(transaction) => transaction,
+        response => panic!("unexpected response {:?}", response),
+    };
+    assert!(Packet::from_data(None, legacy_transaction).is_err());
+
+    let offline_token = offline_token.with_address_lookup_tables(lookup_tables);
+    let versioned_transaction = match offline_token
+        .transfer(&alice_account, &bob_account, &alice.pubkey(), 1, &[&alice])
+        .await
+        .unwrap()
+    {
+        RpcClientResponse::VersionedTransaction(transaction) => transaction,
+        response => panic!("unexpected response {:?}", response),
+    };
+    let VersionedMessage::V0(message) = &versioned_transaction.message else {
+        panic!("expected a v0 message");
+    };
+    assert_eq!(message.address_table_lookups.len(), 1);
+    assert!(Packet::from_data(None, versioned_transaction).is_ok());
+}
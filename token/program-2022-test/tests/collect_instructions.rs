@@ -0,0 +1,113 @@
+#![cfg(feature = "test-sbf")]
+
+mod program_test;
+use {
+    program_test::{keypair_clone, TestContext, TokenContext},
+    solana_program_test::tokio,
+    solana_sdk::{signature::Signer, signer::keypair::Keypair, transaction::Transaction},
+    spl_token_2022::id,
+    spl_token_client::{
+        client::{
+            ProgramBanksClient, ProgramBanksClientProcessTransaction, ProgramClient,
+            ProgramInstructionsClient, ProgramInstructionsClientCollect,
+        },
+        token::Token,
+    },
+    std::sync::Arc,
+};
+
+#[tokio::test]
+async fn combine_collected_instructions() {
+    let mut context = TestContext::new().await;
+    context.init_token_with_mint(vec![]).await.unwrap();
+    let TokenContext {
+        decimals,
+        mint_authority,
+        token,
+        alice,
+        bob,
+        ..
+    } = context.token_context.take().unwrap();
+
+    let alice_account = Keypair::new();
+    token
+        .create_auxiliary_token_account(&alice_account, &alice.pubkey())
+        .await
+        .unwrap();
+    let alice_account = alice_account.pubkey();
+
+    let payer = keypair_clone(&context.context.lock().await.payer);
+    let client: Arc<dyn ProgramClient<ProgramBanksClientProcessTransaction> + Send + Sync> =
+        Arc::new(ProgramBanksClient::new_from_context(
+            Arc::clone(&context.context),
+            ProgramBanksClientProcessTransaction,
+        ));
+    let builder: Token<ProgramInstructionsClientCollect> = Token::new(
+        Arc::new(ProgramInstructionsClient::new(client)),
+        &id(),
+        token.get_address(),
+        Some(decimals),
+        Arc::new(keypair_clone(&payer)),
+    );
+
+    // nothing is sent while collecting
+    let amount = 100;
+    let bob_account = builder.get_associated_token_address(&bob.pubkey());
+    let create = builder
+        .create_associated_token_account(&bob.pubkey())
+        .await
+        .unwrap();
+    let mint_to = builder
+        .mint_to(
+            &alice_account,
+            &mint_authority.pubkey(),
+            amount,
+            &[&mint_authority],
+        )
+        .await
+        .unwrap();
+    let transfer = builder
+        .transfer(
+            &alice_account,
+            &bob_account,
+            &alice.pubkey(),
+            amount,
+            &[&alice],
+        )
+        .await
+        .unwrap();
+    assert_eq!(
+        mint_to.signers,
+        vec![payer.pubkey(), mint_authority.pubkey()]
+    );
+    assert_eq!(transfer.signers, vec![payer.pubkey(), alice.pubkey()]);
+    let alice_state = token.get_account_info(&alice_account).await.unwrap();
+    assert_eq!(alice_state.base.amount, 0);
+
+    // all in one transaction
+    let instructions = [
+        create.instructions,
+        mint_to.instructions,
+        transfer.instructions,
+    ]
+    .concat();
+    {
+        let mut context = context.context.lock().await;
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&payer.pubkey()),
+            &[&payer, &mint_authority, &alice],
+            context.last_blockhash,
+        );
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    let alice_state = token.get_account_info(&alice_account).await.unwrap();
+    assert_eq!(alice_state.base.amount, 0);
+    let bob_state = token.get_account_info(&bob_account).await.unwrap();
+    assert_eq!(bob_state.base.amount, amount);
+}
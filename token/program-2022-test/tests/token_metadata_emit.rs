@@ -57,6 +57,7 @@ async fn setup(mint: Keypair, authority: &Pubkey) -> TestContext {
 #[test_case(Some(50), Some(49) ; "wrong way")]
 #[test_case(Some(50), None ; "truncate start")]
 #[test_case(None, Some(50) ; "truncate end")]
+#[test_case(Some(50), Some(1_000_000) ; "clamped end")]
 #[test_case(None, None ; "full data")]
 #[tokio::test]
 async fn success(start: Option<u64>, end: Option<u64>) {
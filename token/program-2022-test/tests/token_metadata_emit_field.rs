@@ -0,0 +1,128 @@
+#![cfg(feature = "test-sbf")]
+
+mod program_test;
+use {
+    program_test::TestContext,
+    solana_program_test::{processor, tokio, ProgramTest},
+    solana_sdk::{
+        instruction::InstructionError,
+        pubkey::Pubkey,
+        signature::Signer,
+        signer::keypair::Keypair,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token_2022::processor::Processor,
+    spl_token_client::token::ExtensionInitializationParams,
+    spl_token_metadata_interface::{
+        error::TokenMetadataError, instruction::emit_field, state::Field,
+    },
+    std::sync::Arc,
+    test_case::test_case,
+};
+
+fn setup_program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "spl_token_2022",
+        spl_token_2022::id(),
+        processor!(Processor::process),
+    );
+    program_test
+}
+
+async fn setup(mint: Keypair, authority: &Pubkey) -> TestContext {
+    let program_test = setup_program_test();
+
+    let context = program_test.start_with_context().await;
+    let context = Arc::new(tokio::sync::Mutex::new(context));
+    let mut context = TestContext {
+        context,
+        token_context: None,
+    };
+    let metadata_address = Some(mint.pubkey());
+    context
+        .init_token_with_mint_keypair_and_freeze_authority(
+            mint,
+            vec![ExtensionInitializationParams::MetadataPointer {
+                authority: Some(*authority),
+                metadata_address,
+            }],
+            None,
+        )
+        .await
+        .unwrap();
+    context
+}
+
+#[test_case(Field::Name, Some("MySuperCoolToken") ; "name")]
+#[test_case(Field::Symbol, Some("MINE") ; "symbol")]
+#[test_case(Field::Uri, Some("my.super.cool.token") ; "uri")]
+#[test_case(Field::Key("collection".to_string()), Some("my collection") ; "key")]
+#[test_case(Field::Key("missing".to_string()), None ; "missing key")]
+#[tokio::test]
+async fn emit_field_value(field: Field, expected_value: Option<&str>) {
+    let program_id = spl_token_2022::id();
+    let authority = Keypair::new();
+    let mint_keypair = Keypair::new();
+    let mut test_context = setup(mint_keypair, &authority.pubkey()).await;
+    let payer_pubkey = test_context.context.lock().await.payer.pubkey();
+    let token_context = test_context.token_context.take().unwrap();
+
+    let update_authority = Keypair::new();
+    token_context
+        .token
+        .token_metadata_initialize_with_rent_transfer(
+            &payer_pubkey,
+            &update_authority.pubkey(),
+            &token_context.mint_authority.pubkey(),
+            "MySuperCoolToken".to_string(),
+            "MINE".to_string(),
+            "my.super.cool.token".to_string(),
+            &[&token_context.mint_authority],
+        )
+        .await
+        .unwrap();
+    token_context
+        .token
+        .token_metadata_update_field_with_rent_transfer(
+            &payer_pubkey,
+            &update_authority.pubkey(),
+            Field::Key("collection".to_string()),
+            "my collection".to_string(),
+            &[&update_authority],
+        )
+        .await
+        .unwrap();
+
+    let mut context = test_context.context.lock().await;
+
+    let transaction = Transaction::new_signed_with_payer(
+        &[emit_field(
+            &program_id,
+            token_context.token.get_address(),
+            field,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer],
+        context.last_blockhash,
+    );
+    let simulation = context
+        .banks_client
+        .simulate_transaction(transaction)
+        .await
+        .unwrap();
+
+    if let Some(expected_value) = expected_value {
+        let return_data = simulation.simulation_details.unwrap().return_data.unwrap();
+        assert_eq!(return_data.program_id, program_id);
+        assert_eq!(return_data.data, expected_value.as_bytes());
+    } else {
+        assert_eq!(
+            simulation.result.unwrap().unwrap_err(),
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TokenMetadataError::KeyNotFound as u32)
+            )
+        );
+    }
+}
@@ -0,0 +1,256 @@
+#![cfg(feature = "test-sbf")]
+
+mod program_test;
+use {
+    program_test::TestContext,
+    solana_program_test::{processor, tokio, ProgramTest},
+    solana_sdk::{
+        instruction::InstructionError, pubkey::Pubkey, signature::Signer, signer::keypair::Keypair,
+        transaction::TransactionError, transport::TransportError,
+    },
+    spl_token_2022::{extension::BaseStateWithExtensions, processor::Processor},
+    spl_token_client::token::{ExtensionInitializationParams, TokenError as TokenClientError},
+    spl_token_metadata_interface::{
+        error::TokenMetadataError,
+        state::{Field, FieldLocks, FieldUpdate, TokenMetadata},
+    },
+    std::{convert::TryInto, sync::Arc},
+};
+
+fn setup_program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::default();
+    program_test.add_program(
+        "spl_token_2022",
+        spl_token_2022::id(),
+        processor!(Processor::process),
+    );
+    program_test
+}
+
+async fn setup(mint: Keypair, authority: &Pubkey) -> TestContext {
+    let program_test = setup_program_test();
+
+    let context = program_test.start_with_context().await;
+    let context = Arc::new(tokio::sync::Mutex::new(context));
+    let mut context = TestContext {
+        context,
+        token_context: None,
+    };
+    let metadata_address = Some(mint.pubkey());
+    context
+        .init_token_with_mint_keypair_and_freeze_authority(
+            mint,
+            vec![ExtensionInitializationParams::MetadataPointer {
+                authority: Some(*authority),
+                metadata_address,
+            }],
+            None,
+        )
+        .await
+        .unwrap();
+    context
+}
+
+#[tokio::test]
+async fn success_lock_field() {
+    let authority = Keypair::new();
+    let mint_keypair = Keypair::new();
+    let mut test_context = setup(mint_keypair, &authority.pubkey()).await;
+    let payer_pubkey = test_context.context.lock().await.payer.pubkey();
+    let token_context = test_context.token_context.take().unwrap();
+
+    let update_authority = Keypair::new();
+    let name = "MySuperCoolToken".to_string();
+    let symbol = "MINE".to_string();
+    let uri = "my.super.cool.token".to_string();
+    let mut token_metadata = TokenMetadata {
+        name,
+        symbol,
+        uri,
+        update_authority: Some(update_authority.pubkey()).try_into().unwrap(),
+        mint: *token_context.token.get_address(),
+        ..Default::default()
+    };
+
+    token_context
+        .token
+        .token_metadata_initialize_with_rent_transfer(
+            &payer_pubkey,
+            &update_authority.pubkey(),
+            &token_context.mint_authority.pubkey(),
+            token_metadata.name.clone(),
+            token_metadata.symbol.clone(),
+            token_metadata.uri.clone(),
+            &[&token_context.mint_authority],
+        )
+        .await
+        .unwrap();
+
+    // fails without more lamports
+    let error = token_context
+        .token
+        .token_metadata_lock_field(
+            &update_authority.pubkey(),
+            Field::Name,
+            &[&update_authority],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InsufficientFundsForRent { account_index: 2 }
+        )))
+    );
+
+    // lock name and symbol
+    let mut field_locks = FieldLocks::default();
+    for field in [Field::Name, Field::Symbol] {
+        token_context
+            .token
+            .token_metadata_lock_field_with_rent_transfer(
+                &payer_pubkey,
+                &update_authority.pubkey(),
+                field.clone(),
+                &[&update_authority],
+            )
+            .await
+            .unwrap();
+        field_locks.lock(field);
+    }
+    let mint_info = token_context.token.get_mint_info().await.unwrap();
+    let fetched_field_locks = mint_info
+        .get_variable_len_extension::<FieldLocks>()
+        .unwrap();
+    assert_eq!(fetched_field_locks, field_locks);
+
+    // locked fields cannot be updated
+    let locked_error = TokenClientError::Client(Box::new(TransportError::TransactionError(
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TokenMetadataError::FieldLocked as u32),
+        ),
+    )));
+    let error = token_context
+        .token
+        .token_metadata_update_field(
+            &update_authority.pubkey(),
+            Field::Name,
+            "Renamed".to_string(),
+            &[&update_authority],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(error, locked_error);
+    let error = token_context
+        .token
+        .token_metadata_batch_update_fields(
+            &update_authority.pubkey(),
+            vec![
+                FieldUpdate::Set {
+                    field: Field::Uri,
+                    value: "my.even.cooler.token".to_string(),
+                },
+                FieldUpdate::Set {
+                    field: Field::Symbol,
+                    value: "NEW".to_string(),
+                },
+            ],
+            &[&update_authority],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(error, locked_error);
+
+    // but the others can, with both variable-length extensions in the mint
+    let new_uri = "my.even.cooler.token".to_string();
+    token_context
+        .token
+        .token_metadata_update_field_with_rent_transfer(
+            &payer_pubkey,
+            &update_authority.pubkey(),
+            Field::Uri,
+            new_uri.clone(),
+            &[&update_authority],
+        )
+        .await
+        .unwrap();
+    token_metadata.update(Field::Uri, new_uri);
+
+    let mint_info = token_context.token.get_mint_info().await.unwrap();
+    let fetched_metadata = mint_info
+        .get_variable_len_extension::<TokenMetadata>()
+        .unwrap();
+    assert_eq!(fetched_metadata, token_metadata);
+    let fetched_field_locks = mint_info
+        .get_variable_len_extension::<FieldLocks>()
+        .unwrap();
+    assert_eq!(fetched_field_locks, field_locks);
+}
+
+#[tokio::test]
+async fn fail_authority_checks() {
+    let authority = Keypair::new();
+    let mint_keypair = Keypair::new();
+    let mut test_context = setup(mint_keypair, &authority.pubkey()).await;
+    let payer_pubkey = test_context.context.lock().await.payer.pubkey();
+    let token_context = test_context.token_context.take().unwrap();
+
+    let update_authority = Keypair::new();
+    token_context
+        .token
+        .token_metadata_initialize_with_rent_transfer(
+            &payer_pubkey,
+            &update_authority.pubkey(),
+            &token_context.mint_authority.pubkey(),
+            "MySuperCoolToken".to_string(),
+            "MINE".to_string(),
+            "my.super.cool.token".to_string(),
+            &[&token_context.mint_authority],
+        )
+        .await
+        .unwrap();
+
+    // wrong authority
+    let wrong_authority = Keypair::new();
+    let error = token_context
+        .token
+        .token_metadata_lock_field_with_rent_transfer(
+            &payer_pubkey,
+            &wrong_authority.pubkey(),
+            Field::Name,
+            &[&wrong_authority],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                1,
+                InstructionError::Custom(TokenMetadataError::IncorrectUpdateAuthority as u32)
+            )
+        )))
+    );
+
+    // additional field must exist
+    let error = token_context
+        .token
+        .token_metadata_lock_field_with_rent_transfer(
+            &payer_pubkey,
+            &update_authority.pubkey(),
+            Field::Key("missing".to_string()),
+            &[&update_authority],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        error,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                1,
+                InstructionError::Custom(TokenMetadataError::KeyNotFound as u32)
+            )
+        )))
+    );
+}
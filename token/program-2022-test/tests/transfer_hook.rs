@@ -17,17 +17,24 @@ use {
         transaction::TransactionError,
         transport::TransportError,
     },
+    spl_tlv_account_resolution::{error::AccountResolutionError, state::ExtraAccountMetas},
     spl_token_2022::{
         error::TokenError,
         extension::{
-            transfer_hook::{TransferHook, TransferHookAccount},
+            transfer_hook::{TransferHook, TransferHookAccount, TransferHookEvents},
             BaseStateWithExtensions,
         },
         instruction, offchain, onchain,
         processor::Processor,
     },
     spl_token_client::token::{ExtensionInitializationParams, TokenError as TokenClientError},
-    spl_transfer_hook_interface::get_extra_account_metas_address,
+    spl_transfer_hook_interface::{
+        get_extra_account_metas_address,
+        instruction::{
+            ExecuteInstruction, ExecuteOnBurnInstruction, ExecuteOnMintInstruction,
+            TransferHookInstruction,
+        },
+    },
     std::{convert::TryInto, sync::Arc},
 };
 
@@ -125,6 +132,34 @@ pub fn process_instruction_swap(
     Ok(())
 }
 
+/// Test program to record the amounts minted and burned in its extra account,
+/// conforms to transfer-hook-interface
+pub fn process_instruction_record(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    input: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let _token_account_info = next_account_info(account_info_iter)?;
+    let _mint_info = next_account_info(account_info_iter)?;
+    let _authority_info = next_account_info(account_info_iter)?;
+    let _extra_account_metas_info = next_account_info(account_info_iter)?;
+    let record_info = next_account_info(account_info_iter)?;
+
+    let mut record = record_info.try_borrow_mut_data()?;
+    match TransferHookInstruction::unpack(input)? {
+        TransferHookInstruction::ExecuteOnMint { amount } => {
+            record[..8].copy_from_slice(&amount.to_le_bytes())
+        }
+        TransferHookInstruction::ExecuteOnBurn { amount } => {
+            record[8..16].copy_from_slice(&amount.to_le_bytes())
+        }
+        _ => return Err(ProgramError::InvalidInstructionData),
+    }
+    Ok(())
+}
+
 async fn setup_accounts(
     token_context: &TokenContext,
     alice_account: Keypair,
@@ -223,6 +258,70 @@ async fn setup(mint: Keypair, program_id: &Pubkey, authority: &Pubkey) -> TestCo
     context
 }
 
+fn add_validation_account_with_events(
+    program_test: &mut ProgramTest,
+    mint: &Pubkey,
+    program_id: &Pubkey,
+    event_account_metas: &[AccountMeta],
+) {
+    let validation_address = get_extra_account_metas_address(mint, program_id);
+    let event_size = ExtraAccountMetas::size_of(event_account_metas.len()).unwrap();
+    let account_size = ExtraAccountMetas::size_of(0).unwrap() + event_size + event_size;
+    let mut data = vec![0; account_size];
+    ExtraAccountMetas::init_with_account_metas::<ExecuteInstruction>(&mut data, &[]).unwrap();
+    ExtraAccountMetas::init_with_account_metas::<ExecuteOnMintInstruction>(
+        &mut data,
+        event_account_metas,
+    )
+    .unwrap();
+    ExtraAccountMetas::init_with_account_metas::<ExecuteOnBurnInstruction>(
+        &mut data,
+        event_account_metas,
+    )
+    .unwrap();
+    program_test.add_account(
+        validation_address,
+        Account {
+            lamports: 1_000_000_000, // a lot, just to be safe
+            data,
+            owner: *program_id,
+            ..Account::default()
+        },
+    );
+}
+
+async fn setup_with_events(
+    program_test: ProgramTest,
+    mint: Keypair,
+    program_id: &Pubkey,
+    authority: &Pubkey,
+) -> TestContext {
+    let context = program_test.start_with_context().await;
+    let context = Arc::new(tokio::sync::Mutex::new(context));
+    let mut context = TestContext {
+        context,
+        token_context: None,
+    };
+    context
+        .init_token_with_mint_keypair_and_freeze_authority(
+            mint,
+            vec![
+                ExtensionInitializationParams::TransferHook {
+                    authority: Some(*authority),
+                    program_id: Some(*program_id),
+                },
+                ExtensionInitializationParams::TransferHookEvents {
+                    hook_mint: true,
+                    hook_burn: true,
+                },
+            ],
+            None,
+        )
+        .await
+        .unwrap();
+    context
+}
+
 #[tokio::test]
 async fn success_init() {
     let authority = Pubkey::new_unique();
@@ -729,3 +828,259 @@ async fn success_transfers_using_onchain_helper() {
         .await
         .unwrap();
 }
+
+#[tokio::test]
+async fn success_transfer_helpers() {
+    let authority = Keypair::new();
+    let program_id = Pubkey::new_unique();
+    let mint_keypair = Keypair::new();
+    let token_context = setup(mint_keypair, &program_id, &authority.pubkey())
+        .await
+        .token_context
+        .take()
+        .unwrap();
+    let amount = 10;
+    let (alice_account, bob_account) =
+        setup_accounts(&token_context, Keypair::new(), Keypair::new(), amount).await;
+
+    // create the recipient's associated account while transferring
+    let bob_associated_account = token_context
+        .token
+        .get_associated_token_address(&token_context.bob.pubkey());
+    token_context
+        .token
+        .create_recipient_associated_account_and_transfer(
+            &alice_account,
+            &bob_associated_account,
+            &token_context.bob.pubkey(),
+            &token_context.alice.pubkey(),
+            1,
+            None,
+            &[&token_context.alice],
+        )
+        .await
+        .unwrap();
+
+    // the next transfer fetches the validation data again
+    token_context
+        .token
+        .extra_account_metas_cache()
+        .invalidate(token_context.token.get_address());
+
+    // close the account, moving its remaining tokens
+    token_context
+        .token
+        .empty_and_close_account(
+            &alice_account,
+            &token_context.alice.pubkey(),
+            &bob_account,
+            &token_context.alice.pubkey(),
+            &[&token_context.alice],
+        )
+        .await
+        .unwrap();
+
+    let destination = token_context
+        .token
+        .get_account_info(&bob_associated_account)
+        .await
+        .unwrap();
+    assert_eq!(destination.base.amount, 1);
+    let destination = token_context
+        .token
+        .get_account_info(&bob_account)
+        .await
+        .unwrap();
+    assert_eq!(destination.base.amount, amount - 1);
+}
+
+#[tokio::test]
+async fn success_mint_and_burn_with_hook_events() {
+    let authority = Pubkey::new_unique();
+    let program_id = Pubkey::new_unique();
+    let mint_keypair = Keypair::new();
+    let mut program_test = setup_program_test(&program_id);
+    add_validation_account_with_events(
+        &mut program_test,
+        &mint_keypair.pubkey(),
+        &program_id,
+        &[
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+            AccountMeta::new_readonly(Pubkey::new_unique(), false),
+        ],
+    );
+    let token_context = setup_with_events(program_test, mint_keypair, &program_id, &authority)
+        .await
+        .token_context
+        .take()
+        .unwrap();
+
+    let state = token_context.token.get_mint_info().await.unwrap();
+    let extension = state.get_extension::<TransferHookEvents>().unwrap();
+    assert_eq!(extension.hook_mint, true.into());
+    assert_eq!(extension.hook_burn, true.into());
+
+    // the example program checks that the minted account is "transferring"
+    let amount = 10;
+    let (alice_account, _) =
+        setup_accounts(&token_context, Keypair::new(), Keypair::new(), amount).await;
+
+    // and the burned account too
+    token_context
+        .token
+        .burn(
+            &alice_account,
+            &token_context.alice.pubkey(),
+            4,
+            &[&token_context.alice],
+        )
+        .await
+        .unwrap();
+
+    let source = token_context
+        .token
+        .get_account_info(&alice_account)
+        .await
+        .unwrap();
+    assert_eq!(source.base.amount, 6);
+    assert_eq!(
+        source
+            .get_extension::<TransferHookAccount>()
+            .unwrap()
+            .transferring,
+        false.into()
+    );
+}
+
+#[tokio::test]
+async fn success_mint_and_burn_invoke_hook() {
+    let authority = Pubkey::new_unique();
+    let program_id = Pubkey::new_unique();
+    let mint_keypair = Keypair::new();
+    let record_address = Pubkey::new_unique();
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(false);
+    program_test.add_program(
+        "spl_token_2022",
+        spl_token_2022::id(),
+        processor!(Processor::process),
+    );
+    program_test.add_program(
+        "my_transfer_hook",
+        program_id,
+        processor!(process_instruction_record),
+    );
+    program_test.add_account(
+        record_address,
+        Account {
+            lamports: 1_000_000_000, // a lot, just to be safe
+            data: vec![0; 16],
+            owner: program_id,
+            ..Account::default()
+        },
+    );
+    add_validation_account_with_events(
+        &mut program_test,
+        &mint_keypair.pubkey(),
+        &program_id,
+        &[AccountMeta::new(record_address, false)],
+    );
+    let token_context = setup_with_events(program_test, mint_keypair, &program_id, &authority)
+        .await
+        .token_context
+        .take()
+        .unwrap();
+
+    let (alice_account, _) =
+        setup_accounts(&token_context, Keypair::new(), Keypair::new(), 10).await;
+    token_context
+        .token
+        .burn(
+            &alice_account,
+            &token_context.alice.pubkey(),
+            4,
+            &[&token_context.alice],
+        )
+        .await
+        .unwrap();
+
+    // the hook recorded both events, with their amounts
+    let record = token_context
+        .token
+        .get_account(record_address)
+        .await
+        .unwrap();
+    assert_eq!(record.data[..8], 10u64.to_le_bytes());
+    assert_eq!(record.data[8..16], 4u64.to_le_bytes());
+}
+
+#[tokio::test]
+async fn fail_mint_and_burn_missing_extra_account() {
+    let authority = Pubkey::new_unique();
+    let program_id = Pubkey::new_unique();
+    let mint_keypair = Keypair::new();
+    let validation_address = get_extra_account_metas_address(&mint_keypair.pubkey(), &program_id);
+    let mut program_test = setup_program_test(&program_id);
+    add_validation_account_with_events(
+        &mut program_test,
+        &mint_keypair.pubkey(),
+        &program_id,
+        &[AccountMeta::new_readonly(Pubkey::new_unique(), false)],
+    );
+    let token_context = setup_with_events(program_test, mint_keypair, &program_id, &authority)
+        .await
+        .token_context
+        .take()
+        .unwrap();
+    let (alice_account, _) =
+        setup_accounts(&token_context, Keypair::new(), Keypair::new(), 10).await;
+
+    // only provide the hook program and validation account, without the extra
+    // account it requires
+    let missing_extra_account = vec![
+        AccountMeta::new_readonly(program_id, false),
+        AccountMeta::new_readonly(validation_address, false),
+    ];
+    let token = token_context
+        .token
+        .with_mint_hook_accounts(missing_extra_account.clone())
+        .with_burn_hook_accounts(missing_extra_account);
+
+    let err = token
+        .mint_to(
+            &alice_account,
+            &token_context.mint_authority.pubkey(),
+            1,
+            &[&token_context.mint_authority],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(AccountResolutionError::IncorrectAccount as u32)
+            )
+        )))
+    );
+
+    let err = token
+        .burn(
+            &alice_account,
+            &token_context.alice.pubkey(),
+            1,
+            &[&token_context.alice],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(
+        err,
+        TokenClientError::Client(Box::new(TransportError::TransactionError(
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(AccountResolutionError::IncorrectAccount as u32)
+            )
+        )))
+    );
+}
@@ -0,0 +1,316 @@
+#![cfg(feature = "test-sbf")]
+
+mod program_test;
+use {
+    program_test::{TestContext, TokenContext},
+    solana_program_test::{processor, tokio, ProgramTest},
+    solana_sdk::{
+        instruction::{Instruction, InstructionError},
+        pubkey::Pubkey,
+        signature::Signer,
+        signer::keypair::Keypair,
+        system_instruction,
+        transaction::{Transaction, TransactionError},
+        transport::TransportError,
+    },
+    spl_tlv_account_resolution::state::ExtraAccountMetas,
+    spl_token_2022::processor::Processor,
+    spl_token_client::token::{ExtensionInitializationParams, TokenError as TokenClientError},
+    spl_transfer_hook_allowlist::{
+        error::AllowlistError,
+        instruction::{add_owner, initialize_owner_list, remove_owner},
+        state::ListMode,
+    },
+    spl_transfer_hook_interface::{
+        get_extra_account_metas_address, instruction::initialize_extra_account_metas,
+    },
+    std::sync::Arc,
+};
+
+async fn process_instructions(
+    context: &TestContext,
+    instructions: &[Instruction],
+    signers: &[&Keypair],
+) {
+    let context = context.context.lock().await;
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let transaction = Transaction::new_signed_with_payer(
+        instructions,
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+}
+
+/// Create a mint hooked to the allowlist program, with an owner list in the
+/// given mode, and token accounts for alice and bob, alice holding `amount`
+async fn setup(
+    program_id: &Pubkey,
+    admin: &Pubkey,
+    mode: ListMode,
+    amount: u64,
+) -> (TestContext, TokenContext, Pubkey, Pubkey) {
+    let mut program_test = ProgramTest::default();
+    program_test.prefer_bpf(false);
+    program_test.add_program(
+        "spl_token_2022",
+        spl_token_2022::id(),
+        processor!(Processor::process),
+    );
+    program_test.add_program(
+        "spl_transfer_hook_allowlist",
+        *program_id,
+        processor!(spl_transfer_hook_allowlist::processor::process),
+    );
+    let context = program_test.start_with_context().await;
+    let context = Arc::new(tokio::sync::Mutex::new(context));
+    let mut context = TestContext {
+        context,
+        token_context: None,
+    };
+    context
+        .init_token_with_mint(vec![ExtensionInitializationParams::TransferHook {
+            authority: Some(Pubkey::new_unique()),
+            program_id: Some(*program_id),
+        }])
+        .await
+        .unwrap();
+    let token_context = context.token_context.take().unwrap();
+    let mint = token_context.token.get_address();
+    let mint_authority = &token_context.mint_authority;
+
+    // the owner list, and the extra account metas pointing at it
+    let payer = context.context.lock().await.payer.pubkey();
+    let rent = context
+        .context
+        .lock()
+        .await
+        .banks_client
+        .get_rent()
+        .await
+        .unwrap();
+    let extra_account_metas = get_extra_account_metas_address(mint, program_id);
+    process_instructions(
+        &context,
+        &[
+            initialize_owner_list(
+                program_id,
+                mint,
+                &mint_authority.pubkey(),
+                &payer,
+                Some(*admin),
+                mode,
+            )
+            .unwrap(),
+            system_instruction::transfer(
+                &payer,
+                &extra_account_metas,
+                rent.minimum_balance(ExtraAccountMetas::size_of(1).unwrap()),
+            ),
+            initialize_extra_account_metas(
+                program_id,
+                &extra_account_metas,
+                mint,
+                &mint_authority.pubkey(),
+                &[],
+            ),
+        ],
+        &[mint_authority],
+    )
+    .await;
+
+    let alice_account = Keypair::new();
+    token_context
+        .token
+        .create_auxiliary_token_account(&alice_account, &token_context.alice.pubkey())
+        .await
+        .unwrap();
+    let alice_account = alice_account.pubkey();
+    let bob_account = Keypair::new();
+    token_context
+        .token
+        .create_auxiliary_token_account(&bob_account, &token_context.bob.pubkey())
+        .await
+        .unwrap();
+    let bob_account = bob_account.pubkey();
+    token_context
+        .token
+        .mint_to(
+            &alice_account,
+            &mint_authority.pubkey(),
+            amount,
+            &[mint_authority],
+        )
+        .await
+        .unwrap();
+
+    (context, token_context, alice_account, bob_account)
+}
+
+fn owner_list_error(error: AllowlistError) -> TokenClientError {
+    TokenClientError::Client(Box::new(TransportError::TransactionError(
+        TransactionError::InstructionError(0, InstructionError::Custom(error as u32)),
+    )))
+}
+
+#[tokio::test]
+async fn success_transfer_between_allowed_owners() {
+    let program_id = Pubkey::new_unique();
+    let admin = Keypair::new();
+    let amount = 10;
+    let (context, token_context, alice_account, bob_account) =
+        setup(&program_id, &admin.pubkey(), ListMode::Allow, amount).await;
+    let mint = token_context.token.get_address();
+    let payer = context.context.lock().await.payer.pubkey();
+
+    // only alice is allowed
+    process_instructions(
+        &context,
+        &[add_owner(
+            &program_id,
+            mint,
+            &admin.pubkey(),
+            &payer,
+            &token_context.alice.pubkey(),
+        )],
+        &[&admin],
+    )
+    .await;
+    let error = token_context
+        .token
+        .transfer(
+            &alice_account,
+            &bob_account,
+            &token_context.alice.pubkey(),
+            1,
+            &[&token_context.alice],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(error, owner_list_error(AllowlistError::OwnerNotAllowed));
+
+    // once bob is too, the transfer goes through
+    process_instructions(
+        &context,
+        &[add_owner(
+            &program_id,
+            mint,
+            &admin.pubkey(),
+            &payer,
+            &token_context.bob.pubkey(),
+        )],
+        &[&admin],
+    )
+    .await;
+    token_context
+        .token
+        .transfer(
+            &alice_account,
+            &bob_account,
+            &token_context.alice.pubkey(),
+            amount,
+            &[&token_context.alice],
+        )
+        .await
+        .unwrap();
+    let destination = token_context
+        .token
+        .get_account_info(&bob_account)
+        .await
+        .unwrap();
+    assert_eq!(destination.base.amount, amount);
+
+    // and stops once alice is removed
+    process_instructions(
+        &context,
+        &[remove_owner(
+            &program_id,
+            mint,
+            &admin.pubkey(),
+            &token_context.alice.pubkey(),
+        )],
+        &[&admin],
+    )
+    .await;
+    let error = token_context
+        .token
+        .transfer(
+            &bob_account,
+            &alice_account,
+            &token_context.bob.pubkey(),
+            amount,
+            &[&token_context.bob],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(error, owner_list_error(AllowlistError::OwnerNotAllowed));
+}
+
+#[tokio::test]
+async fn fail_transfer_to_denied_owner() {
+    let program_id = Pubkey::new_unique();
+    let admin = Keypair::new();
+    let amount = 10;
+    let (context, token_context, alice_account, bob_account) =
+        setup(&program_id, &admin.pubkey(), ListMode::Deny, amount).await;
+    let mint = token_context.token.get_address();
+    let payer = context.context.lock().await.payer.pubkey();
+
+    // nobody is denied yet
+    token_context
+        .token
+        .transfer(
+            &alice_account,
+            &bob_account,
+            &token_context.alice.pubkey(),
+            1,
+            &[&token_context.alice],
+        )
+        .await
+        .unwrap();
+
+    process_instructions(
+        &context,
+        &[add_owner(
+            &program_id,
+            mint,
+            &admin.pubkey(),
+            &payer,
+            &token_context.bob.pubkey(),
+        )],
+        &[&admin],
+    )
+    .await;
+    let error = token_context
+        .token
+        .transfer(
+            &alice_account,
+            &bob_account,
+            &token_context.alice.pubkey(),
+            2,
+            &[&token_context.alice],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(error, owner_list_error(AllowlistError::OwnerDenied));
+
+    // bob can't send either
+    let error = token_context
+        .token
+        .transfer(
+            &bob_account,
+            &alice_account,
+            &token_context.bob.pubkey(),
+            1,
+            &[&token_context.bob],
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(error, owner_list_error(AllowlistError::OwnerDenied));
+}
@@ -18,7 +18,7 @@ use {
             non_transferable::{NonTransferable, NonTransferableAccount},
             permanent_delegate::PermanentDelegate,
             transfer_fee::{TransferFeeAmount, TransferFeeConfig},
-            transfer_hook::{TransferHook, TransferHookAccount},
+            transfer_hook::{TransferHook, TransferHookAccount, TransferHookEvents},
         },
         pod::*,
         state::{Account, Mint, Multisig},
@@ -900,6 +900,10 @@ pub enum ExtensionType {
     MetadataPointer,
     /// Mint contains token-metadata
     TokenMetadata,
+    /// Mint also invokes its transfer hook program on mints and burns
+    TransferHookEvents,
+    /// Mint contains token-metadata fields that can never be updated
+    TokenMetadataFieldLocks,
     /// Test variable-length mint extension
     #[cfg(test)]
     VariableLenMintTest = u16::MAX - 2,
@@ -932,6 +936,7 @@ impl ExtensionType {
     const fn sized(&self) -> bool {
         match self {
             ExtensionType::TokenMetadata => false,
+            ExtensionType::TokenMetadataFieldLocks => false,
             #[cfg(test)]
             ExtensionType::VariableLenMintTest => false,
             _ => true,
@@ -974,6 +979,8 @@ impl ExtensionType {
             }
             ExtensionType::MetadataPointer => pod_get_packed_len::<MetadataPointer>(),
             ExtensionType::TokenMetadata => unreachable!(),
+            ExtensionType::TransferHookEvents => pod_get_packed_len::<TransferHookEvents>(),
+            ExtensionType::TokenMetadataFieldLocks => unreachable!(),
             #[cfg(test)]
             ExtensionType::AccountPaddingTest => pod_get_packed_len::<AccountPaddingTest>(),
             #[cfg(test)]
@@ -1033,7 +1040,9 @@ impl ExtensionType {
             | ExtensionType::TransferHook
             | ExtensionType::ConfidentialTransferFeeConfig
             | ExtensionType::MetadataPointer
-            | ExtensionType::TokenMetadata => AccountType::Mint,
+            | ExtensionType::TokenMetadata
+            | ExtensionType::TransferHookEvents
+            | ExtensionType::TokenMetadataFieldLocks => AccountType::Mint,
             ExtensionType::ImmutableOwner
             | ExtensionType::TransferFeeAmount
             | ExtensionType::ConfidentialTransferAccount
@@ -1083,6 +1092,8 @@ impl ExtensionType {
         let mut transfer_fee_config = false;
         let mut confidential_transfer_mint = false;
         let mut confidential_transfer_fee_config = false;
+        let mut transfer_hook = false;
+        let mut transfer_hook_events = false;
 
         for extension_type in mint_extension_types {
             match extension_type {
@@ -1091,6 +1102,8 @@ impl ExtensionType {
                 ExtensionType::ConfidentialTransferFeeConfig => {
                     confidential_transfer_fee_config = true
                 }
+                ExtensionType::TransferHook => transfer_hook = true,
+                ExtensionType::TransferHookEvents => transfer_hook_events = true,
                 _ => (),
             }
         }
@@ -1104,6 +1117,10 @@ impl ExtensionType {
             return Err(TokenError::InvalidExtensionCombination);
         }
 
+        if transfer_hook_events && !transfer_hook {
+            return Err(TokenError::InvalidExtensionCombination);
+        }
+
         Ok(())
     }
 }
@@ -1,6 +1,6 @@
 use {
     crate::extension::{Extension, ExtensionType},
-    spl_token_metadata_interface::state::TokenMetadata,
+    spl_token_metadata_interface::state::{FieldLocks, TokenMetadata},
 };
 
 /// Instruction processor for the TokenMetadata extension
@@ -9,3 +9,7 @@ pub mod processor;
 impl Extension for TokenMetadata {
     const TYPE: ExtensionType = ExtensionType::TokenMetadata;
 }
+
+impl Extension for FieldLocks {
+    const TYPE: ExtensionType = ExtensionType::TokenMetadataFieldLocks;
+}
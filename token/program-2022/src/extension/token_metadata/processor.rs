@@ -14,7 +14,7 @@ use {
         account_info::{next_account_info, AccountInfo},
         entrypoint::ProgramResult,
         msg,
-        program::set_return_data,
+        program::{set_return_data, MAX_RETURN_DATA},
         program_error::ProgramError,
         program_option::COption,
         pubkey::Pubkey,
@@ -22,9 +22,10 @@ use {
     spl_token_metadata_interface::{
         error::TokenMetadataError,
         instruction::{
-            Emit, Initialize, RemoveKey, TokenMetadataInstruction, UpdateAuthority, UpdateField,
+            BatchUpdateFields, Emit, EmitField, Initialize, LockField, RemoveKey,
+            TokenMetadataInstruction, UpdateAuthority, UpdateField,
         },
-        state::{OptionalNonZeroPubkey, TokenMetadata},
+        state::{Field, FieldLocks, OptionalNonZeroPubkey, TokenMetadata},
     },
 };
 
@@ -43,6 +44,16 @@ fn check_update_authority(
     Ok(())
 }
 
+/// Gets the locked fields stored in the mint, which are empty if the extension
+/// is not present
+fn get_field_locks(mint: &StateWithExtensions<Mint>) -> Result<FieldLocks, ProgramError> {
+    if mint.get_extension_bytes::<FieldLocks>().is_ok() {
+        mint.get_variable_len_extension::<FieldLocks>()
+    } else {
+        Ok(FieldLocks::default())
+    }
+}
+
 /// Processes a [Initialize](enum.TokenMetadataInstruction.html) instruction.
 pub fn process_initialize(
     _program_id: &Pubkey,
@@ -114,13 +125,17 @@ pub fn process_update_field(
 
     // deserialize the metadata, but scope the data borrow since we'll probably
     // realloc the account
-    let mut token_metadata = {
+    let (mut token_metadata, field_locks) = {
         let buffer = metadata_info.try_borrow_data()?;
         let mint = StateWithExtensions::<Mint>::unpack(&buffer)?;
-        mint.get_variable_len_extension::<TokenMetadata>()?
+        (
+            mint.get_variable_len_extension::<TokenMetadata>()?,
+            get_field_locks(&mint)?,
+        )
     };
 
     check_update_authority(update_authority_info, &token_metadata.update_authority)?;
+    field_locks.check_field(&data.field)?;
 
     // Update the field
     token_metadata.update(data.field, data.value);
@@ -143,13 +158,17 @@ pub fn process_remove_key(
 
     // deserialize the metadata, but scope the data borrow since we'll probably
     // realloc the account
-    let mut token_metadata = {
+    let (mut token_metadata, field_locks) = {
         let buffer = metadata_info.try_borrow_data()?;
         let mint = StateWithExtensions::<Mint>::unpack(&buffer)?;
-        mint.get_variable_len_extension::<TokenMetadata>()?
+        (
+            mint.get_variable_len_extension::<TokenMetadata>()?,
+            get_field_locks(&mint)?,
+        )
     };
 
     check_update_authority(update_authority_info, &token_metadata.update_authority)?;
+    field_locks.check_field(&Field::Key(data.key.clone()))?;
     if !token_metadata.remove_key(&data.key) && !data.idempotent {
         return Err(TokenMetadataError::KeyNotFound.into());
     }
@@ -183,6 +202,80 @@ pub fn process_update_authority(
     Ok(())
 }
 
+/// Processes a [BatchUpdateFields](enum.TokenMetadataInstruction.html) instruction.
+pub fn process_batch_update_fields(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: BatchUpdateFields,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let metadata_info = next_account_info(account_info_iter)?;
+    let update_authority_info = next_account_info(account_info_iter)?;
+
+    // deserialize the metadata, but scope the data borrow since we'll probably
+    // realloc the account
+    let (mut token_metadata, field_locks) = {
+        let buffer = metadata_info.try_borrow_data()?;
+        let mint = StateWithExtensions::<Mint>::unpack(&buffer)?;
+        (
+            mint.get_variable_len_extension::<TokenMetadata>()?,
+            get_field_locks(&mint)?,
+        )
+    };
+
+    check_update_authority(update_authority_info, &token_metadata.update_authority)?;
+    field_locks.check_updates(&data.updates)?;
+
+    // Apply all updates before touching the account, so that it's only
+    // resized once
+    token_metadata.apply_updates(data.updates)?;
+
+    alloc_and_serialize::<Mint, _>(metadata_info, &token_metadata, true)?;
+
+    Ok(())
+}
+
+/// Processes a [LockField](enum.TokenMetadataInstruction.html) instruction.
+pub fn process_lock_field(
+    _program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: LockField,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let metadata_info = next_account_info(account_info_iter)?;
+    let update_authority_info = next_account_info(account_info_iter)?;
+
+    // deserialize the metadata and locks, but scope the data borrow since we'll
+    // probably realloc the account
+    let (token_metadata, mut field_locks) = {
+        let buffer = metadata_info.try_borrow_data()?;
+        let mint = StateWithExtensions::<Mint>::unpack(&buffer)?;
+        (
+            mint.get_variable_len_extension::<TokenMetadata>()?,
+            get_field_locks(&mint)?,
+        )
+    };
+
+    check_update_authority(update_authority_info, &token_metadata.update_authority)?;
+    if let Field::Key(ref key) = data.field {
+        if !token_metadata
+            .additional_metadata
+            .iter()
+            .any(|(k, _)| k == key)
+        {
+            return Err(TokenMetadataError::KeyNotFound.into());
+        }
+    }
+
+    if field_locks.lock(data.field) {
+        // allocates the extension on the first lock, assumes that there's
+        // enough SOL for the new rent-exemption
+        alloc_and_serialize::<Mint, _>(metadata_info, &field_locks, true)?;
+    }
+
+    Ok(())
+}
+
 /// Processes an [Emit](enum.TokenMetadataInstruction.html) instruction.
 pub fn process_emit(program_id: &Pubkey, accounts: &[AccountInfo], data: Emit) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
@@ -202,6 +295,32 @@ pub fn process_emit(program_id: &Pubkey, accounts: &[AccountInfo], data: Emit) -
     Ok(())
 }
 
+/// Processes an [EmitField](enum.TokenMetadataInstruction.html) instruction.
+pub fn process_emit_field(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    data: EmitField,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let metadata_info = next_account_info(account_info_iter)?;
+
+    if metadata_info.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+
+    let buffer = metadata_info.try_borrow_data()?;
+    let state = StateWithExtensions::<Mint>::unpack(&buffer)?;
+    let metadata_bytes = state.get_extension_bytes::<TokenMetadata>()?;
+
+    let value = TokenMetadata::get_field_value_from_bytes(metadata_bytes, &data.field)?
+        .ok_or::<ProgramError>(TokenMetadataError::KeyNotFound.into())?;
+    if value.len() > MAX_RETURN_DATA {
+        return Err(ProgramError::InvalidArgument);
+    }
+    set_return_data(value.as_bytes());
+    Ok(())
+}
+
 /// Processes an [Instruction](enum.Instruction.html).
 pub fn process_instruction(
     program_id: &Pubkey,
@@ -229,5 +348,17 @@ pub fn process_instruction(
             msg!("TokenMetadataInstruction: Emit");
             process_emit(program_id, accounts, data)
         }
+        TokenMetadataInstruction::BatchUpdateFields(data) => {
+            msg!("TokenMetadataInstruction: BatchUpdateFields");
+            process_batch_update_fields(program_id, accounts, data)
+        }
+        TokenMetadataInstruction::LockField(data) => {
+            msg!("TokenMetadataInstruction: LockField");
+            process_lock_field(program_id, accounts, data)
+        }
+        TokenMetadataInstruction::EmitField(data) => {
+            msg!("TokenMetadataInstruction: EmitField");
+            process_emit_field(program_id, accounts, data)
+        }
     }
 }
@@ -2,7 +2,7 @@ use {
     crate::{
         check_program_account,
         instruction::{encode_instruction, TokenInstruction},
-        pod::OptionalNonZeroPubkey,
+        pod::{OptionalNonZeroPubkey, PodBool},
     },
     bytemuck::{Pod, Zeroable},
     num_enum::{IntoPrimitive, TryFromPrimitive},
@@ -53,6 +53,44 @@ pub enum TransferHookInstruction {
     ///   `crate::extension::transfer_hook::UpdateInstructionData`
     ///
     Update,
+    /// Initialize the `TransferHookEvents` extension on a new mint, to also
+    /// invoke the transfer hook program on mints and burns.
+    ///
+    /// Fails if the mint has already been initialized, so must be called before
+    /// `InitializeMint`. The mint must also include the `TransferHook`
+    /// extension.
+    ///
+    /// The mint must have exactly enough space allocated for the base mint (82
+    /// bytes), plus 83 bytes of padding, 1 byte reserved for the account type,
+    /// then space required for this extension, plus any others.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[writable]` The mint to initialize.
+    ///
+    /// Data expected by this instruction:
+    ///   `crate::extension::transfer_hook::instruction::EventsInstructionData`
+    ///
+    InitializeEvents,
+    /// Update which events, other than transfers, invoke the transfer hook
+    /// program. Only supported for mints that include the `TransferHookEvents`
+    /// extension.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   * Single authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[signer]` The transfer hook authority.
+    ///
+    ///   * Multisignature authority
+    ///   0. `[writable]` The mint.
+    ///   1. `[]` The mint's transfer hook authority.
+    ///   2. ..2+M `[signer]` M signer accounts.
+    ///
+    /// Data expected by this instruction:
+    ///   `crate::extension::transfer_hook::instruction::EventsInstructionData`
+    ///
+    UpdateEvents,
 }
 
 /// Data expected by `Initialize`
@@ -73,6 +111,16 @@ pub struct UpdateInstructionData {
     pub program_id: OptionalNonZeroPubkey,
 }
 
+/// Data expected by `InitializeEvents` and `UpdateEvents`
+#[derive(Clone, Copy, Pod, Zeroable)]
+#[repr(C)]
+pub struct EventsInstructionData {
+    /// Whether the program is invoked on mints
+    pub hook_mint: PodBool,
+    /// Whether the program is invoked on burns
+    pub hook_burn: PodBool,
+}
+
 /// Create an `Initialize` instruction
 pub fn initialize(
     token_program_id: &Pubkey,
@@ -120,3 +168,53 @@ pub fn update(
         },
     ))
 }
+
+/// Create an `InitializeEvents` instruction
+pub fn initialize_events(
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    hook_mint: bool,
+    hook_burn: bool,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let accounts = vec![AccountMeta::new(*mint, false)];
+    Ok(encode_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::TransferHookExtension,
+        TransferHookInstruction::InitializeEvents,
+        &EventsInstructionData {
+            hook_mint: hook_mint.into(),
+            hook_burn: hook_burn.into(),
+        },
+    ))
+}
+
+/// Create an `UpdateEvents` instruction
+pub fn update_events(
+    token_program_id: &Pubkey,
+    mint: &Pubkey,
+    authority: &Pubkey,
+    signers: &[&Pubkey],
+    hook_mint: bool,
+    hook_burn: bool,
+) -> Result<Instruction, ProgramError> {
+    check_program_account(token_program_id)?;
+    let mut accounts = vec![
+        AccountMeta::new(*mint, false),
+        AccountMeta::new_readonly(*authority, signers.is_empty()),
+    ];
+    for signer_pubkey in signers.iter() {
+        accounts.push(AccountMeta::new_readonly(**signer_pubkey, true));
+    }
+    Ok(encode_instruction(
+        token_program_id,
+        accounts,
+        TokenInstruction::TransferHookExtension,
+        TransferHookInstruction::UpdateEvents,
+        &EventsInstructionData {
+            hook_mint: hook_mint.into(),
+            hook_burn: hook_burn.into(),
+        },
+    ))
+}
@@ -33,6 +33,17 @@ pub struct TransferHookAccount {
     pub transferring: PodBool,
 }
 
+/// Transfer hook extension data for mints that also invoke the transfer hook
+/// program when minting or burning tokens
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct TransferHookEvents {
+    /// Whether the program's `ExecuteOnMint` is invoked on mints
+    pub hook_mint: PodBool,
+    /// Whether the program's `ExecuteOnBurn` is invoked on burns
+    pub hook_burn: PodBool,
+}
+
 impl Extension for TransferHook {
     const TYPE: ExtensionType = ExtensionType::TransferHook;
 }
@@ -41,6 +52,10 @@ impl Extension for TransferHookAccount {
     const TYPE: ExtensionType = ExtensionType::TransferHookAccount;
 }
 
+impl Extension for TransferHookEvents {
+    const TYPE: ExtensionType = ExtensionType::TransferHookEvents;
+}
+
 /// Attempts to get the transfer hook program id from the TLV data, returning
 /// None if the extension is not found
 pub fn get_program_id<S: BaseState, BSE: BaseStateWithExtensions<S>>(
@@ -52,6 +67,30 @@ pub fn get_program_id<S: BaseState, BSE: BaseStateWithExtensions<S>>(
         .and_then(|e| Option::<Pubkey>::from(e.program_id))
 }
 
+/// Attempts to get the transfer hook program id to invoke on mints, returning
+/// None if the mint doesn't hook mints
+pub fn get_mint_hook_program_id<S: BaseState, BSE: BaseStateWithExtensions<S>>(
+    state: &BSE,
+) -> Option<Pubkey> {
+    state
+        .get_extension::<TransferHookEvents>()
+        .ok()
+        .filter(|e| bool::from(e.hook_mint))
+        .and_then(|_| get_program_id(state))
+}
+
+/// Attempts to get the transfer hook program id to invoke on burns, returning
+/// None if the mint doesn't hook burns
+pub fn get_burn_hook_program_id<S: BaseState, BSE: BaseStateWithExtensions<S>>(
+    state: &BSE,
+) -> Option<Pubkey> {
+    state
+        .get_extension::<TransferHookEvents>()
+        .ok()
+        .filter(|e| bool::from(e.hook_burn))
+        .and_then(|_| get_program_id(state))
+}
+
 /// Helper function to set the transferring flag before calling into transfer hook
 pub fn set_transferring(account: &mut StateWithExtensionsMut<Account>) -> Result<(), ProgramError> {
     let account_extension = account.get_extension_mut::<TransferHookAccount>()?;
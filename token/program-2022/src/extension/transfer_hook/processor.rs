@@ -5,14 +5,15 @@ use {
         extension::{
             transfer_hook::{
                 instruction::{
-                    InitializeInstructionData, TransferHookInstruction, UpdateInstructionData,
+                    EventsInstructionData, InitializeInstructionData, TransferHookInstruction,
+                    UpdateInstructionData,
                 },
-                TransferHook,
+                TransferHook, TransferHookEvents,
             },
-            StateWithExtensionsMut,
+            BaseStateWithExtensions, StateWithExtensionsMut,
         },
         instruction::{decode_instruction_data, decode_instruction_type},
-        pod::OptionalNonZeroPubkey,
+        pod::{OptionalNonZeroPubkey, PodBool},
         processor::Processor,
         state::Mint,
     },
@@ -85,6 +86,52 @@ fn process_update(
     Ok(())
 }
 
+fn process_initialize_events(
+    accounts: &[AccountInfo],
+    hook_mint: PodBool,
+    hook_burn: PodBool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let mut mint_data = mint_account_info.data.borrow_mut();
+    let mut mint = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut mint_data)?;
+
+    let extension = mint.init_extension::<TransferHookEvents>(true)?;
+    extension.hook_mint = hook_mint;
+    extension.hook_burn = hook_burn;
+    Ok(())
+}
+
+fn process_update_events(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    hook_mint: PodBool,
+    hook_burn: PodBool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+    let mint_account_info = next_account_info(account_info_iter)?;
+    let owner_info = next_account_info(account_info_iter)?;
+    let owner_info_data_len = owner_info.data_len();
+
+    let mut mint_data = mint_account_info.data.borrow_mut();
+    let mut mint = StateWithExtensionsMut::<Mint>::unpack(&mut mint_data)?;
+    let authority = Option::<Pubkey>::from(mint.get_extension::<TransferHook>()?.authority)
+        .ok_or(TokenError::NoAuthorityExists)?;
+
+    Processor::validate_owner(
+        program_id,
+        &authority,
+        owner_info,
+        owner_info_data_len,
+        account_info_iter.as_slice(),
+    )?;
+
+    let extension = mint.get_extension_mut::<TransferHookEvents>()?;
+    extension.hook_mint = hook_mint;
+    extension.hook_burn = hook_burn;
+    Ok(())
+}
+
 pub(crate) fn process_instruction(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
@@ -107,5 +154,21 @@ pub(crate) fn process_instruction(
             } = decode_instruction_data(input)?;
             process_update(program_id, accounts, transfer_hook_program_id)
         }
+        TransferHookInstruction::InitializeEvents => {
+            msg!("TransferHookInstruction::InitializeEvents");
+            let EventsInstructionData {
+                hook_mint,
+                hook_burn,
+            } = decode_instruction_data(input)?;
+            process_initialize_events(accounts, *hook_mint, *hook_burn)
+        }
+        TransferHookInstruction::UpdateEvents => {
+            msg!("TransferHookInstruction::UpdateEvents");
+            let EventsInstructionData {
+                hook_mint,
+                hook_burn,
+            } = decode_instruction_data(input)?;
+            process_update_events(program_id, accounts, *hook_mint, *hook_burn)
+        }
     }
 }
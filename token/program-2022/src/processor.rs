@@ -935,6 +935,24 @@ impl Processor {
         mint.pack_base();
         destination_account.pack_base();
 
+        if let Some(transfer_hook_program_id) = transfer_hook::get_mint_hook_program_id(&mint) {
+            transfer_hook::set_transferring(&mut destination_account)?;
+
+            // must drop these to avoid the double-borrow during CPI
+            drop(mint_data);
+            drop(destination_account_data);
+            spl_transfer_hook_interface::onchain::invoke_execute_on_mint(
+                &transfer_hook_program_id,
+                destination_account_info.clone(),
+                mint_info.clone(),
+                owner_info.clone(),
+                account_info_iter.as_slice(),
+                amount,
+            )?;
+
+            transfer_hook::unset_transferring(destination_account_info)?;
+        }
+
         Ok(())
     }
 
@@ -1051,6 +1069,24 @@ impl Processor {
         source_account.pack_base();
         mint.pack_base();
 
+        if let Some(transfer_hook_program_id) = transfer_hook::get_burn_hook_program_id(&mint) {
+            transfer_hook::set_transferring(&mut source_account)?;
+
+            // must drop these to avoid the double-borrow during CPI
+            drop(source_account_data);
+            drop(mint_data);
+            spl_transfer_hook_interface::onchain::invoke_execute_on_burn(
+                &transfer_hook_program_id,
+                source_account_info.clone(),
+                mint_info.clone(),
+                authority_info.clone(),
+                account_info_iter.as_slice(),
+                amount,
+            )?;
+
+            transfer_hook::unset_transferring(source_account_info)?;
+        }
+
         Ok(())
     }
 
@@ -0,0 +1,32 @@
+//! Error types
+
+use spl_program_error::*;
+
+/// Errors that may be returned by the program, on top of the
+/// `TransferHookError`s of the interface.
+///
+/// Codes start well past the interface's, so that the two never overlap.
+#[spl_program_error]
+pub enum AllowlistError {
+    /// Incorrect owner list admin has signed the instruction
+    #[error("Incorrect owner list admin has signed the instruction")]
+    IncorrectAdmin = 1_000,
+    /// Owner list has no admin, and can no longer change
+    #[error("Owner list has no admin, and can no longer change")]
+    ImmutableOwnerList,
+    /// Owner list mode is not recognized
+    #[error("Owner list mode is not recognized")]
+    InvalidListMode,
+    /// Owner already on the list
+    #[error("Owner already on the list")]
+    OwnerAlreadyListed,
+    /// Owner not on the list
+    #[error("Owner not on the list")]
+    OwnerNotListed,
+    /// Token account owner is not on the allowlist
+    #[error("Token account owner is not on the allowlist")]
+    OwnerNotAllowed,
+    /// Token account owner is on the denylist
+    #[error("Token account owner is on the denylist")]
+    OwnerDenied,
+}
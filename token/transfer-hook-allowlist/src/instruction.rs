@@ -0,0 +1,384 @@
+//! Instruction types
+
+use {
+    crate::{get_owner_list_address, state::ListMode},
+    solana_program::{
+        instruction::{AccountMeta, Instruction},
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        system_program,
+    },
+    spl_discriminator::{ArrayDiscriminator, SplDiscriminate},
+    spl_token_2022::pod::OptionalNonZeroPubkey,
+    spl_type_length_value::pod::PodSlice,
+};
+
+/// Instructions to manage owner lists, supported on top of the transfer hook
+/// interface's instructions.
+#[derive(Clone, Debug, PartialEq)]
+pub enum AllowlistInstruction {
+    /// Creates the owner list of a mint, empty. The list is checked on every
+    /// transfer once the mint's extra account metas are initialized.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[w]` Owner list
+    ///   1. `[]` Mint
+    ///   2. `[s]` Mint authority
+    ///   3. `[ws]` Funding account
+    ///   4. `[]` System program
+    ///
+    InitializeOwnerList {
+        /// Authority that can add and remove owners
+        admin: OptionalNonZeroPubkey,
+        /// How the owners on the list are treated
+        mode: ListMode,
+    },
+    /// Adds an owner to the list, failing if already there.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[w]` Owner list
+    ///   1. `[s]` Owner list admin
+    ///   2. `[ws]` Funding account, for the rent of the larger list
+    ///   3. `[]` System program
+    ///
+    AddOwner {
+        /// Owner to add
+        owner: Pubkey,
+    },
+    /// Removes an owner from the list, failing if not there. Lamports freed
+    /// by the smaller list stay in the list account.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[w]` Owner list
+    ///   1. `[s]` Owner list admin
+    ///
+    RemoveOwner {
+        /// Owner to remove
+        owner: Pubkey,
+    },
+    /// Adds many owners to the list at once, skipping the ones already there,
+    /// so that an interrupted import can simply be sent again.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[w]` Owner list
+    ///   1. `[s]` Owner list admin
+    ///   2. `[ws]` Funding account, for the rent of the larger list
+    ///   3. `[]` System program
+    ///
+    ImportOwners {
+        /// Owners to add
+        owners: Vec<Pubkey>,
+    },
+    /// Changes the admin of the list. Without an admin, the list can no
+    /// longer change.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[w]` Owner list
+    ///   1. `[s]` Current owner list admin
+    ///
+    SetAdmin {
+        /// New admin of the list
+        new_admin: OptionalNonZeroPubkey,
+    },
+}
+
+/// Instruction type used to initialize an owner list
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("spl-transfer-hook-allowlist:initialize-owner-list")]
+pub struct InitializeOwnerListInstruction;
+
+/// Instruction type used to add an owner to a list
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("spl-transfer-hook-allowlist:add-owner")]
+pub struct AddOwnerInstruction;
+
+/// Instruction type used to remove an owner from a list
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("spl-transfer-hook-allowlist:remove-owner")]
+pub struct RemoveOwnerInstruction;
+
+/// Instruction type used to add many owners to a list
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("spl-transfer-hook-allowlist:import-owners")]
+pub struct ImportOwnersInstruction;
+
+/// Instruction type used to change the admin of a list
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("spl-transfer-hook-allowlist:set-admin")]
+pub struct SetAdminInstruction;
+
+impl AllowlistInstruction {
+    /// Unpacks a byte buffer into an [AllowlistInstruction](enum.AllowlistInstruction.html).
+    pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
+        if input.len() < ArrayDiscriminator::LENGTH {
+            return Err(ProgramError::InvalidInstructionData);
+        }
+        let (discriminator, rest) = input.split_at(ArrayDiscriminator::LENGTH);
+        Ok(match discriminator {
+            InitializeOwnerListInstruction::SPL_DISCRIMINATOR_SLICE => {
+                let admin = unpack_optional_pubkey(rest)?;
+                let mode = rest
+                    .get(32)
+                    .ok_or(ProgramError::InvalidInstructionData)
+                    .and_then(|mode| ListMode::try_from(*mode))?;
+                Self::InitializeOwnerList { admin, mode }
+            }
+            AddOwnerInstruction::SPL_DISCRIMINATOR_SLICE => Self::AddOwner {
+                owner: unpack_pubkey(rest)?,
+            },
+            RemoveOwnerInstruction::SPL_DISCRIMINATOR_SLICE => Self::RemoveOwner {
+                owner: unpack_pubkey(rest)?,
+            },
+            ImportOwnersInstruction::SPL_DISCRIMINATOR_SLICE => Self::ImportOwners {
+                owners: PodSlice::<Pubkey>::unpack(rest)?.data().to_vec(),
+            },
+            SetAdminInstruction::SPL_DISCRIMINATOR_SLICE => Self::SetAdmin {
+                new_admin: unpack_optional_pubkey(rest)?,
+            },
+            _ => return Err(ProgramError::InvalidInstructionData),
+        })
+    }
+
+    /// Packs an [AllowlistInstruction](enum.AllowlistInstruction.html) into a byte buffer.
+    pub fn pack(&self) -> Vec<u8> {
+        let mut buf = vec![];
+        match self {
+            Self::InitializeOwnerList { admin, mode } => {
+                buf.extend_from_slice(InitializeOwnerListInstruction::SPL_DISCRIMINATOR_SLICE);
+                buf.extend_from_slice(bytemuck::bytes_of(admin));
+                buf.push(*mode as u8);
+            }
+            Self::AddOwner { owner } => {
+                buf.extend_from_slice(AddOwnerInstruction::SPL_DISCRIMINATOR_SLICE);
+                buf.extend_from_slice(owner.as_ref());
+            }
+            Self::RemoveOwner { owner } => {
+                buf.extend_from_slice(RemoveOwnerInstruction::SPL_DISCRIMINATOR_SLICE);
+                buf.extend_from_slice(owner.as_ref());
+            }
+            Self::ImportOwners { owners } => {
+                buf.extend_from_slice(ImportOwnersInstruction::SPL_DISCRIMINATOR_SLICE);
+                buf.extend_from_slice(&(owners.len() as u32).to_le_bytes());
+                buf.extend_from_slice(bytemuck::cast_slice(owners));
+            }
+            Self::SetAdmin { new_admin } => {
+                buf.extend_from_slice(SetAdminInstruction::SPL_DISCRIMINATOR_SLICE);
+                buf.extend_from_slice(bytemuck::bytes_of(new_admin));
+            }
+        };
+        buf
+    }
+}
+
+fn unpack_pubkey(input: &[u8]) -> Result<Pubkey, ProgramError> {
+    input
+        .get(..32)
+        .map(Pubkey::try_from)
+        .and_then(Result::ok)
+        .ok_or(ProgramError::InvalidInstructionData)
+}
+
+fn unpack_optional_pubkey(input: &[u8]) -> Result<OptionalNonZeroPubkey, ProgramError> {
+    input
+        .get(..32)
+        .and_then(|bytes| bytemuck::try_from_bytes::<OptionalNonZeroPubkey>(bytes).ok())
+        .copied()
+        .ok_or(ProgramError::InvalidInstructionData)
+}
+
+/// Creates an `InitializeOwnerList` instruction
+pub fn initialize_owner_list(
+    program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    mint_authority_pubkey: &Pubkey,
+    funding_pubkey: &Pubkey,
+    admin: Option<Pubkey>,
+    mode: ListMode,
+) -> Result<Instruction, ProgramError> {
+    let data = AllowlistInstruction::InitializeOwnerList {
+        admin: admin.try_into()?,
+        mode,
+    }
+    .pack();
+    let accounts = vec![
+        AccountMeta::new(get_owner_list_address(mint_pubkey, program_id), false),
+        AccountMeta::new_readonly(*mint_pubkey, false),
+        AccountMeta::new_readonly(*mint_authority_pubkey, true),
+        AccountMeta::new(*funding_pubkey, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ];
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+/// Creates an `AddOwner` instruction
+pub fn add_owner(
+    program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    admin_pubkey: &Pubkey,
+    funding_pubkey: &Pubkey,
+    owner: &Pubkey,
+) -> Instruction {
+    let data = AllowlistInstruction::AddOwner { owner: *owner }.pack();
+    Instruction {
+        program_id: *program_id,
+        accounts: funded_accounts(program_id, mint_pubkey, admin_pubkey, funding_pubkey),
+        data,
+    }
+}
+
+/// Creates a `RemoveOwner` instruction
+pub fn remove_owner(
+    program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    admin_pubkey: &Pubkey,
+    owner: &Pubkey,
+) -> Instruction {
+    let data = AllowlistInstruction::RemoveOwner { owner: *owner }.pack();
+    let accounts = vec![
+        AccountMeta::new(get_owner_list_address(mint_pubkey, program_id), false),
+        AccountMeta::new_readonly(*admin_pubkey, true),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates an `ImportOwners` instruction
+pub fn import_owners(
+    program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    admin_pubkey: &Pubkey,
+    funding_pubkey: &Pubkey,
+    owners: &[Pubkey],
+) -> Instruction {
+    let data = AllowlistInstruction::ImportOwners {
+        owners: owners.to_vec(),
+    }
+    .pack();
+    Instruction {
+        program_id: *program_id,
+        accounts: funded_accounts(program_id, mint_pubkey, admin_pubkey, funding_pubkey),
+        data,
+    }
+}
+
+/// Creates a `SetAdmin` instruction
+pub fn set_admin(
+    program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    admin_pubkey: &Pubkey,
+    new_admin: Option<Pubkey>,
+) -> Result<Instruction, ProgramError> {
+    let data = AllowlistInstruction::SetAdmin {
+        new_admin: new_admin.try_into()?,
+    }
+    .pack();
+    let accounts = vec![
+        AccountMeta::new(get_owner_list_address(mint_pubkey, program_id), false),
+        AccountMeta::new_readonly(*admin_pubkey, true),
+    ];
+    Ok(Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    })
+}
+
+fn funded_accounts(
+    program_id: &Pubkey,
+    mint_pubkey: &Pubkey,
+    admin_pubkey: &Pubkey,
+    funding_pubkey: &Pubkey,
+) -> Vec<AccountMeta> {
+    vec![
+        AccountMeta::new(get_owner_list_address(mint_pubkey, program_id), false),
+        AccountMeta::new_readonly(*admin_pubkey, true),
+        AccountMeta::new(*funding_pubkey, true),
+        AccountMeta::new_readonly(system_program::id(), false),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use {super::*, crate::NAMESPACE, solana_program::hash};
+
+    fn discriminator(name: &str) -> Vec<u8> {
+        let preimage = hash::hashv(&[format!("{NAMESPACE}:{name}").as_bytes()]);
+        preimage.as_ref()[..ArrayDiscriminator::LENGTH].to_vec()
+    }
+
+    #[test]
+    fn initialize_owner_list_packing() {
+        let admin = Pubkey::new_unique();
+        let check = AllowlistInstruction::InitializeOwnerList {
+            admin: Some(admin).try_into().unwrap(),
+            mode: ListMode::Deny,
+        };
+        let packed = check.pack();
+        let mut expect = discriminator("initialize-owner-list");
+        expect.extend_from_slice(admin.as_ref());
+        expect.push(1);
+        assert_eq!(packed, expect);
+        let unpacked = AllowlistInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+
+        // unknown mode
+        *expect.last_mut().unwrap() = 2;
+        assert!(AllowlistInstruction::unpack(&expect).is_err());
+    }
+
+    #[test]
+    fn owner_packing() {
+        let owner = Pubkey::new_unique();
+        for (check, name) in [
+            (AllowlistInstruction::AddOwner { owner }, "add-owner"),
+            (AllowlistInstruction::RemoveOwner { owner }, "remove-owner"),
+        ] {
+            let packed = check.pack();
+            let mut expect = discriminator(name);
+            expect.extend_from_slice(owner.as_ref());
+            assert_eq!(packed, expect);
+            let unpacked = AllowlistInstruction::unpack(&expect).unwrap();
+            assert_eq!(unpacked, check);
+        }
+    }
+
+    #[test]
+    fn import_owners_packing() {
+        let owners = vec![Pubkey::new_unique(), Pubkey::new_unique()];
+        let check = AllowlistInstruction::ImportOwners {
+            owners: owners.clone(),
+        };
+        let packed = check.pack();
+        let mut expect = discriminator("import-owners");
+        expect.extend_from_slice(&(owners.len() as u32).to_le_bytes());
+        expect.extend_from_slice(bytemuck::cast_slice(&owners));
+        assert_eq!(packed, expect);
+        let unpacked = AllowlistInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn set_admin_packing() {
+        let check = AllowlistInstruction::SetAdmin {
+            new_admin: None.try_into().unwrap(),
+        };
+        let packed = check.pack();
+        let mut expect = discriminator("set-admin");
+        expect.extend_from_slice(&[0; 32]);
+        assert_eq!(packed, expect);
+        let unpacked = AllowlistInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+}
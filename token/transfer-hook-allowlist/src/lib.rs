@@ -0,0 +1,65 @@
+//! Crate defining a transfer hook program that only lets tokens move between
+//! owners on a per-mint list, or between owners not on it, with instructions
+//! for the list's admin to manage its entries.
+
+#![allow(clippy::integer_arithmetic)]
+#![deny(missing_docs)]
+#![cfg_attr(not(test), forbid(unsafe_code))]
+
+pub mod error;
+pub mod instruction;
+pub mod processor;
+pub mod state;
+
+#[cfg(not(feature = "no-entrypoint"))]
+mod entrypoint;
+
+// Export current sdk types for downstream users building with a different sdk
+// version
+pub use solana_program;
+use {
+    solana_program::{program_error::ProgramError, pubkey::Pubkey},
+    spl_tlv_account_resolution::{pod::PodAccountMeta, seeds::Seed},
+};
+
+/// Namespace for the instructions of the program, other than the ones of the
+/// transfer hook interface
+pub const NAMESPACE: &str = "spl-transfer-hook-allowlist";
+
+/// Seed for the owner list
+const OWNER_LIST_SEED: &[u8] = b"owner-list";
+
+/// Get the owner list address of a mint
+pub fn get_owner_list_address(mint: &Pubkey, program_id: &Pubkey) -> Pubkey {
+    get_owner_list_address_and_bump_seed(mint, program_id).0
+}
+
+/// Get the owner list address of a mint, and its bump seed
+pub fn get_owner_list_address_and_bump_seed(mint: &Pubkey, program_id: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&collect_owner_list_seeds(mint), program_id)
+}
+
+/// Get the seeds of the owner list of a mint
+pub fn collect_owner_list_seeds(mint: &Pubkey) -> [&[u8]; 2] {
+    [OWNER_LIST_SEED, mint.as_ref()]
+}
+
+/// Get the signer seeds of the owner list of a mint, when creating it
+pub fn collect_owner_list_signer_seeds<'a>(mint: &'a Pubkey, bump_seed: &'a [u8]) -> [&'a [u8]; 3] {
+    [OWNER_LIST_SEED, mint.as_ref(), bump_seed]
+}
+
+/// Get the extra account meta that resolves to the owner list of the mint
+/// given to the transfer hook's `Execute` instruction
+pub fn get_owner_list_extra_account_meta() -> Result<PodAccountMeta, ProgramError> {
+    PodAccountMeta::new_with_seeds(
+        &[
+            Seed::Literal {
+                bytes: OWNER_LIST_SEED.to_vec(),
+            },
+            Seed::AccountKey { index: 1 }, // mint
+        ],
+        false,
+        false,
+    )
+}
@@ -0,0 +1,414 @@
+//! Program state processor
+
+use {
+    crate::{
+        collect_owner_list_signer_seeds,
+        error::AllowlistError,
+        get_owner_list_address_and_bump_seed, get_owner_list_extra_account_meta,
+        instruction::AllowlistInstruction,
+        state::{merge_owners, ListMode, OwnerList},
+    },
+    solana_program::{
+        account_info::{next_account_info, AccountInfo},
+        entrypoint::ProgramResult,
+        msg,
+        program::{invoke, invoke_signed},
+        program_error::ProgramError,
+        pubkey::Pubkey,
+        rent::Rent,
+        system_instruction,
+        sysvar::Sysvar,
+    },
+    spl_tlv_account_resolution::{pod::PodAccountMeta, state::ExtraAccountMetas},
+    spl_token_2022::{
+        extension::{
+            transfer_hook::TransferHookAccount, BaseStateWithExtensions, StateWithExtensions,
+        },
+        pod::OptionalNonZeroPubkey,
+        state::{Account, Mint},
+    },
+    spl_transfer_hook_interface::{
+        collect_extra_account_metas_signer_seeds,
+        error::TransferHookError,
+        get_extra_account_metas_address, get_extra_account_metas_address_and_bump_seed,
+        instruction::{ExecuteInstruction, TransferHookInstruction},
+    },
+};
+
+/// Get the owner of a token account, checking that it's in the middle of a
+/// transfer
+fn get_transferring_owner(account_info: &AccountInfo) -> Result<Pubkey, ProgramError> {
+    let account_data = account_info.try_borrow_data()?;
+    let token_account = StateWithExtensions::<Account>::unpack(&account_data)?;
+    let extension = token_account.get_extension::<TransferHookAccount>()?;
+    if bool::from(extension.transferring) {
+        Ok(token_account.base.owner)
+    } else {
+        Err(TransferHookError::ProgramCalledOutsideOfTransfer.into())
+    }
+}
+
+/// Check that the mint authority signed, without fully deserializing the mint
+fn check_mint_authority(mint_info: &AccountInfo, authority_info: &AccountInfo) -> ProgramResult {
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+    let mint_authority = mint
+        .base
+        .mint_authority
+        .ok_or(TransferHookError::MintHasNoMintAuthority)?;
+
+    if !authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *authority_info.key != mint_authority {
+        return Err(TransferHookError::IncorrectMintAuthority.into());
+    }
+    Ok(())
+}
+
+/// Check that the admin of an initialized owner list signed
+fn check_admin(
+    program_id: &Pubkey,
+    owner_list_info: &AccountInfo,
+    admin_info: &AccountInfo,
+) -> ProgramResult {
+    if owner_list_info.owner != program_id {
+        return Err(ProgramError::IllegalOwner);
+    }
+    let data = owner_list_info.try_borrow_data()?;
+    let (owner_list, _) = OwnerList::unpack(&data)?;
+    let admin =
+        Option::<Pubkey>::from(owner_list.admin).ok_or(AllowlistError::ImmutableOwnerList)?;
+
+    if !admin_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *admin_info.key != admin {
+        return Err(AllowlistError::IncorrectAdmin.into());
+    }
+    Ok(())
+}
+
+/// Transfer the lamports missing for an account to be rent-exempt at the
+/// given size
+fn fund_for_size<'a>(
+    account_info: &AccountInfo<'a>,
+    funding_info: &AccountInfo<'a>,
+    system_program_info: &AccountInfo<'a>,
+    size: usize,
+) -> ProgramResult {
+    let required_lamports = Rent::get()?
+        .minimum_balance(size)
+        .saturating_sub(account_info.lamports());
+    if required_lamports > 0 {
+        invoke(
+            &system_instruction::transfer(funding_info.key, account_info.key, required_lamports),
+            &[
+                funding_info.clone(),
+                account_info.clone(),
+                system_program_info.clone(),
+            ],
+        )?;
+    }
+    Ok(())
+}
+
+/// Processes an [Execute](enum.TransferHookInstruction.html) instruction.
+pub fn process_execute(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let source_account_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let destination_account_info = next_account_info(account_info_iter)?;
+    let _authority_info = next_account_info(account_info_iter)?;
+    let extra_account_metas_info = next_account_info(account_info_iter)?;
+    let owner_list_info = next_account_info(account_info_iter)?;
+
+    // Only trust the owners of accounts that are in the middle of a transfer
+    let source_owner = get_transferring_owner(source_account_info)?;
+    let destination_owner = get_transferring_owner(destination_account_info)?;
+
+    let expected_validation_address = get_extra_account_metas_address(mint_info.key, program_id);
+    if expected_validation_address != *extra_account_metas_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    let instruction_data = TransferHookInstruction::Execute { amount }.pack();
+    ExtraAccountMetas::check_account_infos::<ExecuteInstruction>(
+        accounts,
+        &instruction_data,
+        program_id,
+        &extra_account_metas_info.try_borrow_data()?,
+    )
+    .map_err(|_| TransferHookError::IncorrectAccount)?;
+
+    // The owner list is the first extra account, so its address was checked
+    // above, but it must also have been initialized
+    if owner_list_info.owner != program_id {
+        return Err(TransferHookError::IncorrectAccount.into());
+    }
+    let owner_list_data = owner_list_info.try_borrow_data()?;
+    let (owner_list, owners) = OwnerList::unpack(&owner_list_data)?;
+    if owner_list.mint != *mint_info.key {
+        return Err(TransferHookError::IncorrectAccount.into());
+    }
+    owner_list.check_owner(owners, &source_owner)?;
+    owner_list.check_owner(owners, &destination_owner)?;
+
+    Ok(())
+}
+
+/// Processes a [InitializeExtraAccountMetas](enum.TransferHookInstruction.html) instruction.
+///
+/// The owner list of the mint is always written as the first extra account,
+/// followed by any other required accounts.
+pub fn process_initialize_extra_account_metas(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let extra_account_metas_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let _system_program_info = next_account_info(account_info_iter)?;
+
+    check_mint_authority(mint_info, authority_info)?;
+
+    // Check validation account
+    let (expected_validation_address, bump_seed) =
+        get_extra_account_metas_address_and_bump_seed(mint_info.key, program_id);
+    if expected_validation_address != *extra_account_metas_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    // Create the account
+    let bump_seed = [bump_seed];
+    let signer_seeds = collect_extra_account_metas_signer_seeds(mint_info.key, &bump_seed);
+    let pod_account_metas = std::iter::once(get_owner_list_extra_account_meta()?)
+        .chain(
+            account_info_iter
+                .as_slice()
+                .iter()
+                .map(PodAccountMeta::from),
+        )
+        .collect::<Vec<_>>();
+    let account_size = ExtraAccountMetas::size_of(pod_account_metas.len())?;
+    invoke_signed(
+        &system_instruction::allocate(extra_account_metas_info.key, account_size as u64),
+        &[extra_account_metas_info.clone()],
+        &[&signer_seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(extra_account_metas_info.key, program_id),
+        &[extra_account_metas_info.clone()],
+        &[&signer_seeds],
+    )?;
+
+    // Write the data
+    let mut data = extra_account_metas_info.try_borrow_mut_data()?;
+    ExtraAccountMetas::init_with_pod_account_metas::<ExecuteInstruction>(
+        &mut data,
+        &pod_account_metas,
+    )?;
+
+    Ok(())
+}
+
+/// Processes an [InitializeOwnerList](enum.AllowlistInstruction.html) instruction.
+pub fn process_initialize_owner_list(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    admin: OptionalNonZeroPubkey,
+    mode: ListMode,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let owner_list_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+    let funding_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    check_mint_authority(mint_info, authority_info)?;
+
+    let (expected_owner_list_address, bump_seed) =
+        get_owner_list_address_and_bump_seed(mint_info.key, program_id);
+    if expected_owner_list_address != *owner_list_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+
+    // Create the account, which may already hold some lamports
+    let account_size = OwnerList::size_of(0);
+    fund_for_size(
+        owner_list_info,
+        funding_info,
+        system_program_info,
+        account_size,
+    )?;
+    let bump_seed = [bump_seed];
+    let signer_seeds = collect_owner_list_signer_seeds(mint_info.key, &bump_seed);
+    invoke_signed(
+        &system_instruction::allocate(owner_list_info.key, account_size as u64),
+        &[owner_list_info.clone()],
+        &[&signer_seeds],
+    )?;
+    invoke_signed(
+        &system_instruction::assign(owner_list_info.key, program_id),
+        &[owner_list_info.clone()],
+        &[&signer_seeds],
+    )?;
+
+    let mut data = owner_list_info.try_borrow_mut_data()?;
+    let (owner_list, _) = OwnerList::unpack_mut(&mut data)?;
+    owner_list.admin = admin;
+    owner_list.mint = *mint_info.key;
+    owner_list.mode = mode as u8;
+
+    Ok(())
+}
+
+/// Processes an [AddOwner](enum.AllowlistInstruction.html) or
+/// [ImportOwners](enum.AllowlistInstruction.html) instruction. Owners already
+/// on the list are skipped if `skip_listed` is set, or fail the instruction
+/// otherwise.
+pub fn process_add_owners(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    owners: &[Pubkey],
+    skip_listed: bool,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let owner_list_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+    let funding_info = next_account_info(account_info_iter)?;
+    let system_program_info = next_account_info(account_info_iter)?;
+
+    check_admin(program_id, owner_list_info, admin_info)?;
+
+    let mut new_owners = owners.to_vec();
+    new_owners.sort();
+    new_owners.dedup();
+    let num_listed = {
+        let data = owner_list_info.try_borrow_data()?;
+        let (_, listed_owners) = OwnerList::unpack(&data)?;
+        if skip_listed {
+            new_owners.retain(|owner| listed_owners.binary_search(owner).is_err());
+        } else if new_owners
+            .iter()
+            .any(|owner| listed_owners.binary_search(owner).is_ok())
+        {
+            return Err(AllowlistError::OwnerAlreadyListed.into());
+        }
+        listed_owners.len()
+    };
+    if new_owners.is_empty() {
+        return Ok(());
+    }
+
+    // Grow the account, then merge the new owners in
+    let account_size = OwnerList::size_of(num_listed.saturating_add(new_owners.len()));
+    fund_for_size(
+        owner_list_info,
+        funding_info,
+        system_program_info,
+        account_size,
+    )?;
+    owner_list_info.realloc(account_size, false)?;
+    let mut data = owner_list_info.try_borrow_mut_data()?;
+    let (_, listed_owners) = OwnerList::unpack_mut(&mut data)?;
+    merge_owners(listed_owners, &new_owners)
+}
+
+/// Processes a [RemoveOwner](enum.AllowlistInstruction.html) instruction.
+pub fn process_remove_owner(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    owner: &Pubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let owner_list_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    check_admin(program_id, owner_list_info, admin_info)?;
+
+    // Close the gap, then shrink the account
+    let account_size = {
+        let mut data = owner_list_info.try_borrow_mut_data()?;
+        let (_, listed_owners) = OwnerList::unpack_mut(&mut data)?;
+        let index = listed_owners
+            .binary_search(owner)
+            .map_err(|_| AllowlistError::OwnerNotListed)?;
+        listed_owners.copy_within(index.saturating_add(1).., index);
+        OwnerList::size_of(listed_owners.len().saturating_sub(1))
+    };
+    owner_list_info.realloc(account_size, false)
+}
+
+/// Processes a [SetAdmin](enum.AllowlistInstruction.html) instruction.
+pub fn process_set_admin(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    new_admin: OptionalNonZeroPubkey,
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let owner_list_info = next_account_info(account_info_iter)?;
+    let admin_info = next_account_info(account_info_iter)?;
+
+    check_admin(program_id, owner_list_info, admin_info)?;
+
+    let mut data = owner_list_info.try_borrow_mut_data()?;
+    let (owner_list, _) = OwnerList::unpack_mut(&mut data)?;
+    owner_list.admin = new_admin;
+
+    Ok(())
+}
+
+/// Processes an [Instruction](enum.Instruction.html), either one of the
+/// transfer hook interface, or one managing an owner list.
+pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
+    if let Ok(instruction) = TransferHookInstruction::unpack(input) {
+        return match instruction {
+            TransferHookInstruction::Execute { amount } => {
+                msg!("Instruction: Execute");
+                process_execute(program_id, accounts, amount)
+            }
+            TransferHookInstruction::InitializeExtraAccountMetas => {
+                msg!("Instruction: InitializeExtraAccountMetas");
+                process_initialize_extra_account_metas(program_id, accounts)
+            }
+            // Hooks on mints and burns, and updates, are not supported
+            _ => Err(ProgramError::InvalidInstructionData),
+        };
+    }
+
+    let instruction = AllowlistInstruction::unpack(input)?;
+    match instruction {
+        AllowlistInstruction::InitializeOwnerList { admin, mode } => {
+            msg!("Instruction: InitializeOwnerList");
+            process_initialize_owner_list(program_id, accounts, admin, mode)
+        }
+        AllowlistInstruction::AddOwner { owner } => {
+            msg!("Instruction: AddOwner");
+            process_add_owners(program_id, accounts, &[owner], false)
+        }
+        AllowlistInstruction::RemoveOwner { owner } => {
+            msg!("Instruction: RemoveOwner");
+            process_remove_owner(program_id, accounts, &owner)
+        }
+        AllowlistInstruction::ImportOwners { owners } => {
+            msg!("Instruction: ImportOwners");
+            process_add_owners(program_id, accounts, &owners, true)
+        }
+        AllowlistInstruction::SetAdmin { new_admin } => {
+            msg!("Instruction: SetAdmin");
+            process_set_admin(program_id, accounts, new_admin)
+        }
+    }
+}
@@ -0,0 +1,171 @@
+//! State of the owner list
+
+use {
+    crate::error::AllowlistError,
+    bytemuck::{Pod, Zeroable},
+    solana_program::{program_error::ProgramError, pubkey::Pubkey},
+    spl_token_2022::pod::OptionalNonZeroPubkey,
+    std::mem::size_of,
+};
+
+/// How the owners on a list are treated
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ListMode {
+    /// Only owners on the list may send or receive tokens
+    Allow,
+    /// Owners on the list may not send or receive tokens
+    Deny,
+}
+impl TryFrom<u8> for ListMode {
+    type Error = ProgramError;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Allow),
+            1 => Ok(Self::Deny),
+            _ => Err(AllowlistError::InvalidListMode.into()),
+        }
+    }
+}
+
+/// Header of the owner list account, followed by the listed owners, sorted
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Pod, Zeroable)]
+pub struct OwnerList {
+    /// Authority that can add and remove owners. If `None`, the list can no
+    /// longer change.
+    pub admin: OptionalNonZeroPubkey,
+    /// Mint whose transfers are checked against the list
+    pub mint: Pubkey,
+    /// How the owners on the list are treated, as a `ListMode`
+    pub mode: u8,
+}
+impl OwnerList {
+    /// Size of an owner list account holding the given number of owners
+    pub fn size_of(num_owners: usize) -> usize {
+        size_of::<Self>().saturating_add(size_of::<Pubkey>().saturating_mul(num_owners))
+    }
+
+    /// Unpack the header and the sorted owners of an owner list account
+    pub fn unpack(data: &[u8]) -> Result<(&Self, &[Pubkey]), ProgramError> {
+        if data.len() < size_of::<Self>() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (header, owners) = data.split_at(size_of::<Self>());
+        let header = bytemuck::try_from_bytes::<Self>(header)
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let owners = bytemuck::try_cast_slice::<u8, Pubkey>(owners)
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        Ok((header, owners))
+    }
+
+    /// Unpack the header and the sorted owners of an owner list account,
+    /// mutably
+    pub fn unpack_mut(data: &mut [u8]) -> Result<(&mut Self, &mut [Pubkey]), ProgramError> {
+        if data.len() < size_of::<Self>() {
+            return Err(ProgramError::InvalidAccountData);
+        }
+        let (header, owners) = data.split_at_mut(size_of::<Self>());
+        let header = bytemuck::try_from_bytes_mut::<Self>(header)
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        let owners = bytemuck::try_cast_slice_mut::<u8, Pubkey>(owners)
+            .map_err(|_| ProgramError::InvalidAccountData)?;
+        Ok((header, owners))
+    }
+
+    /// Check that the given token account owner may send or receive tokens,
+    /// given the sorted owners on the list
+    pub fn check_owner(&self, owners: &[Pubkey], owner: &Pubkey) -> Result<(), ProgramError> {
+        let is_listed = owners.binary_search(owner).is_ok();
+        match ListMode::try_from(self.mode)? {
+            ListMode::Allow if !is_listed => Err(AllowlistError::OwnerNotAllowed.into()),
+            ListMode::Deny if is_listed => Err(AllowlistError::OwnerDenied.into()),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Merge sorted owners, none of which are listed yet, into the sorted owners
+/// of the list. The list must already have room for them at its end.
+pub fn merge_owners(owners: &mut [Pubkey], new_owners: &[Pubkey]) -> Result<(), ProgramError> {
+    let mut listed = owners
+        .len()
+        .checked_sub(new_owners.len())
+        .ok_or(ProgramError::AccountDataTooSmall)?;
+    let mut remaining = new_owners.len();
+    // fill from the back, so that listed owners are only moved once
+    for index in (0..owners.len()).rev() {
+        if remaining == 0 {
+            break;
+        }
+        if listed > 0 && owners[listed - 1] > new_owners[remaining - 1] {
+            owners[index] = owners[listed - 1];
+            listed -= 1;
+        } else {
+            owners[index] = new_owners[remaining - 1];
+            remaining -= 1;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn merge_into_sorted_owners() {
+        let mut all_owners = (0..8).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+        all_owners.sort();
+
+        let listed = [all_owners[1], all_owners[4], all_owners[5]];
+        let new_owners = [
+            all_owners[0],
+            all_owners[2],
+            all_owners[3],
+            all_owners[6],
+            all_owners[7],
+        ];
+        let mut owners = listed.to_vec();
+        owners.resize(all_owners.len(), Pubkey::default());
+        merge_owners(&mut owners, &new_owners).unwrap();
+        assert_eq!(owners, all_owners);
+
+        // no room left
+        let mut owners = listed.to_vec();
+        assert_eq!(
+            merge_owners(&mut owners, &new_owners).unwrap_err(),
+            ProgramError::AccountDataTooSmall
+        );
+    }
+
+    #[test]
+    fn check_owner_by_mode() {
+        let listed_owner = Pubkey::new_unique();
+        let other_owner = Pubkey::new_unique();
+        let owners = [listed_owner];
+
+        let mut list = OwnerList {
+            mode: ListMode::Allow as u8,
+            ..OwnerList::default()
+        };
+        list.check_owner(&owners, &listed_owner).unwrap();
+        assert_eq!(
+            list.check_owner(&owners, &other_owner).unwrap_err(),
+            AllowlistError::OwnerNotAllowed.into()
+        );
+
+        list.mode = ListMode::Deny as u8;
+        list.check_owner(&owners, &other_owner).unwrap();
+        assert_eq!(
+            list.check_owner(&owners, &listed_owner).unwrap_err(),
+            AllowlistError::OwnerDenied.into()
+        );
+
+        list.mode = 2;
+        assert_eq!(
+            list.check_owner(&owners, &listed_owner).unwrap_err(),
+            AllowlistError::InvalidListMode.into()
+        );
+    }
+}
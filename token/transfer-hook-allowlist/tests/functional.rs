@@ -0,0 +1,364 @@
+// Mark this test as SBF-only due to current `ProgramTest` limitations when
+// CPIing into the system program
+#![cfg(feature = "test-sbf")]
+
+use {
+    solana_program_test::{processor, tokio, ProgramTest, ProgramTestContext},
+    solana_sdk::{
+        account::Account as SolanaAccount,
+        instruction::{Instruction, InstructionError},
+        program_option::COption,
+        pubkey::Pubkey,
+        signature::Signer,
+        signer::keypair::Keypair,
+        transaction::{Transaction, TransactionError},
+    },
+    spl_token_2022::{
+        extension::{ExtensionType, StateWithExtensionsMut},
+        state::Mint,
+    },
+    spl_transfer_hook_allowlist::{
+        error::AllowlistError,
+        get_owner_list_address,
+        instruction::{add_owner, import_owners, initialize_owner_list, remove_owner, set_admin},
+        state::{ListMode, OwnerList},
+    },
+    spl_transfer_hook_interface::error::TransferHookError,
+};
+
+fn setup(program_id: &Pubkey, mint_address: &Pubkey, mint_authority: &Pubkey) -> ProgramTest {
+    let mut program_test = ProgramTest::new(
+        "spl_transfer_hook_allowlist",
+        *program_id,
+        processor!(spl_transfer_hook_allowlist::processor::process),
+    );
+    program_test.prefer_bpf(false); // simplicity in the build
+
+    let mint_size = ExtensionType::try_calculate_account_len::<Mint>(&[]).unwrap();
+    let mut mint_data = vec![0; mint_size];
+    let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut mint_data).unwrap();
+    state.base = Mint {
+        mint_authority: COption::Some(*mint_authority),
+        supply: 0,
+        decimals: 2,
+        is_initialized: true,
+        freeze_authority: COption::None,
+    };
+    state.pack_base();
+    program_test.add_account(
+        *mint_address,
+        SolanaAccount {
+            lamports: 1_000_000_000,
+            data: mint_data,
+            owner: spl_token_2022::id(),
+            ..SolanaAccount::default()
+        },
+    );
+
+    program_test
+}
+
+async fn process_instruction(
+    context: &mut ProgramTestContext,
+    instruction: Instruction,
+    signers: &[&Keypair],
+) -> Result<(), TransactionError> {
+    let mut all_signers = vec![&context.payer];
+    all_signers.extend_from_slice(signers);
+    let recent_blockhash = context.get_new_latest_blockhash().await.unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[instruction],
+        Some(&context.payer.pubkey()),
+        &all_signers,
+        recent_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .map_err(|error| error.unwrap())
+}
+
+async fn get_listed_owners(
+    context: &mut ProgramTestContext,
+    mint_address: &Pubkey,
+    program_id: &Pubkey,
+) -> (OwnerList, Vec<Pubkey>) {
+    let account = context
+        .banks_client
+        .get_account(get_owner_list_address(mint_address, program_id))
+        .await
+        .unwrap()
+        .unwrap();
+    let rent = context.banks_client.get_rent().await.unwrap();
+    assert!(rent.is_exempt(account.lamports, account.data.len()));
+    let (owner_list, owners) = OwnerList::unpack(&account.data).unwrap();
+    (*owner_list, owners.to_vec())
+}
+
+#[tokio::test]
+async fn success_manage_owners() {
+    let program_id = Pubkey::new_unique();
+    let mint_address = Pubkey::new_unique();
+    let mint_authority = Keypair::new();
+    let admin = Keypair::new();
+    let mut context = setup(&program_id, &mint_address, &mint_authority.pubkey())
+        .start_with_context()
+        .await;
+    let payer = context.payer.pubkey();
+
+    process_instruction(
+        &mut context,
+        initialize_owner_list(
+            &program_id,
+            &mint_address,
+            &mint_authority.pubkey(),
+            &payer,
+            Some(admin.pubkey()),
+            ListMode::Allow,
+        )
+        .unwrap(),
+        &[&mint_authority],
+    )
+    .await
+    .unwrap();
+    let (owner_list, owners) = get_listed_owners(&mut context, &mint_address, &program_id).await;
+    assert_eq!(owner_list.admin, Some(admin.pubkey()).try_into().unwrap());
+    assert_eq!(owner_list.mint, mint_address);
+    assert_eq!(owner_list.mode, ListMode::Allow as u8);
+    assert!(owners.is_empty());
+
+    // add one
+    let first_owner = Pubkey::new_unique();
+    process_instruction(
+        &mut context,
+        add_owner(
+            &program_id,
+            &mint_address,
+            &admin.pubkey(),
+            &payer,
+            &first_owner,
+        ),
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    let (_, owners) = get_listed_owners(&mut context, &mint_address, &program_id).await;
+    assert_eq!(owners, vec![first_owner]);
+
+    // adding it again fails
+    assert_eq!(
+        process_instruction(
+            &mut context,
+            add_owner(
+                &program_id,
+                &mint_address,
+                &admin.pubkey(),
+                &payer,
+                &first_owner
+            ),
+            &[&admin],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(AllowlistError::OwnerAlreadyListed as u32)
+        )
+    );
+
+    // importing skips it, and keeps the list sorted
+    let mut imported_owners = (0..10).map(|_| Pubkey::new_unique()).collect::<Vec<_>>();
+    imported_owners.push(first_owner);
+    process_instruction(
+        &mut context,
+        import_owners(
+            &program_id,
+            &mint_address,
+            &admin.pubkey(),
+            &payer,
+            &imported_owners,
+        ),
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    imported_owners.sort();
+    let (_, owners) = get_listed_owners(&mut context, &mint_address, &program_id).await;
+    assert_eq!(owners, imported_owners);
+
+    // remove one
+    process_instruction(
+        &mut context,
+        remove_owner(&program_id, &mint_address, &admin.pubkey(), &first_owner),
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    imported_owners.retain(|owner| *owner != first_owner);
+    let (_, owners) = get_listed_owners(&mut context, &mint_address, &program_id).await;
+    assert_eq!(owners, imported_owners);
+
+    // removing it again fails
+    assert_eq!(
+        process_instruction(
+            &mut context,
+            remove_owner(&program_id, &mint_address, &admin.pubkey(), &first_owner),
+            &[&admin],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(AllowlistError::OwnerNotListed as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn fail_initialize_wrong_mint_authority() {
+    let program_id = Pubkey::new_unique();
+    let mint_address = Pubkey::new_unique();
+    let mint_authority = Keypair::new();
+    let wrong_authority = Keypair::new();
+    let mut context = setup(&program_id, &mint_address, &mint_authority.pubkey())
+        .start_with_context()
+        .await;
+    let payer = context.payer.pubkey();
+
+    assert_eq!(
+        process_instruction(
+            &mut context,
+            initialize_owner_list(
+                &program_id,
+                &mint_address,
+                &wrong_authority.pubkey(),
+                &payer,
+                None,
+                ListMode::Deny,
+            )
+            .unwrap(),
+            &[&wrong_authority],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(TransferHookError::IncorrectMintAuthority as u32)
+        )
+    );
+}
+
+#[tokio::test]
+async fn success_set_admin() {
+    let program_id = Pubkey::new_unique();
+    let mint_address = Pubkey::new_unique();
+    let mint_authority = Keypair::new();
+    let admin = Keypair::new();
+    let new_admin = Keypair::new();
+    let mut context = setup(&program_id, &mint_address, &mint_authority.pubkey())
+        .start_with_context()
+        .await;
+    let payer = context.payer.pubkey();
+
+    process_instruction(
+        &mut context,
+        initialize_owner_list(
+            &program_id,
+            &mint_address,
+            &mint_authority.pubkey(),
+            &payer,
+            Some(admin.pubkey()),
+            ListMode::Deny,
+        )
+        .unwrap(),
+        &[&mint_authority],
+    )
+    .await
+    .unwrap();
+
+    // wrong admin
+    assert_eq!(
+        process_instruction(
+            &mut context,
+            set_admin(
+                &program_id,
+                &mint_address,
+                &new_admin.pubkey(),
+                Some(new_admin.pubkey())
+            )
+            .unwrap(),
+            &[&new_admin],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(AllowlistError::IncorrectAdmin as u32)
+        )
+    );
+
+    // hand over to the new admin
+    process_instruction(
+        &mut context,
+        set_admin(
+            &program_id,
+            &mint_address,
+            &admin.pubkey(),
+            Some(new_admin.pubkey()),
+        )
+        .unwrap(),
+        &[&admin],
+    )
+    .await
+    .unwrap();
+    let owner = Pubkey::new_unique();
+    assert_eq!(
+        process_instruction(
+            &mut context,
+            add_owner(&program_id, &mint_address, &admin.pubkey(), &payer, &owner),
+            &[&admin],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(AllowlistError::IncorrectAdmin as u32)
+        )
+    );
+    process_instruction(
+        &mut context,
+        add_owner(
+            &program_id,
+            &mint_address,
+            &new_admin.pubkey(),
+            &payer,
+            &owner,
+        ),
+        &[&new_admin],
+    )
+    .await
+    .unwrap();
+
+    // drop the admin, the list can no longer change
+    process_instruction(
+        &mut context,
+        set_admin(&program_id, &mint_address, &new_admin.pubkey(), None).unwrap(),
+        &[&new_admin],
+    )
+    .await
+    .unwrap();
+    assert_eq!(
+        process_instruction(
+            &mut context,
+            remove_owner(&program_id, &mint_address, &new_admin.pubkey(), &owner),
+            &[&new_admin],
+        )
+        .await
+        .unwrap_err(),
+        TransactionError::InstructionError(
+            0,
+            InstructionError::Custom(AllowlistError::ImmutableOwnerList as u32)
+        )
+    );
+}
@@ -4,14 +4,16 @@ use {
     solana_program::{
         account_info::{next_account_info, AccountInfo},
         entrypoint::ProgramResult,
-        instruction::AccountMeta,
         msg,
         program::invoke_signed,
         program_error::ProgramError,
         pubkey::Pubkey,
+        rent::Rent,
         system_instruction,
+        sysvar::Sysvar,
     },
-    spl_tlv_account_resolution::state::ExtraAccountMetas,
+    spl_discriminator::SplDiscriminate,
+    spl_tlv_account_resolution::{pod::PodAccountMeta, state::ExtraAccountMetas},
     spl_token_2022::{
         extension::{
             transfer_hook::TransferHookAccount, BaseStateWithExtensions, StateWithExtensions,
@@ -22,7 +24,10 @@ use {
         collect_extra_account_metas_signer_seeds,
         error::TransferHookError,
         get_extra_account_metas_address, get_extra_account_metas_address_and_bump_seed,
-        instruction::{ExecuteInstruction, TransferHookInstruction},
+        instruction::{
+            ExecuteInstruction, ExecuteOnBurnInstruction, ExecuteOnMintInstruction, HookedEvent,
+            TransferHookInstruction,
+        },
     },
     spl_type_length_value::state::TlvStateBorrowed,
 };
@@ -38,11 +43,26 @@ fn check_token_account_is_transferring(account_info: &AccountInfo) -> Result<(),
     }
 }
 
+/// Checks that the account holds enough lamports to be rent-exempt at the
+/// given size, before growing it
+fn check_rent_exempt(account_info: &AccountInfo, new_size: usize) -> ProgramResult {
+    if Rent::get()?.is_exempt(account_info.lamports(), new_size) {
+        Ok(())
+    } else {
+        msg!(
+            "Account {} must be funded to be rent-exempt at {} bytes",
+            account_info.key,
+            new_size
+        );
+        Err(ProgramError::AccountNotRentExempt)
+    }
+}
+
 /// Processes an [Execute](enum.TransferHookInstruction.html) instruction.
 pub fn process_execute(
     program_id: &Pubkey,
     accounts: &[AccountInfo],
-    _amount: u64,
+    amount: u64,
 ) -> ProgramResult {
     let account_info_iter = &mut accounts.iter();
 
@@ -75,20 +95,84 @@ pub fn process_execute(
         return Err(TransferHookError::IncorrectAccount.into());
     }
 
-    // Let's assume that they're provided in the correct order
-    for (i, account_info) in extra_account_infos.iter().enumerate() {
-        let meta = AccountMeta::try_from(&account_metas[i])?;
-        if !(&meta.pubkey == account_info.key
-            && meta.is_signer == account_info.is_signer
-            && meta.is_writable == account_info.is_writable)
-        {
-            return Err(TransferHookError::IncorrectAccount.into());
-        }
+    // Let's assume that they're provided in the correct order, resolving any
+    // PDAs against the instruction as it was received
+    let instruction_data = TransferHookInstruction::Execute { amount }.pack();
+    ExtraAccountMetas::check_account_infos::<ExecuteInstruction>(
+        accounts,
+        &instruction_data,
+        program_id,
+        &data,
+    )
+    .map_err(|_| TransferHookError::IncorrectAccount)?;
+
+    Ok(())
+}
+
+/// Checks the validation account and the extra accounts provided to
+/// `ExecuteOnMint` or `ExecuteOnBurn`, both of which take the token account,
+/// mint, authority and validation account first
+fn check_event_extra_account_infos<T: SplDiscriminate>(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    instruction_data: &[u8],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let token_account_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let _authority_info = next_account_info(account_info_iter)?;
+    let extra_account_metas_info = next_account_info(account_info_iter)?;
+
+    // The token program marks the minted or burned account as "transferring"
+    check_token_account_is_transferring(token_account_info)?;
+
+    let expected_validation_address = get_extra_account_metas_address(mint_info.key, program_id);
+    if expected_validation_address != *extra_account_metas_info.key {
+        return Err(ProgramError::InvalidSeeds);
     }
 
+    let data = extra_account_metas_info.try_borrow_data()?;
+    let state = TlvStateBorrowed::unpack(&data)?;
+    let extra_account_metas = ExtraAccountMetas::unpack_with_tlv_state::<T>(&state)?;
+    if account_info_iter.as_slice().len() != extra_account_metas.data().len() {
+        return Err(TransferHookError::IncorrectAccount.into());
+    }
+
+    ExtraAccountMetas::check_account_infos::<T>(accounts, instruction_data, program_id, &data)
+        .map_err(|_| TransferHookError::IncorrectAccount)?;
+
     Ok(())
 }
 
+/// Processes an [ExecuteOnMint](enum.TransferHookInstruction.html) instruction.
+pub fn process_execute_on_mint(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let instruction_data = TransferHookInstruction::ExecuteOnMint { amount }.pack();
+    check_event_extra_account_infos::<ExecuteOnMintInstruction>(
+        program_id,
+        accounts,
+        &instruction_data,
+    )
+}
+
+/// Processes an [ExecuteOnBurn](enum.TransferHookInstruction.html) instruction.
+pub fn process_execute_on_burn(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    amount: u64,
+) -> ProgramResult {
+    let instruction_data = TransferHookInstruction::ExecuteOnBurn { amount }.pack();
+    check_event_extra_account_infos::<ExecuteOnBurnInstruction>(
+        program_id,
+        accounts,
+        &instruction_data,
+    )
+}
+
 /// Processes a [InitializeExtraAccountMetas](enum.TransferHookInstruction.html) instruction.
 pub fn process_initialize_extra_account_metas(
     program_id: &Pubkey,
@@ -151,6 +235,150 @@ pub fn process_initialize_extra_account_metas(
     Ok(())
 }
 
+/// Processes an [InitializeEventExtraAccountMetas](enum.TransferHookInstruction.html) instruction.
+pub fn process_initialize_event_extra_account_metas(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    event: HookedEvent,
+    extra_account_metas: &[PodAccountMeta],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let extra_account_metas_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+
+    // check that the mint authority is valid without fully deserializing
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+    let mint_authority = mint
+        .base
+        .mint_authority
+        .ok_or(TransferHookError::MintHasNoMintAuthority)?;
+
+    // Check signers
+    if !authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *authority_info.key != mint_authority {
+        return Err(TransferHookError::IncorrectMintAuthority.into());
+    }
+
+    // Check validation account, which must already be initialized
+    let expected_validation_address = get_extra_account_metas_address(mint_info.key, program_id);
+    if expected_validation_address != *extra_account_metas_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if extra_account_metas_info.owner != program_id {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    // Grow the account to fit the new entry, the caller provides the rent
+    let pod_account_metas = account_info_iter
+        .as_slice()
+        .iter()
+        .map(PodAccountMeta::from)
+        .chain(extra_account_metas.iter().copied())
+        .collect::<Vec<_>>();
+    let new_account_size = extra_account_metas_info
+        .data_len()
+        .checked_add(ExtraAccountMetas::size_of(pod_account_metas.len())?)
+        .ok_or(ProgramError::InvalidAccountData)?;
+    check_rent_exempt(extra_account_metas_info, new_account_size)?;
+    extra_account_metas_info.realloc(new_account_size, true)?;
+
+    // Write the data
+    let mut data = extra_account_metas_info.try_borrow_mut_data()?;
+    match event {
+        HookedEvent::Mint => ExtraAccountMetas::init_with_pod_account_metas::<
+            ExecuteOnMintInstruction,
+        >(&mut data, &pod_account_metas)?,
+        HookedEvent::Burn => ExtraAccountMetas::init_with_pod_account_metas::<
+            ExecuteOnBurnInstruction,
+        >(&mut data, &pod_account_metas)?,
+    }
+
+    Ok(())
+}
+
+/// Processes an [UpdateExtraAccountMetas](enum.TransferHookInstruction.html) instruction.
+pub fn process_update_extra_account_metas(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    extra_account_metas: &[PodAccountMeta],
+) -> ProgramResult {
+    let account_info_iter = &mut accounts.iter();
+
+    let extra_account_metas_info = next_account_info(account_info_iter)?;
+    let mint_info = next_account_info(account_info_iter)?;
+    let authority_info = next_account_info(account_info_iter)?;
+
+    // check that the mint authority is valid without fully deserializing
+    let mint_data = mint_info.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&mint_data)?;
+    let mint_authority = mint
+        .base
+        .mint_authority
+        .ok_or(TransferHookError::MintHasNoMintAuthority)?;
+
+    // Check signers
+    if !authority_info.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    if *authority_info.key != mint_authority {
+        return Err(TransferHookError::IncorrectMintAuthority.into());
+    }
+
+    // Check validation account, which must already be initialized
+    let expected_validation_address = get_extra_account_metas_address(mint_info.key, program_id);
+    if expected_validation_address != *extra_account_metas_info.key {
+        return Err(ProgramError::InvalidSeeds);
+    }
+    if extra_account_metas_info.owner != program_id {
+        return Err(ProgramError::UninitializedAccount);
+    }
+
+    let pod_account_metas = account_info_iter
+        .as_slice()
+        .iter()
+        .map(PodAccountMeta::from)
+        .chain(extra_account_metas.iter().copied())
+        .collect::<Vec<_>>();
+
+    // Compute the new size, only the `Execute` entry changes
+    let previous_entry_size = {
+        let data = extra_account_metas_info.try_borrow_data()?;
+        let state = TlvStateBorrowed::unpack(&data)?;
+        let previous_extra_account_metas =
+            ExtraAccountMetas::unpack_with_tlv_state::<ExecuteInstruction>(&state)?;
+        ExtraAccountMetas::size_of(previous_extra_account_metas.data().len())?
+    };
+    let new_entry_size = ExtraAccountMetas::size_of(pod_account_metas.len())?;
+    let new_account_size = extra_account_metas_info
+        .data_len()
+        .saturating_sub(previous_entry_size)
+        .saturating_add(new_entry_size);
+
+    // Grow the account before writing, the caller provides the rent, or
+    // shrink it after
+    if new_entry_size > previous_entry_size {
+        check_rent_exempt(extra_account_metas_info, new_account_size)?;
+        extra_account_metas_info.realloc(new_account_size, true)?;
+    }
+    {
+        let mut data = extra_account_metas_info.try_borrow_mut_data()?;
+        ExtraAccountMetas::update_with_pod_account_metas::<ExecuteInstruction>(
+            &mut data,
+            &pod_account_metas,
+        )?;
+    }
+    if new_entry_size < previous_entry_size {
+        extra_account_metas_info.realloc(new_account_size, false)?;
+    }
+
+    Ok(())
+}
+
 /// Processes an [Instruction](enum.Instruction.html).
 pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> ProgramResult {
     let instruction = TransferHookInstruction::unpack(input)?;
@@ -164,5 +392,31 @@ pub fn process(program_id: &Pubkey, accounts: &[AccountInfo], input: &[u8]) -> P
             msg!("Instruction: InitializeExtraAccountMetas");
             process_initialize_extra_account_metas(program_id, accounts)
         }
+        TransferHookInstruction::ExecuteOnMint { amount } => {
+            msg!("Instruction: ExecuteOnMint");
+            process_execute_on_mint(program_id, accounts, amount)
+        }
+        TransferHookInstruction::ExecuteOnBurn { amount } => {
+            msg!("Instruction: ExecuteOnBurn");
+            process_execute_on_burn(program_id, accounts, amount)
+        }
+        TransferHookInstruction::InitializeEventExtraAccountMetas {
+            event,
+            extra_account_metas,
+        } => {
+            msg!("Instruction: InitializeEventExtraAccountMetas");
+            process_initialize_event_extra_account_metas(
+                program_id,
+                accounts,
+                event,
+                &extra_account_metas,
+            )
+        }
+        TransferHookInstruction::UpdateExtraAccountMetas {
+            extra_account_metas,
+        } => {
+            msg!("Instruction: UpdateExtraAccountMetas");
+            process_update_extra_account_metas(program_id, accounts, &extra_account_metas)
+        }
     }
 }
@@ -16,7 +16,7 @@ use {
         system_instruction, sysvar,
         transaction::{Transaction, TransactionError},
     },
-    spl_tlv_account_resolution::state::ExtraAccountMetas,
+    spl_tlv_account_resolution::{pod::PodAccountMeta, seeds::Seed, state::ExtraAccountMetas},
     spl_token_2022::{
         extension::{transfer_hook::TransferHookAccount, ExtensionType, StateWithExtensionsMut},
         state::{Account, AccountState, Mint},
@@ -24,7 +24,12 @@ use {
     spl_transfer_hook_interface::{
         error::TransferHookError,
         get_extra_account_metas_address,
-        instruction::{execute_with_extra_account_metas, initialize_extra_account_metas},
+        instruction::{
+            execute_on_burn, execute_on_mint, execute_with_extra_account_metas,
+            initialize_event_extra_account_metas, initialize_extra_account_metas,
+            update_extra_account_metas, update_extra_account_metas_with_pod_account_metas,
+            HookedEvent,
+        },
         onchain,
     },
 };
@@ -319,6 +324,544 @@ async fn success_execute() {
     }
 }
 
+#[tokio::test]
+async fn success_execute_on_mint_and_burn() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = setup(&program_id);
+
+    let token_program_id = spl_token_2022::id();
+    let wallet = Keypair::new();
+    let mint_address = Pubkey::new_unique();
+    let mint_authority = Keypair::new();
+    let mint_authority_pubkey = mint_authority.pubkey();
+    let source = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+    let decimals = 2;
+
+    setup_token_accounts(
+        &mut program_test,
+        &token_program_id,
+        &mint_address,
+        &mint_authority_pubkey,
+        &source,
+        &destination,
+        &wallet.pubkey(),
+        decimals,
+        true,
+    );
+
+    let extra_account_metas = get_extra_account_metas_address(&mint_address, &program_id);
+
+    let extra_account_pubkeys = [AccountMeta::new_readonly(sysvar::instructions::id(), false)];
+    let mint_account_pubkeys = [AccountMeta::new_readonly(mint_authority_pubkey, true)];
+    let burn_account_pubkeys = [
+        AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        AccountMeta::new_readonly(wallet.pubkey(), false),
+    ];
+    let mut context = program_test.start_with_context().await;
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let initial_size = ExtraAccountMetas::size_of(extra_account_pubkeys.len()).unwrap();
+    let total_size = initial_size
+        + ExtraAccountMetas::size_of(mint_account_pubkeys.len()).unwrap()
+        + ExtraAccountMetas::size_of(burn_account_pubkeys.len()).unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(
+                &context.payer.pubkey(),
+                &extra_account_metas,
+                rent.minimum_balance(total_size),
+            ),
+            initialize_extra_account_metas(
+                &program_id,
+                &extra_account_metas,
+                &mint_address,
+                &mint_authority_pubkey,
+                &extra_account_pubkeys,
+            ),
+            initialize_event_extra_account_metas(
+                &program_id,
+                &extra_account_metas,
+                &mint_address,
+                &mint_authority_pubkey,
+                HookedEvent::Mint,
+                &mint_account_pubkeys,
+                &[],
+            ),
+            initialize_event_extra_account_metas(
+                &program_id,
+                &extra_account_metas,
+                &mint_address,
+                &mint_authority_pubkey,
+                HookedEvent::Burn,
+                &burn_account_pubkeys,
+                &[],
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &mint_authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    let account = context
+        .banks_client
+        .get_account(extra_account_metas)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), total_size);
+
+    // fail with the transfer accounts on mint
+    {
+        let mut instruction = execute_on_mint(
+            &program_id,
+            &destination,
+            &mint_address,
+            &mint_authority_pubkey,
+            &extra_account_metas,
+            0,
+        );
+        instruction
+            .accounts
+            .extend_from_slice(&extra_account_pubkeys);
+        let transaction = Transaction::new_signed_with_payer(
+            &[instruction],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.last_blockhash,
+        );
+        let error = context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(
+            error,
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TransferHookError::IncorrectAccount as u32),
+            )
+        );
+    }
+
+    // success with each event's own accounts
+    {
+        let mut mint_instruction = execute_on_mint(
+            &program_id,
+            &destination,
+            &mint_address,
+            &mint_authority_pubkey,
+            &extra_account_metas,
+            0,
+        );
+        mint_instruction
+            .accounts
+            .extend_from_slice(&mint_account_pubkeys);
+        let mut burn_instruction = execute_on_burn(
+            &program_id,
+            &source,
+            &mint_address,
+            &wallet.pubkey(),
+            &extra_account_metas,
+            0,
+        );
+        burn_instruction
+            .accounts
+            .extend_from_slice(&burn_account_pubkeys);
+        let transaction = Transaction::new_signed_with_payer(
+            &[mint_instruction, burn_instruction],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &mint_authority],
+            context.last_blockhash,
+        );
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+}
+
+#[tokio::test]
+async fn success_update_extra_account_metas() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = setup(&program_id);
+
+    let token_program_id = spl_token_2022::id();
+    let wallet = Keypair::new();
+    let mint_address = Pubkey::new_unique();
+    let mint_authority = Keypair::new();
+    let mint_authority_pubkey = mint_authority.pubkey();
+    let source = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+    let decimals = 2;
+
+    setup_token_accounts(
+        &mut program_test,
+        &token_program_id,
+        &mint_address,
+        &mint_authority_pubkey,
+        &source,
+        &destination,
+        &wallet.pubkey(),
+        decimals,
+        true,
+    );
+
+    let extra_account_metas = get_extra_account_metas_address(&mint_address, &program_id);
+
+    let init_account_pubkeys = [AccountMeta::new_readonly(sysvar::instructions::id(), false)];
+    let updated_account_pubkeys = [
+        AccountMeta::new_readonly(sysvar::instructions::id(), false),
+        AccountMeta::new_readonly(mint_authority_pubkey, true),
+        AccountMeta::new(extra_account_metas, false),
+    ];
+    let mut context = program_test.start_with_context().await;
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let updated_size = ExtraAccountMetas::size_of(updated_account_pubkeys.len()).unwrap();
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(
+                &context.payer.pubkey(),
+                &extra_account_metas,
+                rent.minimum_balance(updated_size),
+            ),
+            initialize_extra_account_metas(
+                &program_id,
+                &extra_account_metas,
+                &mint_address,
+                &mint_authority_pubkey,
+                &init_account_pubkeys,
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &mint_authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // fail with wrong mint authority
+    {
+        let wrong_authority = Keypair::new();
+        let transaction = Transaction::new_signed_with_payer(
+            &[update_extra_account_metas(
+                &program_id,
+                &extra_account_metas,
+                &mint_address,
+                &wrong_authority.pubkey(),
+                &updated_account_pubkeys,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &wrong_authority],
+            context.last_blockhash,
+        );
+        let error = context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(
+            error,
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TransferHookError::IncorrectMintAuthority as u32),
+            )
+        );
+    }
+
+    // fail if the account is not funded for the larger list
+    {
+        let mut larger_account_pubkeys = updated_account_pubkeys.to_vec();
+        larger_account_pubkeys.push(AccountMeta::new_readonly(Pubkey::new_unique(), false));
+        let transaction = Transaction::new_signed_with_payer(
+            &[update_extra_account_metas(
+                &program_id,
+                &extra_account_metas,
+                &mint_address,
+                &mint_authority_pubkey,
+                &larger_account_pubkeys,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &mint_authority],
+            context.last_blockhash,
+        );
+        let error = context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(
+            error,
+            TransactionError::InstructionError(0, InstructionError::AccountNotRentExempt)
+        );
+    }
+
+    // grow the list
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_extra_account_metas(
+            &program_id,
+            &extra_account_metas,
+            &mint_address,
+            &mint_authority_pubkey,
+            &updated_account_pubkeys,
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &mint_authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let account = context
+        .banks_client
+        .get_account(extra_account_metas)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), updated_size);
+
+    // the old list is rejected, and the new one accepted
+    {
+        let transaction = Transaction::new_signed_with_payer(
+            &[execute_with_extra_account_metas(
+                &program_id,
+                &source,
+                &mint_address,
+                &destination,
+                &wallet.pubkey(),
+                &extra_account_metas,
+                &init_account_pubkeys,
+                0,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.last_blockhash,
+        );
+        let error = context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(
+            error,
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TransferHookError::IncorrectAccount as u32),
+            )
+        );
+
+        let transaction = Transaction::new_signed_with_payer(
+            &[execute_with_extra_account_metas(
+                &program_id,
+                &source,
+                &mint_address,
+                &destination,
+                &wallet.pubkey(),
+                &extra_account_metas,
+                &updated_account_pubkeys,
+                0,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer, &mint_authority],
+            context.last_blockhash,
+        );
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+
+    // shrink the list to nothing
+    let transaction = Transaction::new_signed_with_payer(
+        &[update_extra_account_metas(
+            &program_id,
+            &extra_account_metas,
+            &mint_address,
+            &mint_authority_pubkey,
+            &[],
+        )],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &mint_authority],
+        context.last_blockhash,
+    );
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+    let account = context
+        .banks_client
+        .get_account(extra_account_metas)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.data.len(), ExtraAccountMetas::size_of(0).unwrap());
+}
+
+#[tokio::test]
+async fn success_execute_with_seeds() {
+    let program_id = Pubkey::new_unique();
+    let mut program_test = setup(&program_id);
+
+    let token_program_id = spl_token_2022::id();
+    let wallet = Keypair::new();
+    let mint_address = Pubkey::new_unique();
+    let mint_authority = Keypair::new();
+    let mint_authority_pubkey = mint_authority.pubkey();
+    let source = Pubkey::new_unique();
+    let destination = Pubkey::new_unique();
+    let decimals = 2;
+
+    setup_token_accounts(
+        &mut program_test,
+        &token_program_id,
+        &mint_address,
+        &mint_authority_pubkey,
+        &source,
+        &destination,
+        &wallet.pubkey(),
+        decimals,
+        true,
+    );
+
+    let extra_account_metas = get_extra_account_metas_address(&mint_address, &program_id);
+
+    let extra_account_pubkeys = [AccountMeta::new_readonly(sysvar::instructions::id(), false)];
+    // PDA derived from the source account and the owner of the source account
+    let extra_pda = PodAccountMeta::new_with_seeds(
+        &[
+            Seed::Literal {
+                bytes: b"seed-prefix".to_vec(),
+            },
+            Seed::AccountKey { index: 0 },
+            Seed::AccountKey { index: 3 },
+        ],
+        false,
+        false,
+    )
+    .unwrap();
+    let pda_address = Pubkey::find_program_address(
+        &[b"seed-prefix", source.as_ref(), wallet.pubkey().as_ref()],
+        &program_id,
+    )
+    .0;
+
+    let mut context = program_test.start_with_context().await;
+    let rent = context.banks_client.get_rent().await.unwrap();
+    let rent_lamports = rent.minimum_balance(ExtraAccountMetas::size_of(2).unwrap());
+    let transaction = Transaction::new_signed_with_payer(
+        &[
+            system_instruction::transfer(
+                &context.payer.pubkey(),
+                &extra_account_metas,
+                rent_lamports,
+            ),
+            initialize_extra_account_metas(
+                &program_id,
+                &extra_account_metas,
+                &mint_address,
+                &mint_authority_pubkey,
+                &extra_account_pubkeys,
+            ),
+            // PDAs can only be written through an update
+            update_extra_account_metas_with_pod_account_metas(
+                &program_id,
+                &extra_account_metas,
+                &mint_address,
+                &mint_authority_pubkey,
+                &extra_account_pubkeys,
+                &[extra_pda],
+            ),
+        ],
+        Some(&context.payer.pubkey()),
+        &[&context.payer, &mint_authority],
+        context.last_blockhash,
+    );
+
+    context
+        .banks_client
+        .process_transaction(transaction)
+        .await
+        .unwrap();
+
+    // fail with wrong pda
+    {
+        let transaction = Transaction::new_signed_with_payer(
+            &[execute_with_extra_account_metas(
+                &program_id,
+                &source,
+                &mint_address,
+                &destination,
+                &wallet.pubkey(),
+                &extra_account_metas,
+                &[
+                    extra_account_pubkeys[0].clone(),
+                    AccountMeta::new_readonly(Pubkey::new_unique(), false),
+                ],
+                0,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.last_blockhash,
+        );
+        let error = context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap_err()
+            .unwrap();
+        assert_eq!(
+            error,
+            TransactionError::InstructionError(
+                0,
+                InstructionError::Custom(TransferHookError::IncorrectAccount as u32),
+            )
+        );
+    }
+
+    // success with resolved pda
+    {
+        let transaction = Transaction::new_signed_with_payer(
+            &[execute_with_extra_account_metas(
+                &program_id,
+                &source,
+                &mint_address,
+                &destination,
+                &wallet.pubkey(),
+                &extra_account_metas,
+                &[
+                    extra_account_pubkeys[0].clone(),
+                    AccountMeta::new_readonly(pda_address, false),
+                ],
+                0,
+            )],
+            Some(&context.payer.pubkey()),
+            &[&context.payer],
+            context.last_blockhash,
+        );
+        context
+            .banks_client
+            .process_transaction(transaction)
+            .await
+            .unwrap();
+    }
+}
+
 #[tokio::test]
 async fn fail_incorrect_derivation() {
     let program_id = Pubkey::new_unique();
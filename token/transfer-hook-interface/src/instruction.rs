@@ -8,6 +8,8 @@ use {
         system_program,
     },
     spl_discriminator::{ArrayDiscriminator, SplDiscriminate},
+    spl_tlv_account_resolution::pod::PodAccountMeta,
+    spl_type_length_value::pod::PodSlice,
     std::convert::TryInto,
 };
 
@@ -42,6 +44,96 @@ pub enum TransferHookInstruction {
     ///   4..4+M `[]` `M` additional accounts, to be written to validation data
     ///
     InitializeExtraAccountMetas,
+    /// Runs additional mint logic, for mints that also hook minting.
+    /// Optional: only programs configured to be invoked on mints need to
+    /// support it.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Destination account
+    ///   1. `[]` Token mint
+    ///   2. `[]` Mint authority
+    ///   3. `[]` Validation account
+    ///   4..4+M `[]` `M` additional accounts, written in validation account data
+    ///
+    ExecuteOnMint {
+        /// Amount of tokens to mint
+        amount: u64,
+    },
+    /// Runs additional burn logic, for mints that also hook burning.
+    /// Optional: only programs configured to be invoked on burns need to
+    /// support it.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[]` Source account
+    ///   1. `[]` Token mint
+    ///   2. `[]` Source account's owner/delegate
+    ///   3. `[]` Validation account
+    ///   4..4+M `[]` `M` additional accounts, written in validation account data
+    ///
+    ExecuteOnBurn {
+        /// Amount of tokens to burn
+        amount: u64,
+    },
+    /// Adds the extra account metas of `ExecuteOnMint` or `ExecuteOnBurn` to
+    /// an account already initialized with `InitializeExtraAccountMetas`,
+    /// growing it as needed. The account must already hold enough lamports
+    /// to be rent-exempt at its new size.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[w]` Account with extra account metas
+    ///   1. `[]` Mint
+    ///   2. `[s]` Mint authority
+    ///   3..3+M `[]` `M` additional accounts, to be written to validation data
+    ///
+    InitializeEventExtraAccountMetas {
+        /// Event whose extra account metas are initialized
+        event: HookedEvent,
+        /// Required accounts provided through instruction data
+        extra_account_metas: Vec<PodAccountMeta>,
+    },
+    /// Replaces the extra account metas of `Execute` on an account already
+    /// initialized with `InitializeExtraAccountMetas`, resizing the account
+    /// as needed. If the account grows, it must already hold enough lamports
+    /// to be rent-exempt at its new size.
+    ///
+    /// Accounts expected by this instruction:
+    ///
+    ///   0. `[w]` Account with extra account metas
+    ///   1. `[]` Mint
+    ///   2. `[s]` Mint authority
+    ///   3..3+M `[]` `M` additional accounts, to be written to validation data
+    ///
+    /// Any required accounts that cannot be provided as account metas, such
+    /// as PDAs derived from seed configurations, are written to validation
+    /// data after the `M` additional accounts.
+    ///
+    UpdateExtraAccountMetas {
+        /// Required accounts provided through instruction data
+        extra_account_metas: Vec<PodAccountMeta>,
+    },
+}
+
+/// Token events, other than transfers, which may invoke the hook
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HookedEvent {
+    /// Tokens minted, with `ExecuteOnMint`
+    Mint,
+    /// Tokens burned, with `ExecuteOnBurn`
+    Burn,
+}
+impl TryFrom<u8> for HookedEvent {
+    type Error = ProgramError;
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(Self::Mint),
+            1 => Ok(Self::Burn),
+            _ => Err(ProgramError::InvalidInstructionData),
+        }
+    }
 }
 /// TLV instruction type only used to define the discriminator. The actual data
 /// is entirely managed by `ExtraAccountMetas`, and it is the only data contained
@@ -56,6 +148,30 @@ pub struct ExecuteInstruction;
 #[discriminator_hash_input("spl-transfer-hook-interface:initialize-extra-account-metas")]
 pub struct InitializeExtraAccountMetasInstruction;
 
+/// TLV instruction type for `ExecuteOnMint`, also used to store its extra
+/// account metas, separately from the ones of `Execute`
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("spl-transfer-hook-interface:execute-on-mint")]
+pub struct ExecuteOnMintInstruction;
+
+/// TLV instruction type for `ExecuteOnBurn`, also used to store its extra
+/// account metas, separately from the ones of `Execute`
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("spl-transfer-hook-interface:execute-on-burn")]
+pub struct ExecuteOnBurnInstruction;
+
+/// TLV instruction type used to initialize the extra account metas of
+/// `ExecuteOnMint` or `ExecuteOnBurn`
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("spl-transfer-hook-interface:initialize-event-extra-account-metas")]
+pub struct InitializeEventExtraAccountMetasInstruction;
+
+/// TLV instruction type used to update extra account metas
+/// for the transfer hook
+#[derive(SplDiscriminate)]
+#[discriminator_hash_input("spl-transfer-hook-interface:update-extra-account-metas")]
+pub struct UpdateExtraAccountMetasInstruction;
+
 impl TransferHookInstruction {
     /// Unpacks a byte buffer into a [TransferHookInstruction](enum.TransferHookInstruction.html).
     pub fn unpack(input: &[u8]) -> Result<Self, ProgramError> {
@@ -64,17 +180,35 @@ impl TransferHookInstruction {
         }
         let (discriminator, rest) = input.split_at(ArrayDiscriminator::LENGTH);
         Ok(match discriminator {
-            ExecuteInstruction::SPL_DISCRIMINATOR_SLICE => {
-                let amount = rest
-                    .get(..8)
-                    .and_then(|slice| slice.try_into().ok())
-                    .map(u64::from_le_bytes)
-                    .ok_or(ProgramError::InvalidInstructionData)?;
-                Self::Execute { amount }
-            }
+            ExecuteInstruction::SPL_DISCRIMINATOR_SLICE => Self::Execute {
+                amount: unpack_amount(rest)?,
+            },
             InitializeExtraAccountMetasInstruction::SPL_DISCRIMINATOR_SLICE => {
                 Self::InitializeExtraAccountMetas
             }
+            ExecuteOnMintInstruction::SPL_DISCRIMINATOR_SLICE => Self::ExecuteOnMint {
+                amount: unpack_amount(rest)?,
+            },
+            ExecuteOnBurnInstruction::SPL_DISCRIMINATOR_SLICE => Self::ExecuteOnBurn {
+                amount: unpack_amount(rest)?,
+            },
+            InitializeEventExtraAccountMetasInstruction::SPL_DISCRIMINATOR_SLICE => {
+                let (event, rest) = rest
+                    .split_first()
+                    .ok_or(ProgramError::InvalidInstructionData)?;
+                let event = HookedEvent::try_from(*event)?;
+                let extra_account_metas = PodSlice::<PodAccountMeta>::unpack(rest)?.data().to_vec();
+                Self::InitializeEventExtraAccountMetas {
+                    event,
+                    extra_account_metas,
+                }
+            }
+            UpdateExtraAccountMetasInstruction::SPL_DISCRIMINATOR_SLICE => {
+                let extra_account_metas = PodSlice::<PodAccountMeta>::unpack(rest)?.data().to_vec();
+                Self::UpdateExtraAccountMetas {
+                    extra_account_metas,
+                }
+            }
             _ => return Err(ProgramError::InvalidInstructionData),
         })
     }
@@ -92,11 +226,45 @@ impl TransferHookInstruction {
                     InitializeExtraAccountMetasInstruction::SPL_DISCRIMINATOR_SLICE,
                 );
             }
+            Self::ExecuteOnMint { amount } => {
+                buf.extend_from_slice(ExecuteOnMintInstruction::SPL_DISCRIMINATOR_SLICE);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            Self::ExecuteOnBurn { amount } => {
+                buf.extend_from_slice(ExecuteOnBurnInstruction::SPL_DISCRIMINATOR_SLICE);
+                buf.extend_from_slice(&amount.to_le_bytes());
+            }
+            Self::InitializeEventExtraAccountMetas {
+                event,
+                extra_account_metas,
+            } => {
+                buf.extend_from_slice(
+                    InitializeEventExtraAccountMetasInstruction::SPL_DISCRIMINATOR_SLICE,
+                );
+                buf.push(*event as u8);
+                buf.extend_from_slice(&(extra_account_metas.len() as u32).to_le_bytes());
+                buf.extend_from_slice(bytemuck::cast_slice(extra_account_metas));
+            }
+            Self::UpdateExtraAccountMetas {
+                extra_account_metas,
+            } => {
+                buf.extend_from_slice(UpdateExtraAccountMetasInstruction::SPL_DISCRIMINATOR_SLICE);
+                buf.extend_from_slice(&(extra_account_metas.len() as u32).to_le_bytes());
+                buf.extend_from_slice(bytemuck::cast_slice(extra_account_metas));
+            }
         };
         buf
     }
 }
 
+fn unpack_amount(input: &[u8]) -> Result<u64, ProgramError> {
+    input
+        .get(..8)
+        .and_then(|slice| slice.try_into().ok())
+        .map(u64::from_le_bytes)
+        .ok_or(ProgramError::InvalidInstructionData)
+}
+
 /// Creates an `Execute` instruction, provided all of the additional required
 /// account metas
 #[allow(clippy::too_many_arguments)]
@@ -174,9 +342,136 @@ pub fn initialize_extra_account_metas(
     }
 }
 
+/// Creates an `UpdateExtraAccountMetas` instruction.
+pub fn update_extra_account_metas(
+    program_id: &Pubkey,
+    extra_account_metas_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    additional_accounts: &[AccountMeta],
+) -> Instruction {
+    update_extra_account_metas_with_pod_account_metas(
+        program_id,
+        extra_account_metas_pubkey,
+        mint_pubkey,
+        authority_pubkey,
+        additional_accounts,
+        &[],
+    )
+}
+
+/// Creates an `UpdateExtraAccountMetas` instruction, including required
+/// accounts that must be provided through instruction data, such as PDAs
+pub fn update_extra_account_metas_with_pod_account_metas(
+    program_id: &Pubkey,
+    extra_account_metas_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    additional_accounts: &[AccountMeta],
+    extra_account_metas: &[PodAccountMeta],
+) -> Instruction {
+    let data = TransferHookInstruction::UpdateExtraAccountMetas {
+        extra_account_metas: extra_account_metas.to_vec(),
+    }
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*extra_account_metas_pubkey, false),
+        AccountMeta::new_readonly(*mint_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, true),
+    ];
+    accounts.extend_from_slice(additional_accounts);
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates an `ExecuteOnMint` instruction, without the additional accounts
+pub fn execute_on_mint(
+    program_id: &Pubkey,
+    destination_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    validate_state_pubkey: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let data = TransferHookInstruction::ExecuteOnMint { amount }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*destination_pubkey, false),
+        AccountMeta::new_readonly(*mint_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, false),
+        AccountMeta::new_readonly(*validate_state_pubkey, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates an `ExecuteOnBurn` instruction, without the additional accounts
+pub fn execute_on_burn(
+    program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    validate_state_pubkey: &Pubkey,
+    amount: u64,
+) -> Instruction {
+    let data = TransferHookInstruction::ExecuteOnBurn { amount }.pack();
+    let accounts = vec![
+        AccountMeta::new_readonly(*source_pubkey, false),
+        AccountMeta::new_readonly(*mint_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, false),
+        AccountMeta::new_readonly(*validate_state_pubkey, false),
+    ];
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
+/// Creates an `InitializeEventExtraAccountMetas` instruction, including
+/// required accounts that must be provided through instruction data, such as
+/// PDAs
+pub fn initialize_event_extra_account_metas(
+    program_id: &Pubkey,
+    extra_account_metas_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    event: HookedEvent,
+    additional_accounts: &[AccountMeta],
+    extra_account_metas: &[PodAccountMeta],
+) -> Instruction {
+    let data = TransferHookInstruction::InitializeEventExtraAccountMetas {
+        event,
+        extra_account_metas: extra_account_metas.to_vec(),
+    }
+    .pack();
+
+    let mut accounts = vec![
+        AccountMeta::new(*extra_account_metas_pubkey, false),
+        AccountMeta::new_readonly(*mint_pubkey, false),
+        AccountMeta::new_readonly(*authority_pubkey, true),
+    ];
+    accounts.extend_from_slice(additional_accounts);
+
+    Instruction {
+        program_id: *program_id,
+        accounts,
+        data,
+    }
+}
+
 #[cfg(test)]
 mod test {
-    use {super::*, crate::NAMESPACE, solana_program::hash};
+    use {
+        super::*, crate::NAMESPACE, solana_program::hash, spl_tlv_account_resolution::seeds::Seed,
+    };
 
     #[test]
     fn validate_packing() {
@@ -210,4 +505,72 @@ mod test {
         let unpacked = TransferHookInstruction::unpack(&expect).unwrap();
         assert_eq!(unpacked, check);
     }
+
+    #[test]
+    fn execute_on_mint_and_burn_packing() {
+        let amount = 222_222_222;
+        for (check, hash_input) in [
+            (
+                TransferHookInstruction::ExecuteOnMint { amount },
+                "execute-on-mint",
+            ),
+            (
+                TransferHookInstruction::ExecuteOnBurn { amount },
+                "execute-on-burn",
+            ),
+        ] {
+            let packed = check.pack();
+            let preimage = hash::hashv(&[format!("{NAMESPACE}:{hash_input}").as_bytes()]);
+            let discriminator = &preimage.as_ref()[..ArrayDiscriminator::LENGTH];
+            let mut expect = vec![];
+            expect.extend_from_slice(discriminator.as_ref());
+            expect.extend_from_slice(&amount.to_le_bytes());
+            assert_eq!(packed, expect);
+            let unpacked = TransferHookInstruction::unpack(&expect).unwrap();
+            assert_eq!(unpacked, check);
+        }
+    }
+
+    #[test]
+    fn initialize_event_extra_account_metas_packing() {
+        let extra_account_metas =
+            vec![PodAccountMeta::new_with_pubkey(&Pubkey::new_unique(), false, false).unwrap()];
+        let check = TransferHookInstruction::InitializeEventExtraAccountMetas {
+            event: HookedEvent::Burn,
+            extra_account_metas: extra_account_metas.clone(),
+        };
+        let packed = check.pack();
+        let preimage =
+            hash::hashv(&[format!("{NAMESPACE}:initialize-event-extra-account-metas").as_bytes()]);
+        let discriminator = &preimage.as_ref()[..ArrayDiscriminator::LENGTH];
+        let mut expect = vec![];
+        expect.extend_from_slice(discriminator.as_ref());
+        expect.push(1);
+        expect.extend_from_slice(&(extra_account_metas.len() as u32).to_le_bytes());
+        expect.extend_from_slice(bytemuck::cast_slice(&extra_account_metas));
+        assert_eq!(packed, expect);
+        let unpacked = TransferHookInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
+
+    #[test]
+    fn update_extra_account_metas_packing() {
+        let extra_account_metas = vec![
+            PodAccountMeta::new_with_pubkey(&Pubkey::new_unique(), true, false).unwrap(),
+            PodAccountMeta::new_with_seeds(&[Seed::AccountKey { index: 0 }], false, true).unwrap(),
+        ];
+        let check = TransferHookInstruction::UpdateExtraAccountMetas {
+            extra_account_metas: extra_account_metas.clone(),
+        };
+        let packed = check.pack();
+        let preimage = hash::hashv(&[format!("{NAMESPACE}:update-extra-account-metas").as_bytes()]);
+        let discriminator = &preimage.as_ref()[..ArrayDiscriminator::LENGTH];
+        let mut expect = vec![];
+        expect.extend_from_slice(discriminator.as_ref());
+        expect.extend_from_slice(&(extra_account_metas.len() as u32).to_le_bytes());
+        expect.extend_from_slice(bytemuck::cast_slice(&extra_account_metas));
+        assert_eq!(packed, expect);
+        let unpacked = TransferHookInstruction::unpack(&expect).unwrap();
+        assert_eq!(unpacked, check);
+    }
 }
@@ -1,12 +1,19 @@
 //! Offchain helper for fetching required accounts to build instructions
 
 use {
-    crate::{get_extra_account_metas_address, instruction::ExecuteInstruction},
+    crate::{
+        get_extra_account_metas_address,
+        instruction::{
+            execute, execute_on_burn, execute_on_mint, ExecuteInstruction,
+            ExecuteOnBurnInstruction, ExecuteOnMintInstruction,
+        },
+    },
     solana_program::{
         instruction::{AccountMeta, Instruction},
         program_error::ProgramError,
         pubkey::Pubkey,
     },
+    spl_discriminator::SplDiscriminate,
     spl_tlv_account_resolution::state::ExtraAccountMetas,
     std::future::Future,
 };
@@ -71,3 +78,161 @@ where
 
     Ok(())
 }
+
+/// Offchain helper to add the accounts required by a transfer hook's `Execute`
+/// instruction to an instruction that invokes it through the token program,
+/// such as a transfer.
+///
+/// Unlike `resolve_extra_account_metas`, PDAs are resolved against the
+/// `Execute` instruction that the token program builds on-chain, so extra
+/// accounts seeded from the hook's accounts or data get the same addresses
+/// offchain as onchain.
+#[allow(clippy::too_many_arguments)]
+pub async fn add_extra_accounts_for_execute_cpi<F, Fut>(
+    cpi_instruction: &mut Instruction,
+    fetch_account_data_fn: F,
+    program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    amount: u64,
+) -> Result<(), AccountFetchError>
+where
+    F: Fn(Pubkey) -> Fut,
+    Fut: Future<Output = AccountDataResult>,
+{
+    let validate_state_pubkey = get_extra_account_metas_address(mint_pubkey, program_id);
+    let validate_state_data = fetch_account_data_fn(validate_state_pubkey)
+        .await?
+        .ok_or(ProgramError::InvalidAccountData)?;
+    add_extra_accounts_for_execute(
+        cpi_instruction,
+        &validate_state_data,
+        program_id,
+        source_pubkey,
+        mint_pubkey,
+        destination_pubkey,
+        authority_pubkey,
+        amount,
+    )?;
+    Ok(())
+}
+
+/// Same as `add_extra_accounts_for_execute_cpi`, but using validation account
+/// data that was already fetched, e.g. from a cache
+#[allow(clippy::too_many_arguments)]
+pub fn add_extra_accounts_for_execute(
+    cpi_instruction: &mut Instruction,
+    validate_state_data: &[u8],
+    program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    destination_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    amount: u64,
+) -> Result<(), ProgramError> {
+    let validate_state_pubkey = get_extra_account_metas_address(mint_pubkey, program_id);
+    let execute_instruction = execute(
+        program_id,
+        source_pubkey,
+        mint_pubkey,
+        destination_pubkey,
+        authority_pubkey,
+        &validate_state_pubkey,
+        amount,
+    );
+    add_extra_accounts::<ExecuteInstruction>(
+        cpi_instruction,
+        execute_instruction,
+        validate_state_data,
+        &validate_state_pubkey,
+    )
+}
+
+/// Offchain helper to add the accounts required by a transfer hook's
+/// `ExecuteOnMint` instruction to a mint instruction of the token program,
+/// using already fetched validation account data
+pub fn add_extra_accounts_for_execute_on_mint(
+    cpi_instruction: &mut Instruction,
+    validate_state_data: &[u8],
+    program_id: &Pubkey,
+    destination_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    amount: u64,
+) -> Result<(), ProgramError> {
+    let validate_state_pubkey = get_extra_account_metas_address(mint_pubkey, program_id);
+    let execute_instruction = execute_on_mint(
+        program_id,
+        destination_pubkey,
+        mint_pubkey,
+        authority_pubkey,
+        &validate_state_pubkey,
+        amount,
+    );
+    add_extra_accounts::<ExecuteOnMintInstruction>(
+        cpi_instruction,
+        execute_instruction,
+        validate_state_data,
+        &validate_state_pubkey,
+    )
+}
+
+/// Offchain helper to add the accounts required by a transfer hook's
+/// `ExecuteOnBurn` instruction to a burn instruction of the token program,
+/// using already fetched validation account data
+pub fn add_extra_accounts_for_execute_on_burn(
+    cpi_instruction: &mut Instruction,
+    validate_state_data: &[u8],
+    program_id: &Pubkey,
+    source_pubkey: &Pubkey,
+    mint_pubkey: &Pubkey,
+    authority_pubkey: &Pubkey,
+    amount: u64,
+) -> Result<(), ProgramError> {
+    let validate_state_pubkey = get_extra_account_metas_address(mint_pubkey, program_id);
+    let execute_instruction = execute_on_burn(
+        program_id,
+        source_pubkey,
+        mint_pubkey,
+        authority_pubkey,
+        &validate_state_pubkey,
+        amount,
+    );
+    add_extra_accounts::<ExecuteOnBurnInstruction>(
+        cpi_instruction,
+        execute_instruction,
+        validate_state_data,
+        &validate_state_pubkey,
+    )
+}
+
+/// Resolve the extra accounts stored for `T` against the instruction the
+/// token program builds onchain, and add them to `cpi_instruction`, followed
+/// by the hook program and validation account
+fn add_extra_accounts<T: SplDiscriminate>(
+    cpi_instruction: &mut Instruction,
+    mut execute_instruction: Instruction,
+    validate_state_data: &[u8],
+    validate_state_pubkey: &Pubkey,
+) -> Result<(), ProgramError> {
+    let execute_accounts_len = execute_instruction.accounts.len();
+    ExtraAccountMetas::add_to_instruction::<T>(&mut execute_instruction, validate_state_data)?;
+
+    cpi_instruction.accounts.extend(
+        execute_instruction
+            .accounts
+            .into_iter()
+            .skip(execute_accounts_len),
+    );
+    cpi_instruction.accounts.push(AccountMeta::new_readonly(
+        execute_instruction.program_id,
+        false,
+    ));
+    cpi_instruction
+        .accounts
+        .push(AccountMeta::new_readonly(*validate_state_pubkey, false));
+
+    Ok(())
+}
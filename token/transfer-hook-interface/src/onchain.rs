@@ -9,6 +9,7 @@ use {
         program::invoke,
         pubkey::Pubkey,
     },
+    spl_discriminator::SplDiscriminate,
     spl_tlv_account_resolution::state::ExtraAccountMetas,
 };
 /// Helper to CPI into a transfer-hook program on-chain, looking through the
@@ -53,6 +54,84 @@ pub fn invoke_execute<'a>(
     invoke(&cpi_instruction, &cpi_account_infos)
 }
 
+/// Helper to CPI into a transfer-hook program's `ExecuteOnMint` on-chain,
+/// looking through the additional account infos to create the proper
+/// instruction
+pub fn invoke_execute_on_mint<'a>(
+    program_id: &Pubkey,
+    destination_info: AccountInfo<'a>,
+    mint_info: AccountInfo<'a>,
+    authority_info: AccountInfo<'a>,
+    additional_accounts: &[AccountInfo<'a>],
+    amount: u64,
+) -> ProgramResult {
+    let validation_pubkey = get_extra_account_metas_address(mint_info.key, program_id);
+    let cpi_instruction = instruction::execute_on_mint(
+        program_id,
+        destination_info.key,
+        mint_info.key,
+        authority_info.key,
+        &validation_pubkey,
+        amount,
+    );
+    invoke_with_extra_account_metas::<instruction::ExecuteOnMintInstruction>(
+        cpi_instruction,
+        vec![destination_info, mint_info, authority_info],
+        &validation_pubkey,
+        additional_accounts,
+    )
+}
+
+/// Helper to CPI into a transfer-hook program's `ExecuteOnBurn` on-chain,
+/// looking through the additional account infos to create the proper
+/// instruction
+pub fn invoke_execute_on_burn<'a>(
+    program_id: &Pubkey,
+    source_info: AccountInfo<'a>,
+    mint_info: AccountInfo<'a>,
+    authority_info: AccountInfo<'a>,
+    additional_accounts: &[AccountInfo<'a>],
+    amount: u64,
+) -> ProgramResult {
+    let validation_pubkey = get_extra_account_metas_address(mint_info.key, program_id);
+    let cpi_instruction = instruction::execute_on_burn(
+        program_id,
+        source_info.key,
+        mint_info.key,
+        authority_info.key,
+        &validation_pubkey,
+        amount,
+    );
+    invoke_with_extra_account_metas::<instruction::ExecuteOnBurnInstruction>(
+        cpi_instruction,
+        vec![source_info, mint_info, authority_info],
+        &validation_pubkey,
+        additional_accounts,
+    )
+}
+
+/// Add the validation account and the extra accounts stored for `T` to an
+/// instruction, then invoke it
+fn invoke_with_extra_account_metas<'a, T: SplDiscriminate>(
+    mut cpi_instruction: Instruction,
+    mut cpi_account_infos: Vec<AccountInfo<'a>>,
+    validation_pubkey: &Pubkey,
+    additional_accounts: &[AccountInfo<'a>],
+) -> ProgramResult {
+    let validation_info = additional_accounts
+        .iter()
+        .find(|&x| x.key == validation_pubkey)
+        .ok_or(TransferHookError::IncorrectAccount)?;
+    cpi_account_infos.push(validation_info.clone());
+    ExtraAccountMetas::add_to_cpi_instruction::<T>(
+        &mut cpi_instruction,
+        &mut cpi_account_infos,
+        &validation_info.try_borrow_data()?,
+        additional_accounts,
+    )?;
+    invoke(&cpi_instruction, &cpi_account_infos)
+}
+
 /// Helper to add accounts required for the transfer-hook program on-chain, looking
 /// through the additional account infos to add the proper accounts
 pub fn add_cpi_accounts_for_execute<'a>(